@@ -0,0 +1,449 @@
+//! Безголовые боты: клиентский протокол без игры.
+//!
+//! Каждый бот — задача tokio с одним TCP-соединением: `Connect`, потом
+//! snapshot'ы по маршруту (`path`), изредка события и чат, и раз в
+//! `probe_every` чат-команда `/money` для замера RTT до ответа сервера.
+//!
+//! В `tick` snapshot'а бот пишет миллисекунды от старта прогона, поэтому
+//! snapshot другого бота того же процесса даёт задержку пересылки через
+//! сервер (`relay`) без синхронизации часов.
+
+pub mod path;
+pub mod stats;
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use protocol::{
+    ClientPacket, NetPlayerEvent, NetPlayerSnapshot, NetVec3, PROTOCOL_VERSION, PlayerId,
+    ServerPacket,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use path::{Route, Walker};
+use stats::{Stats, Window};
+
+/// Здоровье живого игрока на нормальной сложности.
+const BOT_HEALTH: f32 = 720.0;
+
+/// Сколько ждать `ConnectAccepted`.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Команда для замера RTT: ответ приходит только автору.
+const PROBE_COMMAND: &str = "/money";
+
+const CHAT_LINES: &[&str] = &[
+    "привет всем",
+    "кто на гонку?",
+    "где тут автосалон?",
+    "gg",
+    "проверка связи",
+];
+
+/// Оружие, которое бот «достаёт» (`constants::weapons::COLT_M1911A1`).
+const BOT_WEAPON_ID: u32 = 4;
+
+/// Высота ствола над ступнями — откуда «летит» выстрел бота.
+const BOT_MUZZLE_HEIGHT_M: f32 = 1.4;
+
+const ZERO: NetVec3 = NetVec3 {
+    x: 0.0,
+    y: 0.0,
+    z: 0.0,
+};
+
+const EVENTS: &[NetPlayerEvent] = &[
+    NetPlayerEvent::WeaponSelect {
+        weapon_id: BOT_WEAPON_ID,
+    },
+    // Начало и направление подставляются из позы бота при отправке.
+    NetPlayerEvent::ShotFired {
+        weapon_id: BOT_WEAPON_ID,
+        origin: ZERO,
+        direction: ZERO,
+        hit_player: None,
+        hit_pos: None,
+    },
+    NetPlayerEvent::WeaponReload {
+        weapon_id: BOT_WEAPON_ID,
+    },
+    NetPlayerEvent::WeaponHide,
+];
+
+#[derive(Debug, Clone)]
+pub struct BotConfig {
+    pub server: SocketAddr,
+    pub bots: usize,
+    /// Ник бота: `<name_prefix><номер>`. Сервер закрепляет ник за первым
+    /// вошедшим, а токены бот не хранит — повторный прогон против того же
+    /// сервера нужен с другим префиксом.
+    pub name_prefix: String,
+    pub password: Option<String>,
+    /// `None` — до остановки процесса.
+    pub duration: Option<Duration>,
+    /// Snapshot'ов в секунду на бота.
+    pub snapshot_rate: u32,
+    pub route: Route,
+    /// Метров в секунду.
+    pub speed: f32,
+    /// `None` — без чата / событий / замера RTT.
+    pub chat_every: Option<Duration>,
+    pub event_every: Option<Duration>,
+    pub probe_every: Option<Duration>,
+    /// Пауза между подключениями ботов.
+    pub ramp: Duration,
+    /// `None` — без промежуточных отчётов.
+    pub report_every: Option<Duration>,
+    pub seed: u64,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            server: SocketAddr::from(([127, 0, 0, 1], protocol::DEFAULT_PORT)),
+            bots: 10,
+            name_prefix: "bot".to_string(),
+            password: None,
+            duration: None,
+            snapshot_rate: 20,
+            route: Route::Random {
+                center: protocol::NetVec3::default(),
+                radius: 50.0,
+            },
+            speed: 1.5,
+            chat_every: Some(Duration::from_secs(30)),
+            event_every: Some(Duration::from_secs(10)),
+            probe_every: Some(Duration::from_secs(2)),
+            ramp: Duration::from_millis(50),
+            report_every: Some(Duration::from_secs(5)),
+            seed: 1,
+        }
+    }
+}
+
+/// Итог прогона.
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub elapsed: Duration,
+    pub total: Window,
+    /// Больше всего ботов в игре одновременно.
+    pub peak_connected: usize,
+    /// `bot<n>: причина` для каждого упавшего бота.
+    pub failures: Vec<String>,
+}
+
+impl Summary {
+    pub fn ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Промежуточный отчёт, раз в `report_every`.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub connected: usize,
+    pub failed: usize,
+    /// Статистика с прошлого отчёта.
+    pub window: Window,
+    /// Длина окна.
+    pub elapsed: Duration,
+}
+
+struct Ctx {
+    config: BotConfig,
+    started: Instant,
+    stats: Stats,
+    /// Id всех наших ботов — только их snapshot'ы годятся для `relay`.
+    own_ids: Mutex<HashSet<PlayerId>>,
+}
+
+/// Запустить ботов и дождаться их завершения; `on_progress` вызывается
+/// раз в `report_every`.
+pub async fn run(config: BotConfig, mut on_progress: impl FnMut(&Progress)) -> Summary {
+    let ctx = Arc::new(Ctx {
+        started: Instant::now(),
+        stats: Stats::default(),
+        own_ids: Mutex::new(HashSet::new()),
+        config,
+    });
+
+    let mut handles = Vec::new();
+    let mut total = Window::default();
+    let mut peak = 0;
+    let mut last_report = Instant::now();
+
+    let poll = Duration::from_millis(100);
+    loop {
+        if handles.len() < ctx.config.bots {
+            let index = handles.len();
+            let bot_ctx = Arc::clone(&ctx);
+            handles.push(tokio::spawn(async move {
+                let result = run_bot(index, &bot_ctx).await;
+                if result.is_err() {
+                    bot_ctx.stats.failed.fetch_add(1, Ordering::Relaxed);
+                }
+                result
+            }));
+            tokio::time::sleep(ctx.config.ramp).await;
+        } else if handles.iter().all(|h| h.is_finished()) {
+            break;
+        } else {
+            tokio::time::sleep(poll).await;
+        }
+
+        let connected = ctx.stats.connected.load(Ordering::Relaxed);
+        peak = peak.max(connected);
+        if let Some(every) = ctx.config.report_every
+            && last_report.elapsed() >= every
+        {
+            let window = ctx.stats.take_window();
+            total.add(&window);
+            on_progress(&Progress {
+                connected,
+                failed: ctx.stats.failed.load(Ordering::Relaxed),
+                window,
+                elapsed: last_report.elapsed(),
+            });
+            last_report = Instant::now();
+        }
+    }
+    total.add(&ctx.stats.take_window());
+
+    let mut failures = Vec::new();
+    for (index, handle) in handles.into_iter().enumerate() {
+        match handle.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => failures.push(format!("{}{index}: {e}", ctx.config.name_prefix)),
+            Err(e) => failures.push(format!(
+                "{}{index}: task failed: {e}",
+                ctx.config.name_prefix
+            )),
+        }
+    }
+
+    Summary {
+        elapsed: ctx.started.elapsed(),
+        total,
+        peak_connected: peak,
+        failures,
+    }
+}
+
+/// Интервал со случайным сдвигом первого тика, чтобы боты не шли залпами.
+fn interval(every: Option<Duration>, rng: &mut path::Rng) -> Option<Interval> {
+    let every = every?;
+    let offset = every.mul_f32(rng.next_f32());
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + offset, every);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    Some(interval)
+}
+
+/// Тик интервала; выключенный не срабатывает никогда.
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn send(
+    writer: &mut OwnedWriteHalf,
+    stats: &Stats,
+    packet: &ClientPacket,
+) -> Result<(), String> {
+    let mut line = serde_json::to_string(packet).map_err(|e| e.to_string())?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("write failed: {e}"))?;
+    stats.sent(line.len());
+    Ok(())
+}
+
+async fn run_bot(index: usize, ctx: &Ctx) -> Result<(), String> {
+    let config = &ctx.config;
+    let stream = TcpStream::connect(config.server)
+        .await
+        .map_err(|e| format!("connect failed: {e}"))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    send(
+        &mut writer,
+        &ctx.stats,
+        &ClientPacket::Connect {
+            name: format!("{}{index}", config.name_prefix),
+            version: PROTOCOL_VERSION,
+            password: config.password.clone(),
+            account_token: None,
+        },
+    )
+    .await?;
+
+    let player_id = tokio::time::timeout(CONNECT_TIMEOUT, async {
+        loop {
+            let line = lines
+                .next_line()
+                .await
+                .map_err(|e| format!("read failed: {e}"))?
+                .ok_or("server closed connection")?;
+            ctx.stats.received(line.len() + 1);
+            match serde_json::from_str(&line) {
+                Ok(ServerPacket::ConnectAccepted { player_id }) => return Ok(player_id),
+                Ok(ServerPacket::ConnectRejected { reason }) => {
+                    return Err(format!("rejected: {reason}"));
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .map_err(|_| "no ConnectAccepted".to_string())??;
+
+    if let Ok(mut ids) = ctx.own_ids.lock() {
+        ids.insert(player_id);
+    }
+    ctx.stats.connected.fetch_add(1, Ordering::Relaxed);
+
+    let result = play(index, player_id, ctx, &mut lines, &mut writer).await;
+
+    ctx.stats.connected.fetch_sub(1, Ordering::Relaxed);
+    if let Ok(mut ids) = ctx.own_ids.lock() {
+        ids.remove(&player_id);
+    }
+    result
+}
+
+async fn play(
+    index: usize,
+    player_id: PlayerId,
+    ctx: &Ctx,
+    lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+    writer: &mut OwnedWriteHalf,
+) -> Result<(), String> {
+    let config = &ctx.config;
+    let seed = config.seed.wrapping_add(index as u64);
+    let mut rng = path::Rng::new(seed);
+    let mut walker = Walker::new(config.route.clone(), config.speed, seed);
+
+    let snapshot_every = Duration::from_secs(1) / config.snapshot_rate.max(1);
+    let mut snapshots = interval(Some(snapshot_every), &mut rng);
+    let mut chat = interval(config.chat_every, &mut rng);
+    let mut events = interval(config.event_every, &mut rng);
+    let mut probes = interval(config.probe_every, &mut rng);
+    let mut probe_sent: Option<Instant> = None;
+    // Что в руках — согласуем snapshot'ы с последним событием оружия.
+    let mut weapon: Option<u32> = None;
+    // Последняя поза — из неё выстрелы берут начало и направление.
+    let mut last_pose: Option<path::Pose> = None;
+    let mut last_step = Instant::now();
+
+    let deadline = config
+        .duration
+        .map(|d| tokio::time::Instant::from_std(ctx.started + d));
+    let until_deadline = async {
+        match deadline {
+            Some(at) => tokio::time::sleep_until(at).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(until_deadline);
+
+    loop {
+        tokio::select! {
+            _ = &mut until_deadline => {
+                return send(writer, &ctx.stats, &ClientPacket::Disconnect).await;
+            }
+            line = lines.next_line() => {
+                let line = line
+                    .map_err(|e| format!("read failed: {e}"))?
+                    .ok_or("server closed connection")?;
+                ctx.stats.received(line.len() + 1);
+                let Ok(packet) = serde_json::from_str::<ServerPacket>(&line) else {
+                    continue;
+                };
+                match packet {
+                    ServerPacket::Snapshot(s) if s.player_id != player_id => {
+                        let own = ctx.own_ids.lock().is_ok_and(|ids| ids.contains(&s.player_id));
+                        let now_ms = ctx.started.elapsed().as_millis() as u64;
+                        if own && now_ms >= s.tick {
+                            ctx.stats.relay((now_ms - s.tick) as f32);
+                        }
+                    }
+                    ServerPacket::SystemMessage { .. } => {
+                        if let Some(sent) = probe_sent.take() {
+                            ctx.stats.rtt(sent.elapsed().as_secs_f32() * 1000.0);
+                        }
+                    }
+                    ServerPacket::Respawn { position, .. } => walker.teleport(position),
+                    _ => {}
+                }
+            }
+            _ = tick(&mut snapshots) => {
+                let dt = last_step.elapsed().as_secs_f32();
+                last_step = Instant::now();
+                let pose = walker.step(dt);
+                last_pose = Some(pose);
+                let snapshot = NetPlayerSnapshot {
+                    tick: ctx.started.elapsed().as_millis() as u64,
+                    player_id,
+                    position: pose.position,
+                    forward: pose.forward,
+                    health: BOT_HEALTH,
+                    is_dead: false,
+                    state_code: 0,
+                    car_wrapper_state: 0,
+                    ctrl_style_mask: 0,
+                    sub45c_state: 0,
+                    in_vehicle: false,
+                    is_aiming: false,
+                    aim_dir: None,
+                    is_moving: pose.is_moving,
+                    movement_mode: 0,
+                    weapon_id: weapon,
+                };
+                send(writer, &ctx.stats, &ClientPacket::Snapshot(snapshot)).await?;
+            }
+            _ = tick(&mut chat) => {
+                let text = CHAT_LINES[rng.next_u64() as usize % CHAT_LINES.len()].to_string();
+                send(writer, &ctx.stats, &ClientPacket::ChatMessage { text }).await?;
+            }
+            _ = tick(&mut events) => {
+                let mut event = EVENTS[rng.next_u64() as usize % EVENTS.len()].clone();
+                match &mut event {
+                    NetPlayerEvent::WeaponSelect { weapon_id } => weapon = Some(*weapon_id),
+                    NetPlayerEvent::WeaponHide => weapon = None,
+                    NetPlayerEvent::ShotFired { origin, direction, .. } => {
+                        // Сервер сверяет выстрел с оружием из snapshot'а.
+                        let Some(pose) = last_pose.filter(|_| weapon.is_some()) else {
+                            continue;
+                        };
+                        *origin = NetVec3 {
+                            z: pose.position.z + BOT_MUZZLE_HEIGHT_M,
+                            ..pose.position
+                        };
+                        *direction = pose.forward;
+                    }
+                    _ => {}
+                }
+                send(writer, &ctx.stats, &ClientPacket::Event(event)).await?;
+            }
+            _ = tick(&mut probes) => {
+                if probe_sent.is_none() {
+                    probe_sent = Some(Instant::now());
+                    let text = PROBE_COMMAND.to_string();
+                    send(writer, &ctx.stats, &ClientPacket::ChatMessage { text }).await?;
+                }
+            }
+        }
+    }
+}
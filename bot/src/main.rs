@@ -0,0 +1,160 @@
+//! Нагрузочный клиент: N ботов на одном сервере.
+//!
+//! ```text
+//! bot --server <host:port> [--bots <n>] [--name <префикс>] [--password <пароль>]
+//!     [--duration <сек>] [--rate <гц>] [--route random|circle|<file.json>]
+//!     [--center <x,y,z>] [--radius <м>] [--speed <м/с>] [--seed <n>]
+//!     [--chat-secs <сек>] [--event-secs <сек>] [--probe-secs <сек>]
+//!     [--ramp-ms <мс>] [--report-secs <сек>]
+//! ```
+//!
+//! `--route <file.json>` — массив точек `[{"x":..,"y":..,"z":..}, ...]`.
+//! Интервал `0` выключает чат / события / замер RTT / отчёты. Без
+//! `--duration` боты работают до остановки процесса. Код выхода — ошибка,
+//! если хоть один бот не подключился или потерял соединение.
+
+use std::net::ToSocketAddrs;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use bot::BotConfig;
+use bot::path::Route;
+use protocol::NetVec3;
+
+const USAGE: &str = "usage:
+  bot --server <host:port> [--bots <n>] [--name <prefix>] [--password <pw>] [--duration <secs>]
+      [--rate <hz>] [--route random|circle|<file.json>] [--center <x,y,z>] [--radius <m>]
+      [--speed <m/s>] [--seed <n>] [--chat-secs <secs>] [--event-secs <secs>]
+      [--probe-secs <secs>] [--ramp-ms <ms>] [--report-secs <secs>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = match parse_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("cannot start runtime: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!(
+        "[bot] {} bots -> {} at {} Hz",
+        config.bots, config.server, config.snapshot_rate
+    );
+    let bots = config.bots;
+    let summary = runtime.block_on(bot::run(config.clone(), |progress| {
+        println!(
+            "[bot] {}/{bots} connected, {} failed | {}",
+            progress.connected,
+            progress.failed,
+            progress.window.report(progress.elapsed)
+        );
+    }));
+
+    println!(
+        "[bot] done in {:.1}s, peak {}/{} connected",
+        summary.elapsed.as_secs_f32(),
+        summary.peak_connected,
+        config.bots
+    );
+    println!("[bot] total: {}", summary.total.report(summary.elapsed));
+    for failure in &summary.failures {
+        println!("[bot] FAILED {failure}");
+    }
+    if summary.ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn parse<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {flag}: {value}"))
+}
+
+/// Секунды; `0` — выключено.
+fn interval(flag: &str, value: &str) -> Result<Option<Duration>, String> {
+    let secs: f32 = parse(flag, value)?;
+    Ok((secs > 0.0).then(|| Duration::from_secs_f32(secs)))
+}
+
+fn parse_vec3(flag: &str, value: &str) -> Result<NetVec3, String> {
+    let parts: Vec<f32> = value
+        .split(',')
+        .map(|p| parse(flag, p.trim()))
+        .collect::<Result<_, _>>()?;
+    match parts[..] {
+        [x, y, z] => Ok(NetVec3 { x, y, z }),
+        _ => Err(format!(
+            "invalid value for {flag}: {value} (expected x,y,z)"
+        )),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<BotConfig, String> {
+    let mut config = BotConfig::default();
+    let mut server = None;
+    let mut route = "random".to_string();
+    let mut center = NetVec3::default();
+    let mut radius = 50.0;
+
+    let mut rest = args.iter();
+    while let Some(flag) = rest.next() {
+        let flag = flag.as_str();
+        let value = rest.next().ok_or(USAGE)?.as_str();
+        match flag {
+            "--server" => {
+                server = value
+                    .to_socket_addrs()
+                    .map_err(|e| format!("cannot resolve {value}: {e}"))?
+                    .next()
+            }
+            "--bots" => config.bots = parse(flag, value)?,
+            "--name" => config.name_prefix = value.to_string(),
+            "--password" => config.password = Some(value.to_string()),
+            "--duration" => config.duration = interval(flag, value)?,
+            "--rate" => config.snapshot_rate = parse(flag, value)?,
+            "--route" => route = value.to_string(),
+            "--center" => center = parse_vec3(flag, value)?,
+            "--radius" => radius = parse(flag, value)?,
+            "--speed" => config.speed = parse(flag, value)?,
+            "--seed" => config.seed = parse(flag, value)?,
+            "--chat-secs" => config.chat_every = interval(flag, value)?,
+            "--event-secs" => config.event_every = interval(flag, value)?,
+            "--probe-secs" => config.probe_every = interval(flag, value)?,
+            "--ramp-ms" => config.ramp = Duration::from_millis(parse(flag, value)?),
+            "--report-secs" => config.report_every = interval(flag, value)?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    config.server = server.ok_or(USAGE)?;
+    config.route = match route.as_str() {
+        "random" => Route::Random { center, radius },
+        "circle" => Route::Circle { center, radius },
+        file => {
+            let text =
+                std::fs::read_to_string(file).map_err(|e| format!("cannot read {file}: {e}"))?;
+            let points: Vec<NetVec3> =
+                serde_json::from_str(&text).map_err(|e| format!("invalid route {file}: {e}"))?;
+            if points.is_empty() {
+                return Err(format!("route {file} has no points"));
+            }
+            Route::Waypoints(points)
+        }
+    };
+    Ok(config)
+}
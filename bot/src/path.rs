@@ -0,0 +1,274 @@
+//! Маршруты ботов: куда идти и с какой скоростью.
+//!
+//! Всё детерминировано от `seed`: один и тот же запуск воспроизводится.
+
+use protocol::NetVec3;
+
+/// Маленький xorshift: внешних зависимостей ради случайных точек не тянем.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Нулевое состояние xorshift не покидает.
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Равномерно в `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Маршрут.
+#[derive(Debug, Clone)]
+pub enum Route {
+    /// Случайные точки в круге.
+    Random { center: NetVec3, radius: f32 },
+    /// По окружности; боты разнесены по фазе.
+    Circle { center: NetVec3, radius: f32 },
+    /// По точкам из файла, по кругу; боты начинают с разных точек.
+    Waypoints(Vec<NetVec3>),
+}
+
+/// Положение бота на шаге.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    pub position: NetVec3,
+    /// Единичный, в плоскости XY.
+    pub forward: NetVec3,
+    pub is_moving: bool,
+}
+
+/// Ходьба по маршруту.
+#[derive(Debug, Clone)]
+pub struct Walker {
+    route: Route,
+    /// Метров в секунду.
+    speed: f32,
+    position: NetVec3,
+    forward: NetVec3,
+    target: NetVec3,
+    /// Индекс следующей точки (`Waypoints`) или угол (`Circle`).
+    waypoint: usize,
+    angle: f32,
+    rng: Rng,
+}
+
+impl Walker {
+    pub fn new(route: Route, speed: f32, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut walker = Self {
+            route,
+            speed,
+            position: NetVec3::default(),
+            forward: NetVec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            target: NetVec3::default(),
+            waypoint: 0,
+            angle: rng.next_f32() * std::f32::consts::TAU,
+            rng,
+        };
+        walker.position = walker.start(seed);
+        walker.target = walker.next_target();
+        walker
+    }
+
+    fn start(&mut self, seed: u64) -> NetVec3 {
+        match &self.route {
+            Route::Random { center, radius } => {
+                let (center, radius) = (*center, *radius);
+                self.random_point(center, radius)
+            }
+            Route::Circle { center, radius } => on_circle(*center, *radius, self.angle),
+            Route::Waypoints(points) if !points.is_empty() => {
+                self.waypoint = seed as usize % points.len();
+                points[self.waypoint]
+            }
+            Route::Waypoints(_) => NetVec3::default(),
+        }
+    }
+
+    fn random_point(&mut self, center: NetVec3, radius: f32) -> NetVec3 {
+        // sqrt — равномерно по площади, а не к центру.
+        let r = radius * self.rng.next_f32().sqrt();
+        let a = self.rng.next_f32() * std::f32::consts::TAU;
+        NetVec3 {
+            x: center.x + r * a.cos(),
+            y: center.y + r * a.sin(),
+            z: center.z,
+        }
+    }
+
+    fn next_target(&mut self) -> NetVec3 {
+        match &self.route {
+            Route::Random { center, radius } => {
+                let (center, radius) = (*center, *radius);
+                self.random_point(center, radius)
+            }
+            Route::Circle { .. } => self.position,
+            Route::Waypoints(points) if !points.is_empty() => {
+                self.waypoint = (self.waypoint + 1) % points.len();
+                points[self.waypoint]
+            }
+            Route::Waypoints(_) => self.position,
+        }
+    }
+
+    /// Перенос (серверный респаун): маршрут продолжается отсюда.
+    pub fn teleport(&mut self, position: NetVec3) {
+        self.position = position;
+    }
+
+    /// Пройти `dt` секунд.
+    pub fn step(&mut self, dt: f32) -> Pose {
+        let distance = self.speed * dt;
+        let before = self.position;
+
+        match self.route.clone() {
+            Route::Circle { center, radius } => {
+                if radius > 0.0 {
+                    self.angle = (self.angle + distance / radius) % std::f32::consts::TAU;
+                }
+                self.position = on_circle(center, radius, self.angle);
+            }
+            Route::Random { .. } | Route::Waypoints(_) => {
+                let mut left = distance;
+                // Несколько близких точек могут пройтись за один шаг.
+                for _ in 0..8 {
+                    let to_target = sub(self.target, self.position);
+                    let len = length(to_target);
+                    if len <= left {
+                        self.position = self.target;
+                        left -= len;
+                        self.target = self.next_target();
+                        if left <= 0.0 || self.target == self.position {
+                            break;
+                        }
+                    } else {
+                        self.position = add(self.position, scale(to_target, left / len));
+                        break;
+                    }
+                }
+            }
+        }
+
+        let moved = sub(self.position, before);
+        let flat = NetVec3 { z: 0.0, ..moved };
+        let is_moving = length(flat) > 1e-4;
+        if is_moving {
+            self.forward = scale(flat, 1.0 / length(flat));
+        }
+        Pose {
+            position: self.position,
+            forward: self.forward,
+            is_moving,
+        }
+    }
+}
+
+fn on_circle(center: NetVec3, radius: f32, angle: f32) -> NetVec3 {
+    NetVec3 {
+        x: center.x + radius * angle.cos(),
+        y: center.y + radius * angle.sin(),
+        z: center.z,
+    }
+}
+
+fn add(a: NetVec3, b: NetVec3) -> NetVec3 {
+    NetVec3 {
+        x: a.x + b.x,
+        y: a.y + b.y,
+        z: a.z + b.z,
+    }
+}
+
+fn sub(a: NetVec3, b: NetVec3) -> NetVec3 {
+    NetVec3 {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+    }
+}
+
+fn scale(v: NetVec3, k: f32) -> NetVec3 {
+    NetVec3 {
+        x: v.x * k,
+        y: v.y * k,
+        z: v.z * k,
+    }
+}
+
+fn length(v: NetVec3) -> f32 {
+    (v.x * v.x + v.y * v.y + v.z * v.z).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32) -> NetVec3 {
+        NetVec3 { x, y, z: 0.0 }
+    }
+
+    #[test]
+    fn random_walk_stays_in_radius_at_walking_speed() {
+        let center = v(100.0, -50.0);
+        let mut walker = Walker::new(
+            Route::Random {
+                center,
+                radius: 20.0,
+            },
+            1.5,
+            7,
+        );
+        let mut last = walker.step(0.0).position;
+        for _ in 0..2_000 {
+            let pose = walker.step(0.05);
+            assert!(length(sub(pose.position, center)) <= 20.0 + 1e-3);
+            assert!(length(sub(pose.position, last)) <= 1.5 * 0.05 + 1e-3);
+            last = pose.position;
+        }
+    }
+
+    #[test]
+    fn waypoints_loop_in_order_and_face_the_direction_of_travel() {
+        let points = vec![v(0.0, 0.0), v(10.0, 0.0), v(10.0, 10.0)];
+        let mut walker = Walker::new(Route::Waypoints(points), 5.0, 0);
+
+        let pose = walker.step(1.0);
+        assert_eq!(pose.position, v(5.0, 0.0));
+        assert_eq!(pose.forward, v(1.0, 0.0));
+        assert!(pose.is_moving);
+
+        assert_eq!(walker.step(1.0).position, v(10.0, 0.0));
+        let pose = walker.step(1.0);
+        assert_eq!(pose.position, v(10.0, 5.0));
+        assert_eq!(pose.forward, v(0.0, 1.0));
+    }
+
+    #[test]
+    fn seeds_spread_bots_and_repeat_exactly() {
+        let route = Route::Circle {
+            center: v(0.0, 0.0),
+            radius: 30.0,
+        };
+        let a = Walker::new(route.clone(), 2.0, 1).step(0.1);
+        let b = Walker::new(route.clone(), 2.0, 2).step(0.1);
+        let a_again = Walker::new(route, 2.0, 1).step(0.1);
+        assert_ne!(a.position, b.position);
+        assert_eq!(a, a_again);
+    }
+}
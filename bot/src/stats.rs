@@ -0,0 +1,184 @@
+//! Статистика нагрузки: счётчики трафика и выборки задержек.
+//!
+//! Окно сбрасывается на каждом отчёте (`take_window`), итог копится за весь
+//! прогон.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Выборка задержек в миллисекундах.
+#[derive(Debug, Clone, Default)]
+pub struct Latencies(Vec<f32>);
+
+impl Latencies {
+    pub fn push(&mut self, ms: f32) {
+        self.0.push(ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn extend(&mut self, other: &Latencies) {
+        self.0.extend_from_slice(&other.0);
+    }
+
+    /// Перцентиль `p` в `[0, 1]` (ближайший ранг). `None` — выборка пуста.
+    pub fn percentile(&self, p: f32) -> Option<f32> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut sorted = self.0.clone();
+        sorted.sort_by(f32::total_cmp);
+        let rank = (p.clamp(0.0, 1.0) * sorted.len() as f32).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// `p50/p95/max` или `-`.
+    pub fn summary(&self) -> String {
+        match (
+            self.percentile(0.5),
+            self.percentile(0.95),
+            self.percentile(1.0),
+        ) {
+            (Some(p50), Some(p95), Some(max)) => {
+                format!("p50 {p50:.1}ms p95 {p95:.1}ms max {max:.1}ms")
+            }
+            _ => "-".to_string(),
+        }
+    }
+}
+
+/// Трафик за окно или за весь прогон.
+#[derive(Debug, Clone, Default)]
+pub struct Window {
+    pub packets_out: u64,
+    pub bytes_out: u64,
+    pub packets_in: u64,
+    pub bytes_in: u64,
+    /// Snapshot другого бота: отправка -> приём через сервер.
+    pub relay: Latencies,
+    /// Чат-команда -> ответ сервера.
+    pub rtt: Latencies,
+}
+
+impl Window {
+    pub fn add(&mut self, other: &Window) {
+        self.packets_out += other.packets_out;
+        self.bytes_out += other.bytes_out;
+        self.packets_in += other.packets_in;
+        self.bytes_in += other.bytes_in;
+        self.relay.extend(&other.relay);
+        self.rtt.extend(&other.rtt);
+    }
+
+    /// Строка отчёта за `elapsed`.
+    pub fn report(&self, elapsed: Duration) -> String {
+        let secs = elapsed.as_secs_f32().max(1e-3);
+        format!(
+            "out {:.0} pkt/s {:.1} KB/s | in {:.0} pkt/s {:.1} KB/s | relay {} | rtt {}",
+            self.packets_out as f32 / secs,
+            self.bytes_out as f32 / secs / 1024.0,
+            self.packets_in as f32 / secs,
+            self.bytes_in as f32 / secs / 1024.0,
+            self.relay.summary(),
+            self.rtt.summary(),
+        )
+    }
+}
+
+/// Общие счётчики всех ботов.
+#[derive(Debug, Default)]
+pub struct Stats {
+    packets_out: AtomicU64,
+    bytes_out: AtomicU64,
+    packets_in: AtomicU64,
+    bytes_in: AtomicU64,
+    latencies: Mutex<(Latencies, Latencies)>,
+    /// Ботов с принятым `Connect`.
+    pub connected: AtomicUsize,
+    /// Ботов, потерявших соединение или получивших отказ.
+    pub failed: AtomicUsize,
+}
+
+impl Stats {
+    pub fn sent(&self, bytes: usize) {
+        self.packets_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn received(&self, bytes: usize) {
+        self.packets_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn relay(&self, ms: f32) {
+        if let Ok(mut l) = self.latencies.lock() {
+            l.0.push(ms);
+        }
+    }
+
+    pub fn rtt(&self, ms: f32) {
+        if let Ok(mut l) = self.latencies.lock() {
+            l.1.push(ms);
+        }
+    }
+
+    /// Забрать окно и обнулить его.
+    pub fn take_window(&self) -> Window {
+        let (relay, rtt) = self
+            .latencies
+            .lock()
+            .map(|mut l| std::mem::take(&mut *l))
+            .unwrap_or_default();
+        Window {
+            packets_out: self.packets_out.swap(0, Ordering::Relaxed),
+            bytes_out: self.bytes_out.swap(0, Ordering::Relaxed),
+            packets_in: self.packets_in.swap(0, Ordering::Relaxed),
+            bytes_in: self.bytes_in.swap(0, Ordering::Relaxed),
+            relay,
+            rtt,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let mut l = Latencies::default();
+        assert_eq!(l.percentile(0.5), None);
+        assert_eq!(l.summary(), "-");
+
+        for ms in [5.0, 1.0, 3.0, 2.0, 4.0, 100.0, 6.0, 7.0, 8.0, 9.0] {
+            l.push(ms);
+        }
+        assert_eq!(l.percentile(0.5), Some(5.0));
+        assert_eq!(l.percentile(0.95), Some(100.0));
+        assert_eq!(l.percentile(0.0), Some(1.0));
+        assert_eq!(l.summary(), "p50 5.0ms p95 100.0ms max 100.0ms");
+    }
+
+    #[test]
+    fn window_is_reset_after_take() {
+        let stats = Stats::default();
+        stats.sent(100);
+        stats.received(40);
+        stats.relay(3.0);
+
+        let w = stats.take_window();
+        assert_eq!((w.packets_out, w.bytes_out, w.packets_in), (1, 100, 1));
+        assert_eq!(w.relay.len(), 1);
+
+        let empty = stats.take_window();
+        assert_eq!(empty.packets_out, 0);
+        assert!(empty.relay.is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! Внешний вид локального игрока для сервера.
+//!
+//! Модель и вариант одежды уходят `ClientPacket::Appearance` сразу после
+//! `ConnectAccepted` и при каждой смене (гардероб, миссия). Сервер хранит
+//! последний и раздаёт его остальным, они перекрашивают proxy
+//! (`remote_players::apply_appearance`).
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::NetAppearance;
+use sdk::game::Player;
+
+/// Модель читаем не каждый тик: смена одежды — редкое событие.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+struct AppearanceState {
+    last_check: Option<Instant>,
+    /// Последний отправленный серверу вид.
+    sent: Option<NetAppearance>,
+}
+
+static STATE: Mutex<AppearanceState> = Mutex::new(AppearanceState {
+    last_check: None,
+    sent: None,
+});
+
+/// Новая сессия: отправить вид заново.
+pub fn reset() {
+    if let Ok(mut s) = STATE.lock() {
+        s.last_check = None;
+        s.sent = None;
+    }
+}
+
+/// Вызывается на game thread каждый tick.
+pub fn tick_main_thread() {
+    if crate::network::local_player_id().is_none() {
+        return;
+    }
+    let Ok(mut s) = STATE.lock() else {
+        return;
+    };
+    if s.last_check.is_some_and(|t| t.elapsed() < CHECK_INTERVAL) {
+        return;
+    }
+    s.last_check = Some(Instant::now());
+
+    let Some(current) = Player::get_active()
+        .filter(|p| p.is_ready())
+        .and_then(|p| read(&p))
+    else {
+        return;
+    };
+    if s.sent.as_ref() == Some(&current) {
+        return;
+    }
+
+    // Невалидное имя тоже запоминаем — чтобы не предупреждать каждый раз.
+    if current.is_valid() {
+        logger::info(&format!(
+            "[appearance] local model={} appearance={:?}",
+            current.model, current.appearance_id
+        ));
+        crate::network::push_appearance(current.clone());
+    } else {
+        logger::warn(&format!(
+            "[appearance] model name {:?} not sendable, others keep the default look",
+            current.model
+        ));
+    }
+    s.sent = Some(current);
+}
+
+fn read(player: &Player) -> Option<NetAppearance> {
+    Some(NetAppearance {
+        model: player.get_model_name()?,
+        appearance_id: player.get_appearance_id(),
+    })
+}
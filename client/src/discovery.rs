@@ -0,0 +1,226 @@
+//! Поиск серверов для браузера: мастер-сервер, LAN broadcast и опрос
+//! известных адресов.
+//!
+//! Один раунд опроса — отдельный поток: забирает список у мастера (если
+//! задан), шлёт `QueryRequest` на broadcast, на каждый сервер мастера и на
+//! каждый адрес из списка, затем `QUERY_TIMEOUT` собирает ответы. В
+//! результаты попадают только ответившие серверы — так у каждого есть пинг.
+//! Результаты копятся в `RESULTS` по мере прихода, браузер читает их
+//! через `results()`. Пинг — время от запроса до ответа.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use common::logger;
+use protocol::DEFAULT_PORT;
+use protocol::master::{ListFilter, MasterRequest, MasterResponse};
+use protocol::query::{QUERY_MAGIC, QueryRequest, QueryResponse, ServerInfo};
+
+/// Сколько ждать ответов после отправки запросов.
+const QUERY_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Максимальный размер ответа.
+const MAX_RESPONSE_BYTES: usize = 8 * 1024;
+
+/// Таймауты запроса списка у мастера.
+const MASTER_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const MASTER_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Найденный сервер.
+#[derive(Debug, Clone)]
+pub struct DiscoveredServer {
+    /// Адрес для TCP-подключения (IP ответившего + `info.port`).
+    pub addr: SocketAddr,
+    pub info: ServerInfo,
+    pub ping_ms: u32,
+    /// Найден broadcast'ом в локальной сети.
+    pub lan: bool,
+    /// Есть в списке мастер-сервера.
+    pub from_master: bool,
+}
+
+static RESULTS: Mutex<Vec<DiscoveredServer>> = Mutex::new(Vec::new());
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+/// Начать опрос: список `master` (`host:port`), LAN broadcast и `targets`
+/// (UDP query-адреса).
+///
+/// `false`, если предыдущий опрос ещё идёт.
+pub fn refresh(master: Option<String>, targets: Vec<SocketAddr>) -> bool {
+    if REFRESHING.swap(true, Ordering::AcqRel) {
+        return false;
+    }
+    if let Ok(mut r) = RESULTS.lock() {
+        r.clear();
+    }
+
+    thread::spawn(move || {
+        let listed = match master {
+            Some(addr) => fetch_master_list(&addr).unwrap_or_else(|e| {
+                logger::warn(&format!("[discovery] master {addr}: {e}"));
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        if let Err(e) = run_query(&listed, &targets) {
+            logger::warn(&format!("[discovery] query failed: {e}"));
+        }
+        REFRESHING.store(false, Ordering::Release);
+    });
+    true
+}
+
+pub fn is_refreshing() -> bool {
+    REFRESHING.load(Ordering::Acquire)
+}
+
+pub fn results() -> Vec<DiscoveredServer> {
+    RESULTS.lock().map(|r| r.clone()).unwrap_or_default()
+}
+
+/// Адреса серверов из списка мастера. Фильтруем на клиенте: свежие данные
+/// всё равно придут в ответе query.
+fn fetch_master_list(addr: &str) -> Result<Vec<SocketAddr>, String> {
+    let target = addr
+        .to_socket_addrs()
+        .map_err(|e| format!("resolve failed: {e}"))?
+        .next()
+        .ok_or_else(|| "адрес не найден".to_string())?;
+
+    let stream = TcpStream::connect_timeout(&target, MASTER_CONNECT_TIMEOUT)
+        .map_err(|e| format!("connect failed: {e}"))?;
+    stream
+        .set_read_timeout(Some(MASTER_READ_TIMEOUT))
+        .map_err(|e| format!("set_read_timeout failed: {e}"))?;
+
+    let request = MasterRequest::List {
+        filter: ListFilter::default(),
+    };
+    let mut line = serde_json::to_string(&request).map_err(|e| format!("serialize failed: {e}"))?;
+    line.push('\n');
+    (&stream)
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("write failed: {e}"))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("read failed: {e}"))?;
+
+    match serde_json::from_str::<MasterResponse>(reply.trim_end()) {
+        Ok(MasterResponse::Servers { servers }) => {
+            logger::info(&format!(
+                "[discovery] master {addr}: {} servers",
+                servers.len()
+            ));
+            Ok(servers.into_iter().map(|s| s.addr).collect())
+        }
+        Ok(MasterResponse::Error { reason }) => Err(reason),
+        Ok(other) => Err(format!("unexpected response: {other:?}")),
+        Err(e) => Err(format!("invalid response: {e}")),
+    }
+}
+
+fn run_query(listed: &[SocketAddr], targets: &[SocketAddr]) -> std::io::Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    socket.set_read_timeout(Some(Duration::from_millis(100)))?;
+
+    let base = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+
+    // nonce -> (момент отправки, broadcast ли это, из списка мастера ли)
+    let mut sent: HashMap<u64, (Instant, bool, bool)> = HashMap::new();
+
+    let broadcast = SocketAddr::from((Ipv4Addr::BROADCAST, DEFAULT_PORT));
+    if send_request(&socket, broadcast, base) {
+        sent.insert(base, (Instant::now(), true, false));
+    }
+    let all = listed
+        .iter()
+        .map(|a| (a, true))
+        .chain(targets.iter().map(|a| (a, false)));
+    for (i, (target, from_master)) in all.enumerate() {
+        let nonce = base.wrapping_add(i as u64 + 1);
+        if send_request(&socket, *target, nonce) {
+            sent.insert(nonce, (Instant::now(), false, from_master));
+        }
+    }
+
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let mut buf = vec![0u8; MAX_RESPONSE_BYTES];
+
+    while Instant::now() < deadline {
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(r) => r,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue;
+            }
+            // Windows отдаёт ConnectionReset на ICMP port unreachable.
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => continue,
+            Err(e) => return Err(e),
+        };
+
+        let Ok(response) = serde_json::from_slice::<QueryResponse>(&buf[..len]) else {
+            continue;
+        };
+        if response.magic != QUERY_MAGIC {
+            continue;
+        }
+        let Some((sent_at, lan, from_master)) = sent.get(&response.nonce).copied() else {
+            continue;
+        };
+
+        let server = DiscoveredServer {
+            addr: SocketAddr::new(from.ip(), response.info.port),
+            ping_ms: sent_at.elapsed().as_millis() as u32,
+            info: response.info,
+            lan,
+            from_master,
+        };
+        merge(server);
+    }
+
+    Ok(())
+}
+
+fn send_request(socket: &UdpSocket, to: SocketAddr, nonce: u64) -> bool {
+    let Ok(bytes) = serde_json::to_vec(&QueryRequest::new(nonce)) else {
+        return false;
+    };
+    match socket.send_to(&bytes, to) {
+        Ok(_) => true,
+        Err(e) => {
+            logger::debug(&format!("[discovery] send to {to} failed: {e}"));
+            false
+        }
+    }
+}
+
+/// Сервер может ответить и на broadcast, и на прямой запрос — храним один.
+fn merge(server: DiscoveredServer) {
+    let Ok(mut results) = RESULTS.lock() else {
+        return;
+    };
+    match results.iter_mut().find(|s| s.addr == server.addr) {
+        Some(existing) => {
+            existing.lan |= server.lan;
+            existing.from_master |= server.from_master;
+            existing.ping_ms = existing.ping_ms.min(server.ping_ms);
+            existing.info = server.info;
+        }
+        None => results.push(server),
+    }
+}
@@ -0,0 +1,106 @@
+//! Клиентская часть серверной экономики.
+//!
+//! Сервер — единственный источник правды о балансе:
+//! - `ServerPacket::MoneySet` выставляет кошелёк через `Player::set_money`
+//! - локальные изменения кошелька (`PlayerEvent::MoneyChanged`) уходят
+//!   на сервер заявкой `ClientPacket::MoneyReport` относительно последнего
+//!   известного серверного баланса
+//!
+//! Коррекция от сервера сама по себе тоже меняет кошелёк — такой
+//! `MoneyChanged` совпадает с серверным балансом и повторно не отправляется.
+
+use std::sync::Mutex;
+
+use common::logger;
+use sdk::game::Player;
+
+#[derive(Debug)]
+struct EconomyState {
+    /// Последний баланс, подтверждённый сервером.
+    server_balance: Option<i64>,
+    /// Баланс, который ещё нужно записать в кошелёк (игрок не был готов).
+    pending_apply: Option<i64>,
+}
+
+static STATE: Mutex<EconomyState> = Mutex::new(EconomyState {
+    server_balance: None,
+    pending_apply: None,
+});
+
+/// Сбросить состояние (disconnect).
+pub fn reset() {
+    if let Ok(mut s) = STATE.lock() {
+        s.server_balance = None;
+        s.pending_apply = None;
+    }
+}
+
+/// Сервер прислал авторитетный баланс. Вызывается на game thread.
+pub fn on_server_balance(balance_cents: i64) {
+    if let Ok(mut s) = STATE.lock() {
+        s.server_balance = Some(balance_cents);
+        s.pending_apply = Some(balance_cents);
+    }
+    apply_pending();
+}
+
+/// Дописать в кошелёк отложенный баланс, когда игрок готов.
+///
+/// Вызывается на game thread каждый tick.
+pub fn tick_main_thread() {
+    apply_pending();
+}
+
+fn apply_pending() {
+    let Some(player) = Player::get_active().filter(|p| p.is_wallet_ready()) else {
+        return;
+    };
+
+    let pending = match STATE.lock() {
+        Ok(mut s) => s.pending_apply.take(),
+        Err(_) => return,
+    };
+
+    let Some(balance_cents) = pending else {
+        return;
+    };
+
+    if player.get_money_cents() == Some(balance_cents) {
+        return;
+    }
+
+    if player.set_money(balance_cents) {
+        logger::info(&format!(
+            "[economy] баланс от сервера: {balance_cents} центов"
+        ));
+    } else if let Ok(mut s) = STATE.lock() {
+        s.pending_apply.get_or_insert(balance_cents);
+    }
+}
+
+/// Локальный кошелёк изменился (из `PlayerEvent::MoneyChanged`).
+pub fn on_local_money_changed(new_cents: i64) {
+    if !crate::network::is_connected() {
+        return;
+    }
+
+    let server_balance = match STATE.lock() {
+        Ok(s) => s.server_balance,
+        Err(_) => return,
+    };
+
+    // Баланс ещё не пришёл или это наша же коррекция.
+    let Some(old_cents) = server_balance else {
+        return;
+    };
+    if old_cents == new_cents {
+        return;
+    }
+
+    // Оптимистично считаем заявку принятой; если нет — придёт MoneySet.
+    if let Ok(mut s) = STATE.lock() {
+        s.server_balance = Some(new_cents);
+    }
+
+    crate::network::push_money_report(old_cents, new_cents);
+}
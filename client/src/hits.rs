@@ -0,0 +1,117 @@
+//! Попадания локального игрока по proxy удалённых игроков.
+//!
+//! Remote proxy — обычный NPC, поэтому движок рассылает ему DAMAGE как
+//! любому человеку. `human_messages` ловит такие сообщения и складывает их
+//! сюда; на game thread накопленное уходит на сервер как `ClientPacket::Hit`.
+//!
+//! Proxy стреляют только повторяя чужие выстрелы (`shots`) и задевают
+//! друг друга, а атакующего в payload нет — поэтому DAMAGE засчитываем
+//! локальному игроку, только если он сам только что стрелял
+//! (`shots::local_fired_recently`). Оружие берём из его рук, а не из
+//! payload: поле `weapon_type` в DAMAGE подтверждено только как «тип».
+//!
+//! Хедшот — `body_part` головы в payload DAMAGE. Отдельный `HEAD_DAMAGE`
+//! движок рассылает, уже обрабатывая DAMAGE (hook видит сообщение до
+//! доставки), поэтому он относится к DAMAGE, пришедшему прямо перед ним по
+//! той же жертве, — и помечает только это попадание.
+
+use std::sync::{LazyLock, Mutex};
+
+use common::logger;
+use protocol::PlayerId;
+use sdk::game::Player;
+use sdk::structures::DamageMessagePayload;
+
+/// `body_part` головы в DAMAGE payload.
+pub const BODY_PART_HEAD: u32 = 0x10;
+
+#[derive(Debug, Clone, Copy)]
+struct PendingHit {
+    victim: PlayerId,
+    weapon_id: u32,
+    damage: f32,
+    headshot: bool,
+}
+
+#[derive(Debug)]
+struct HitQueue {
+    hits: Vec<PendingHit>,
+    /// Индекс попадания из последнего DAMAGE — ждёт своего `HEAD_DAMAGE`.
+    /// `None`, если последний DAMAGE не засчитан или уже помечен.
+    last_damage: Option<usize>,
+}
+
+static QUEUE: LazyLock<Mutex<HitQueue>> = LazyLock::new(|| {
+    Mutex::new(HitQueue {
+        hits: Vec::new(),
+        last_damage: None,
+    })
+});
+
+/// DAMAGE по proxy игрока `victim`. Вызывается из hook'а.
+pub fn on_proxy_damage(victim: PlayerId, payload: &DamageMessagePayload) {
+    let counted = payload.damage_amount.is_finite()
+        && payload.damage_amount > 0.0
+        && crate::shots::local_fired_recently();
+    if !counted {
+        // Следующий HEAD_DAMAGE — от этого DAMAGE, не от прошлого.
+        if let Ok(mut q) = QUEUE.lock() {
+            q.last_damage = None;
+        }
+        return;
+    }
+
+    let weapon_id = Player::get_active()
+        .and_then(|p| p.get_weapon_in_hand_id())
+        .unwrap_or(payload.weapon_type);
+
+    if let Ok(mut q) = QUEUE.lock() {
+        q.last_damage = Some(q.hits.len());
+        q.hits.push(PendingHit {
+            victim,
+            weapon_id,
+            damage: payload.damage_amount,
+            headshot: payload.body_part == BODY_PART_HEAD,
+        });
+    }
+}
+
+/// HEAD_DAMAGE по proxy игрока `victim`: хедшот для DAMAGE перед ним.
+/// Вызывается из hook'а.
+pub fn on_proxy_head_damage(victim: PlayerId) {
+    if let Ok(mut q) = QUEUE.lock()
+        && let Some(index) = q.last_damage.take()
+        && let Some(hit) = q.hits.get_mut(index)
+        && hit.victim == victim
+    {
+        hit.headshot = true;
+    }
+}
+
+/// Отправить накопленные попадания. Вызывается на game thread каждый tick.
+///
+/// Возвращает жертв по порядку — для `ShotFired` (`shots::tick_main_thread`).
+pub fn flush() -> Vec<PlayerId> {
+    let hits = match QUEUE.lock() {
+        Ok(mut q) => {
+            q.last_damage = None;
+            std::mem::take(&mut q.hits)
+        }
+        Err(_) => return Vec::new(),
+    };
+
+    let mut victims = Vec::with_capacity(hits.len());
+    for hit in hits {
+        logger::debug(&format!(
+            "[hits] -> player {} weapon={} dmg={:.1}{}",
+            hit.victim,
+            hit.weapon_id,
+            hit.damage,
+            if hit.headshot { " (head)" } else { "" }
+        ));
+
+        crate::network::push_hit(hit.victim, hit.weapon_id, hit.damage, hit.headshot);
+        victims.push(hit.victim);
+    }
+    victims
+}
@@ -72,7 +72,9 @@ unsafe extern "C" fn fire_event_by_id_detour(
 }
 
 unsafe extern "C" fn entity_broadcast_detour(entity_ptr: usize, msg_ptr: usize) -> u8 {
-    crate::human_messages::process_broadcast(entity_ptr, msg_ptr);
+    if !crate::human_messages::process_broadcast(entity_ptr, msg_ptr) {
+        return 0;
+    }
 
     if let Some(original) = ORIGINAL_ENTITY_BROADCAST.get() {
         unsafe { original(entity_ptr, msg_ptr) }
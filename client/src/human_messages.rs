@@ -2,7 +2,9 @@
 //!
 //! Модуль питается от hook'а на `M2DE_EntityMessageRegistry_Broadcast`.
 //! Мы фильтруем общий поток сообщений до локального игрока и превращаем
-//! интересные `message_id` в `PlayerEvent`.
+//! интересные `message_id` в `PlayerEvent`. Урон по proxy удалённых игроков
+//! уходит в `hits` (атрибуция фрагов), а урон локальному игроку от пуль
+//! proxy не доставляется вовсе — его применит `ServerPacket::Damage`.
 //!
 //! Опционально: **`M2MP_LOG_ENTITY_MSG_VTABLES=1`** — один лог на пару `(message_id, vtable)`
 //! для weapon-сообщений локального игрока
@@ -11,7 +13,9 @@ use std::collections::HashSet;
 use std::sync::{LazyLock, Mutex};
 
 use sdk::{
-    addresses::constants::human_messages as hm, game::Player, structures::EntityMessageHeader,
+    addresses::constants::human_messages as hm,
+    game::Player,
+    structures::{DamageMessage, EntityMessageHeader},
 };
 
 use crate::{
@@ -34,6 +38,7 @@ fn is_interesting_message(id: u32) -> bool {
             | hm::SHOT
             | hm::WEAPON_DRAW
             | hm::WEAPON_HOLSTER
+            | hm::HEAD_DAMAGE
     )
 }
 
@@ -103,14 +108,17 @@ pub fn message_name(id: u32) -> &'static str {
 /// Вызывается из detour'а на EntityMessageRegistry_Broadcast.
 /// entity_ptr и msg_ptr приходят из движка — обычно валидны,
 /// но лучше перестраховаться, чем ловить access violation в хуке.
-pub fn process_broadcast(entity_ptr: usize, msg_ptr: usize) {
+///
+/// `false` — сообщение не доставлять: broadcast несёт DAMAGE до
+/// `HandleMessage` / `ProcessDamage`, и без оригинала урона не будет.
+pub fn process_broadcast(entity_ptr: usize, msg_ptr: usize) -> bool {
     // Быстрая отсечка по состоянию сессии — не тратим время
     // на разбор сообщений в меню/загрузке
     if !matches!(
         state::get(),
         GameSessionState::InGame | GameSessionState::Paused
     ) {
-        return;
+        return true;
     }
 
     // Проверяем оба указателя перед разыменованием.
@@ -118,26 +126,43 @@ pub fn process_broadcast(entity_ptr: usize, msg_ptr: usize) {
     // но мы в чужом процессе — осторожность не помешает.
     // К тому же, если msg_ptr невалидный, то разыменование
     if !sdk::memory::is_valid_ptr(msg_ptr) || !sdk::memory::is_valid_ptr(entity_ptr) {
-        return;
+        return true;
     }
 
     let header = unsafe { &*(msg_ptr as *const EntityMessageHeader) };
     let id = header.message_id;
 
     if is_spam_message(id) {
-        return;
+        return true;
     }
 
     if !is_interesting_message(id) {
-        return;
+        return true;
+    }
+
+    if let Some(victim) = crate::remote_players::proxy_owner(entity_ptr) {
+        process_proxy_message(victim, msg_ptr, id);
+        return true;
     }
 
     let Some(player) = Player::get_active() else {
-        return;
+        return true;
     };
 
     if entity_ptr != player.as_ptr() {
-        return;
+        return true;
+    }
+
+    match id {
+        hm::DAMAGE => {
+            let msg = unsafe { &*(msg_ptr as *const DamageMessage) };
+            crate::shots::remember_damage_message(msg);
+            if crate::shots::is_proxy_damage(Some(msg.payload.weapon_type)) {
+                return false;
+            }
+        }
+        hm::HEAD_DAMAGE if crate::shots::is_proxy_damage(None) => return false,
+        _ => {}
     }
 
     if matches!(
@@ -160,8 +185,22 @@ pub fn process_broadcast(entity_ptr: usize, msg_ptr: usize) {
         hm::SHOT => PlayerEvent::Shot,
         hm::WEAPON_DRAW => PlayerEvent::WeaponSelect,
         hm::WEAPON_HOLSTER => PlayerEvent::WeaponHide,
-        _ => return,
+        _ => return true,
     };
 
     player_events::push(event);
+    true
+}
+
+/// Сообщение, адресованное proxy удалённого игрока `victim`.
+fn process_proxy_message(victim: protocol::PlayerId, msg_ptr: usize, id: u32) {
+    match id {
+        hm::DAMAGE => {
+            let msg = unsafe { &*(msg_ptr as *const DamageMessage) };
+            crate::shots::remember_damage_message(msg);
+            crate::hits::on_proxy_damage(victim, &msg.payload);
+        }
+        hm::HEAD_DAMAGE => crate::hits::on_proxy_head_damage(victim),
+        _ => {}
+    }
 }
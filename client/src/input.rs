@@ -1,7 +1,8 @@
 use std::time::Duration;
-use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
-use crate::overlay::input::just_pressed;
+use common::keybind::Action;
+
+use crate::keybinds::{self, pressed};
 use crate::state;
 use crate::utils;
 use common::logger;
@@ -9,17 +10,7 @@ use common::logger;
 const INPUT_POLL_MS: u64 = 16;
 
 pub fn log_keybinds() {
-    logger::info("  Горячие клавиши:");
-    logger::info("    DELETE  - Выгрузить клиент");
-    logger::info("    F9      - Показать/скрыть overlay");
-    logger::info("    F10     - Показать/скрыть отладку (FPS/позиция)");
-    logger::info("    F2      - Меню подключения к серверу");
-    logger::info("    F3      - Список игроков онлайн");
-    logger::info("    F4      - Lua консоль");
-    logger::info("    T       - Открыть чат");
-    logger::info("    TAB     - Скорборд (удерживать)");
-    logger::info("    F11     - Загрузить демо-данные");
-    logger::info("    F12     - Очистить демо-данные");
+    keybinds::log();
 }
 
 pub fn run() {
@@ -36,26 +27,30 @@ pub fn run() {
             continue;
         }
 
-        if just_pressed(VK_DELETE) {
+        if pressed(Action::Unload) {
             logger::info("[input] запрошено завершение");
             state::shutdown();
             break;
         }
 
-        if just_pressed(VK_F9) {
+        if pressed(Action::ToggleOverlay) {
             crate::overlay::toggle_visibility();
         }
 
-        if just_pressed(VK_F10) {
+        if pressed(Action::ToggleDebug) {
             crate::overlay::state::toggle_debug();
         }
 
-        if just_pressed(VK_F11) {
+        if pressed(Action::NetGraph) {
+            crate::overlay::state::toggle_netgraph();
+        }
+
+        if pressed(Action::DemoLoad) {
             logger::info("[input] загрузка демо-данных");
             crate::overlay::demo::populate();
         }
 
-        if just_pressed(VK_F12) {
+        if pressed(Action::DemoClear) {
             logger::info("[input] очистка демо-данных");
             crate::overlay::demo::clear();
         }
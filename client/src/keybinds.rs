@@ -0,0 +1,188 @@
+//! Горячие клавиши: текущая раскладка из настроек, опрос действий и
+//! перехват нажатия для переназначения.
+//!
+//! Разбор и конфликты — в `common::keybind`; здесь только состояние и
+//! `GetAsyncKeyState`. Фронт нажатия считается по действию, а не по
+//! клавише: поток ввода и оверлей опрашивают разные действия, и общий
+//! `just_pressed` съел бы фронт у того, кто опросит вторым.
+
+use std::sync::{LazyLock, Mutex};
+
+use common::keybind::{self, Action, Binding, Conflict, Keymap, Mods};
+use common::logger;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::overlay::input::is_held;
+use crate::settings;
+
+static KEYMAP: LazyLock<Mutex<Keymap>> = LazyLock::new(|| Mutex::new(Keymap::default()));
+
+static ACTION_PREV: Mutex<[bool; Action::ALL.len()]> = Mutex::new([false; Action::ALL.len()]);
+
+struct Capture {
+    action: Action,
+    /// Что было зажато на момент начала — чтобы не поймать ту же клавишу.
+    held: [bool; 256],
+}
+
+static CAPTURE: Mutex<Option<Capture>> = Mutex::new(None);
+
+/// Привязка не применена: занята другим действием, ждёт решения в окне
+/// настроек.
+static PENDING: Mutex<Option<(Action, Conflict)>> = Mutex::new(None);
+
+/// Загрузить раскладку из настроек. Вызывать после `settings::init()`.
+pub fn init() {
+    let (keymap, warnings) = Keymap::from_strings(&settings::get().keybindings);
+    for w in warnings {
+        logger::warn(&format!("[keybinds] {w}"));
+    }
+    if let Ok(mut k) = KEYMAP.lock() {
+        *k = keymap;
+    }
+}
+
+pub fn get() -> Keymap {
+    KEYMAP.lock().map(|k| k.clone()).unwrap_or_default()
+}
+
+pub fn binding(action: Action) -> Binding {
+    KEYMAP
+        .lock()
+        .map(|k| k.get(action))
+        .unwrap_or_else(|_| Keymap::default().get(action))
+}
+
+pub fn log() {
+    logger::info("  Горячие клавиши:");
+    let keymap = get();
+    for action in Action::ALL {
+        logger::info(&format!(
+            "    {:<18} - {}",
+            keymap.get(action).to_string(),
+            action.label()
+        ));
+    }
+}
+
+fn current_mods() -> Mods {
+    Mods {
+        ctrl: is_held(VK_CONTROL),
+        shift: is_held(VK_SHIFT),
+        alt: is_held(VK_MENU),
+    }
+}
+
+fn is_active(b: Binding) -> bool {
+    is_held(VIRTUAL_KEY(b.key as u16)) && current_mods() == b.mods
+}
+
+/// Действие нажато в этом опросе. Пока идёт перехват — всегда false.
+pub fn pressed(action: Action) -> bool {
+    let down = is_active(binding(action));
+    let Ok(mut prev) = ACTION_PREV.lock() else {
+        return false;
+    };
+    let was = std::mem::replace(&mut prev[action as usize], down);
+    down && !was && capturing().is_none()
+}
+
+/// Действие удерживается (скорборд).
+pub fn held(action: Action) -> bool {
+    capturing().is_none() && is_active(binding(action))
+}
+
+/// Сохранить и применить; при конфликте ничего не меняется.
+pub fn set(action: Action, b: Binding) -> Result<(), Conflict> {
+    let Ok(mut keymap) = KEYMAP.lock() else {
+        return Ok(());
+    };
+    keymap.set(action, b)?;
+    persist(&keymap);
+    Ok(())
+}
+
+/// Назначить, отдав прежнюю клавишу действию, которое её занимало.
+pub fn set_swapping(action: Action, b: Binding) {
+    if let Ok(mut keymap) = KEYMAP.lock() {
+        keymap.set_swapping(action, b);
+        persist(&keymap);
+    }
+}
+
+pub fn reset_all() {
+    if let Ok(mut keymap) = KEYMAP.lock() {
+        *keymap = Keymap::default();
+        persist(&keymap);
+    }
+    clear_pending();
+}
+
+fn persist(keymap: &Keymap) {
+    let map = keymap.to_strings();
+    settings::update(|s| s.keybindings = map);
+}
+
+pub fn start_capture(action: Action) {
+    let mut held = [false; 256];
+    for key in keybind::bindable_keys() {
+        held[key as usize] = is_held(VIRTUAL_KEY(key as u16));
+    }
+    clear_pending();
+    if let Ok(mut c) = CAPTURE.lock() {
+        *c = Some(Capture { action, held });
+    }
+}
+
+pub fn capturing() -> Option<Action> {
+    CAPTURE.lock().ok()?.as_ref().map(|c| c.action)
+}
+
+pub fn cancel_capture() {
+    if let Ok(mut c) = CAPTURE.lock() {
+        *c = None;
+    }
+}
+
+/// Первая нажатая после `start_capture` клавиша с текущими модификаторами.
+/// Занятая привязка уходит в `pending`. ESC отменяет перехват в
+/// `ui::handle_hotkeys`.
+pub fn poll_capture() {
+    let Ok(mut guard) = CAPTURE.lock() else {
+        return;
+    };
+    let Some(capture) = guard.as_mut() else {
+        return;
+    };
+    let mut pressed = None;
+    for key in keybind::bindable_keys() {
+        let down = is_held(VIRTUAL_KEY(key as u16));
+        let was = std::mem::replace(&mut capture.held[key as usize], down);
+        if down && !was && pressed.is_none() {
+            pressed = Some(key);
+        }
+    }
+    let Some(key) = pressed else {
+        return;
+    };
+    let action = capture.action;
+    *guard = None;
+    drop(guard);
+
+    let b = Binding::new(current_mods(), key);
+    if let Err(conflict) = set(action, b)
+        && let Ok(mut p) = PENDING.lock()
+    {
+        *p = Some((action, conflict));
+    }
+}
+
+pub fn pending() -> Option<(Action, Conflict)> {
+    PENDING.lock().ok().and_then(|p| *p)
+}
+
+pub fn clear_pending() {
+    if let Ok(mut p) = PENDING.lock() {
+        *p = None;
+    }
+}
@@ -1,22 +1,35 @@
 // Клиентская DLL для Mafia II: DE Multiplayer
 
+mod appearance;
+mod discovery;
+mod economy;
 mod events;
+mod hits;
 mod hooks;
 mod human_messages;
 mod input;
+mod keybinds;
 mod lua_queue;
 mod main_thread;
 mod multiplayer;
+mod nametags;
 mod net_debug;
 mod network;
 mod overlay;
 mod player_events;
 mod player_tracker;
+mod race;
 mod remote_players;
+mod respawn;
+mod server_browser;
+mod settings;
+mod shots;
 mod single_instance_bypass;
 mod state;
 mod utils;
 mod vehicle_tracker;
+mod vehicles;
+mod world;
 
 use common::logger;
 use std::ffi::c_void;
@@ -73,6 +86,10 @@ fn initialize() {
 
     sdk::game::log_module_info();
 
+    settings::init();
+    server_browser::init();
+    keybinds::init();
+
     // Подсистемы
     lua_queue::init();
     player_tracker::init();
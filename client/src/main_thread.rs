@@ -41,6 +41,7 @@ pub fn on_main_thread_tick() {
 
     crate::multiplayer::on_main_thread_tick();
     crate::remote_players::tick_interpolation();
+    crate::nametags::capture();
     crate::hooks::try_deferred_present_hook();
     crate::overlay::state::sync_from_game();
 }
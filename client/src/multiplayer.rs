@@ -24,6 +24,13 @@ pub fn init() {
 /// 3. обновление vehicle трекера
 /// 4. обработка накопленных локальных событий -> network queue
 /// 5. применение входящих пакетов от сервера
+/// 6. отложенная запись серверного баланса в кошелёк
+/// 7. отложенный серверный респаун
+/// 8. отправка попаданий по proxy удалённых игроков и выстрелов (`ShotFired`)
+/// 9. race HUD (стрелка на следующий чекпоинт)
+/// 10. серверные время суток и погода
+/// 11. смена внешнего вида локального игрока -> network queue
+/// 12. snapshot'ы машины локального водителя и машины удалённых водителей
 pub fn on_main_thread_tick() {
     crate::network::auto_disconnect_if_session_invalid();
 
@@ -33,4 +40,12 @@ pub fn on_main_thread_tick() {
 
     crate::overlay::state::sync_player_controls();
     crate::network::poll_main_thread();
+    crate::economy::tick_main_thread();
+    crate::respawn::tick_main_thread();
+    let victims = crate::hits::flush();
+    crate::shots::tick_main_thread(&victims);
+    crate::race::tick_main_thread();
+    crate::world::tick_main_thread();
+    crate::appearance::tick_main_thread();
+    crate::vehicles::tick_main_thread();
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+//! Ники над удалёнными игроками: на game thread снимаем камеру и позиции
+//! proxy-NPC, оверлей проецирует их на экран в своём кадре.
+//!
+//! Известное ограничение: камера — приближение по позе и FOV улицы
+//! (`camera::current_view`), матриц движка у нас нет. В прицеле FOV
+//! другой, и ники прячем, а в катсценах и интерьерах они могут смещаться.
+
+use common::interp;
+use common::projection;
+use sdk::game::{Player, camera};
+
+use crate::overlay::state::{self, Nametag, NametagFrame};
+use crate::remote_players;
+
+/// Высота точки ника над ступнями NPC, метры.
+const HEAD_OFFSET_M: f32 = 2.1;
+
+/// Здоровье на нормальной сложности — 100% полоски.
+const FULL_HEALTH: f32 = 720.0;
+
+/// Вызывается на main thread каждый тик.
+pub fn capture() {
+    let targets = remote_players::nametag_targets();
+    if targets.is_empty() {
+        state::set_nametags(None);
+        return;
+    }
+    // Зум прицела меняет FOV, которого мы не знаем.
+    let aiming = Player::get_active().is_some_and(|p| p.is_aiming().unwrap_or(false));
+    if aiming {
+        state::set_nametags(None);
+        return;
+    }
+    let Some(camera) = camera::current_view() else {
+        state::set_nametags(None);
+        return;
+    };
+
+    let tags = targets
+        .into_iter()
+        .filter(|t| !t.is_dead)
+        .map(|t| Nametag {
+            player_id: t.player_id,
+            name: state::player_name(t.player_id).unwrap_or(t.name),
+            head: interp::Vec3::new(t.position.x, t.position.y, t.position.z + HEAD_OFFSET_M),
+            health: (t.health / FULL_HEALTH).clamp(0.0, 1.0),
+        })
+        .collect();
+
+    state::set_nametags(Some(NametagFrame { camera, tags }));
+}
+
+/// Для `overlay::ui::nametags`: ники ближе этого — непрозрачные, дальше
+/// `cutoff` — не рисуются.
+pub const RANGE: projection::TagRange = projection::TagRange {
+    fade_start: 35.0,
+    cutoff: 80.0,
+};
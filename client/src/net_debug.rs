@@ -4,26 +4,279 @@
 //! - не спамить лог каждым snapshot
 //! - иметь понятную статистику: сколько packet'ов отправлено/получено
 //! - логировать важные события (connect, disconnect, events, chat)
+//! - копить статистику для netgraph оверлея (`stats`): RTT, пакеты и
+//!   байты в секунду по типам, интервалы между snapshot'ами, очередь
+//!   отправки, история по секундам для графиков
 //!
-//! Вызывается из network.rs.
+//! Вызывается из network.rs (transport thread).
 
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
 
 use common::logger;
-use protocol::{ClientPacket, ServerPacket};
+use protocol::{ClientPacket, PlayerId, ServerPacket};
 
 static OUT_SNAPSHOT_COUNT: AtomicU64 = AtomicU64::new(0);
 static OUT_EVENT_COUNT: AtomicU64 = AtomicU64::new(0);
 static OUT_CHAT_COUNT: AtomicU64 = AtomicU64::new(0);
+static OUT_VEHICLE_SNAPSHOT_COUNT: AtomicU64 = AtomicU64::new(0);
 
 static IN_SNAPSHOT_COUNT: AtomicU64 = AtomicU64::new(0);
 static IN_EVENT_COUNT: AtomicU64 = AtomicU64::new(0);
 static IN_CHAT_COUNT: AtomicU64 = AtomicU64::new(0);
+static IN_VEHICLE_SNAPSHOT_COUNT: AtomicU64 = AtomicU64::new(0);
 
-/// Логировать отправку пакета с throttling.
-pub fn on_outbound(packet: &ClientPacket) {
+// =============================================================================
+//  Статистика для netgraph
+// =============================================================================
+
+/// Сколько секунд истории у графиков.
+pub const HISTORY_SECS: usize = 60;
+
+/// Как часто transport thread шлёт `Ping`.
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Верхние границы корзин гистограммы интервалов между snapshot'ами, мс;
+/// последняя корзина — всё, что дольше.
+pub const INTERARRIVAL_BUCKETS_MS: [u32; 6] = [25, 50, 75, 100, 150, 250];
+
+/// По скольким последним интервалам строится гистограмма.
+const INTERARRIVAL_WINDOW: usize = 200;
+
+/// Часы `Ping::sent_ms`.
+static NET_CLOCK: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+fn clock_ms() -> u64 {
+    NET_CLOCK.elapsed().as_millis() as u64
+}
+
+/// Пакеты и байты за секунду.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rate {
+    pub packets: u32,
+    pub bytes: u32,
+}
+
+impl Rate {
+    fn add(&mut self, bytes: usize) {
+        self.packets += 1;
+        self.bytes = self.bytes.saturating_add(bytes as u32);
+    }
+}
+
+/// Одна секунда истории.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Second {
+    pub rtt_ms: Option<f32>,
+    pub bytes_in: u32,
+    pub bytes_out: u32,
+    pub send_queue: usize,
+}
+
+/// Снимок статистики для оверлея. Скорости — за последнюю полную секунду.
+#[derive(Debug, Clone, Default)]
+pub struct NetStats {
+    pub rtt_ms: Option<f32>,
+    /// Сглаженное изменение RTT между замерами (как в RFC 3550).
+    pub rtt_jitter_ms: f32,
+    pub rate_in: BTreeMap<&'static str, Rate>,
+    pub rate_out: BTreeMap<&'static str, Rate>,
+    /// Самая длинная очередь отправки за секунду.
+    pub send_queue: usize,
+    /// Корзины `INTERARRIVAL_BUCKETS_MS` плюс «дольше».
+    pub interarrival: [u32; INTERARRIVAL_BUCKETS_MS.len() + 1],
+    /// Старые секунды первыми.
+    pub history: Vec<Second>,
+}
+
+impl NetStats {
+    pub fn total_in(&self) -> Rate {
+        sum(&self.rate_in)
+    }
+
+    pub fn total_out(&self) -> Rate {
+        sum(&self.rate_out)
+    }
+}
+
+fn sum(rates: &BTreeMap<&'static str, Rate>) -> Rate {
+    rates.values().fold(Rate::default(), |acc, r| Rate {
+        packets: acc.packets + r.packets,
+        bytes: acc.bytes.saturating_add(r.bytes),
+    })
+}
+
+#[derive(Default)]
+struct Collector {
+    /// Начало текущей секунды.
+    window_start: Option<Instant>,
+    current_in: BTreeMap<&'static str, Rate>,
+    current_out: BTreeMap<&'static str, Rate>,
+    current_queue: usize,
+    last_in: BTreeMap<&'static str, Rate>,
+    last_out: BTreeMap<&'static str, Rate>,
+    last_queue: usize,
+    history: VecDeque<Second>,
+    rtt_ms: Option<f32>,
+    rtt_jitter_ms: f32,
+    last_ping: Option<Instant>,
+    last_snapshot_at: HashMap<PlayerId, Instant>,
+    interarrivals: VecDeque<u32>,
+}
+
+impl Collector {
+    /// Закрыть прошедшие секунды; пустые секунды тоже попадают в историю.
+    fn roll(&mut self, now: Instant) {
+        let start = *self.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(start).as_secs() as usize;
+        if elapsed == 0 {
+            return;
+        }
+        self.last_in = std::mem::take(&mut self.current_in);
+        self.last_out = std::mem::take(&mut self.current_out);
+        self.last_queue = std::mem::take(&mut self.current_queue);
+        self.push_second(Second {
+            rtt_ms: self.rtt_ms,
+            bytes_in: sum(&self.last_in).bytes,
+            bytes_out: sum(&self.last_out).bytes,
+            send_queue: self.last_queue,
+        });
+        for _ in 1..elapsed.min(HISTORY_SECS) {
+            self.push_second(Second {
+                rtt_ms: self.rtt_ms,
+                ..Second::default()
+            });
+        }
+        if elapsed > 1 {
+            self.last_in.clear();
+            self.last_out.clear();
+            self.last_queue = 0;
+        }
+        self.window_start = Some(start + Duration::from_secs(elapsed as u64));
+    }
+
+    fn push_second(&mut self, second: Second) {
+        if self.history.len() == HISTORY_SECS {
+            self.history.pop_front();
+        }
+        self.history.push_back(second);
+    }
+
+    fn interarrival(&mut self, player_id: PlayerId, now: Instant) {
+        if let Some(prev) = self.last_snapshot_at.insert(player_id, now) {
+            if self.interarrivals.len() == INTERARRIVAL_WINDOW {
+                self.interarrivals.pop_front();
+            }
+            self.interarrivals
+                .push_back(now.duration_since(prev).as_millis() as u32);
+        }
+    }
+
+    fn rtt(&mut self, rtt_ms: f32) {
+        if let Some(prev) = self.rtt_ms {
+            self.rtt_jitter_ms += ((rtt_ms - prev).abs() - self.rtt_jitter_ms) / 16.0;
+        }
+        self.rtt_ms = Some(rtt_ms);
+    }
+}
+
+static COLLECTOR: LazyLock<Mutex<Collector>> = LazyLock::new(|| Mutex::new(Collector::default()));
+
+/// Новая сессия: старые замеры не относятся к новому серверу.
+pub fn reset_stats() {
+    if let Ok(mut c) = COLLECTOR.lock() {
+        *c = Collector::default();
+    }
+}
+
+/// Пора ли слать `Ping`; если да — `sent_ms` для него.
+pub fn ping_due() -> Option<u64> {
+    let now = Instant::now();
+    let mut c = COLLECTOR.lock().ok()?;
+    if c.last_ping
+        .is_some_and(|at| now.duration_since(at) < PING_INTERVAL)
+    {
+        return None;
+    }
+    c.last_ping = Some(now);
+    Some(clock_ms())
+}
+
+/// Последний замер RTT, мс.
+pub fn rtt_ms() -> Option<f32> {
+    COLLECTOR.lock().ok()?.rtt_ms
+}
+
+/// Длина очереди отправки перед сбросом в сокет.
+pub fn on_send_queue(len: usize) {
+    if let Ok(mut c) = COLLECTOR.lock() {
+        c.roll(Instant::now());
+        c.current_queue = c.current_queue.max(len);
+    }
+}
+
+pub fn stats() -> NetStats {
+    let Ok(mut c) = COLLECTOR.lock() else {
+        return NetStats::default();
+    };
+    c.roll(Instant::now());
+    let mut interarrival = [0u32; INTERARRIVAL_BUCKETS_MS.len() + 1];
+    for &ms in &c.interarrivals {
+        let bucket = INTERARRIVAL_BUCKETS_MS
+            .iter()
+            .position(|&max| ms < max)
+            .unwrap_or(INTERARRIVAL_BUCKETS_MS.len());
+        interarrival[bucket] += 1;
+    }
+    NetStats {
+        rtt_ms: c.rtt_ms,
+        rtt_jitter_ms: c.rtt_jitter_ms,
+        rate_in: c.last_in.clone(),
+        rate_out: c.last_out.clone(),
+        send_queue: c.last_queue,
+        interarrival,
+        history: c.history.iter().copied().collect(),
+    }
+}
+
+fn record_outbound(packet: &ClientPacket, bytes: usize) {
+    if let Ok(mut c) = COLLECTOR.lock() {
+        c.roll(Instant::now());
+        c.current_out.entry(packet.kind()).or_default().add(bytes);
+    }
+}
+
+fn record_inbound(packet: &ServerPacket, bytes: usize) {
+    let now = Instant::now();
+    let Ok(mut c) = COLLECTOR.lock() else {
+        return;
+    };
+    c.roll(now);
+    c.current_in.entry(packet.kind()).or_default().add(bytes);
+    match packet {
+        ServerPacket::Snapshot(snapshot) => c.interarrival(snapshot.player_id, now),
+        ServerPacket::PlayerDespawn { player_id } => {
+            c.last_snapshot_at.remove(player_id);
+        }
+        ServerPacket::Pong { sent_ms } => {
+            let rtt = clock_ms().saturating_sub(*sent_ms) as f32;
+            c.rtt(rtt);
+        }
+        _ => {}
+    }
+}
+
+// =============================================================================
+//  Лог
+// =============================================================================
+
+/// Учесть отправку (`bytes` — длина JSON-строки) и залогировать с throttling.
+pub fn on_outbound(packet: &ClientPacket, bytes: usize) {
+    record_outbound(packet, bytes);
     match packet {
-        ClientPacket::Connect { name, version } => {
+        ClientPacket::Connect { name, version, .. } => {
             logger::info(&format!(
                 "[net/out] Connect name='{}' version={}",
                 name, version
@@ -47,11 +300,61 @@ pub fn on_outbound(packet: &ClientPacket) {
             let n = OUT_CHAT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
             logger::info(&format!("[net/out] Chat #{n}: {}", text));
         }
+        ClientPacket::TeamChat { text } => {
+            let n = OUT_CHAT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+            logger::info(&format!("[net/out] TeamChat #{n}: {}", text));
+        }
+        ClientPacket::MoneyReport {
+            old_cents,
+            new_cents,
+        } => {
+            logger::info(&format!(
+                "[net/out] MoneyReport {} -> {}",
+                old_cents, new_cents
+            ));
+        }
+        ClientPacket::Hit {
+            victim_id,
+            weapon_id,
+            damage,
+            headshot,
+        } => {
+            logger::debug(&format!(
+                "[net/out] Hit victim={} weapon={} dmg={:.1} head={}",
+                victim_id, weapon_id, damage, headshot
+            ));
+        }
+        ClientPacket::Ping { .. } => {}
+        ClientPacket::Appearance(appearance) => {
+            logger::info(&format!(
+                "[net/out] Appearance model={} id={:?}",
+                appearance.model, appearance.appearance_id
+            ));
+        }
+        ClientPacket::VehicleEnter {
+            model_hash,
+            position,
+        } => {
+            logger::info(&format!(
+                "[net/out] VehicleEnter model={:#x} pos=({:.1}, {:.1}, {:.1})",
+                model_hash, position.x, position.y, position.z
+            ));
+        }
+        ClientPacket::VehicleLeave => {
+            logger::info("[net/out] VehicleLeave");
+        }
+        ClientPacket::VehicleSnapshot(_) => {
+            let n = OUT_VEHICLE_SNAPSHOT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+            if n % 20 == 0 {
+                logger::debug(&format!("[net/out] VehicleSnapshot count={}", n));
+            }
+        }
     }
 }
 
-/// Логировать получение пакета с throttling.
-pub fn on_inbound(packet: &ServerPacket) {
+/// Учесть получение и залогировать с throttling.
+pub fn on_inbound(packet: &ServerPacket, bytes: usize) {
+    record_inbound(packet, bytes);
     match packet {
         ServerPacket::ConnectAccepted { player_id } => {
             logger::info(&format!("[net/in] ConnectAccepted id={}", player_id));
@@ -59,6 +362,9 @@ pub fn on_inbound(packet: &ServerPacket) {
         ServerPacket::ConnectRejected { reason } => {
             logger::warn(&format!("[net/in] ConnectRejected: {}", reason));
         }
+        ServerPacket::AccountToken { .. } => {
+            logger::info("[net/in] AccountToken");
+        }
         ServerPacket::PlayerSpawn { player_id, name } => {
             logger::info(&format!(
                 "[net/in] PlayerSpawn id={} name='{}'",
@@ -88,5 +394,202 @@ pub fn on_inbound(packet: &ServerPacket) {
             let n = IN_CHAT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
             logger::info(&format!("[net/in] Chat #{n} from {}: {}", player_id, text));
         }
+        ServerPacket::SystemMessage { text } => {
+            logger::info(&format!("[net/in] System: {}", text));
+        }
+        ServerPacket::MoneySet { balance_cents } => {
+            logger::info(&format!("[net/in] MoneySet balance={}", balance_cents));
+        }
+        ServerPacket::RespawnPending { delay_ms } => {
+            logger::info(&format!("[net/in] RespawnPending delay={}ms", delay_ms));
+        }
+        ServerPacket::Respawn {
+            position,
+            heading,
+            health,
+            loadout,
+        } => {
+            logger::info(&format!(
+                "[net/in] Respawn pos=({:.1}, {:.1}, {:.1}) heading={:.0} hp={:.0} weapons={}",
+                position.x,
+                position.y,
+                position.z,
+                heading,
+                health,
+                loadout.len()
+            ));
+        }
+        ServerPacket::Kill {
+            killer_id,
+            victim_id,
+            weapon_id,
+            headshot,
+        } => {
+            logger::info(&format!(
+                "[net/in] Kill killer={:?} victim={} weapon={:?} head={}",
+                killer_id, victim_id, weapon_id, headshot
+            ));
+        }
+        ServerPacket::Damage {
+            attacker_id,
+            weapon_id,
+            damage,
+            headshot,
+        } => {
+            logger::info(&format!(
+                "[net/in] Damage attacker={} weapon={} dmg={:.1} head={}",
+                attacker_id, weapon_id, damage, headshot
+            ));
+        }
+        ServerPacket::ScoreUpdate {
+            player_id,
+            kills,
+            deaths,
+        } => {
+            logger::debug(&format!(
+                "[net/in] ScoreUpdate id={} K={} D={}",
+                player_id, kills, deaths
+            ));
+        }
+        ServerPacket::RoundStart {
+            mode,
+            score_limit,
+            time_limit_secs,
+        } => {
+            logger::info(&format!(
+                "[net/in] RoundStart mode={} score_limit={} time_limit={}s",
+                mode, score_limit, time_limit_secs
+            ));
+        }
+        ServerPacket::RoundEnd {
+            winner_id,
+            reason,
+            next_round_in_secs,
+        } => {
+            logger::info(&format!(
+                "[net/in] RoundEnd winner={:?} reason={:?} next_in={}s",
+                winner_id, reason, next_round_in_secs
+            ));
+        }
+        ServerPacket::TeamList { teams } => {
+            logger::info(&format!(
+                "[net/in] TeamList {:?}",
+                teams.iter().map(|t| t.id.as_str()).collect::<Vec<_>>()
+            ));
+        }
+        ServerPacket::TeamAssigned { player_id, team } => {
+            logger::info(&format!(
+                "[net/in] TeamAssigned id={} team={:?}",
+                player_id, team
+            ));
+        }
+        ServerPacket::TeamChat { player_id, text } => {
+            logger::debug(&format!(
+                "[net/in] TeamChat id={} len={}",
+                player_id,
+                text.len()
+            ));
+        }
+        ServerPacket::RaceTrack {
+            name,
+            laps,
+            checkpoints,
+        } => {
+            logger::info(&format!(
+                "[net/in] RaceTrack name={} laps={} checkpoints={}",
+                name,
+                laps,
+                checkpoints.len()
+            ));
+        }
+        ServerPacket::RaceCountdown { seconds } => {
+            logger::info(&format!("[net/in] RaceCountdown {}s", seconds));
+        }
+        ServerPacket::RaceStart => {
+            logger::info("[net/in] RaceStart");
+        }
+        ServerPacket::RaceCheckpoint {
+            checkpoint,
+            lap,
+            split_ms,
+            lap_ms,
+        } => {
+            logger::debug(&format!(
+                "[net/in] RaceCheckpoint cp={} lap={} split={}ms lap_time={:?}",
+                checkpoint, lap, split_ms, lap_ms
+            ));
+        }
+        ServerPacket::RaceStandings { order } => {
+            logger::debug(&format!("[net/in] RaceStandings {:?}", order));
+        }
+        ServerPacket::RaceFinish {
+            player_id,
+            place,
+            total_ms,
+        } => {
+            logger::info(&format!(
+                "[net/in] RaceFinish id={} place={} total={}ms",
+                player_id, place, total_ms
+            ));
+        }
+        ServerPacket::RaceResults {
+            results,
+            next_race_in_secs,
+        } => {
+            logger::info(&format!(
+                "[net/in] RaceResults racers={} next_in={}s",
+                results.len(),
+                next_race_in_secs
+            ));
+        }
+        ServerPacket::WorldState {
+            hour,
+            time_scale,
+            weather,
+        } => {
+            logger::info(&format!(
+                "[net/in] WorldState hour={hour:.2} scale={time_scale} weather={weather:?}"
+            ));
+        }
+        ServerPacket::Pong { .. } => {}
+        ServerPacket::PlayerAppearance {
+            player_id,
+            appearance,
+        } => {
+            logger::info(&format!(
+                "[net/in] PlayerAppearance id={} model={} appearance={:?}",
+                player_id, appearance.model, appearance.appearance_id
+            ));
+        }
+        ServerPacket::VehicleEnter {
+            player_id,
+            vehicle_id,
+            seat,
+            model_hash,
+            ..
+        } => {
+            logger::info(&format!(
+                "[net/in] VehicleEnter id={} vehicle={} seat={} model={:#x}",
+                player_id, vehicle_id, seat, model_hash
+            ));
+        }
+        ServerPacket::VehicleLeave {
+            player_id,
+            vehicle_id,
+        } => {
+            logger::info(&format!(
+                "[net/in] VehicleLeave id={} vehicle={}",
+                player_id, vehicle_id
+            ));
+        }
+        ServerPacket::VehicleSnapshot(snapshot) => {
+            let n = IN_VEHICLE_SNAPSHOT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+            if n % 20 == 0 {
+                logger::debug(&format!(
+                    "[net/in] VehicleSnapshot count={} last_vehicle={}",
+                    n, snapshot.vehicle_id
+                ));
+            }
+        }
     }
 }
@@ -14,17 +14,26 @@
 //! ВАЖНО:
 //! - gameplay/NPC application происходит ТОЛЬКО на game thread
 //! - transport thread не лезет в движок напрямую
+//!
+//! Автопереподключение: если принятая сервером сессия оборвалась с ошибкой
+//! сокета, отдельный поток повторяет `Connect` с тем же ником, адресом и
+//! паролем — с экспоненциальной задержкой, не больше
+//! `RECONNECT_MAX_ATTEMPTS` раз. Отменяется из окна подключения,
+//! `disconnect()` или выходом в меню.
 
 use std::collections::VecDeque;
 use std::io::{Read, Write};
-use std::net::{Shutdown, TcpStream};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, OnceLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use common::logger;
-use protocol::{ClientPacket, NetPlayerEvent, NetPlayerSnapshot, PlayerId, ServerPacket};
+use protocol::{
+    ClientPacket, NetAppearance, NetPlayerEvent, NetPlayerSnapshot, PlayerId, RoundEndReason,
+    ServerPacket,
+};
 
 /// Транспорт считается активным, пока transport thread крутится.
 static TRANSPORT_RUNNING: AtomicBool = AtomicBool::new(false);
@@ -32,12 +41,32 @@ static TRANSPORT_RUNNING: AtomicBool = AtomicBool::new(false);
 /// Флаг запроса на остановку transport thread.
 static TRANSPORT_STOP: AtomicBool = AtomicBool::new(false);
 
+/// Поток переподключения крутится.
+static RECONNECT_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Запрос на отмену переподключения.
+static RECONNECT_CANCEL: AtomicBool = AtomicBool::new(false);
+
+/// Сколько попыток переподключения после обрыва.
+const RECONNECT_MAX_ATTEMPTS: u32 = 6;
+
+/// Задержка перед первой попыткой; дальше удваивается до `RECONNECT_MAX_DELAY`.
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Таймаут TCP connect попытки — мёртвый сервер не держит поток минутами.
+const RECONNECT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 struct NetworkState {
     connected: bool,
     local_player_id: Option<PlayerId>,
     nickname: String,
     server_addr: String,
+    password: String,
+
+    /// Номер текущей попытки переподключения; 0 — сессия не восстанавливается.
+    reconnect_attempt: u32,
 
     /// Очередь исходящих пакетов.
     outbound: VecDeque<ClientPacket>,
@@ -55,6 +84,8 @@ fn state() -> &'static Mutex<NetworkState> {
             local_player_id: None,
             nickname: String::new(),
             server_addr: String::new(),
+            password: String::new(),
+            reconnect_attempt: 0,
             outbound: VecDeque::new(),
             inbound: VecDeque::new(),
         })
@@ -78,12 +109,18 @@ pub fn local_player_id() -> Option<PlayerId> {
     state().lock().ok().and_then(|s| s.local_player_id)
 }
 
+/// Идёт ли автопереподключение.
+pub fn is_reconnecting() -> bool {
+    RECONNECT_RUNNING.load(Ordering::Acquire)
+        || state().lock().is_ok_and(|s| s.reconnect_attempt > 0)
+}
+
 /// Подключение к серверу.
 ///
 /// 1. Открывает TCP
 /// 2. Запускает transport thread
 /// 3. Кладёт `Connect` packet в outbound queue
-pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
+pub fn connect(ip: &str, port: u16, nickname: &str, password: &str) -> bool {
     let addr = format!("{ip}:{port}");
 
     {
@@ -100,10 +137,14 @@ pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
             return false;
         }
 
+        // Ручное подключение заменяет незавершённое переподключение.
+        RECONNECT_CANCEL.store(true, Ordering::Release);
         guard.connected = true;
         guard.local_player_id = None;
         guard.nickname = nickname.to_string();
         guard.server_addr = addr.clone();
+        guard.password = password.to_string();
+        guard.reconnect_attempt = 0;
         guard.outbound.clear();
         guard.inbound.clear();
     }
@@ -112,6 +153,13 @@ pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
         false,
         format!("Подключение к {addr}..."),
     );
+    crate::settings::update(|s| {
+        s.last_server = crate::settings::LastServer {
+            ip: ip.to_string(),
+            port,
+        };
+        s.nickname = nickname.to_string();
+    });
 
     let stream = match TcpStream::connect(&addr) {
         Ok(s) => s,
@@ -131,6 +179,23 @@ pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
         }
     };
 
+    start_transport(stream, nickname, password)
+}
+
+/// Ключ токена аккаунта в `Settings::account_tokens`: аккаунт — это ник
+/// на конкретном сервере.
+fn account_key(nickname: &str, server_addr: &str) -> String {
+    format!("{nickname}@{server_addr}")
+}
+
+/// Запустить transport thread на открытом сокете и поставить `Connect`
+/// в очередь. `false` — transport уже работает.
+fn start_transport(stream: TcpStream, nickname: &str, password: &str) -> bool {
+    let addr = stream
+        .peer_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_else(|_| "<unknown>".to_string());
+
     if let Err(e) = stream.set_nodelay(true) {
         logger::warn(&format!("[network] set_nodelay failed: {e}"));
     }
@@ -139,8 +204,15 @@ pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
         logger::warn(&format!("[network] set_nonblocking failed: {e}"));
     }
 
+    if TRANSPORT_RUNNING
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        logger::warn("[network] transport уже работает, сокет закрыт");
+        return false;
+    }
     TRANSPORT_STOP.store(false, Ordering::Release);
-    TRANSPORT_RUNNING.store(true, Ordering::Release);
+    crate::net_debug::reset_stats();
 
     {
         let mut guard = match state().lock() {
@@ -151,9 +223,15 @@ pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
             }
         };
 
+        let account_token = crate::settings::get()
+            .account_tokens
+            .get(&account_key(nickname, &guard.server_addr))
+            .cloned();
         guard.outbound.push_back(ClientPacket::Connect {
             name: nickname.to_string(),
             version: protocol::PROTOCOL_VERSION,
+            password: (!password.is_empty()).then(|| password.to_string()),
+            account_token,
         });
     }
 
@@ -170,6 +248,7 @@ pub fn connect(ip: &str, port: u16, nickname: &str) -> bool {
 
 /// Отключение от сервера.
 pub fn disconnect() -> bool {
+    let cancelled = cancel_reconnect();
     {
         let mut guard = match state().lock() {
             Ok(g) => g,
@@ -180,7 +259,7 @@ pub fn disconnect() -> bool {
         };
 
         if !guard.connected && !TRANSPORT_RUNNING.load(Ordering::Acquire) {
-            return false;
+            return cancelled;
         }
 
         guard.outbound.push_back(ClientPacket::Disconnect);
@@ -191,6 +270,13 @@ pub fn disconnect() -> bool {
     TRANSPORT_STOP.store(true, Ordering::Release);
 
     crate::remote_players::clear_all();
+    crate::economy::reset();
+    crate::respawn::reset();
+    crate::race::reset();
+    crate::world::reset();
+    crate::vehicles::reset();
+    crate::overlay::state::set_round(None);
+    crate::overlay::state::set_teams(Vec::new());
     crate::overlay::state::clear_players();
     crate::overlay::state::set_connection_status(false, "Отключен".to_string());
     crate::overlay::state::add_system_message("Отключено от сервера".to_string());
@@ -233,6 +319,87 @@ pub fn push_local_event(event: NetPlayerEvent) {
     guard.outbound.push_back(ClientPacket::Event(event));
 }
 
+/// Сообщить серверу об изменении локального кошелька.
+///
+/// Сервер либо примет заявку молча, либо ответит `ServerPacket::MoneySet`.
+pub fn push_money_report(old_cents: i64, new_cents: i64) {
+    let mut guard = match state().lock() {
+        Ok(g) => g,
+        Err(_) => {
+            logger::error("[network] mutex poisoned in push_money_report");
+            return;
+        }
+    };
+
+    if !guard.connected || guard.local_player_id.is_none() {
+        return;
+    }
+
+    guard.outbound.push_back(ClientPacket::MoneyReport {
+        old_cents,
+        new_cents,
+    });
+}
+
+/// Сообщить серверу о попадании локального игрока в proxy `victim_id`.
+pub fn push_hit(victim_id: PlayerId, weapon_id: u32, damage: f32, headshot: bool) {
+    let mut guard = match state().lock() {
+        Ok(g) => g,
+        Err(_) => {
+            logger::error("[network] mutex poisoned in push_hit");
+            return;
+        }
+    };
+
+    if !guard.connected || guard.local_player_id.is_none() {
+        return;
+    }
+
+    guard.outbound.push_back(ClientPacket::Hit {
+        victim_id,
+        weapon_id,
+        damage,
+        headshot,
+    });
+}
+
+/// Сообщить серверу внешний вид локального игрока.
+pub fn push_appearance(appearance: NetAppearance) {
+    let mut guard = match state().lock() {
+        Ok(g) => g,
+        Err(_) => {
+            logger::error("[network] mutex poisoned in push_appearance");
+            return;
+        }
+    };
+
+    if !guard.connected || guard.local_player_id.is_none() {
+        return;
+    }
+
+    guard
+        .outbound
+        .push_back(ClientPacket::Appearance(appearance));
+}
+
+/// Положить пакет машины (`VehicleEnter` / `VehicleLeave` / `VehicleSnapshot`)
+/// в outbound queue.
+pub fn push_vehicle_packet(packet: ClientPacket) {
+    let mut guard = match state().lock() {
+        Ok(g) => g,
+        Err(_) => {
+            logger::error("[network] mutex poisoned in push_vehicle_packet");
+            return;
+        }
+    };
+
+    if !guard.connected || guard.local_player_id.is_none() {
+        return;
+    }
+
+    guard.outbound.push_back(packet);
+}
+
 /// Отправить сообщение чата.
 pub fn send_chat_message(text: String) {
     let mut guard = match state().lock() {
@@ -255,6 +422,26 @@ pub fn send_chat_message(text: String) {
         .push_back(ClientPacket::ChatMessage { text: text.clone() });
 }
 
+/// Отправить сообщение командного чата.
+pub fn send_team_chat_message(text: String) {
+    let mut guard = match state().lock() {
+        Ok(g) => g,
+        Err(_) => {
+            logger::error("[network] mutex poisoned in send_team_chat_message");
+            return;
+        }
+    };
+
+    if !guard.connected {
+        crate::overlay::state::add_system_message(
+            "Нельзя отправить сообщение: нет подключения".to_string(),
+        );
+        return;
+    }
+
+    guard.outbound.push_back(ClientPacket::TeamChat { text });
+}
+
 /// Вызывается на game thread — применяет inbound packets к runtime.
 pub fn poll_main_thread() {
     let inbound = {
@@ -281,15 +468,19 @@ pub fn poll_main_thread() {
 fn handle_incoming_packet(packet: ServerPacket) {
     match packet {
         ServerPacket::ConnectAccepted { player_id } => {
-            let nickname = {
+            let (nickname, restored) = {
                 let mut guard = match state().lock() {
                     Ok(g) => g,
                     Err(_) => return,
                 };
                 guard.local_player_id = Some(player_id);
-                guard.nickname.clone()
+                let restored = std::mem::take(&mut guard.reconnect_attempt) > 0;
+                (guard.nickname.clone(), restored)
             };
 
+            // Новая сессия — сервер нашего вида ещё не знает.
+            crate::appearance::reset();
+            crate::vehicles::reset();
             crate::overlay::state::clear_players();
             crate::overlay::state::set_connection_status(
                 true,
@@ -299,6 +490,10 @@ fn handle_incoming_packet(packet: ServerPacket) {
             crate::overlay::state::add_system_message(format!(
                 "Подключение принято. Ваш ID: {player_id}"
             ));
+            if restored {
+                logger::info("[network] session restored after reconnect");
+                crate::overlay::state::add_system_message("Соединение восстановлено".to_string());
+            }
 
             logger::info(&format!(
                 "[network] connect accepted: player_id={player_id}, nickname={nickname}"
@@ -319,6 +514,19 @@ fn handle_incoming_packet(packet: ServerPacket) {
             );
         }
 
+        ServerPacket::AccountToken { token } => {
+            let Some(key) = state()
+                .lock()
+                .ok()
+                .map(|g| account_key(&g.nickname, &g.server_addr))
+            else {
+                return;
+            };
+            crate::settings::update(|s| {
+                s.account_tokens.insert(key, token);
+            });
+        }
+
         ServerPacket::PlayerSpawn { player_id, name } => {
             if Some(player_id) == local_player_id() {
                 return;
@@ -354,6 +562,234 @@ fn handle_incoming_packet(packet: ServerPacket) {
             let author = format!("Player#{player_id}");
             crate::overlay::state::add_chat_message(author, text);
         }
+
+        ServerPacket::TeamChat { player_id, text } => {
+            let author = crate::overlay::state::player_name(player_id as u32)
+                .unwrap_or_else(|| format!("Player#{player_id}"));
+            crate::overlay::state::add_team_chat_msg(&author, &text);
+        }
+
+        ServerPacket::SystemMessage { text } => {
+            crate::overlay::state::add_system_message(text);
+        }
+
+        ServerPacket::TeamList { teams } => {
+            crate::overlay::state::set_teams(
+                teams
+                    .into_iter()
+                    .map(|t| crate::overlay::state::TeamInfo {
+                        id: t.id,
+                        name: t.name,
+                        color: t.color,
+                    })
+                    .collect(),
+            );
+        }
+
+        ServerPacket::TeamAssigned { player_id, team } => {
+            if Some(player_id) == local_player_id()
+                && let Some(id) = &team
+            {
+                crate::overlay::state::add_system_message(format!("Ваша команда: {id}"));
+            }
+            crate::overlay::state::set_player_team(player_id as u32, team);
+        }
+
+        ServerPacket::MoneySet { balance_cents } => {
+            crate::economy::on_server_balance(balance_cents);
+        }
+
+        ServerPacket::RespawnPending { delay_ms } => {
+            crate::respawn::on_pending(delay_ms);
+        }
+
+        ServerPacket::Respawn {
+            position,
+            heading,
+            health,
+            loadout,
+        } => {
+            crate::respawn::on_respawn(position, heading, health, loadout);
+        }
+
+        ServerPacket::Kill {
+            killer_id,
+            victim_id,
+            weapon_id,
+            headshot,
+        } => {
+            let local = local_player_id();
+            let name_of = |id: PlayerId| {
+                crate::overlay::state::player_name(id as u32)
+                    .unwrap_or_else(|| format!("Player#{id}"))
+            };
+
+            crate::overlay::state::add_kill(crate::overlay::state::KillFeedEntry {
+                killer: killer_id.map(name_of),
+                victim: name_of(victim_id),
+                weapon_id,
+                headshot,
+                involves_local: local.is_some()
+                    && (local == killer_id || local == Some(victim_id)),
+                created: Instant::now(),
+            });
+        }
+
+        ServerPacket::Damage {
+            attacker_id,
+            weapon_id,
+            damage,
+            headshot,
+        } => {
+            crate::shots::on_server_damage(attacker_id, weapon_id, damage, headshot);
+        }
+
+        ServerPacket::VehicleEnter {
+            player_id,
+            vehicle_id,
+            seat,
+            model_hash,
+            position,
+        } => {
+            crate::vehicles::on_enter(player_id, vehicle_id, seat, model_hash, position);
+        }
+
+        ServerPacket::VehicleLeave {
+            player_id,
+            vehicle_id,
+        } => {
+            crate::vehicles::on_leave(player_id, vehicle_id);
+        }
+
+        ServerPacket::VehicleSnapshot(snapshot) => {
+            crate::vehicles::on_snapshot(snapshot);
+        }
+
+        ServerPacket::ScoreUpdate {
+            player_id,
+            kills,
+            deaths,
+        } => {
+            crate::overlay::state::set_player_score(player_id as u32, kills, deaths);
+        }
+
+        ServerPacket::RoundStart {
+            mode,
+            score_limit,
+            time_limit_secs,
+        } => {
+            crate::overlay::state::reset_scores();
+
+            let limit = if score_limit > 0 {
+                format!(", до {score_limit} фрагов")
+            } else {
+                String::new()
+            };
+            crate::overlay::state::add_system_message(format!("Раунд начался: {mode}{limit}"));
+
+            crate::overlay::state::set_round(Some(crate::overlay::state::RoundInfo {
+                mode,
+                score_limit,
+                ends_at: (time_limit_secs > 0).then(|| {
+                    Instant::now() + Duration::from_secs(time_limit_secs as u64)
+                }),
+                finished: false,
+            }));
+        }
+
+        ServerPacket::RoundEnd {
+            winner_id,
+            reason,
+            next_round_in_secs,
+        } => {
+            crate::overlay::state::finish_round();
+
+            let winner = match winner_id {
+                Some(id) if Some(id) == local_player_id() => "вы победили!".to_string(),
+                Some(id) => format!(
+                    "победил {}",
+                    crate::overlay::state::player_name(id as u32)
+                        .unwrap_or_else(|| format!("Player#{id}"))
+                ),
+                None => "ничья".to_string(),
+            };
+            let why = match reason {
+                RoundEndReason::ScoreLimit => "лимит фрагов",
+                RoundEndReason::TimeLimit => "время вышло",
+            };
+            crate::overlay::state::add_system_message(format!(
+                "Раунд окончен ({why}): {winner}. Следующий через {next_round_in_secs} с"
+            ));
+        }
+
+        ServerPacket::RaceTrack {
+            name,
+            laps,
+            checkpoints,
+        } => {
+            crate::race::on_track(name, laps, checkpoints);
+        }
+
+        ServerPacket::RaceCountdown { seconds } => {
+            crate::race::on_countdown(seconds);
+        }
+
+        ServerPacket::RaceStart => {
+            crate::race::on_start();
+        }
+
+        ServerPacket::RaceCheckpoint {
+            checkpoint,
+            lap,
+            split_ms,
+            lap_ms,
+        } => {
+            crate::race::on_checkpoint(checkpoint, lap, split_ms, lap_ms);
+        }
+
+        ServerPacket::RaceStandings { order } => {
+            crate::race::on_standings(order);
+        }
+
+        ServerPacket::RaceFinish {
+            player_id,
+            place,
+            total_ms,
+        } => {
+            crate::race::on_finish(player_id, place, total_ms);
+        }
+
+        ServerPacket::RaceResults {
+            results,
+            next_race_in_secs,
+        } => {
+            crate::race::on_results(&results, next_race_in_secs);
+        }
+
+        ServerPacket::WorldState {
+            hour,
+            time_scale,
+            weather,
+        } => {
+            crate::world::on_world_state(hour, time_scale, weather);
+        }
+
+        ServerPacket::Pong { .. } => {
+            // RTT уже посчитан в transport thread.
+            if let (Some(id), Some(rtt)) = (local_player_id(), crate::net_debug::rtt_ms()) {
+                crate::overlay::state::update_player_ping(id as u32, rtt.round() as u32);
+            }
+        }
+
+        ServerPacket::PlayerAppearance {
+            player_id,
+            appearance,
+        } => {
+            if Some(player_id) == local_player_id() {
+                return;
+            }
+            crate::remote_players::apply_appearance(player_id, appearance);
+        }
     }
 }
 
@@ -384,16 +820,20 @@ fn transport_thread_main(mut stream: TcpStream) {
                 }
             };
 
+            crate::net_debug::on_send_queue(guard.outbound.len());
             guard.outbound.drain(..).collect::<Vec<_>>()
         };
 
-        for packet in outbound_packets {
-            crate::net_debug::on_outbound(&packet);
+        let ping = crate::net_debug::ping_due().map(|sent_ms| ClientPacket::Ping { sent_ms });
 
-            if let Err(e) = write_packet_line(&mut stream, &packet) {
-                logger::error(&format!("[network] write packet failed: {e}"));
-                transport_fail_disconnect("Ошибка записи в сокет");
-                return;
+        for packet in outbound_packets.into_iter().chain(ping) {
+            match write_packet_line(&mut stream, &packet) {
+                Ok(bytes) => crate::net_debug::on_outbound(&packet, bytes),
+                Err(e) => {
+                    logger::error(&format!("[network] write packet failed: {e}"));
+                    transport_fail_disconnect("Ошибка записи в сокет");
+                    return;
+                }
             }
         }
 
@@ -423,7 +863,7 @@ fn transport_thread_main(mut stream: TcpStream) {
 
                     match serde_json::from_str::<ServerPacket>(&line) {
                         Ok(packet) => {
-                            crate::net_debug::on_inbound(&packet);
+                            crate::net_debug::on_inbound(&packet, line.len() + 1);
 
                             if let Ok(mut guard) = state().lock() {
                                 guard.inbound.push_back(packet);
@@ -456,35 +896,198 @@ fn transport_thread_main(mut stream: TcpStream) {
     logger::info("[network] transport thread stopped");
 }
 
-/// Сериализует пакет в JSON line (`...\n`).
-fn write_packet_line(stream: &mut TcpStream, packet: &ClientPacket) -> std::io::Result<()> {
+/// Сериализует пакет в JSON line (`...\n`); возвращает число байт.
+fn write_packet_line(stream: &mut TcpStream, packet: &ClientPacket) -> std::io::Result<usize> {
     let json = serde_json::to_string(packet)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
 
     stream.write_all(json.as_bytes())?;
     stream.write_all(b"\n")?;
-    Ok(())
+    Ok(json.len() + 1)
 }
 
 /// Переводит network subsystem в disconnected state после ошибки transport thread.
+///
+/// Принятую сессию (или уже восстанавливаемую) пробует восстановить
+/// `start_reconnect`.
 fn transport_fail_disconnect(reason: &str) {
     logger::warn(&format!("[network] transport disconnected: {reason}"));
 
-    if let Ok(mut guard) = state().lock() {
-        guard.connected = false;
-        guard.local_player_id = None;
-        guard.outbound.clear();
-    }
+    let restore = match state().lock() {
+        Ok(mut guard) => {
+            let restore = guard.local_player_id.is_some() || guard.reconnect_attempt > 0;
+            guard.connected = false;
+            guard.local_player_id = None;
+            guard.outbound.clear();
+            restore
+        }
+        Err(_) => false,
+    };
 
     TRANSPORT_RUNNING.store(false, Ordering::Release);
     TRANSPORT_STOP.store(true, Ordering::Release);
 
     crate::remote_players::clear_all();
+    crate::economy::reset();
+    crate::respawn::reset();
+    crate::race::reset();
+    crate::world::reset();
+    crate::vehicles::reset();
+    crate::overlay::state::set_round(None);
+    crate::overlay::state::set_teams(Vec::new());
     crate::overlay::state::clear_players();
+
+    if restore {
+        start_reconnect(reason);
+        return;
+    }
     crate::overlay::state::set_connection_status(false, reason.to_string());
     crate::overlay::state::add_system_message(reason.to_string());
 }
 
+/// Задержка перед попыткой `attempt` (с 1).
+fn reconnect_delay(attempt: u32) -> Duration {
+    let doublings = attempt.saturating_sub(1).min(16);
+    RECONNECT_BASE_DELAY
+        .saturating_mul(1 << doublings)
+        .min(RECONNECT_MAX_DELAY)
+}
+
+/// Запустить поток переподключения, если он ещё не идёт.
+fn start_reconnect(reason: &str) {
+    if RECONNECT_RUNNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    RECONNECT_CANCEL.store(false, Ordering::Release);
+
+    let reason = reason.to_string();
+    thread::spawn(move || {
+        reconnect_thread_main(reason);
+        RECONNECT_RUNNING.store(false, Ordering::Release);
+    });
+}
+
+/// Попытки с backoff, пока одна не откроет сокет, их не исчерпаем или
+/// не отменят. Открытый сокет отдаётся transport thread; если сессия
+/// снова оборвётся до `ConnectAccepted`, `transport_fail_disconnect`
+/// запустит следующую попытку.
+fn reconnect_thread_main(mut reason: String) {
+    loop {
+        let (attempt, addr, nickname, password) = {
+            let Ok(mut guard) = state().lock() else {
+                return;
+            };
+            if RECONNECT_CANCEL.load(Ordering::Acquire) {
+                guard.reconnect_attempt = 0;
+                return;
+            }
+            guard.reconnect_attempt += 1;
+            (
+                guard.reconnect_attempt,
+                guard.server_addr.clone(),
+                guard.nickname.clone(),
+                guard.password.clone(),
+            )
+        };
+
+        if attempt > RECONNECT_MAX_ATTEMPTS {
+            if let Ok(mut guard) = state().lock() {
+                guard.reconnect_attempt = 0;
+            }
+            logger::warn(&format!(
+                "[network] reconnect to {addr} gave up after {RECONNECT_MAX_ATTEMPTS} attempts"
+            ));
+            let text = format!("Не удалось переподключиться: {reason}");
+            crate::overlay::state::set_connection_status(false, text.clone());
+            crate::overlay::state::add_system_message(text);
+            return;
+        }
+        if attempt == 1 {
+            crate::overlay::state::add_system_message(format!(
+                "{reason}. Переподключение..."
+            ));
+        }
+
+        let info = crate::overlay::state::ReconnectInfo {
+            attempt,
+            max_attempts: RECONNECT_MAX_ATTEMPTS,
+        };
+        let delay = reconnect_delay(attempt);
+        logger::info(&format!(
+            "[network] reconnect attempt {attempt}/{RECONNECT_MAX_ATTEMPTS} to {addr} in {delay:?}"
+        ));
+        crate::overlay::state::set_reconnecting(
+            info,
+            format!("Переподключение через {} с: {reason}", delay.as_secs()),
+        );
+
+        if !sleep_unless_reconnect_cancelled(delay) {
+            return;
+        }
+
+        crate::overlay::state::set_reconnecting(info, format!("Переподключение к {addr}..."));
+        match open_stream(&addr) {
+            Ok(stream) => {
+                if RECONNECT_CANCEL.load(Ordering::Acquire) {
+                    return;
+                }
+                if let Ok(mut guard) = state().lock() {
+                    guard.connected = true;
+                    guard.local_player_id = None;
+                    guard.outbound.clear();
+                    guard.inbound.clear();
+                }
+                start_transport(stream, &nickname, &password);
+                return;
+            }
+            Err(e) => {
+                logger::warn(&format!("[network] reconnect to {addr} failed: {e}"));
+                reason = format!("Ошибка подключения: {e}");
+            }
+        }
+    }
+}
+
+/// `false` — переподключение отменили во время ожидания.
+fn sleep_unless_reconnect_cancelled(delay: Duration) -> bool {
+    let until = Instant::now() + delay;
+    while Instant::now() < until {
+        if RECONNECT_CANCEL.load(Ordering::Acquire) {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    !RECONNECT_CANCEL.load(Ordering::Acquire)
+}
+
+fn open_stream(addr: &str) -> std::io::Result<TcpStream> {
+    let resolved = addr.to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "адрес не разрешился")
+    })?;
+    TcpStream::connect_timeout(&resolved, RECONNECT_CONNECT_TIMEOUT)
+}
+
+/// Отменить автопереподключение. `false` — оно не шло.
+pub fn cancel_reconnect() -> bool {
+    if !is_reconnecting() {
+        return false;
+    }
+    RECONNECT_CANCEL.store(true, Ordering::Release);
+
+    if let Ok(mut guard) = state().lock() {
+        guard.reconnect_attempt = 0;
+        // Попытка могла уже открыть сокет и ждать `ConnectAccepted`.
+        if guard.local_player_id.is_none() && TRANSPORT_RUNNING.load(Ordering::Acquire) {
+            guard.connected = false;
+            TRANSPORT_STOP.store(true, Ordering::Release);
+        }
+    }
+
+    logger::info("[network] reconnect cancelled");
+    crate::overlay::state::set_connection_status(false, "Переподключение отменено".to_string());
+    true
+}
+
 /// Автоматически оборвать session, если игра ушла в меню/выгрузку.
 pub fn auto_disconnect_if_session_invalid() {
     use crate::state::GameSessionState;
@@ -493,7 +1096,7 @@ pub fn auto_disconnect_if_session_invalid() {
         GameSessionState::Boot
         | GameSessionState::FrontendMenu
         | GameSessionState::ShuttingDown => {
-            if is_connected() || TRANSPORT_RUNNING.load(Ordering::Acquire) {
+            if is_connected() || TRANSPORT_RUNNING.load(Ordering::Acquire) || is_reconnecting() {
                 logger::info("[network] auto-disconnect: session no longer in game");
                 let _ = disconnect();
             }
@@ -59,16 +59,19 @@ struct MouseState {
 static MOUSE: LazyLock<Mutex<MouseState>> =
     LazyLock::new(|| Mutex::new(MouseState { lmb: false, rmb: false }));
 
+/// `scale` — zoom factor контекста: egui ждёт координаты в точках, а не
+/// в пикселях.
 pub fn collect(
     screen_w: f32,
     screen_h: f32,
     hwnd: Option<usize>,
     wants_input: bool,
+    scale: f32,
 ) -> egui::RawInput {
     let mut raw = egui::RawInput {
         screen_rect: Some(egui::Rect::from_min_size(
             egui::Pos2::ZERO,
-            egui::Vec2::new(screen_w, screen_h),
+            egui::Vec2::new(screen_w, screen_h) / scale,
         )),
         modifiers: modifiers(),
         ..Default::default()
@@ -78,7 +81,7 @@ pub fn collect(
         return raw;
     }
 
-    if let Some(pos) = cursor_pos(hwnd) {
+    if let Some(pos) = cursor_pos(hwnd).map(|p| egui::pos2(p.x / scale, p.y / scale)) {
         raw.events.push(egui::Event::PointerMoved(pos));
         collect_mouse_buttons(&mut raw.events, pos);
     }
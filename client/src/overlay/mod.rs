@@ -139,11 +139,15 @@ pub fn render_frame() {
     let hwnd = sdk::game::render::get_hwnd();
     let wants_input = state::wants_input();
     input_lock::tick(wants_input);
+    if inner.ctx.zoom_factor() != snap.ui_scale {
+        inner.ctx.set_zoom_factor(snap.ui_scale);
+    }
     let mut raw_input = input::collect(
         inner.screen_w as f32,
         inner.screen_h as f32,
         hwnd,
         wants_input,
+        snap.ui_scale,
     );
     raw_input.time = Some(inner.start_time.elapsed().as_secs_f64());
 
@@ -2,33 +2,53 @@
 
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{LazyLock, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use sdk::game::Player;
 
 const NOTIFICATION_DURATION_SECS: u64 = 4;
 const MAX_NOTIFICATIONS: usize = 5;
 const NOTIFY_DEDUP_SECS: u64 = 2;
+const KILL_FEED_DURATION_SECS: u64 = 6;
+const MAX_KILL_FEED: usize = 5;
 
 static SHOW_DEBUG: AtomicBool = AtomicBool::new(true);
+static CHAT_FADE: AtomicBool = AtomicBool::new(true);
+static SHOW_NETGRAPH: AtomicBool = AtomicBool::new(false);
+/// Масштаб оверлея (`f32` битами).
+static UI_SCALE: AtomicU32 = AtomicU32::new(1.0f32.to_bits());
 static FPS: AtomicU32 = AtomicU32::new(0);
 static POS_X: AtomicU32 = AtomicU32::new(0);
 static POS_Y: AtomicU32 = AtomicU32::new(0);
 static POS_Z: AtomicU32 = AtomicU32::new(0);
 
 pub(crate) static SHOW_CONNECT: AtomicBool = AtomicBool::new(false);
+pub(crate) static SHOW_BROWSER: AtomicBool = AtomicBool::new(false);
 pub(crate) static SHOW_PLAYERS: AtomicBool = AtomicBool::new(false);
 pub(crate) static SHOW_SCOREBOARD: AtomicBool = AtomicBool::new(false);
 pub(crate) static SHOW_CONSOLE: AtomicBool = AtomicBool::new(false);
+pub(crate) static SHOW_SETTINGS: AtomicBool = AtomicBool::new(false);
 pub(crate) static CHAT_INPUT_OPEN: AtomicBool = AtomicBool::new(false);
+/// Поле ввода открыто для командного чата.
+static CHAT_TEAM: AtomicBool = AtomicBool::new(false);
 
 #[derive(Clone)]
 pub struct ConnectionInfo {
     pub ip: String,
     pub port: String,
     pub nickname: String,
+    /// Пароль сервера; пустой — без пароля.
+    pub password: String,
     pub connected: bool,
     pub status: String,
+    /// Идёт автопереподключение после обрыва.
+    pub reconnect: Option<ReconnectInfo>,
+}
+
+#[derive(Clone, Copy)]
+pub struct ReconnectInfo {
+    pub attempt: u32,
+    pub max_attempts: u32,
 }
 
 impl Default for ConnectionInfo {
@@ -37,8 +57,10 @@ impl Default for ConnectionInfo {
             ip: "127.0.0.1".into(),
             port: "7788".into(),
             nickname: "Player".into(),
+            password: String::new(),
             connected: false,
             status: "Не подключен".into(),
+            reconnect: None,
         }
     }
 }
@@ -49,6 +71,103 @@ pub struct PlayerEntry {
     pub name: String,
     pub ping: u32,
     pub is_local: bool,
+    pub kills: u32,
+    pub deaths: u32,
+    /// `id` команды; `None` — без команды.
+    pub team: Option<String>,
+}
+
+/// Команда сервера.
+#[derive(Clone)]
+pub struct TeamInfo {
+    pub id: String,
+    pub name: String,
+    pub color: [u8; 3],
+}
+
+#[derive(Clone)]
+pub struct KillFeedEntry {
+    /// `None` — смерть без убийцы.
+    pub killer: Option<String>,
+    pub victim: String,
+    pub weapon_id: Option<u32>,
+    pub headshot: bool,
+    /// Локальный игрок — убийца или жертва (подсветка).
+    pub involves_local: bool,
+    pub created: Instant,
+}
+
+/// Текущий раунд режима с лимитами (deathmatch).
+#[derive(Clone)]
+pub struct RoundInfo {
+    pub mode: String,
+    pub score_limit: u32,
+    /// Конец раунда по времени; `None` — без лимита.
+    pub ends_at: Option<Instant>,
+    pub finished: bool,
+}
+
+/// Race HUD локального игрока (пока он записан в заезд).
+#[derive(Clone, Debug)]
+pub struct RaceHud {
+    pub track: String,
+    /// Старт (идёт отсчёт).
+    pub go_at: Option<Instant>,
+    /// Момент старта (идёт заезд).
+    pub started: Option<Instant>,
+    /// Место и время, если финишировал.
+    pub finished: Option<(u32, u32)>,
+    /// Позиция в гонке / число гонщиков.
+    pub place: Option<(usize, usize)>,
+    pub lap: u32,
+    pub laps: u32,
+    /// Индекс следующего чекпоинта.
+    pub checkpoint: usize,
+    pub checkpoints: usize,
+    pub splits: Vec<RaceSplit>,
+    pub best_lap_ms: Option<u32>,
+    pub target: Option<RaceTarget>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RaceSplit {
+    pub lap: u32,
+    pub checkpoint: u32,
+    pub split_ms: u32,
+    pub lap_ms: Option<u32>,
+}
+
+/// Следующий чекпоинт относительно игрока.
+#[derive(Clone, Copy, Debug)]
+pub struct RaceTarget {
+    /// Угол от направления взгляда, градусы; положительный — справа.
+    pub bearing_deg: f32,
+    pub distance_m: f32,
+    pub is_finish: bool,
+}
+
+/// Данные netgraph: транспорт и буферы интерполяции удалённых игроков.
+#[derive(Clone, Debug)]
+pub struct NetGraph {
+    pub net: crate::net_debug::NetStats,
+    pub remotes: Vec<crate::remote_players::InterpStats>,
+}
+
+/// Камера и головы удалённых игроков на момент последнего game-тика.
+#[derive(Clone, Debug)]
+pub struct NametagFrame {
+    pub camera: common::projection::Camera,
+    pub tags: Vec<Nametag>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Nametag {
+    pub player_id: u32,
+    pub name: String,
+    /// Точка над головой, мировые координаты.
+    pub head: common::interp::Vec3,
+    /// Доля здоровья `0..=1`.
+    pub health: f32,
 }
 
 #[derive(Clone)]
@@ -57,6 +176,8 @@ pub struct ChatMsg {
     pub text: String,
     pub time: String,
     pub system: bool,
+    /// Сообщение командного чата.
+    pub team: bool,
     pub created: Instant,
 }
 
@@ -104,6 +225,39 @@ static CONSOLE_INPUT: LazyLock<Mutex<String>> =
 
 static CONSOLE_COUNTER: AtomicU32 = AtomicU32::new(0);
 
+static KILL_FEED: LazyLock<Mutex<Vec<KillFeedEntry>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+static ROUND: Mutex<Option<RoundInfo>> = Mutex::new(None);
+
+static TEAMS: Mutex<Vec<TeamInfo>> = Mutex::new(Vec::new());
+
+static RACE_HUD: Mutex<Option<RaceHud>> = Mutex::new(None);
+
+static NAMETAGS: Mutex<Option<NametagFrame>> = Mutex::new(None);
+
+/// Момент серверного респауна (пока игрок мёртв).
+static RESPAWN_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Показать/скрыть отсчёт до респауна.
+pub fn set_respawn_countdown(delay: Option<Duration>) {
+    if let Ok(mut at) = RESPAWN_AT.lock() {
+        *at = delay.map(|d| Instant::now() + d);
+    }
+}
+
+pub fn set_race_hud(hud: Option<RaceHud>) {
+    if let Ok(mut h) = RACE_HUD.lock() {
+        *h = hud;
+    }
+}
+
+pub fn set_nametags(frame: Option<NametagFrame>) {
+    if let Ok(mut n) = NAMETAGS.lock() {
+        *n = frame;
+    }
+}
+
 pub fn set_fps(fps: f32) {
     FPS.store(fps.to_bits(), Ordering::Relaxed);
 }
@@ -111,6 +265,33 @@ pub fn set_fps(fps: f32) {
 pub fn toggle_debug() {
     let v = !SHOW_DEBUG.load(Ordering::Relaxed);
     SHOW_DEBUG.store(v, Ordering::Relaxed);
+    crate::settings::update(|s| s.overlay.show_debug = v);
+}
+
+pub fn set_show_debug(v: bool) {
+    SHOW_DEBUG.store(v, Ordering::Relaxed);
+}
+
+pub fn toggle_netgraph() {
+    let v = !SHOW_NETGRAPH.load(Ordering::Relaxed);
+    SHOW_NETGRAPH.store(v, Ordering::Relaxed);
+    crate::settings::update(|s| s.overlay.show_netgraph = v);
+}
+
+pub fn set_show_netgraph(v: bool) {
+    SHOW_NETGRAPH.store(v, Ordering::Relaxed);
+}
+
+pub fn set_chat_fade(v: bool) {
+    CHAT_FADE.store(v, Ordering::Relaxed);
+}
+
+pub fn set_ui_scale(scale: f32) {
+    UI_SCALE.store(scale.to_bits(), Ordering::Relaxed);
+}
+
+pub fn ui_scale() -> f32 {
+    f32::from_bits(UI_SCALE.load(Ordering::Relaxed))
 }
 
 pub fn notify(text: &str) {
@@ -140,6 +321,17 @@ pub fn close_connect() {
     SHOW_CONNECT.store(false, Ordering::Relaxed);
 }
 
+/// Переключить браузер серверов. `true` — окно открылось.
+pub fn toggle_browser() -> bool {
+    let v = !SHOW_BROWSER.load(Ordering::Relaxed);
+    SHOW_BROWSER.store(v, Ordering::Relaxed);
+    v
+}
+
+pub fn close_browser() {
+    SHOW_BROWSER.store(false, Ordering::Relaxed);
+}
+
 pub fn toggle_players() {
     let v = !SHOW_PLAYERS.load(Ordering::Relaxed);
     SHOW_PLAYERS.store(v, Ordering::Relaxed);
@@ -154,7 +346,25 @@ pub fn toggle_console() {
     SHOW_CONSOLE.store(v, Ordering::Relaxed);
 }
 
+pub fn toggle_settings() {
+    let v = !SHOW_SETTINGS.load(Ordering::Relaxed);
+    SHOW_SETTINGS.store(v, Ordering::Relaxed);
+}
+
+pub fn close_settings() {
+    SHOW_SETTINGS.store(false, Ordering::Relaxed);
+    crate::keybinds::cancel_capture();
+    crate::keybinds::clear_pending();
+}
+
 pub fn open_chat_input() {
+    CHAT_TEAM.store(false, Ordering::Relaxed);
+    CHAT_INPUT_OPEN.store(true, Ordering::Relaxed);
+    super::input::flush();
+}
+
+pub fn open_team_chat_input() {
+    CHAT_TEAM.store(true, Ordering::Relaxed);
     CHAT_INPUT_OPEN.store(true, Ordering::Relaxed);
     super::input::flush();
 }
@@ -168,6 +378,10 @@ pub fn close_topmost() {
         SHOW_CONSOLE.store(false, Ordering::Relaxed);
     } else if CHAT_INPUT_OPEN.load(Ordering::Relaxed) {
         close_chat_input();
+    } else if SHOW_SETTINGS.load(Ordering::Relaxed) {
+        close_settings();
+    } else if SHOW_BROWSER.load(Ordering::Relaxed) {
+        close_browser();
     } else if SHOW_CONNECT.load(Ordering::Relaxed) {
         close_connect();
     } else if SHOW_PLAYERS.load(Ordering::Relaxed) {
@@ -177,15 +391,18 @@ pub fn close_topmost() {
 
 pub fn wants_input() -> bool {
     SHOW_CONNECT.load(Ordering::Relaxed)
+        || SHOW_BROWSER.load(Ordering::Relaxed)
         || SHOW_PLAYERS.load(Ordering::Relaxed)
         || CHAT_INPUT_OPEN.load(Ordering::Relaxed)
         || SHOW_CONSOLE.load(Ordering::Relaxed)
+        || SHOW_SETTINGS.load(Ordering::Relaxed)
 }
 
 pub fn set_connection(connected: bool, status: &str) {
     if let Ok(mut c) = CONNECTION.lock() {
         c.connected = connected;
         c.status = status.to_string();
+        c.reconnect = None;
     }
     notify(if connected { "Подключено к серверу" } else { status });
 }
@@ -194,10 +411,19 @@ pub fn set_connection_status(connected: bool, status: String) {
     set_connection(connected, &status);
 }
 
+/// Статус автопереподключения — без уведомления на каждую попытку.
+pub fn set_reconnecting(info: ReconnectInfo, status: String) {
+    if let Ok(mut c) = CONNECTION.lock() {
+        c.connected = false;
+        c.status = status;
+        c.reconnect = Some(info);
+    }
+}
+
 pub fn add_player(id: u32, name: String, ping: u32, is_local: bool) {
     if let Ok(mut p) = PLAYERS.lock() {
         if !p.iter().any(|e| e.id == id) {
-            p.push(PlayerEntry { id, name, ping, is_local });
+            p.push(PlayerEntry { id, name, ping, is_local, kills: 0, deaths: 0, team: None });
         }
     }
 }
@@ -220,6 +446,66 @@ pub fn update_player_ping(id: u32, ping: u32) {
     update_ping(id, ping);
 }
 
+pub fn set_player_score(id: u32, kills: u32, deaths: u32) {
+    if let Ok(mut p) = PLAYERS.lock() {
+        if let Some(e) = p.iter_mut().find(|e| e.id == id) {
+            e.kills = kills;
+            e.deaths = deaths;
+        }
+    }
+}
+
+pub fn reset_scores() {
+    if let Ok(mut p) = PLAYERS.lock() {
+        for e in p.iter_mut() {
+            e.kills = 0;
+            e.deaths = 0;
+        }
+    }
+}
+
+pub fn set_teams(teams: Vec<TeamInfo>) {
+    if let Ok(mut t) = TEAMS.lock() {
+        *t = teams;
+    }
+}
+
+pub fn set_player_team(id: u32, team: Option<String>) {
+    if let Ok(mut p) = PLAYERS.lock() {
+        if let Some(e) = p.iter_mut().find(|e| e.id == id) {
+            e.team = team;
+        }
+    }
+}
+
+pub fn player_name(id: u32) -> Option<String> {
+    PLAYERS.lock().ok()?.iter().find(|e| e.id == id).map(|e| e.name.clone())
+}
+
+pub fn add_kill(entry: KillFeedEntry) {
+    if let Ok(mut feed) = KILL_FEED.lock() {
+        feed.push(entry);
+        while feed.len() > MAX_KILL_FEED {
+            feed.remove(0);
+        }
+    }
+}
+
+pub fn set_round(round: Option<RoundInfo>) {
+    if let Ok(mut r) = ROUND.lock() {
+        *r = round;
+    }
+}
+
+pub fn finish_round() {
+    if let Ok(mut r) = ROUND.lock() {
+        if let Some(round) = r.as_mut() {
+            round.finished = true;
+            round.ends_at = None;
+        }
+    }
+}
+
 pub fn clear_players() {
     if let Ok(mut p) = PLAYERS.lock() {
         p.clear();
@@ -227,12 +513,21 @@ pub fn clear_players() {
 }
 
 pub fn add_chat_msg(author: &str, text: &str) {
+    push_chat_msg(author, text, false);
+}
+
+pub fn add_team_chat_msg(author: &str, text: &str) {
+    push_chat_msg(author, text, true);
+}
+
+fn push_chat_msg(author: &str, text: &str, team: bool) {
     if let Ok(mut msgs) = CHAT.lock() {
         msgs.push(ChatMsg {
             author: author.to_string(),
             text: text.to_string(),
             time: chrono::Local::now().format("%H:%M").to_string(),
             system: false,
+            team,
             created: Instant::now(),
         });
         if msgs.len() > 100 { msgs.remove(0); }
@@ -250,6 +545,7 @@ pub fn add_system_msg(text: &str) {
             text: text.to_string(),
             time: chrono::Local::now().format("%H:%M").to_string(),
             system: true,
+            team: false,
             created: Instant::now(),
         });
         if msgs.len() > 100 { msgs.remove(0); }
@@ -328,16 +624,23 @@ pub fn sync_player_controls() {
 #[derive(Clone)]
 pub struct Snapshot {
     pub show_debug: bool,
+    /// Пассивный чат гаснет.
+    pub chat_fade: bool,
+    pub ui_scale: f32,
     pub fps: f32,
     pub pos: [f32; 3],
     pub game_state: &'static str,
     pub local_ping: u32,
 
     pub show_connect: bool,
+    pub show_browser: bool,
     pub show_players: bool,
     pub show_scoreboard: bool,
     pub show_console: bool,
+    pub show_settings: bool,
     pub chat_input_open: bool,
+    /// Поле ввода — командный чат.
+    pub chat_team: bool,
 
     pub connection: ConnectionInfo,
     pub players: Vec<PlayerEntry>,
@@ -347,6 +650,25 @@ pub struct Snapshot {
 
     pub console_entries: Vec<ConsoleEntry>,
     pub console_input: String,
+
+    /// Секунд до респауна; `None` — игрок жив.
+    pub respawn_countdown: Option<f32>,
+
+    pub kill_feed: Vec<KillFeedEntry>,
+    pub round: Option<RoundInfo>,
+    pub race: Option<RaceHud>,
+    pub teams: Vec<TeamInfo>,
+    pub nametags: Option<NametagFrame>,
+    /// `Some` — панель netgraph включена.
+    pub netgraph: Option<NetGraph>,
+}
+
+impl Snapshot {
+    /// Цвет команды `team` (RGB).
+    pub fn team_color(&self, team: Option<&str>) -> Option<[u8; 3]> {
+        let team = team?;
+        self.teams.iter().find(|t| t.id == team).map(|t| t.color)
+    }
 }
 
 pub fn snapshot() -> Snapshot {
@@ -380,6 +702,28 @@ pub fn snapshot() -> Snapshot {
         crate::state::GameSessionState::ShuttingDown => "Выход",
     };
 
+    let kill_feed = KILL_FEED
+        .lock()
+        .map(|mut feed| {
+            feed.retain(|k| k.created.elapsed().as_secs() < KILL_FEED_DURATION_SECS);
+            feed.clone()
+        })
+        .unwrap_or_default();
+    let round = ROUND.lock().map(|r| r.clone()).unwrap_or_default();
+    let race = RACE_HUD.lock().map(|r| r.clone()).unwrap_or_default();
+    let nametags = NAMETAGS.lock().map(|n| n.clone()).unwrap_or_default();
+    let netgraph = SHOW_NETGRAPH.load(Ordering::Relaxed).then(|| NetGraph {
+        net: crate::net_debug::stats(),
+        remotes: crate::remote_players::interp_stats(),
+    });
+    let teams = TEAMS.lock().map(|t| t.clone()).unwrap_or_default();
+
+    let respawn_countdown = RESPAWN_AT
+        .lock()
+        .ok()
+        .and_then(|at| *at)
+        .map(|at| at.saturating_duration_since(Instant::now()).as_secs_f32());
+
     let local_ping = players
         .iter()
         .find(|p| p.is_local)
@@ -388,6 +732,8 @@ pub fn snapshot() -> Snapshot {
 
     Snapshot {
         show_debug: SHOW_DEBUG.load(Ordering::Relaxed),
+        chat_fade: CHAT_FADE.load(Ordering::Relaxed),
+        ui_scale: ui_scale(),
         fps: f32::from_bits(FPS.load(Ordering::Relaxed)),
         pos: [
             f32::from_bits(POS_X.load(Ordering::Relaxed)),
@@ -397,11 +743,15 @@ pub fn snapshot() -> Snapshot {
         game_state,
         local_ping,
         show_connect: SHOW_CONNECT.load(Ordering::Relaxed),
+        show_browser: SHOW_BROWSER.load(Ordering::Relaxed),
         show_players: SHOW_PLAYERS.load(Ordering::Relaxed),
         show_scoreboard: SHOW_SCOREBOARD.load(Ordering::Relaxed),
         show_console: SHOW_CONSOLE.load(Ordering::Relaxed),
+        show_settings: SHOW_SETTINGS.load(Ordering::Relaxed),
         chat_input_open: CHAT_INPUT_OPEN.load(Ordering::Relaxed),
+        chat_team: CHAT_TEAM.load(Ordering::Relaxed),
         connection, players, chat_msgs, chat_input, notifications,
-        console_entries, console_input,
+        console_entries, console_input, respawn_countdown,
+        kill_feed, round, race, teams, nametags, netgraph,
     }
 }
\ No newline at end of file
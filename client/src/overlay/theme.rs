@@ -59,6 +59,10 @@ pub mod sizes {
     pub const CHAT_HEIGHT: f32 = 320.0;
     pub const PLAYER_LIST_WIDTH: f32 = 280.0;
     pub const CONNECT_WIDTH: f32 = 400.0;
+    pub const SETTINGS_WIDTH: f32 = 360.0;
+    pub const BROWSER_WIDTH: f32 = 620.0;
+    /// Зазор между окном подключения и браузером серверов.
+    pub const WINDOW_GAP: f32 = 12.0;
     pub const SCOREBOARD_WIDTH: f32 = 540.0;
     pub const CONSOLE_WIDTH: f32 = 640.0;
     pub const CONSOLE_HEIGHT: f32 = 420.0;
@@ -159,6 +163,11 @@ pub fn hotkey_chip(ui: &mut egui::Ui, key: &str) {
         });
 }
 
+/// Цвет команды из RGB сервера.
+pub fn team_color(rgb: [u8; 3]) -> Color32 {
+    Color32::from_rgb(rgb[0], rgb[1], rgb[2])
+}
+
 /// Применить альфу поверх премультиплицированного цвета.
 pub fn fade(color: Color32, alpha: f32) -> Color32 {
     let a = alpha.clamp(0.0, 1.0);
@@ -0,0 +1,396 @@
+//! Браузер серверов — окно рядом с окном подключения.
+//!
+//! Клик по заголовку — сортировка, клик по ★ — избранное, двойной клик по
+//! строке — подключение.
+
+use egui::{Align, Align2, Color32, FontId, Layout, RichText, Sense, TextEdit, Vec2};
+
+use crate::overlay::state::{self, Snapshot};
+use crate::overlay::theme::{self, colors, sizes};
+use crate::server_browser::{self, Row, SortKey, Tab, View};
+
+const COL_FAV: f32 = 22.0;
+const COL_PLAYERS: f32 = 70.0;
+const COL_MODE: f32 = 100.0;
+const COL_PING: f32 = 64.0;
+const ROW_HEIGHT: f32 = 22.0;
+const LIST_HEIGHT: f32 = 300.0;
+
+pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
+    let offset = if snap.show_connect {
+        (sizes::CONNECT_WIDTH + sizes::WINDOW_GAP) * 0.5
+    } else {
+        0.0
+    };
+
+    let mut view = server_browser::view();
+
+    egui::Window::new("server_browser")
+        .anchor(Align2::CENTER_CENTER, Vec2::new(offset, 0.0))
+        .resizable(false)
+        .collapsible(false)
+        .title_bar(false)
+        .frame(theme::panel_frame().inner_margin(egui::Margin::ZERO))
+        .fixed_size(Vec2::new(sizes::BROWSER_WIDTH, 0.0))
+        .show(ctx, |ui| {
+            theme::header_bar(ui, "СЕРВЕРЫ", Some("двойной клик — подключиться"));
+            egui::Frame::NONE
+                .inner_margin(egui::Margin::symmetric(14, 10))
+                .show(ui, |ui| {
+                    draw_tabs(ui, &mut view);
+                    ui.add_space(6.0);
+                    draw_filters(ui, &mut view);
+                    ui.add_space(6.0);
+                    draw_table_header(ui, &mut view);
+                    ui.add(egui::Separator::default().spacing(2.0));
+                    draw_rows(ui, &mut view);
+                    ui.add_space(8.0);
+                    draw_actions(ui, &view);
+                });
+        });
+
+    server_browser::set_view(view);
+}
+
+fn draw_tabs(ui: &mut egui::Ui, view: &mut View) {
+    ui.horizontal(|ui| {
+        for tab in Tab::ALL {
+            let active = view.tab == tab;
+            let button = egui::Button::new(
+                RichText::new(tab.label())
+                    .size(10.5)
+                    .extra_letter_spacing(1.5)
+                    .color(if active {
+                        colors::GOLD
+                    } else {
+                        colors::TEXT_SECONDARY
+                    }),
+            )
+            .fill(if active {
+                colors::BG_ACTIVE
+            } else {
+                colors::BG_WIDGET
+            })
+            .stroke(egui::Stroke::new(
+                1.0,
+                if active {
+                    colors::BORDER_ACTIVE
+                } else {
+                    colors::BORDER
+                },
+            ));
+            if ui.add(button).clicked() {
+                view.tab = tab;
+                view.selected = None;
+            }
+        }
+    });
+}
+
+fn draw_filters(ui: &mut egui::Ui, view: &mut View) {
+    ui.horizontal(|ui| {
+        ui.add(
+            TextEdit::singleline(&mut view.search)
+                .desired_width(180.0)
+                .hint_text("Поиск по имени")
+                .font(FontId::proportional(12.0))
+                .margin(egui::Margin::symmetric(8, 4)),
+        );
+        ui.checkbox(&mut view.hide_full, filter_label("без полных"));
+        ui.checkbox(&mut view.hide_password, filter_label("без пароля"));
+        ui.checkbox(&mut view.compatible_only, filter_label("совместимые"));
+    });
+}
+
+fn filter_label(text: &str) -> RichText {
+    RichText::new(text).size(11.0).color(colors::TEXT_SECONDARY)
+}
+
+fn name_width(ui: &egui::Ui) -> f32 {
+    ui.available_width() - COL_FAV - COL_PLAYERS - COL_MODE - COL_PING - 16.0
+}
+
+fn draw_table_header(ui: &mut egui::Ui, view: &mut View) {
+    let name_w = name_width(ui);
+
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 4.0;
+        ui.allocate_ui_with_layout(
+            Vec2::new(COL_FAV, ROW_HEIGHT),
+            Layout::left_to_right(Align::Center),
+            |ui| {
+                ui.add_space(0.0);
+            },
+        );
+        for (key, label, width, layout) in [
+            (
+                SortKey::Name,
+                "ИМЯ",
+                name_w,
+                Layout::left_to_right(Align::Center),
+            ),
+            (
+                SortKey::Players,
+                "ИГРОКИ",
+                COL_PLAYERS,
+                Layout::right_to_left(Align::Center),
+            ),
+            (
+                SortKey::Mode,
+                "РЕЖИМ",
+                COL_MODE,
+                Layout::left_to_right(Align::Center),
+            ),
+            (
+                SortKey::Ping,
+                "ПИНГ",
+                COL_PING,
+                Layout::right_to_left(Align::Center),
+            ),
+        ] {
+            ui.allocate_ui_with_layout(Vec2::new(width, ROW_HEIGHT), layout, |ui| {
+                let arrow = match (view.sort == key, view.descending) {
+                    (true, true) => " ▼",
+                    (true, false) => " ▲",
+                    (false, _) => "",
+                };
+                let color = if view.sort == key {
+                    colors::GOLD
+                } else {
+                    colors::TEXT_MUTED
+                };
+                let header = ui.add(
+                    egui::Label::new(
+                        RichText::new(format!("{label}{arrow}"))
+                            .size(10.5)
+                            .color(color)
+                            .strong()
+                            .extra_letter_spacing(2.0),
+                    )
+                    .sense(Sense::click()),
+                );
+                if header.clicked() {
+                    view.sort_by(key);
+                }
+            });
+        }
+    });
+}
+
+fn draw_rows(ui: &mut egui::Ui, view: &mut View) {
+    let rows = server_browser::rows(view);
+
+    egui::ScrollArea::vertical()
+        .id_salt("browser_rows")
+        .max_height(LIST_HEIGHT)
+        .min_scrolled_height(LIST_HEIGHT)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if rows.is_empty() {
+                empty_state(ui, view.tab);
+                return;
+            }
+            for (i, row) in rows.iter().enumerate() {
+                draw_row(ui, row, i, view);
+            }
+        });
+}
+
+fn empty_state(ui: &mut egui::Ui, tab: Tab) {
+    let text = if crate::discovery::is_refreshing() {
+        "Поиск серверов..."
+    } else {
+        match tab {
+            Tab::Favorites => "Нет избранных серверов",
+            Tab::Recent => "Вы ещё ни к кому не подключались",
+            Tab::Internet | Tab::Lan => "Серверы не найдены",
+        }
+    };
+    ui.vertical_centered(|ui| {
+        ui.add_space(40.0);
+        ui.label(RichText::new(text).size(12.0).color(colors::TEXT_MUTED));
+    });
+}
+
+fn draw_row(ui: &mut egui::Ui, row: &Row, idx: usize, view: &mut View) {
+    let name_w = name_width(ui);
+    let selected = view.selected == Some(row.addr);
+    let bg = if selected {
+        colors::BG_ACTIVE
+    } else if idx % 2 == 1 {
+        colors::BG_ROW_ALT
+    } else {
+        Color32::TRANSPARENT
+    };
+    let online = row.info.is_some();
+    let text_color = if online {
+        colors::TEXT_PRIMARY
+    } else {
+        colors::TEXT_MUTED
+    };
+
+    let response = egui::Frame::NONE
+        .fill(bg)
+        .corner_radius(egui::CornerRadius::same(2))
+        .inner_margin(egui::Margin::symmetric(2, 0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 4.0;
+
+                ui.allocate_ui_with_layout(
+                    Vec2::new(COL_FAV, ROW_HEIGHT),
+                    Layout::left_to_right(Align::Center),
+                    |ui| {
+                        let (star, color) = if row.favorite {
+                            ("★", colors::GOLD)
+                        } else {
+                            ("☆", colors::TEXT_MUTED)
+                        };
+                        let fav = ui.add(
+                            egui::Label::new(RichText::new(star).size(13.0).color(color))
+                                .sense(Sense::click()),
+                        );
+                        if fav.clicked() {
+                            server_browser::toggle_favorite(row.addr, &row.name);
+                        }
+                    },
+                );
+
+                ui.allocate_ui_with_layout(
+                    Vec2::new(name_w, ROW_HEIGHT),
+                    Layout::left_to_right(Align::Center),
+                    |ui| {
+                        ui.add(
+                            egui::Label::new(RichText::new(&row.name).size(12.5).color(text_color))
+                                .truncate(),
+                        );
+                        if row.info.as_ref().is_some_and(|i| i.password) {
+                            ui.label(RichText::new("🔒").size(11.0).color(colors::GOLD_DIM));
+                        }
+                    },
+                );
+
+                ui.allocate_ui_with_layout(
+                    Vec2::new(COL_PLAYERS, ROW_HEIGHT),
+                    Layout::right_to_left(Align::Center),
+                    |ui| {
+                        let text = match &row.info {
+                            Some(info) => format!("{}/{}", info.players, info.max_players),
+                            None => "—".to_string(),
+                        };
+                        ui.label(RichText::new(text).size(12.0).color(text_color).monospace());
+                    },
+                );
+
+                ui.allocate_ui_with_layout(
+                    Vec2::new(COL_MODE, ROW_HEIGHT),
+                    Layout::left_to_right(Align::Center),
+                    |ui| {
+                        let text =
+                            row.info.as_ref().map_or("нет ответа", |i| i.mode.as_str());
+                        ui.add(
+                            egui::Label::new(
+                                RichText::new(text).size(11.5).color(colors::TEXT_SECONDARY),
+                            )
+                            .truncate(),
+                        );
+                    },
+                );
+
+                ui.allocate_ui_with_layout(
+                    Vec2::new(COL_PING, ROW_HEIGHT),
+                    Layout::right_to_left(Align::Center),
+                    |ui| match row.ping_ms {
+                        Some(ping) => {
+                            let color = ping_color(ping);
+                            ui.label(
+                                RichText::new(format!("{ping}ms"))
+                                    .size(12.0)
+                                    .color(color)
+                                    .monospace(),
+                            );
+                            ui.add_space(4.0);
+                            theme::status_dot(ui, color, 5.0);
+                        }
+                        None => {
+                            ui.label(RichText::new("—").size(12.0).color(colors::TEXT_MUTED));
+                        }
+                    },
+                );
+            });
+        })
+        .response
+        .interact(Sense::click());
+
+    if response.double_clicked() {
+        server_browser::join(row);
+    } else if response.clicked() {
+        view.selected = Some(row.addr);
+    }
+}
+
+fn draw_actions(ui: &mut egui::Ui, view: &View) {
+    let refreshing = crate::discovery::is_refreshing();
+
+    ui.horizontal(|ui| {
+        let label = if refreshing {
+            "ПОИСК..."
+        } else {
+            "ОБНОВИТЬ"
+        };
+        let refresh = ui.add_enabled(
+            !refreshing,
+            egui::Button::new(RichText::new(label).size(12.0).color(colors::GOLD))
+                .fill(colors::BG_WIDGET)
+                .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+        );
+        if refresh.clicked() {
+            server_browser::refresh();
+        }
+
+        let selected = view.selected.and_then(|addr| {
+            server_browser::rows(view)
+                .into_iter()
+                .find(|r| r.addr == addr)
+        });
+        let join = ui.add_enabled(
+            selected.is_some(),
+            egui::Button::new(
+                RichText::new("ПОДКЛЮЧИТЬСЯ")
+                    .size(12.0)
+                    .color(colors::GOLD)
+                    .strong(),
+            )
+            .fill(colors::BG_WIDGET)
+            .stroke(egui::Stroke::new(1.0, colors::BORDER_STRONG)),
+        );
+        if join.clicked()
+            && let Some(row) = &selected
+        {
+            server_browser::join(row);
+        }
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            let close = ui.add(
+                egui::Button::new(
+                    RichText::new("ЗАКРЫТЬ")
+                        .size(12.0)
+                        .color(colors::TEXT_SECONDARY),
+                )
+                .fill(colors::BG_WIDGET)
+                .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+            );
+            if close.clicked() {
+                state::close_browser();
+            }
+        });
+    });
+}
+
+fn ping_color(ping: u32) -> Color32 {
+    match ping {
+        0..=50 => colors::GREEN,
+        51..=100 => colors::YELLOW,
+        _ => colors::RED,
+    }
+}
@@ -1,4 +1,5 @@
-//! Чат — пассивная "лента" внизу экрана + полноценное окно при открытии (T).
+//! Чат — пассивная "лента" внизу экрана + полноценное окно при открытии
+//! (T — общий, Y — командный).
 //!
 //! Пассивный режим: последние сообщения с плавным fade всего блока.
 //! Активный режим: тайтл-бар, история со скроллом, поле ввода.
@@ -11,6 +12,8 @@ use crate::overlay::theme::{self, colors, sizes};
 const PASSIVE_MAX_MSGS: usize = 8;
 const PASSIVE_VISIBLE_SECS: f32 = 10.0;
 const PASSIVE_FADE_START: f32 = 7.0;
+/// Метка сообщений командного чата.
+const TEAM_TAG: &str = "[КОМАНДА]";
 
 pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
     if snap.chat_input_open {
@@ -24,7 +27,7 @@ fn draw_passive(ctx: &egui::Context, snap: &Snapshot) {
     let recent: Vec<&ChatMsg> = snap
         .chat_msgs
         .iter()
-        .filter(|m| m.created.elapsed().as_secs_f32() < PASSIVE_VISIBLE_SECS)
+        .filter(|m| !snap.chat_fade || m.created.elapsed().as_secs_f32() < PASSIVE_VISIBLE_SECS)
         .rev()
         .take(PASSIVE_MAX_MSGS)
         .collect::<Vec<_>>()
@@ -36,10 +39,14 @@ fn draw_passive(ctx: &egui::Context, snap: &Snapshot) {
         return;
     }
 
-    let block_alpha = recent
-        .iter()
-        .map(|m| message_alpha(m))
-        .fold(0.0_f32, f32::max);
+    let block_alpha = if snap.chat_fade {
+        recent
+            .iter()
+            .map(|m| message_alpha(m))
+            .fold(0.0_f32, f32::max)
+    } else {
+        1.0
+    };
 
     egui::Area::new(egui::Id::new("chat_passive"))
         .anchor(Align2::LEFT_BOTTOM, Vec2::new(12.0, -12.0))
@@ -52,14 +59,18 @@ fn draw_passive(ctx: &egui::Context, snap: &Snapshot) {
                     ui.spacing_mut().item_spacing.y = 1.0;
 
                     for msg in &recent {
-                        draw_passive_msg(ui, msg, block_alpha);
+                        draw_passive_msg(ui, msg, block_alpha, snap.chat_fade);
                     }
                 });
         });
 }
 
-fn draw_passive_msg(ui: &mut egui::Ui, msg: &ChatMsg, block_alpha: f32) {
-    let alpha = message_alpha(msg).min(block_alpha);
+fn draw_passive_msg(ui: &mut egui::Ui, msg: &ChatMsg, block_alpha: f32, fade: bool) {
+    let alpha = if fade {
+        message_alpha(msg).min(block_alpha)
+    } else {
+        1.0
+    };
 
     ui.horizontal_wrapped(|ui| {
         ui.spacing_mut().item_spacing.x = 4.0;
@@ -78,6 +89,14 @@ fn draw_passive_msg(ui: &mut egui::Ui, msg: &ChatMsg, block_alpha: f32) {
                     .color(theme::fade(colors::CHAT_SYSTEM, alpha)),
             );
         } else {
+            if msg.team {
+                ui.label(
+                    RichText::new(TEAM_TAG)
+                        .size(11.0)
+                        .strong()
+                        .color(theme::fade(colors::BLUE, alpha)),
+                );
+            }
             ui.label(
                 RichText::new(format!("{}:", msg.author))
                     .size(12.0)
@@ -102,7 +121,8 @@ fn draw_active(ctx: &egui::Context, snap: &Snapshot) {
         .frame(theme::panel_frame().inner_margin(egui::Margin::ZERO))
         .fixed_size(egui::Vec2::new(sizes::CHAT_WIDTH, 0.0))
         .show(ctx, |ui| {
-            theme::header_bar(ui, "ЧАТЕРСЫ", Some("ENTER — отправить · ESC — закрыть"));
+            let title = if snap.chat_team { "КОМАНДНЫЙ ЧАТ" } else { "ЧАТЕРСЫ" };
+            theme::header_bar(ui, title, Some("ENTER — отправить · ESC — закрыть"));
 
             egui::Frame::NONE
                 .inner_margin(egui::Margin::same(10))
@@ -156,6 +176,14 @@ fn draw_active_history(ui: &mut egui::Ui, snap: &Snapshot) {
                                 .size(12.0),
                         );
                     } else {
+                        if msg.team {
+                            ui.label(
+                                RichText::new(TEAM_TAG)
+                                    .color(colors::BLUE)
+                                    .size(11.0)
+                                    .strong(),
+                            );
+                        }
                         ui.label(
                             RichText::new(format!("{}:", msg.author))
                                 .color(colors::CHAT_AUTHOR)
@@ -179,7 +207,11 @@ fn draw_active_input(ui: &mut egui::Ui, snap: &Snapshot) {
     let response = ui.add(
         TextEdit::singleline(&mut input)
             .desired_width(ui.available_width())
-            .hint_text("Сообщение...")
+            .hint_text(if snap.chat_team {
+                "Сообщение команде..."
+            } else {
+                "Сообщение..."
+            })
             .font(FontId::proportional(13.0))
             .margin(egui::Margin::symmetric(8, 6))
             .char_limit(255),
@@ -194,9 +226,16 @@ fn draw_active_input(ui: &mut egui::Ui, snap: &Snapshot) {
         let text = input.trim().to_string();
         if !text.is_empty() {
             state::save_chat_input("");
-            let nick = state::get_nickname();
-            state::add_chat_msg(&nick, &text);
-            crate::network::send_chat_message(text);
+            // Команды (`/pay ...`) не эхоим в чат — ответит сервер.
+            if snap.chat_team && !text.starts_with('/') {
+                state::add_team_chat_msg(&state::get_nickname(), &text);
+                crate::network::send_team_chat_message(text);
+            } else {
+                if !text.starts_with('/') {
+                    state::add_chat_msg(&state::get_nickname(), &text);
+                }
+                crate::network::send_chat_message(text);
+            }
         }
         state::close_chat_input();
     }
@@ -1,13 +1,21 @@
 //! Окно подключения к серверу (F2).
 
+use common::keybind::Action;
 use egui::{Align2, RichText, TextEdit, Vec2};
 
 use crate::overlay::state::{self, Snapshot};
 use crate::overlay::theme::{self, colors, sizes};
 
 pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
+    // Браузер серверов открыт справа — сдвигаемся влево.
+    let offset = if snap.show_browser {
+        -(sizes::BROWSER_WIDTH + sizes::WINDOW_GAP) * 0.5
+    } else {
+        0.0
+    };
+
     egui::Window::new("connect")
-        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .anchor(Align2::CENTER_CENTER, Vec2::new(offset, 0.0))
         .resizable(false)
         .collapsible(false)
         .title_bar(false)
@@ -20,6 +28,8 @@ pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
                 .show(ui, |ui| {
                     draw_form(ui, snap);
                     ui.add_space(10.0);
+                    draw_lan(ui);
+                    ui.add_space(10.0);
                     draw_status(ui, snap);
                     ui.add_space(10.0);
                     draw_actions(ui, snap);
@@ -93,12 +103,81 @@ fn draw_form(ui: &mut egui::Ui, snap: &Snapshot) {
                     .margin(egui::Margin::symmetric(8, 5)),
             );
             ui.end_row();
+
+            field_label(ui, "Пароль");
+            ui.add(
+                TextEdit::singleline(&mut conn.password)
+                    .desired_width(ui.available_width())
+                    .hint_text("если нужен")
+                    .password(true)
+                    .margin(egui::Margin::symmetric(8, 5)),
+            );
+            ui.end_row();
         });
 
     if let Ok(mut c) = state::CONNECTION.lock() {
         c.ip.clone_from(&conn.ip);
         c.port.clone_from(&conn.port);
         c.nickname.clone_from(&conn.nickname);
+        c.password.clone_from(&conn.password);
+    }
+}
+
+/// Серверы в локальной сети (UDP broadcast). Клик — подставить адрес.
+fn draw_lan(ui: &mut egui::Ui) {
+    let refreshing = crate::discovery::is_refreshing();
+
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new("ЛОКАЛЬНАЯ СЕТЬ")
+                .size(10.5)
+                .color(colors::TEXT_MUTED)
+                .strong()
+                .extra_letter_spacing(2.0),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let browser = ui.add(
+                egui::Button::new(RichText::new("ВСЕ СЕРВЕРЫ").size(10.5).color(colors::GOLD))
+                    .fill(colors::BG_WIDGET)
+                    .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+            );
+            if browser.clicked() && state::toggle_browser() {
+                crate::server_browser::refresh();
+            }
+
+            let label = if refreshing { "ПОИСК..." } else { "НАЙТИ" };
+            let button = ui.add_enabled(
+                !refreshing,
+                egui::Button::new(RichText::new(label).size(10.5).color(colors::GOLD))
+                    .fill(colors::BG_WIDGET)
+                    .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+            );
+            if button.clicked() {
+                crate::discovery::refresh(None, Vec::new());
+            }
+        });
+    });
+
+    for server in crate::discovery::results().iter().filter(|s| s.lan) {
+        let lock = if server.info.password { " 🔒" } else { "" };
+        let text = format!(
+            "{}{lock} · {} · {}/{} · {}ms",
+            server.info.name,
+            server.info.mode,
+            server.info.players,
+            server.info.max_players,
+            server.ping_ms
+        );
+        let row = ui.add(
+            egui::Label::new(RichText::new(text).size(11.5).color(colors::TEXT_SECONDARY))
+                .sense(egui::Sense::click()),
+        );
+        if row.clicked()
+            && let Ok(mut c) = state::CONNECTION.lock()
+        {
+            c.ip = server.addr.ip().to_string();
+            c.port = server.addr.port().to_string();
+        }
     }
 }
 
@@ -116,6 +195,8 @@ fn draw_status(ui: &mut egui::Ui, snap: &Snapshot) {
     let conn = &snap.connection;
     let (label, color) = if conn.connected {
         ("ONLINE", colors::GREEN)
+    } else if conn.reconnect.is_some() {
+        ("RECONNECTING", colors::YELLOW)
     } else if conn.status.contains("одключение") {
         ("CONNECTING", colors::YELLOW)
     } else {
@@ -158,6 +239,10 @@ fn draw_actions(ui: &mut egui::Ui, snap: &Snapshot) {
             primary_label = "ОТКЛЮЧИТЬСЯ";
             primary_color = colors::RED;
             primary_active = true;
+        } else if conn.reconnect.is_some() {
+            primary_label = "ОТМЕНИТЬ";
+            primary_color = colors::RED;
+            primary_active = true;
         } else {
             primary_label = "ПОДКЛЮЧИТЬСЯ";
             primary_color = colors::GOLD;
@@ -165,7 +250,7 @@ fn draw_actions(ui: &mut egui::Ui, snap: &Snapshot) {
         }
 
         let primary = ui.add_sized(
-            [ui.available_width() - 96.0, 32.0],
+            [ui.available_width() - 192.0, 32.0],
             egui::Button::new(
                 RichText::new(primary_label)
                     .size(12.5)
@@ -181,13 +266,32 @@ fn draw_actions(ui: &mut egui::Ui, snap: &Snapshot) {
             if conn.connected {
                 crate::network::disconnect();
                 state::close_connect();
+            } else if conn.reconnect.is_some() {
+                crate::network::cancel_reconnect();
             } else {
                 let port: u16 = conn.port.parse().unwrap_or(protocol::DEFAULT_PORT);
-                crate::network::connect(&conn.ip, port, &conn.nickname);
+                if crate::network::connect(&conn.ip, port, &conn.nickname, &conn.password) {
+                    remember_recent(&conn.ip, port);
+                }
                 state::close_connect();
+                state::close_browser();
             }
         }
 
+        let settings = ui.add_sized(
+            [88.0, 32.0],
+            egui::Button::new(
+                RichText::new("НАСТРОЙКИ")
+                    .size(12.0)
+                    .color(colors::TEXT_SECONDARY),
+            )
+            .fill(colors::BG_WIDGET)
+            .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+        );
+        if settings.clicked() {
+            state::toggle_settings();
+        }
+
         let close = ui.add_sized(
             [88.0, 32.0],
             egui::Button::new(
@@ -204,17 +308,34 @@ fn draw_actions(ui: &mut egui::Ui, snap: &Snapshot) {
     });
 }
 
+/// Ручной ввод адреса тоже попадает в «недавние». Имя — из последнего
+/// опроса, если сервер в нём был.
+fn remember_recent(ip: &str, port: u16) {
+    let Ok(ip) = ip.trim().parse::<std::net::IpAddr>() else {
+        return;
+    };
+    let addr = std::net::SocketAddr::new(ip, port);
+    let name = crate::discovery::results()
+        .into_iter()
+        .find(|s| s.addr == addr)
+        .map(|s| s.info.name)
+        .unwrap_or_else(|| addr.to_string());
+    crate::server_browser::add_recent(addr, &name);
+}
+
 fn draw_hotkeys(ui: &mut egui::Ui) {
     ui.horizontal_wrapped(|ui| {
         ui.spacing_mut().item_spacing.x = 4.0;
-        for (key, label) in [
-            ("F2", "меню"),
-            ("F3", "игроки"),
-            ("F4", "консоль"),
-            ("T", "чат"),
-            ("TAB", "скорборд"),
+        let keymap = crate::keybinds::get();
+        for (action, label) in [
+            (Action::Connect, "меню"),
+            (Action::Players, "игроки"),
+            (Action::Console, "консоль"),
+            (Action::Chat, "чат"),
+            (Action::TeamChat, "команде"),
+            (Action::Scoreboard, "скорборд"),
         ] {
-            theme::hotkey_chip(ui, key);
+            theme::hotkey_chip(ui, &keymap.get(action).to_string());
             ui.label(
                 RichText::new(label)
                     .size(10.0)
@@ -1,5 +1,5 @@
 //! HUD — компактная диагностическая панель (FPS, пинг, координаты, состояние),
-//! бейдж подключения и плашка с версией.
+//! бейдж подключения, отсчёт до респауна и плашка с версией.
 
 use egui::{Align2, RichText, Vec2};
 
@@ -9,6 +9,7 @@ use crate::overlay::theme::{self, colors};
 const PANEL_ID: &str = "hud_debug";
 const BADGE_ID: &str = "hud_conn_badge";
 const VERSION_ID: &str = "hud_version";
+const RESPAWN_ID: &str = "hud_respawn";
 
 pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
     egui::Area::new(egui::Id::new(PANEL_ID))
@@ -105,13 +106,17 @@ pub fn draw_connection_badge(ctx: &egui::Context, conn: &ConnectionInfo) {
         .interactable(false)
         .order(egui::Order::Background)
         .show(ctx, |ui| {
+            let (label, color) = match conn.reconnect {
+                Some(_) => ("RECONNECT", colors::YELLOW),
+                None => ("ONLINE", colors::GREEN),
+            };
             theme::overlay_frame(colors::HUD_BG).show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    theme::status_dot(ui, colors::GREEN, 8.0);
+                    theme::status_dot(ui, color, 8.0);
                     ui.label(
-                        RichText::new("ONLINE")
+                        RichText::new(label)
                             .size(11.0)
-                            .color(colors::GREEN)
+                            .color(color)
                             .strong()
                             .extra_letter_spacing(1.5),
                     );
@@ -122,6 +127,45 @@ pub fn draw_connection_badge(ctx: &egui::Context, conn: &ConnectionInfo) {
                             .color(colors::TEXT_SECONDARY)
                             .monospace(),
                     );
+                    if let Some(r) = conn.reconnect {
+                        ui.label(
+                            RichText::new(format!("{}/{}", r.attempt, r.max_attempts))
+                                .size(11.0)
+                                .color(colors::YELLOW)
+                                .monospace(),
+                        );
+                    }
+                });
+            });
+        });
+}
+
+pub fn draw_respawn_countdown(ctx: &egui::Context, seconds: f32) {
+    egui::Area::new(egui::Id::new(RESPAWN_ID))
+        .anchor(Align2::CENTER_CENTER, Vec2::new(0.0, 80.0))
+        .interactable(false)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            theme::overlay_frame(colors::HUD_BG).show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        RichText::new("ВЫ ПОГИБЛИ")
+                            .size(18.0)
+                            .color(colors::RED)
+                            .strong()
+                            .extra_letter_spacing(2.0),
+                    );
+                    let text = if seconds > 0.0 {
+                        format!("Респаун через {:.0} с", seconds.ceil())
+                    } else {
+                        "Респаун...".to_string()
+                    };
+                    ui.label(
+                        RichText::new(text)
+                            .size(12.0)
+                            .color(colors::TEXT_SECONDARY)
+                            .monospace(),
+                    );
                 });
             });
         });
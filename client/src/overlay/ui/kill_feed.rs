@@ -0,0 +1,111 @@
+//! Kill feed — последние убийства в правом верхнем углу.
+
+use egui::{Align2, RichText, Vec2};
+
+use sdk::addresses::constants::weapons;
+
+use crate::overlay::state::{KillFeedEntry, Snapshot};
+use crate::overlay::theme::{self, colors};
+
+const FEED_ID: &str = "kill_feed";
+
+pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
+    if snap.kill_feed.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new(FEED_ID))
+        .anchor(Align2::RIGHT_TOP, Vec2::new(-12.0, 36.0))
+        .interactable(false)
+        .order(egui::Order::Background)
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                ui.spacing_mut().item_spacing.y = 3.0;
+                for entry in &snap.kill_feed {
+                    draw_entry(ui, entry);
+                }
+            });
+        });
+}
+
+fn draw_entry(ui: &mut egui::Ui, entry: &KillFeedEntry) {
+    let bg = if entry.involves_local {
+        colors::BG_ROW_LOCAL
+    } else {
+        colors::HUD_BG
+    };
+
+    theme::overlay_frame(bg).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 6.0;
+
+            if let Some(killer) = &entry.killer {
+                ui.label(
+                    RichText::new(killer)
+                        .size(12.0)
+                        .color(colors::GOLD)
+                        .strong(),
+                );
+            }
+
+            let how = match entry.weapon_id {
+                Some(id) => format!("[{}]", weapon_label(id)),
+                None if entry.killer.is_some() => "[?]".to_string(),
+                None => "☠".to_string(),
+            };
+            ui.label(
+                RichText::new(how)
+                    .size(11.0)
+                    .color(colors::TEXT_SECONDARY)
+                    .monospace(),
+            );
+
+            if entry.headshot {
+                ui.label(RichText::new("HS").size(10.0).color(colors::RED).strong());
+            }
+
+            ui.label(
+                RichText::new(&entry.victim)
+                    .size(12.0)
+                    .color(colors::TEXT_PRIMARY),
+            );
+        });
+    });
+}
+
+/// Короткое имя оружия по ID из `constants::weapons`.
+fn weapon_label(id: u32) -> String {
+    let name = match id {
+        weapons::EMPTY_HANDS => "Кулаки",
+        weapons::MODEL_12_REVOLVER => ".38",
+        weapons::MAUSER_C96 => "C96",
+        weapons::COLT_M1911A1 => "1911",
+        weapons::COLT_M1911_SPECIAL => "1911 Ext",
+        weapons::MODEL_19_REVOLVER => ".357",
+        weapons::REMINGTON_870 => "M870",
+        weapons::M3_GREASE_GUN => "M3",
+        weapons::MP40 => "MP40",
+        weapons::THOMPSON_1928 => "Tommy",
+        weapons::M1A1_THOMPSON => "M1A1",
+        weapons::BERETTA_38A => "Beretta",
+        weapons::M1_GARAND => "Garand",
+        weapons::MG42 => "MG42",
+        weapons::KAR98K => "98k",
+        weapons::KAR98K_SNIPER => "98k Sniper",
+        weapons::BAZOOKA => "Bazooka",
+        weapons::GRENADE_SICILY | weapons::MK2_FRAG_GRENADE | weapons::STIELHANDGRANATE => {
+            "Граната"
+        }
+        weapons::MOLOTOV_COCKTAIL => "Молотов",
+        weapons::KNIFE => "Нож",
+        weapons::KNUCKLES => "Кастет",
+        weapons::WRENCH => "Ключ",
+        weapons::PIPE => "Труба",
+        weapons::CROWBAR => "Лом",
+        weapons::BASEBALL_BAT => "Бита",
+        weapons::SHOVEL => "Лопата",
+        weapons::HAMMER => "Молоток",
+        _ => return format!("#{id}"),
+    };
+    name.to_string()
+}
@@ -1,35 +1,57 @@
+pub mod browser;
 pub mod chat;
 pub mod connect;
 pub mod console;
 pub mod cursor;
 pub mod hud;
+pub mod kill_feed;
+pub mod nametags;
+pub mod netgraph;
 pub mod notifications;
 pub mod player_list;
+pub mod race_hud;
 pub mod scoreboard;
+pub mod settings;
+
+use common::keybind::Action;
+use windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE;
 
 use super::input;
 use super::state::{self, Snapshot};
-use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use crate::keybinds::{self, pressed};
 
 pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
     handle_hotkeys();
 
+    if let Some(frame) = &snap.nametags {
+        nametags::draw(ctx, snap, frame);
+    }
+
     if snap.show_debug {
         hud::draw(ctx, snap);
     }
 
+    if let Some(graph) = &snap.netgraph {
+        netgraph::draw(ctx, graph);
+    }
+
     notifications::draw(ctx, snap);
+    kill_feed::draw(ctx, snap);
     chat::draw(ctx, snap);
 
     if snap.show_connect {
         connect::draw(ctx, snap);
     }
 
+    if snap.show_browser {
+        browser::draw(ctx, snap);
+    }
+
     if snap.show_players {
         player_list::draw(ctx, snap);
     }
 
-    if snap.show_scoreboard && !snap.show_connect {
+    if snap.show_scoreboard && !snap.show_connect && !snap.show_browser {
         scoreboard::draw(ctx, snap);
     }
 
@@ -37,10 +59,22 @@ pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
         console::draw(ctx, snap);
     }
 
-    if snap.connection.connected {
+    if snap.show_settings {
+        settings::draw(ctx, snap);
+    }
+
+    if snap.connection.connected || snap.connection.reconnect.is_some() {
         hud::draw_connection_badge(ctx, &snap.connection);
     }
 
+    if let Some(race) = &snap.race {
+        race_hud::draw(ctx, race);
+    }
+
+    if let Some(seconds) = snap.respawn_countdown {
+        hud::draw_respawn_countdown(ctx, seconds);
+    }
+
     hud::draw_version(ctx);
 
     if state::wants_input() {
@@ -49,24 +83,33 @@ pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
 }
 
 fn handle_hotkeys() {
-    if input::just_pressed(VK_F2) {
+    if pressed(Action::Connect) {
         state::toggle_connect();
     }
-    if input::just_pressed(VK_F3) {
+    if pressed(Action::Players) {
         state::toggle_players();
     }
-    if input::just_pressed(VK_F4) {
+    if pressed(Action::Console) {
         state::toggle_console();
     }
 
-    if input::just_pressed(VK_T) && !state::wants_input() {
+    if pressed(Action::Chat) && !state::wants_input() {
         state::open_chat_input();
     }
+    if pressed(Action::TeamChat) && !state::wants_input() {
+        state::open_team_chat_input();
+    }
 
+    // ESC не переназначается: закрывает верхнее окно или отменяет перехват
+    // клавиши в настройках.
     if input::just_pressed(VK_ESCAPE) {
-        state::close_topmost();
+        if keybinds::capturing().is_some() {
+            keybinds::cancel_capture();
+        } else {
+            state::close_topmost();
+        }
     }
 
-    let tab = input::is_held(VK_TAB) && !state::wants_input();
-    state::set_scoreboard(tab);
+    let scoreboard = keybinds::held(Action::Scoreboard) && !state::wants_input();
+    state::set_scoreboard(scoreboard);
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+//! Ники и полоски здоровья над удалёнными игроками.
+//!
+//! Рисуем на фоновом слое, под окнами оверлея. Ник скрыт за камерой, вне
+//! кадра и дальше `nametags::RANGE.cutoff`; проверки прямой видимости пока
+//! нет — рейкаст движка не отреверсен.
+
+use egui::{Align2, Color32, FontId, LayerId, Order, Pos2, Rect, Stroke, Vec2};
+
+use crate::nametags::RANGE;
+use crate::overlay::state::{NametagFrame, Snapshot};
+use crate::overlay::theme::{self, colors};
+
+const BAR_WIDTH: f32 = 56.0;
+const BAR_HEIGHT: f32 = 4.0;
+
+pub fn draw(ctx: &egui::Context, snap: &Snapshot, frame: &NametagFrame) {
+    let screen = ctx.content_rect();
+    let Some(view_proj) = frame.camera.view_proj(screen.width() / screen.height()) else {
+        return;
+    };
+    let painter = ctx.layer_painter(LayerId::new(Order::Background, egui::Id::new("nametags")));
+
+    // Дальние первыми, чтобы ближние ложились сверху.
+    let mut visible: Vec<_> = frame
+        .tags
+        .iter()
+        .filter_map(|tag| {
+            let p = view_proj.project(tag.head, screen.width(), screen.height())?;
+            let alpha = RANGE.alpha((tag.head - frame.camera.position).length());
+            (p.on_screen && alpha > 0.0).then_some((tag, p, alpha))
+        })
+        .collect();
+    visible.sort_by(|a, b| b.1.depth.total_cmp(&a.1.depth));
+
+    for (tag, p, alpha) in visible {
+        let team = snap
+            .players
+            .iter()
+            .find(|pl| pl.id == tag.player_id)
+            .and_then(|pl| snap.team_color(pl.team.as_deref()))
+            .map(theme::team_color);
+        let name_color = team.unwrap_or(colors::TEXT_PRIMARY);
+        let anchor = Pos2::new(p.x, p.y);
+
+        painter.text(
+            anchor + Vec2::new(1.0, 1.0),
+            Align2::CENTER_BOTTOM,
+            &tag.name,
+            FontId::proportional(13.0),
+            Color32::BLACK.gamma_multiply(alpha * 0.8),
+        );
+        painter.text(
+            anchor,
+            Align2::CENTER_BOTTOM,
+            &tag.name,
+            FontId::proportional(13.0),
+            name_color.gamma_multiply(alpha),
+        );
+
+        let bar = Rect::from_center_size(
+            anchor + Vec2::new(0.0, 2.0 + BAR_HEIGHT / 2.0),
+            Vec2::new(BAR_WIDTH, BAR_HEIGHT),
+        );
+        painter.rect_filled(bar, 1.0, colors::HUD_BG.gamma_multiply(alpha));
+        let fill = Rect::from_min_size(bar.min, Vec2::new(BAR_WIDTH * tag.health, BAR_HEIGHT));
+        painter.rect_filled(fill, 1.0, health_color(tag.health).gamma_multiply(alpha));
+        painter.rect_stroke(
+            bar,
+            1.0,
+            Stroke::new(1.0, colors::BORDER.gamma_multiply(alpha)),
+            egui::StrokeKind::Outside,
+        );
+    }
+}
+
+fn health_color(health: f32) -> Color32 {
+    if health > 0.6 {
+        colors::GREEN
+    } else if health > 0.3 {
+        colors::YELLOW
+    } else {
+        colors::RED
+    }
+}
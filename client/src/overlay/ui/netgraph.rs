@@ -0,0 +1,213 @@
+//! Netgraph: RTT, трафик по типам пакетов, интервалы между snapshot'ами,
+//! буферы интерполяции удалённых игроков и графики за последнюю минуту.
+
+use egui::{Align2, Color32, Pos2, Rect, RichText, Sense, Stroke, Vec2};
+
+use crate::net_debug::{HISTORY_SECS, INTERARRIVAL_BUCKETS_MS, NetStats, Rate};
+use crate::overlay::state::NetGraph;
+use crate::overlay::theme::{self, colors};
+
+const PANEL_ID: &str = "hud_netgraph";
+const GRAPH_SIZE: Vec2 = Vec2::new(240.0, 28.0);
+
+pub fn draw(ctx: &egui::Context, graph: &NetGraph) {
+    egui::Area::new(egui::Id::new(PANEL_ID))
+        .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-12.0, -36.0))
+        .interactable(false)
+        .order(egui::Order::Background)
+        .show(ctx, |ui| {
+            theme::overlay_frame(colors::HUD_BG).show(ui, |ui| {
+                ui.set_width(GRAPH_SIZE.x);
+                ui.spacing_mut().item_spacing.y = 2.0;
+                let net = &graph.net;
+
+                draw_rtt(ui, net);
+                sparkline(
+                    ui,
+                    net.history.iter().map(|s| s.rtt_ms.unwrap_or(0.0)),
+                    colors::GREEN,
+                );
+
+                ui.add(egui::Separator::default().spacing(4.0));
+                draw_rates(ui, net);
+                sparkline(
+                    ui,
+                    net.history.iter().map(|s| s.bytes_in as f32),
+                    colors::BLUE,
+                );
+                sparkline(
+                    ui,
+                    net.history.iter().map(|s| s.bytes_out as f32),
+                    colors::GOLD,
+                );
+
+                ui.add(egui::Separator::default().spacing(4.0));
+                draw_interarrival(ui, &net.interarrival);
+
+                if !graph.remotes.is_empty() {
+                    ui.add(egui::Separator::default().spacing(4.0));
+                    draw_remotes(ui, graph);
+                }
+            });
+        });
+}
+
+fn row(ui: &mut egui::Ui, label: &str, value: String, color: Color32) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(label)
+                .size(10.0)
+                .monospace()
+                .color(colors::TEXT_MUTED),
+        );
+        ui.label(RichText::new(value).size(11.0).monospace().color(color));
+    });
+}
+
+fn draw_rtt(ui: &mut egui::Ui, net: &NetStats) {
+    let (text, color) = match net.rtt_ms {
+        Some(rtt) => (
+            format!("{rtt:>4.0} ms  ±{:.0}", net.rtt_jitter_ms),
+            match rtt as u32 {
+                0..=50 => colors::GREEN,
+                51..=100 => colors::YELLOW,
+                _ => colors::RED,
+            },
+        ),
+        None => ("   — ms".to_string(), colors::TEXT_MUTED),
+    };
+    row(ui, "RTT ", text, color);
+    row(
+        ui,
+        "SQ  ",
+        format!("{} в очереди", net.send_queue),
+        colors::TEXT_SECONDARY,
+    );
+}
+
+fn format_rate(rate: Rate) -> String {
+    format!(
+        "{:>3}/s {:>6.1} KB/s",
+        rate.packets,
+        rate.bytes as f32 / 1024.0
+    )
+}
+
+fn draw_rates(ui: &mut egui::Ui, net: &NetStats) {
+    row(ui, "IN  ", format_rate(net.total_in()), colors::BLUE);
+    for (kind, rate) in &net.rate_in {
+        row(
+            ui,
+            "  ",
+            format!("{kind:<12} {}", format_rate(*rate)),
+            colors::TEXT_SECONDARY,
+        );
+    }
+    row(ui, "OUT ", format_rate(net.total_out()), colors::GOLD);
+    for (kind, rate) in &net.rate_out {
+        row(
+            ui,
+            "  ",
+            format!("{kind:<12} {}", format_rate(*rate)),
+            colors::TEXT_SECONDARY,
+        );
+    }
+}
+
+fn draw_interarrival(ui: &mut egui::Ui, buckets: &[u32]) {
+    ui.label(
+        RichText::new("Интервалы snapshot'ов, мс")
+            .size(10.0)
+            .color(colors::TEXT_MUTED),
+    );
+    let max = buckets.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let (rect, _) = ui.allocate_exact_size(GRAPH_SIZE, Sense::hover());
+    let painter = ui.painter_at(rect);
+    let width = rect.width() / buckets.len() as f32;
+    for (i, &count) in buckets.iter().enumerate() {
+        let h = (rect.height() - 10.0) * count as f32 / max;
+        let x = rect.left() + i as f32 * width;
+        let bar = Rect::from_min_max(
+            Pos2::new(x + 1.0, rect.bottom() - 10.0 - h),
+            Pos2::new(x + width - 1.0, rect.bottom() - 10.0),
+        );
+        painter.rect_filled(bar, 1.0, colors::GOLD_DIM);
+        let label = match INTERARRIVAL_BUCKETS_MS.get(i) {
+            Some(max_ms) => format!("<{max_ms}"),
+            None => "…".to_string(),
+        };
+        painter.text(
+            Pos2::new(x + width / 2.0, rect.bottom()),
+            Align2::CENTER_BOTTOM,
+            label,
+            egui::FontId::monospace(8.0),
+            colors::TEXT_MUTED,
+        );
+    }
+}
+
+fn draw_remotes(ui: &mut egui::Ui, graph: &NetGraph) {
+    for r in &graph.remotes {
+        let ms = |v: Option<f32>| v.map_or("—".to_string(), |v| format!("{v:.0}"));
+        row(
+            ui,
+            "",
+            format!("{} [{:?}]", r.name, r.playout),
+            colors::TEXT_PRIMARY,
+        );
+        row(
+            ui,
+            "  ",
+            format!(
+                "буф {} · задержка {} · джиттер {:.0} · шаг {}",
+                r.buffered,
+                ms(r.delay_ms),
+                r.jitter_ms,
+                ms(r.interval_ms)
+            ),
+            colors::TEXT_SECONDARY,
+        );
+        row(
+            ui,
+            "  ",
+            format!(
+                "экстраполяций {} · телепортов {}",
+                r.extrapolations, r.teleports
+            ),
+            colors::TEXT_SECONDARY,
+        );
+    }
+}
+
+/// График значений за `HISTORY_SECS`, новые справа.
+fn sparkline(ui: &mut egui::Ui, values: impl Iterator<Item = f32>, color: Color32) {
+    let values: Vec<f32> = values.collect();
+    let (rect, _) = ui.allocate_exact_size(GRAPH_SIZE, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, colors::BG_ROW_ALT);
+
+    let max = values.iter().copied().fold(0.0f32, f32::max);
+    if values.len() < 2 || max <= 0.0 {
+        return;
+    }
+    let step = rect.width() / (HISTORY_SECS - 1) as f32;
+    let offset = (HISTORY_SECS - values.len()) as f32 * step;
+    let points: Vec<Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            Pos2::new(
+                rect.left() + offset + i as f32 * step,
+                rect.bottom() - 2.0 - (rect.height() - 4.0) * v / max,
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(points, Stroke::new(1.0, color)));
+    painter.text(
+        rect.right_top() + Vec2::new(-2.0, 1.0),
+        Align2::RIGHT_TOP,
+        format!("{max:.0}"),
+        egui::FontId::monospace(8.0),
+        colors::TEXT_MUTED,
+    );
+}
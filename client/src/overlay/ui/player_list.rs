@@ -39,14 +39,17 @@ pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
                         .auto_shrink([false, true])
                         .show(ui, |ui| {
                             for (i, p) in snap.players.iter().enumerate() {
-                                draw_row(ui, p, i);
+                                let team = snap
+                                    .team_color(p.team.as_deref())
+                                    .map(theme::team_color);
+                                draw_row(ui, p, i, team);
                             }
                         });
                 });
         });
 }
 
-fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
+fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize, team: Option<egui::Color32>) {
     let (bg, stroke) = if p.is_local {
         (
             colors::BG_ROW_LOCAL,
@@ -65,17 +68,18 @@ fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
         .corner_radius(egui::CornerRadius::same(2))
         .show(ui, |ui| {
             ui.horizontal(|ui| {
-                theme::status_dot(
-                    ui,
-                    if p.is_local { colors::GOLD } else { colors::TEXT_MUTED },
-                    6.0,
-                );
+                let dot = match team {
+                    Some(color) => color,
+                    None if p.is_local => colors::GOLD,
+                    None => colors::TEXT_MUTED,
+                };
+                theme::status_dot(ui, dot, 6.0);
                 ui.add_space(2.0);
 
-                let name_color = if p.is_local {
-                    colors::TEXT_PRIMARY
-                } else {
-                    colors::TEXT_SECONDARY
+                let name_color = match team {
+                    Some(color) => color,
+                    None if p.is_local => colors::TEXT_PRIMARY,
+                    None => colors::TEXT_SECONDARY,
                 };
                 let mut name = RichText::new(&p.name).color(name_color).size(12.5);
                 if p.is_local {
@@ -0,0 +1,175 @@
+//! Race HUD: отсчёт, место, круг, сплиты и стрелка на следующий чекпоинт.
+
+use std::time::Instant;
+
+use egui::{Align2, Pos2, RichText, Stroke, Vec2};
+
+use crate::overlay::state::RaceHud;
+use crate::overlay::theme::{self, colors};
+use crate::race::format_ms;
+
+const RACE_ID: &str = "race_hud";
+const COUNTDOWN_ID: &str = "race_countdown";
+const ARROW_ID: &str = "race_arrow";
+const ARROW_SIZE: f32 = 56.0;
+
+pub fn draw(ctx: &egui::Context, hud: &RaceHud) {
+    if let Some(go_at) = hud.go_at {
+        draw_countdown(ctx, go_at);
+    }
+
+    draw_panel(ctx, hud);
+
+    if let Some(target) = &hud.target {
+        draw_arrow(ctx, target.bearing_deg, target.distance_m, target.is_finish);
+    }
+}
+
+fn draw_countdown(ctx: &egui::Context, go_at: Instant) {
+    let left = go_at
+        .saturating_duration_since(Instant::now())
+        .as_secs_f32();
+    let text = if left > 0.0 {
+        format!("{:.0}", left.ceil())
+    } else {
+        "СТАРТ!".to_string()
+    };
+
+    egui::Area::new(egui::Id::new(COUNTDOWN_ID))
+        .anchor(Align2::CENTER_CENTER, Vec2::new(0.0, -60.0))
+        .interactable(false)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(text)
+                    .size(48.0)
+                    .color(colors::GOLD_BRIGHT)
+                    .strong(),
+            );
+        });
+}
+
+fn draw_panel(ctx: &egui::Context, hud: &RaceHud) {
+    egui::Area::new(egui::Id::new(RACE_ID))
+        .anchor(Align2::LEFT_CENTER, Vec2::new(12.0, 0.0))
+        .interactable(false)
+        .order(egui::Order::Background)
+        .show(ctx, |ui| {
+            theme::overlay_frame(colors::HUD_BG).show(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 2.0;
+
+                ui.label(
+                    RichText::new(hud.track.to_uppercase())
+                        .size(11.0)
+                        .color(colors::GOLD_DIM)
+                        .extra_letter_spacing(2.0),
+                );
+
+                let place = match (hud.finished, hud.place) {
+                    (Some((place, _)), _) => format!("ФИНИШ: {place}"),
+                    (None, Some((pos, total))) => format!("МЕСТО {pos}/{total}"),
+                    (None, None) => "МЕСТО —".to_string(),
+                };
+                ui.label(RichText::new(place).size(20.0).color(colors::GOLD).strong());
+
+                ui.label(
+                    RichText::new(format!(
+                        "КРУГ {}/{}  ·  ЧП {}/{}",
+                        hud.lap.max(1),
+                        hud.laps,
+                        hud.checkpoint + 1,
+                        hud.checkpoints
+                    ))
+                    .size(12.0)
+                    .color(colors::TEXT_PRIMARY)
+                    .monospace(),
+                );
+
+                let elapsed = match (hud.finished, hud.started) {
+                    (Some((_, total_ms)), _) => Some(total_ms),
+                    (None, Some(started)) => Some(started.elapsed().as_millis() as u32),
+                    (None, None) => None,
+                };
+                if let Some(ms) = elapsed {
+                    ui.label(
+                        RichText::new(format_ms(ms))
+                            .size(16.0)
+                            .color(colors::TEXT_PRIMARY)
+                            .monospace(),
+                    );
+                }
+
+                if let Some(best) = hud.best_lap_ms {
+                    ui.label(
+                        RichText::new(format!("лучший круг {}", format_ms(best)))
+                            .size(11.0)
+                            .color(colors::GREEN)
+                            .monospace(),
+                    );
+                }
+
+                if !hud.splits.is_empty() {
+                    ui.add(egui::Separator::default().spacing(4.0));
+                    for split in hud.splits.iter().rev() {
+                        let text = match split.lap_ms {
+                            Some(lap_ms) => format!(
+                                "К{} финиш  {}  ({})",
+                                split.lap,
+                                format_ms(split.split_ms),
+                                format_ms(lap_ms)
+                            ),
+                            None => format!(
+                                "К{} ЧП{}  {}",
+                                split.lap,
+                                split.checkpoint + 1,
+                                format_ms(split.split_ms)
+                            ),
+                        };
+                        ui.label(
+                            RichText::new(text)
+                                .size(11.0)
+                                .color(colors::TEXT_SECONDARY)
+                                .monospace(),
+                        );
+                    }
+                }
+            });
+        });
+}
+
+/// Стрелка на следующий чекпоинт: вверх — прямо по курсу.
+fn draw_arrow(ctx: &egui::Context, bearing_deg: f32, distance_m: f32, is_finish: bool) {
+    egui::Area::new(egui::Id::new(ARROW_ID))
+        .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 48.0))
+        .interactable(false)
+        .order(egui::Order::Background)
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                let (response, painter) =
+                    ui.allocate_painter(Vec2::splat(ARROW_SIZE), egui::Sense::hover());
+                let center: Pos2 = response.rect.center();
+                let angle = bearing_deg.to_radians();
+                let dir = Vec2::new(angle.sin(), -angle.cos());
+                let half = ARROW_SIZE * 0.38;
+                let color = if is_finish {
+                    colors::GREEN
+                } else {
+                    colors::GOLD_BRIGHT
+                };
+
+                painter.circle_filled(center, ARROW_SIZE * 0.5, colors::HUD_BG);
+                painter.arrow(
+                    center - dir * half,
+                    dir * half * 2.0,
+                    Stroke::new(4.0, color),
+                );
+
+                ui.label(
+                    RichText::new(format!("{:.0} м", distance_m))
+                        .size(12.0)
+                        .color(colors::TEXT_PRIMARY)
+                        .monospace(),
+                );
+            });
+        });
+}
@@ -2,11 +2,13 @@
 
 use egui::{Align, Align2, Color32, Layout, RichText, Stroke, Vec2};
 
-use crate::overlay::state::{PlayerEntry, Snapshot};
+use crate::overlay::state::{PlayerEntry, RoundInfo, Snapshot, TeamInfo};
 use crate::overlay::theme::{self, colors, sizes};
 
 const COL_MARK: f32 = 14.0;
 const COL_ID: f32 = 50.0;
+const COL_KILLS: f32 = 44.0;
+const COL_DEATHS: f32 = 44.0;
 const COL_PING: f32 = 90.0;
 const ROW_HEIGHT: f32 = 24.0;
 
@@ -33,8 +35,11 @@ pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
                     if snap.players.is_empty() {
                         empty_state(ui);
                     } else {
-                        for (i, p) in snap.players.iter().enumerate() {
-                            draw_row(ui, p, i);
+                        for (i, p) in ranked(&snap.players, &snap.teams).into_iter().enumerate() {
+                            let team = snap
+                                .team_color(p.team.as_deref())
+                                .map(theme::team_color);
+                            draw_row(ui, p, i, team);
                         }
                     }
                 });
@@ -77,13 +82,21 @@ fn draw_header(ui: &mut egui::Ui, snap: &Snapshot) {
                         .size(11.5)
                         .color(colors::TEXT_SECONDARY),
                 );
+                if let Some(round) = &snap.round {
+                    ui.label(
+                        RichText::new(round_line(round))
+                            .size(11.0)
+                            .color(colors::GOLD_DIM)
+                            .monospace(),
+                    );
+                }
             });
         });
 }
 
 fn draw_table_header(ui: &mut egui::Ui) {
     let total_w = ui.available_width();
-    let name_w = total_w - COL_MARK - COL_ID - COL_PING - 12.0;
+    let name_w = total_w - COL_MARK - COL_ID - COL_KILLS - COL_DEATHS - COL_PING - 20.0;
 
     ui.horizontal(|ui| {
         ui.spacing_mut().item_spacing.x = 4.0;
@@ -105,6 +118,16 @@ fn draw_table_header(ui: &mut egui::Ui) {
             Layout::left_to_right(Align::Center),
             |ui| header_label(ui, "ИМЯ"),
         );
+        ui.allocate_ui_with_layout(
+            Vec2::new(COL_KILLS, ROW_HEIGHT),
+            Layout::right_to_left(Align::Center),
+            |ui| header_label(ui, "УБ"),
+        );
+        ui.allocate_ui_with_layout(
+            Vec2::new(COL_DEATHS, ROW_HEIGHT),
+            Layout::right_to_left(Align::Center),
+            |ui| header_label(ui, "СМ"),
+        );
         // ПИНГ (правый край)
         ui.allocate_ui_with_layout(
             Vec2::new(COL_PING, ROW_HEIGHT),
@@ -124,7 +147,7 @@ fn header_label(ui: &mut egui::Ui, text: &str) {
     );
 }
 
-fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
+fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize, team: Option<Color32>) {
     let bg = if p.is_local {
         colors::BG_ROW_LOCAL
     } else if idx % 2 == 1 {
@@ -134,7 +157,8 @@ fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
     };
 
     let total_w = ui.available_width();
-    let name_w = total_w - COL_MARK - COL_ID - COL_PING - 12.0 /* item_spacing */;
+    let name_w =
+        total_w - COL_MARK - COL_ID - COL_KILLS - COL_DEATHS - COL_PING - 20.0 /* item_spacing */;
 
     let frame = egui::Frame::NONE
         .fill(bg)
@@ -153,10 +177,14 @@ fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
             ui.allocate_ui_with_layout(
                 Vec2::new(COL_MARK, ROW_HEIGHT),
                 Layout::left_to_right(Align::Center),
-                |ui| {
-                    if p.is_local {
+                |ui| match team {
+                    Some(color) => {
+                        theme::status_dot(ui, color, 7.0);
+                    }
+                    None if p.is_local => {
                         theme::status_dot(ui, colors::GOLD, 7.0);
                     }
+                    None => {}
                 },
             );
 
@@ -178,10 +206,10 @@ fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
                 Vec2::new(name_w, ROW_HEIGHT),
                 Layout::left_to_right(Align::Center),
                 |ui| {
-                    let color = if p.is_local {
-                        colors::TEXT_PRIMARY
-                    } else {
-                        colors::TEXT_SECONDARY
+                    let color = match team {
+                        Some(color) => color,
+                        None if p.is_local => colors::TEXT_PRIMARY,
+                        None => colors::TEXT_SECONDARY,
                     };
                     let mut name = RichText::new(&p.name).size(13.0).color(color);
                     if p.is_local {
@@ -199,6 +227,9 @@ fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
                 },
             );
 
+            score_cell(ui, COL_KILLS, p.kills, colors::TEXT_PRIMARY);
+            score_cell(ui, COL_DEATHS, p.deaths, colors::TEXT_SECONDARY);
+
             ui.allocate_ui_with_layout(
                 Vec2::new(COL_PING, ROW_HEIGHT),
                 Layout::right_to_left(Align::Center),
@@ -218,6 +249,49 @@ fn draw_row(ui: &mut egui::Ui, p: &PlayerEntry, idx: usize) {
     });
 }
 
+fn score_cell(ui: &mut egui::Ui, width: f32, value: u32, color: Color32) {
+    ui.allocate_ui_with_layout(
+        Vec2::new(width, ROW_HEIGHT),
+        Layout::right_to_left(Align::Center),
+        |ui| {
+            ui.label(
+                RichText::new(value.to_string())
+                    .size(12.5)
+                    .color(color)
+                    .monospace(),
+            );
+        },
+    );
+}
+
+/// Игроки сгруппированы по командам (в порядке сервера), внутри — по
+/// убыванию фрагов, при равенстве — по возрастанию смертей.
+fn ranked<'a>(players: &'a [PlayerEntry], teams: &[TeamInfo]) -> Vec<&'a PlayerEntry> {
+    let team_index = |p: &PlayerEntry| {
+        p.team
+            .as_deref()
+            .and_then(|id| teams.iter().position(|t| t.id == id))
+            .unwrap_or(usize::MAX)
+    };
+    let mut sorted: Vec<_> = players.iter().collect();
+    sorted.sort_by_key(|p| (team_index(p), std::cmp::Reverse(p.kills), p.deaths));
+    sorted
+}
+
+fn round_line(round: &RoundInfo) -> String {
+    let mut parts = vec![round.mode.to_uppercase()];
+    if round.score_limit > 0 {
+        parts.push(format!("до {} фрагов", round.score_limit));
+    }
+    if round.finished {
+        parts.push("раунд окончен".to_string());
+    } else if let Some(ends_at) = round.ends_at {
+        let left = ends_at.saturating_duration_since(std::time::Instant::now()).as_secs();
+        parts.push(format!("{:02}:{:02}", left / 60, left % 60));
+    }
+    parts.join(" · ")
+}
+
 fn empty_state(ui: &mut egui::Ui) {
     ui.vertical_centered(|ui| {
         ui.add_space(20.0);
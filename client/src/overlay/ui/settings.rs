@@ -0,0 +1,194 @@
+//! Окно настроек: оверлей, масштаб интерфейса и горячие клавиши.
+//! Изменения сразу сохраняются в файл настроек клиента.
+
+use common::keybind::Action;
+use egui::{Align2, RichText, Vec2};
+
+use crate::keybinds;
+use crate::overlay::state::{self, Snapshot};
+use crate::overlay::theme::{self, colors, sizes};
+use crate::settings::{self, UI_SCALE_RANGE};
+
+pub fn draw(ctx: &egui::Context, snap: &Snapshot) {
+    egui::Window::new("settings")
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .resizable(false)
+        .collapsible(false)
+        .title_bar(false)
+        .frame(theme::panel_frame().inner_margin(egui::Margin::ZERO))
+        .fixed_size(egui::Vec2::new(sizes::SETTINGS_WIDTH, 0.0))
+        .show(ctx, |ui| {
+            theme::header_bar(ui, "НАСТРОЙКИ", Some("ESC — закрыть"));
+            egui::Frame::NONE
+                .inner_margin(egui::Margin::symmetric(16, 12))
+                .show(ui, |ui| {
+                    draw_overlay(ui, snap);
+                    ui.add_space(12.0);
+                    draw_keys(ui);
+                    ui.add_space(12.0);
+                    draw_close(ui);
+                });
+        });
+}
+
+fn section(ui: &mut egui::Ui, title: &str) {
+    ui.label(
+        RichText::new(title)
+            .size(10.5)
+            .color(colors::TEXT_MUTED)
+            .strong()
+            .extra_letter_spacing(2.0),
+    );
+    ui.add_space(4.0);
+}
+
+fn draw_overlay(ui: &mut egui::Ui, snap: &Snapshot) {
+    section(ui, "ОВЕРЛЕЙ");
+
+    let mut show_debug = snap.show_debug;
+    if ui
+        .checkbox(&mut show_debug, RichText::new("Панель отладки").size(12.0))
+        .changed()
+    {
+        state::set_show_debug(show_debug);
+        settings::update(|s| s.overlay.show_debug = show_debug);
+    }
+
+    let mut show_netgraph = snap.netgraph.is_some();
+    if ui
+        .checkbox(
+            &mut show_netgraph,
+            RichText::new("Сетевая статистика").size(12.0),
+        )
+        .changed()
+    {
+        state::set_show_netgraph(show_netgraph);
+        settings::update(|s| s.overlay.show_netgraph = show_netgraph);
+    }
+
+    let mut chat_fade = snap.chat_fade;
+    if ui
+        .checkbox(&mut chat_fade, RichText::new("Гасить чат").size(12.0))
+        .changed()
+    {
+        state::set_chat_fade(chat_fade);
+        settings::update(|s| s.overlay.chat_fade = chat_fade);
+    }
+
+    // Масштаб применяем по отпусканию ползунка: иначе окно меняет размер
+    // прямо под курсором.
+    let id = egui::Id::new("settings_ui_scale");
+    let mut scale = ui.data(|d| d.get_temp::<f32>(id)).unwrap_or(snap.ui_scale);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Масштаб").size(12.0));
+        let slider = ui.add(
+            egui::Slider::new(&mut scale, UI_SCALE_RANGE)
+                .step_by(0.05)
+                .fixed_decimals(2),
+        );
+        if slider.dragged() {
+            ui.data_mut(|d| d.insert_temp(id, scale));
+        } else {
+            ui.data_mut(|d| d.remove::<f32>(id));
+            if scale != snap.ui_scale {
+                state::set_ui_scale(scale);
+                settings::update(|s| s.ui_scale = scale);
+            }
+        }
+    });
+}
+
+/// Клик по клавише — ждём нажатия; занятая клавиша не применяется, пока
+/// игрок не согласится поменять действия местами.
+fn draw_keys(ui: &mut egui::Ui) {
+    section(ui, "КЛАВИШИ");
+    keybinds::poll_capture();
+
+    let keymap = keybinds::get();
+    let capturing = keybinds::capturing();
+    let pending = keybinds::pending();
+
+    egui::Grid::new("settings_keys")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(
+                    RichText::new(action.label())
+                        .size(12.0)
+                        .color(colors::TEXT_SECONDARY),
+                );
+                let conflicted = pending.is_some_and(|(a, _)| a == action);
+                let (text, color) = if capturing == Some(action) {
+                    ("нажмите клавишу…".to_string(), colors::GOLD_BRIGHT)
+                } else if conflicted {
+                    (keymap.get(action).to_string(), colors::RED)
+                } else {
+                    (keymap.get(action).to_string(), colors::GOLD_DIM)
+                };
+                let button = ui.add_sized(
+                    [ui.available_width(), 20.0],
+                    egui::Button::new(RichText::new(text).size(11.0).color(color).monospace())
+                        .fill(colors::BG_WIDGET)
+                        .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+                );
+                if button.clicked() {
+                    keybinds::start_capture(action);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some((action, conflict)) = pending {
+        ui.add_space(6.0);
+        ui.label(
+            RichText::new(format!(
+                "{} уже занята: «{}»",
+                conflict.binding,
+                conflict.with.label()
+            ))
+            .size(11.0)
+            .color(colors::RED),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(RichText::new("ПОМЕНЯТЬ").size(11.0)).clicked() {
+                keybinds::set_swapping(action, conflict.binding);
+                keybinds::clear_pending();
+            }
+            if ui.button(RichText::new("ОТМЕНА").size(11.0)).clicked() {
+                keybinds::clear_pending();
+            }
+        });
+    }
+
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new("ESC — отмена, Ctrl/Shift/Alt — модификаторы")
+                .size(10.0)
+                .color(colors::TEXT_MUTED),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button(RichText::new("ПО УМОЛЧАНИЮ").size(10.5)).clicked() {
+                keybinds::cancel_capture();
+                keybinds::reset_all();
+            }
+        });
+    });
+}
+
+fn draw_close(ui: &mut egui::Ui) {
+    let close = ui.add_sized(
+        [ui.available_width(), 28.0],
+        egui::Button::new(
+            RichText::new("ЗАКРЫТЬ")
+                .size(12.0)
+                .color(colors::TEXT_SECONDARY),
+        )
+        .fill(colors::BG_WIDGET)
+        .stroke(egui::Stroke::new(1.0, colors::BORDER)),
+    );
+    if close.clicked() {
+        state::close_settings();
+    }
+}
@@ -34,6 +34,16 @@ pub enum PlayerEvent {
     WeaponSelect,
     WeaponHide,
 
+    /// Сменилось оружие в руках (замечено `PlayerTracker`), `None` — руки пусты.
+    WeaponChanged {
+        from: Option<u32>,
+        to: Option<u32>,
+    },
+    /// Обойма оружия в руках пополнилась.
+    WeaponReloaded {
+        weapon_id: u32,
+    },
+
     MoneyChanged {
         old_cents: i64,
         new_cents: i64,
@@ -83,16 +93,23 @@ pub fn push(event: PlayerEvent) {
 /// Маппинг локального события в сетевое.
 ///
 /// Возвращает `None` для событий, которые не нужно транслировать по сети
-/// (например, MoneyChanged — это только локальное состояние).
+/// как `NetPlayerEvent` (MoneyChanged уходит отдельной заявкой через `economy`).
+/// Оружие уходит по `WeaponChanged`, а не по Human Message'ам
+/// `WeaponSelect`/`WeaponHide`: в момент сообщения ID ещё не в руках.
+/// `Shot` уходит через `shots` — `ShotFired` нужны направление и жертва.
 pub fn to_net_event(ev: &PlayerEvent) -> Option<NetPlayerEvent> {
     match ev {
         PlayerEvent::VehicleEntered { .. } => Some(NetPlayerEvent::EnterVehicleDone),
         PlayerEvent::VehicleLeft => Some(NetPlayerEvent::LeaveVehicleDone),
         PlayerEvent::Damage => Some(NetPlayerEvent::Damage),
         PlayerEvent::Death => Some(NetPlayerEvent::Death),
-        PlayerEvent::Shot => Some(NetPlayerEvent::Shot),
-        PlayerEvent::WeaponSelect => Some(NetPlayerEvent::WeaponSelect),
-        PlayerEvent::WeaponHide => Some(NetPlayerEvent::WeaponHide),
+        PlayerEvent::WeaponChanged { to: Some(id), .. } => {
+            Some(NetPlayerEvent::WeaponSelect { weapon_id: *id })
+        }
+        PlayerEvent::WeaponChanged { to: None, .. } => Some(NetPlayerEvent::WeaponHide),
+        PlayerEvent::WeaponReloaded { weapon_id } => Some(NetPlayerEvent::WeaponReload {
+            weapon_id: *weapon_id,
+        }),
         _ => None,
     }
 }
@@ -109,6 +126,19 @@ pub fn process_pending() {
     for ev in &drained {
         log_event(ev);
 
+        if let PlayerEvent::MoneyChanged { new_cents, .. } = ev {
+            crate::economy::on_local_money_changed(*new_cents);
+        }
+
+        match ev {
+            PlayerEvent::Shot => crate::shots::on_local_shot(),
+            PlayerEvent::VehicleEntered { vehicle_ptr } => {
+                crate::vehicles::on_local_enter(*vehicle_ptr)
+            }
+            PlayerEvent::VehicleLeft => crate::vehicles::on_local_leave(),
+            _ => {}
+        }
+
         if let Some(net_ev) = to_net_event(ev) {
             crate::network::push_local_event(net_ev);
         }
@@ -159,6 +189,15 @@ fn log_event(ev: &PlayerEvent) {
         PlayerEvent::WeaponHide => {
             logger::info("[player-event] WeaponHide");
         }
+        PlayerEvent::WeaponChanged { from, to } => {
+            logger::info(&format!(
+                "[player-event] WeaponChanged: {:?} -> {:?}",
+                from, to
+            ));
+        }
+        PlayerEvent::WeaponReloaded { weapon_id } => {
+            logger::info(&format!("[player-event] WeaponReloaded ({})", weapon_id));
+        }
 
         PlayerEvent::MoneyChanged {
             old_cents,
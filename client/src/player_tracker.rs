@@ -3,7 +3,7 @@
 //! Работает на главном игровом потоке из `Game Tick Always` hook.
 //! Превращает snapshot'ы игрока в высокоуровневые события.
 
-use std::sync::{Mutex, OnceLock};
+use std::sync::{LazyLock, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
 use common::logger;
@@ -12,6 +12,7 @@ use protocol::{NetPlayerSnapshot, NetVec3};
 fn vec3_to_net(v: Vec3) -> NetVec3 {
     NetVec3 { x: v.x, y: v.y, z: v.z }
 }
+use sdk::addresses::constants::weapons;
 use sdk::game::Player;
 use sdk::types::Vec3;
 
@@ -38,6 +39,10 @@ pub struct PlayerSnapshot {
     pub controls_locked: Option<bool>,
     pub control_style: Option<String>,
     pub in_vehicle: Option<bool>,
+    /// Оружие в руках, `None` — руки пусты.
+    pub weapon_id: Option<u32>,
+    /// Патроны в обойме оружия в руках.
+    pub clip_ammo: Option<i32>,
 }
 
 #[derive(Debug)]
@@ -50,7 +55,8 @@ struct PlayerTracker {
 }
 
 static TRACKER: OnceLock<Mutex<PlayerTracker>> = OnceLock::new();
-static NET_SNAPSHOT_TICK: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+/// Начало отсчёта `NetPlayerSnapshot::tick`.
+static NET_SNAPSHOT_CLOCK: LazyLock<Instant> = LazyLock::new(Instant::now);
 
 fn tracker() -> &'static Mutex<PlayerTracker> {
     TRACKER.get_or_init(|| {
@@ -150,9 +156,18 @@ fn capture_snapshot(player: &Player) -> PlayerSnapshot {
         controls_locked: player.are_controls_locked(),
         control_style: player.get_control_style_str(),
         in_vehicle: player.is_in_vehicle(),
+        weapon_id: weapon_in_hand(player),
+        clip_ammo: player.get_current_ammo(),
     }
 }
 
+/// ID оружия в руках; кулаки (`EMPTY_HANDS`) — тоже «руки пусты».
+fn weapon_in_hand(player: &Player) -> Option<u32> {
+    player
+        .get_weapon_in_hand_id()
+        .filter(|&id| id != weapons::EMPTY_HANDS)
+}
+
 /// Собрать сетевой snapshot локального игрока.
 ///
 /// Это multiplayer-ready snapshot:
@@ -184,7 +199,7 @@ fn capture_network_snapshot(player: &Player) -> Option<NetPlayerSnapshot> {
 
     let movement_mode = player.get_movement_mode_byte().unwrap_or(0);
 
-    let tick = NET_SNAPSHOT_TICK.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let tick = NET_SNAPSHOT_CLOCK.elapsed().as_millis() as u64;
 
     Some(NetPlayerSnapshot {
         tick,
@@ -202,6 +217,7 @@ fn capture_network_snapshot(player: &Player) -> Option<NetPlayerSnapshot> {
         aim_dir,
         is_moving: false,
         movement_mode,
+        weapon_id: weapon_in_hand(player),
     })
 }
 
@@ -256,6 +272,21 @@ impl PlayerTracker {
             }
         }
 
+        if previous.weapon_id != current.weapon_id {
+            player_events::push(PlayerEvent::WeaponChanged {
+                from: previous.weapon_id,
+                to: current.weapon_id,
+            });
+        } else if let (Some(weapon_id), Some(old), Some(new)) =
+            (current.weapon_id, previous.clip_ammo, current.clip_ammo)
+        {
+            // Обойма пополняется только перезарядкой (подобранные патроны
+            // уходят в запас).
+            if new > old {
+                player_events::push(PlayerEvent::WeaponReloaded { weapon_id });
+            }
+        }
+
         let prev_in_vehicle = previous.in_vehicle.unwrap_or(false);
         let curr_in_vehicle = current.in_vehicle.unwrap_or(false);
 
@@ -0,0 +1,260 @@
+//! Гонка: трасса, прогресс локального игрока и данные для race HUD.
+//!
+//! Чекпоинты засчитывает сервер по snapshot'ам; клиент только показывает
+//! следующий чекпоинт (стрелка + расстояние) и то, что прислал сервер:
+//! место, круг, сплиты. Следующий чекпоинт сдвигается по
+//! `ServerPacket::RaceCheckpoint`, а не по локальной геометрии.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use protocol::{NetCheckpoint, NetRaceResult, PlayerId};
+use sdk::game::Player;
+
+use crate::overlay::state::{RaceHud, RaceSplit, RaceTarget};
+
+/// Сколько последних сплитов показывать.
+const MAX_SPLITS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Countdown { go_at: Instant },
+    Running { started: Instant },
+    Finished { place: u32, total_ms: u32 },
+}
+
+#[derive(Debug)]
+struct RaceState {
+    track_name: String,
+    laps: u32,
+    checkpoints: Vec<NetCheckpoint>,
+    phase: Phase,
+    /// Записан ли локальный игрок в текущий заезд.
+    entered: bool,
+    next_checkpoint: usize,
+    /// Текущий круг (с единицы).
+    lap: u32,
+    splits: Vec<RaceSplit>,
+    best_lap_ms: Option<u32>,
+    standings: Vec<PlayerId>,
+}
+
+impl RaceState {
+    const fn new() -> Self {
+        Self {
+            track_name: String::new(),
+            laps: 0,
+            checkpoints: Vec::new(),
+            phase: Phase::Idle,
+            entered: false,
+            next_checkpoint: 0,
+            lap: 1,
+            splits: Vec::new(),
+            best_lap_ms: None,
+            standings: Vec::new(),
+        }
+    }
+
+    fn reset_progress(&mut self) {
+        self.next_checkpoint = 0;
+        self.lap = 1;
+        self.splits.clear();
+        self.best_lap_ms = None;
+        self.standings.clear();
+    }
+}
+
+static RACE: Mutex<RaceState> = Mutex::new(RaceState::new());
+
+/// Сбросить состояние (disconnect).
+pub fn reset() {
+    if let Ok(mut r) = RACE.lock() {
+        *r = RaceState::new();
+    }
+    crate::overlay::state::set_race_hud(None);
+}
+
+pub fn on_track(name: String, laps: u32, checkpoints: Vec<NetCheckpoint>) {
+    if let Ok(mut r) = RACE.lock() {
+        r.track_name = name;
+        r.laps = laps;
+        r.checkpoints = checkpoints;
+        r.reset_progress();
+    }
+}
+
+/// Локальный игрок записан в заезд.
+pub fn on_countdown(seconds: u32) {
+    if let Ok(mut r) = RACE.lock() {
+        r.entered = true;
+        r.reset_progress();
+        r.phase = Phase::Countdown {
+            go_at: Instant::now() + Duration::from_secs(seconds as u64),
+        };
+    }
+}
+
+pub fn on_start() {
+    if let Ok(mut r) = RACE.lock()
+        && r.entered
+    {
+        r.phase = Phase::Running {
+            started: Instant::now(),
+        };
+    }
+}
+
+/// Сервер засчитал чекпоинт локальному игроку.
+pub fn on_checkpoint(checkpoint: u32, lap: u32, split_ms: u32, lap_ms: Option<u32>) {
+    if let Ok(mut r) = RACE.lock() {
+        let count = r.checkpoints.len().max(1);
+        r.next_checkpoint = (checkpoint as usize + 1) % count;
+        r.lap = if lap_ms.is_some() { lap + 1 } else { lap };
+
+        if let Some(ms) = lap_ms {
+            r.best_lap_ms = Some(r.best_lap_ms.map_or(ms, |b| b.min(ms)));
+        }
+
+        r.splits.push(RaceSplit {
+            lap,
+            checkpoint,
+            split_ms,
+            lap_ms,
+        });
+        if r.splits.len() > MAX_SPLITS {
+            r.splits.remove(0);
+        }
+    }
+}
+
+pub fn on_standings(order: Vec<PlayerId>) {
+    if let Ok(mut r) = RACE.lock() {
+        r.standings = order;
+    }
+}
+
+pub fn on_finish(player_id: PlayerId, place: u32, total_ms: u32) {
+    let name = crate::overlay::state::player_name(player_id as u32)
+        .unwrap_or_else(|| format!("Player#{player_id}"));
+
+    if Some(player_id) == crate::network::local_player_id() {
+        if let Ok(mut r) = RACE.lock() {
+            r.phase = Phase::Finished { place, total_ms };
+        }
+        crate::overlay::state::add_system_message(format!(
+            "Финиш! Место {place}, время {}",
+            format_ms(total_ms)
+        ));
+    } else {
+        crate::overlay::state::add_system_message(format!(
+            "{name} финишировал {place}-м ({})",
+            format_ms(total_ms)
+        ));
+    }
+}
+
+pub fn on_results(results: &[NetRaceResult], next_race_in_secs: u32) {
+    if let Ok(mut r) = RACE.lock() {
+        r.phase = Phase::Idle;
+        r.entered = false;
+    }
+
+    crate::overlay::state::add_system_message("Заезд окончен:".to_string());
+    for result in results {
+        let name = crate::overlay::state::player_name(result.player_id as u32)
+            .unwrap_or_else(|| format!("Player#{}", result.player_id));
+        let line = match (result.place, result.total_ms) {
+            (Some(place), Some(total)) => format!("{place}. {name} — {}", format_ms(total)),
+            _ => format!("—  {name} — не финишировал"),
+        };
+        crate::overlay::state::add_system_message(line);
+    }
+    crate::overlay::state::add_system_message(format!(
+        "Следующий заезд через {next_race_in_secs} с"
+    ));
+}
+
+/// Пересчитать race HUD. Вызывается на game thread каждый tick.
+pub fn tick_main_thread() {
+    let hud = match RACE.lock() {
+        Ok(r) => build_hud(&r),
+        Err(_) => return,
+    };
+    crate::overlay::state::set_race_hud(hud);
+}
+
+fn build_hud(r: &RaceState) -> Option<RaceHud> {
+    if !r.entered || r.checkpoints.is_empty() {
+        return None;
+    }
+
+    let local = crate::network::local_player_id();
+    let place = local
+        .and_then(|id| r.standings.iter().position(|p| *p == id))
+        .map(|i| (i + 1, r.standings.len()));
+
+    let (go_at, started, finished) = match r.phase {
+        Phase::Idle => return None,
+        Phase::Countdown { go_at } => (Some(go_at), None, None),
+        Phase::Running { started } => (None, Some(started), None),
+        Phase::Finished { place, total_ms } => (None, None, Some((place, total_ms))),
+    };
+
+    let target = if finished.is_none() {
+        target_for(&r.checkpoints, r.next_checkpoint)
+    } else {
+        None
+    };
+
+    Some(RaceHud {
+        track: r.track_name.clone(),
+        go_at,
+        started,
+        finished,
+        place,
+        lap: r.lap.min(r.laps),
+        laps: r.laps,
+        checkpoint: r.next_checkpoint,
+        checkpoints: r.checkpoints.len(),
+        splits: r.splits.clone(),
+        best_lap_ms: r.best_lap_ms,
+        target,
+    })
+}
+
+/// Направление и расстояние от локального игрока до чекпоинта.
+fn target_for(checkpoints: &[NetCheckpoint], index: usize) -> Option<RaceTarget> {
+    let cp = checkpoints.get(index)?;
+    let player = Player::get_active()?;
+    let pos = player.get_position()?;
+    let forward = player.get_forward()?;
+
+    let dx = cp.position.x - pos.x;
+    let dy = cp.position.y - pos.y;
+    let dz = cp.position.z - pos.z;
+    let distance_m = (dx * dx + dy * dy + dz * dz).sqrt();
+
+    // Угол в плоскости XY: положительный — чекпоинт справа.
+    let heading = forward.x.atan2(forward.y);
+    let bearing = dx.atan2(dy);
+    let mut relative = (bearing - heading).to_degrees();
+    if relative > 180.0 {
+        relative -= 360.0;
+    } else if relative < -180.0 {
+        relative += 360.0;
+    }
+
+    Some(RaceTarget {
+        bearing_deg: relative,
+        distance_m,
+        is_finish: index + 1 == checkpoints.len(),
+    })
+}
+
+/// `m:ss.mmm`.
+pub fn format_ms(ms: u32) -> String {
+    let minutes = ms / 60_000;
+    let seconds = (ms / 1_000) % 60;
+    format!("{minutes}:{seconds:02}.{:03}", ms % 1_000)
+}
@@ -8,26 +8,33 @@
 //!
 //! Ограничения v0:
 //! - максимум 2 удалённых игрока (Joe, Henry)
-//! - только position / forward / health / death
-//! - vehicle enter/leave пока только логируются
+//! - синхронизируются position / forward / health / death / оружие в руках /
+//!   модель и посадка в машину
+//! - машины (`vehicles`): proxy садится в ту же машину на то же место, что
+//!   игрок, и выходит из неё; пока он сидит, snapshot'ы игрока двигают только
+//!   здоровье — машину водителя ведут snapshot'ы машины
+//! - машину, которой нет в нашем стриминге, не спавним: proxy остаётся пешим
 //!
-//! **Locomotion:** в движении — `MoveDir` + `apply_from_delta(prev, target, dt_net)` на
-//! каждом snapshot, где **`dt_net`** — фактический интервал между приходами snapshot
-//! (clamp 45..550 мс), а не константа 150 мс. Между snapshot позицию ведёт движок.
-//! В стоянии — только `SetPos` + lerp на тике. Сброс `CleanMoveCommands` — при стопе,
-//! телепорте, первом snapshot и входе в движение (не на каждом шаге).
+//! **Locomotion:** snapshot'ы копятся в `common::interp::SnapshotBuffer` (джиттер,
+//! задержка воспроизведения, Эрмит, экстраполяция, телепорты — там). На каждом
+//! игровом тике биндинг берёт из буфера точку: в движении — `MoveDir` +
+//! `apply_from_delta(prev, target, dt)` от прошлой точки к новой, в стоянии —
+//! `SetPos`. Сброс `CleanMoveCommands` — при стопе, телепорте, первом snapshot и
+//! входе в движение (не на каждом шаге).
 //!
 //! Реверс DE / vtable сообщений: `sdk::game::remote_locomotion_de`, env
 //! `M2MP_LOG_ENTITY_MSG_VTABLES=1` в `human_messages`.
 //! TODO: см. sdk::game::remote_locomotion_de
 
 use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{LazyLock, Mutex, OnceLock};
 use std::time::Instant;
 
+use common::interp::{self, InterpConfig, Playout, SnapshotBuffer};
 use common::logger;
-use protocol::{NetPlayerEvent, NetPlayerSnapshot, PlayerId};
+use protocol::{NetAppearance, NetPlayerEvent, NetPlayerSnapshot, NetVec3, PlayerId, VehicleId};
 use sdk::game::aim_look::HumanAim;
+use sdk::game::car::Car;
 use sdk::game::npc::Npc;
 use sdk::game::npc_motion::MoveDirCommand;
 use sdk::game::spawn;
@@ -35,9 +42,6 @@ use sdk::game::Player;
 use sdk::structures::CHumanAIController;
 use sdk::types::Vec3;
 
-/// Если рассинхрон позиций > этого значения — форсим SetPos (телепорт).
-const TELEPORT_THRESHOLD_M: f32 = 8.0;
-
 /// Если drift между фактической позицией NPC и целью > этого значения,
 /// делаем мягкую коррекцию через SetPos (без телепорта).
 ///
@@ -45,10 +49,6 @@ const TELEPORT_THRESHOLD_M: f32 = 8.0;
 /// Больше плавнее анимация, но NPC может отставать.
 const DRIFT_CORRECTION_M: f32 = 1.5;
 
-/// Сколько секунд между snapshot'ами (приблизительно — зависит
-/// от `TRACK_INTERVAL_MS` в player_tracker)
-const SNAPSHOT_DT_SECS: f32 = 0.15;
-
 /// Сброс MoveDir handle + нативной очереди move-команд.
 fn hard_reset_remote_locomotion(binding: &mut RemoteBinding) {
     if let Some(cmd) = binding.move_cmd {
@@ -63,11 +63,32 @@ const EST_GAME_DT_SECS: f32 = 1.0 / 60.0;
 
 static INTERP_LAST_CLOCK: Mutex<Option<Instant>> = Mutex::new(None);
 
-/// Стандартный пистолет (Colt M1911A1) для авто-выдачи remote NPC.
-/// Без оружия SetupAimDir не виден визуально (нет ствола = нет подъёма).
-/// Нужно добавлять CHuman_WeaponChangeAnim
-const DEFAULT_REMOTE_WEAPON_ID: u32 = 4;
-const DEFAULT_REMOTE_WEAPON_AMMO: u32 = 100;
+/// Локальные часы буферов интерполяции (`arrival` и выборка).
+static INTERP_CLOCK: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+fn interp_now() -> f64 {
+    INTERP_CLOCK.elapsed().as_secs_f64()
+}
+
+fn to_interp(v: NetVec3) -> interp::Vec3 {
+    interp::Vec3::new(v.x, v.y, v.z)
+}
+
+fn from_interp(v: interp::Vec3) -> Vec3 {
+    Vec3 { x: v.x, y: v.y, z: v.z }
+}
+
+/// Что кроме позиции воспроизводится из snapshot'а вместе с ней.
+#[derive(Debug, Clone, Copy)]
+struct Motion {
+    is_moving: bool,
+    movement_mode: u8,
+    forward: NetVec3,
+}
+
+/// Патроны при выдаче proxy-NPC оружия удалённого игрока: proxy не
+/// стреляет сам, патроны нужны только для анимации перезарядки.
+const REMOTE_WEAPON_AMMO: u32 = 100;
 
 #[derive(Debug)]
 struct RemoteBinding {
@@ -76,32 +97,80 @@ struct RemoteBinding {
     npc_name: String,
     npc: Npc,
     move_cmd: Option<MoveDirCommand>,
-    /// Предыдущая позиция из **сетевого** snapshot (для телепорта / длины шага).
-    last_net_pos: Option<Vec3>,
-    /// Интерполяция: начало сегмента (прошлая сетевая точка).
-    interp_from: Vec3,
-    /// Интерполяция: конец сегмента (последняя сетевая точка).
-    interp_to: Vec3,
-    /// Когда пришёл текущий `interp_to`.
-    interp_begin: Instant,
-    /// Время предыдущего сетевого snapshot (для `dt` в `apply_from_delta`).
-    last_net_sample_at: Option<Instant>,
-    /// Копия последнего snapshot: идёт ли удалённый игрок.
+    /// Сетевые snapshot'ы позиции в ожидании воспроизведения.
+    motion: SnapshotBuffer<Motion>,
+    /// Точка, выставленная на прошлом тике (начало шага `apply_from_delta`).
+    last_played: Option<Vec3>,
+    /// Идёт ли удалённый игрок в воспроизводимый момент.
     remote_is_moving: bool,
-    /// Копия `movement_mode` из последнего snapshot.
+    /// `movement_mode` в воспроизводимый момент.
     remote_movement_mode: u8,
+    /// Режим буфера на прошлом тике и счётчики для netgraph.
+    playout: Playout,
+    extrapolations: u32,
+    teleports: u32,
+    /// Здоровье из последнего snapshot'а (для ника над головой).
+    health: f32,
+    is_dead: bool,
+    /// Внешний вид, который последним пытались выставить proxy
+    /// (`None` — вид по умолчанию от `spawn_human_npc`).
+    appearance: Option<NetAppearance>,
+    /// Оружие в руках proxy, выставленное последним (`None` — убрано).
+    weapon_in_hand: Option<u32>,
+    /// Что уже выдано в инвентарь proxy.
+    weapons_given: Vec<u32>,
+    /// Оружие, которое движок выдать отказался: не пробуем и не
+    /// предупреждаем повторно на каждом snapshot.
+    weapons_failed: Vec<u32>,
     /// Включён ли сейчас SetupAimDir на этом NPC
     /// (используем чтобы не дёргать движок на каждом snapshot,
     /// а только при смене состояния).
     aim_active: bool,
+    /// Машина и место proxy (`vehicles`), `None` — пешком.
+    vehicle: Option<(VehicleId, u8)>,
 }
 
 static BINDINGS: OnceLock<Mutex<HashMap<PlayerId, RemoteBinding>>> = OnceLock::new();
 
+/// Внешний вид удалённых игроков от сервера. Отдельно от `BINDINGS`:
+/// `PlayerAppearance` может прийти раньше, чем proxy заспавнится
+/// (`ensure_binding` откладывается, пока локальный игрок не готов).
+static APPEARANCES: LazyLock<Mutex<HashMap<PlayerId, NetAppearance>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 fn bindings() -> &'static Mutex<HashMap<PlayerId, RemoteBinding>> {
     BINDINGS.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+/// Адрес proxy-NPC -> PlayerId.
+///
+/// Отдельно от `BINDINGS`: читается из hook'а рассылки сообщений, который
+/// может сработать синхронно из вызова движка, пока `BINDINGS` заблокирован.
+static PROXY_INDEX: Mutex<Vec<(usize, PlayerId)>> = Mutex::new(Vec::new());
+
+/// Какому удалённому игроку принадлежит entity (если это proxy).
+pub fn proxy_owner(entity_ptr: usize) -> Option<PlayerId> {
+    PROXY_INDEX
+        .lock()
+        .ok()?
+        .iter()
+        .find(|(ptr, _)| *ptr == entity_ptr)
+        .map(|(_, id)| *id)
+}
+
+fn index_proxy(entity_ptr: usize, player_id: PlayerId) {
+    if let Ok(mut index) = PROXY_INDEX.lock() {
+        index.retain(|(_, id)| *id != player_id);
+        index.push((entity_ptr, player_id));
+    }
+}
+
+fn unindex_proxy(player_id: PlayerId) {
+    if let Ok(mut index) = PROXY_INDEX.lock() {
+        index.retain(|(_, id)| *id != player_id);
+    }
+}
+
 /// Смещение начального спавна proxy-NPC относительно локального игрока.
 /// 3 метра в сторону, чтобы не появиться внутри player'а.
 const SPAWN_OFFSET: Vec3 = Vec3 { x: 3.0, y: 0.0, z: 0.0 };
@@ -110,7 +179,7 @@ pub fn init() {
     let _ = bindings();
 }
 
-/// Плавное сближение с сетевой позицией на каждом игровом тике
+/// Воспроизвести буферы snapshot'ов на каждом игровом тике
 ///
 /// Вызывается из `main_thread::on_main_thread_tick` (game thread).
 pub fn tick_interpolation() {
@@ -120,7 +189,9 @@ pub fn tick_interpolation() {
         Err(_) => return,
     };
     for b in map.values_mut() {
-        b.tick_interpolate(dt);
+        if b.vehicle.is_none() {
+            b.tick_interpolate(dt);
+        }
     }
 }
 
@@ -143,12 +214,22 @@ pub fn clear_all() {
     if let Ok(mut map) = bindings().lock() {
         map.clear();
     }
+    if let Ok(mut index) = PROXY_INDEX.lock() {
+        index.clear();
+    }
+    if let Ok(mut appearances) = APPEARANCES.lock() {
+        appearances.clear();
+    }
 }
 
 /// Удалить привязку одного удалённого игрока.
 pub fn remove_binding(player_id: PlayerId) {
+    if let Ok(mut appearances) = APPEARANCES.lock() {
+        appearances.remove(&player_id);
+    }
     if let Ok(mut map) = bindings().lock() {
         if let Some(mut binding) = map.remove(&player_id) {
+            unindex_proxy(player_id);
             hard_reset_remote_locomotion(&mut binding);
             if binding.aim_active {
                 unsafe {
@@ -228,15 +309,12 @@ pub fn ensure_binding(player_id: PlayerId, player_name: &str) {
         }
     };
 
-    // Авто-выдача оружия — для visible aim sync.
-    let weapon_ok = npc.add_weapon(DEFAULT_REMOTE_WEAPON_ID, DEFAULT_REMOTE_WEAPON_AMMO);
-
     logger::info(&format!(
-        "[remote] bound player {} ('{}') -> fresh NPC 0x{:X} \
-         (ai_off={}, weapon_given={})",
-        player_id, player_name, entity_ptr, ai_inactivated, weapon_ok
+        "[remote] bound player {} ('{}') -> fresh NPC 0x{:X} (ai_off={})",
+        player_id, player_name, entity_ptr, ai_inactivated
     ));
 
+    index_proxy(entity_ptr, player_id);
     map.insert(
         player_id,
         RemoteBinding {
@@ -245,16 +323,79 @@ pub fn ensure_binding(player_id: PlayerId, player_name: &str) {
             npc_name: format!("Proxy#{}", player_id),
             npc,
             move_cmd: None,
-            last_net_pos: None,
-            interp_from: Vec3::ZERO,
-            interp_to: Vec3::ZERO,
-            interp_begin: Instant::now(),
-            last_net_sample_at: None,
+            motion: SnapshotBuffer::new(InterpConfig::default()),
+            last_played: None,
             remote_is_moving: false,
             remote_movement_mode: 0,
+            playout: Playout::Buffering,
+            extrapolations: 0,
+            teleports: 0,
+            health: 0.0,
+            is_dead: false,
+            appearance: None,
+            weapon_in_hand: None,
+            weapons_given: Vec::new(),
+            weapons_failed: Vec::new(),
             aim_active: false,
+            vehicle: None,
         },
     );
+
+    let wanted = APPEARANCES
+        .lock()
+        .ok()
+        .and_then(|a| a.get(&player_id).cloned());
+    if let (Some(binding), Some(wanted)) = (map.get_mut(&player_id), wanted) {
+        apply_appearance_to_binding(binding, &wanted);
+    }
+}
+
+/// Сервер прислал внешний вид удалённого игрока. Вызывается на game thread.
+///
+/// Если proxy ещё нет — вид применится в `ensure_binding`.
+pub fn apply_appearance(player_id: PlayerId, appearance: NetAppearance) {
+    if let Ok(mut appearances) = APPEARANCES.lock() {
+        appearances.insert(player_id, appearance.clone());
+    }
+    let mut map = match bindings().lock() {
+        Ok(m) => m,
+        Err(_) => {
+            logger::error("[remote] mutex poisoned in apply_appearance");
+            return;
+        }
+    };
+    if let Some(binding) = map.get_mut(&player_id) {
+        apply_appearance_to_binding(binding, &appearance);
+    }
+}
+
+/// Перекрасить proxy. Неизвестная или невалидная модель — proxy остаётся
+/// в прежнем виде; повторно ту же модель не пробуем.
+fn apply_appearance_to_binding(binding: &mut RemoteBinding, wanted: &NetAppearance) {
+    if binding.appearance.as_ref() == Some(wanted) {
+        return;
+    }
+    binding.appearance = Some(wanted.clone());
+
+    if !wanted.is_valid() {
+        logger::warn(&format!(
+            "[remote] {} (NPC '{}'): invalid model name {:?}, keeping current look",
+            binding.player_name, binding.npc_name, wanted.model
+        ));
+        return;
+    }
+
+    let ok = binding.npc.set_model(&wanted.model, wanted.appearance_id);
+    logger::info(&format!(
+        "[remote] {} (NPC '{}') model={} appearance={:?} ok={}",
+        binding.player_name, binding.npc_name, wanted.model, wanted.appearance_id, ok
+    ));
+    if ok {
+        // Смена модели могла сбросить оружие и прицел — следующий snapshot
+        // выставит их заново.
+        binding.weapon_in_hand = None;
+        binding.aim_active = false;
+    }
 }
 
 /// Применить удалённый snapshot к связанному NPC.
@@ -294,36 +435,65 @@ pub fn apply_snapshot(snapshot: NetPlayerSnapshot) {
 }
 
 fn apply_snapshot_to_binding(binding: &mut RemoteBinding, snapshot: &NetPlayerSnapshot) {
-    let pos = Vec3 {
-        x: snapshot.position.x,
-        y: snapshot.position.y,
-        z: snapshot.position.z,
-    };
-    let dir = Vec3 {
-        x: snapshot.forward.x,
-        y: snapshot.forward.y,
-        z: snapshot.forward.z,
-    };
-
-    let dir_len_sq = dir.x * dir.x + dir.y * dir.y + dir.z * dir.z;
-    let dir_valid = dir_len_sq > 0.0001;
-
-    apply_network_motion_sample(
-        binding,
-        pos,
-        snapshot.is_moving,
-        snapshot.movement_mode,
-    );
-    // В стоянии — forward из сети; в движении корпус по шагу задаёт `apply_network_motion_sample`.
-    if dir_valid && !snapshot.is_moving {
-        let _ = binding.npc.set_forward(&dir);
-    }
     apply_health(binding, snapshot);
+    // В машине proxy везёт машина (`vehicles`).
+    if binding.vehicle.is_some() {
+        return;
+    }
+    binding.motion.push(
+        snapshot.tick,
+        interp_now(),
+        to_interp(snapshot.position),
+        Motion {
+            is_moving: snapshot.is_moving,
+            movement_mode: snapshot.movement_mode,
+            forward: snapshot.forward,
+        },
+    );
+    apply_weapon(binding, snapshot.weapon_id);
     apply_aim(binding, snapshot);
 
     crate::overlay::state::update_player_ping(binding.player_id as u32, 0);
 }
 
+/// Выставить proxy-NPC оружие в руках (`None` — убрать в кобуру).
+///
+/// Источник — и события `WeaponSelect`/`WeaponHide`, и snapshot'ы: событие
+/// даёт мгновенную реакцию, snapshot чинит пропущенное и подхватывает
+/// оружие уже вооружённого игрока при подключении. Движок дёргаем только
+/// при смене.
+fn apply_weapon(binding: &mut RemoteBinding, weapon_id: Option<u32>) {
+    if binding.weapon_in_hand == weapon_id {
+        return;
+    }
+    let ok = match weapon_id {
+        Some(id) if binding.weapons_failed.contains(&id) => return,
+        Some(id) => {
+            if !binding.weapons_given.contains(&id) {
+                if !binding.npc.add_weapon(id, REMOTE_WEAPON_AMMO) {
+                    logger::warn(&format!(
+                        "[remote] {} (NPC '{}'): add_weapon({}) failed, больше не пробуем",
+                        binding.player_name, binding.npc_name, id
+                    ));
+                    binding.weapons_failed.push(id);
+                    return;
+                }
+                binding.weapons_given.push(id);
+            }
+            binding.npc.select_weapon(id)
+        }
+        None => binding.npc.holster_weapon(),
+    };
+    logger::info(&format!(
+        "[remote] {} (NPC '{}') weapon {:?} -> {:?} ok={}",
+        binding.player_name, binding.npc_name, binding.weapon_in_hand, weapon_id, ok
+    ));
+    // При неудаче не запоминаем — следующий snapshot попробует снова.
+    if ok {
+        binding.weapon_in_hand = weapon_id;
+    }
+}
+
 /// Применить состояние прицела к remote NPC.
 ///
 /// - `is_aiming = true`  → SetupAimDir(npc, true, &dir)
@@ -359,123 +529,91 @@ fn apply_aim(binding: &mut RemoteBinding, snapshot: &NetPlayerSnapshot) {
     }
 }
 
-/// Записать новую сетевую позицию в состояние биндинга.
-///
-/// См. модульный комментарий вверху файла.
-fn apply_network_motion_sample(
-    binding: &mut RemoteBinding,
-    target_pos: Vec3,
-    is_moving: bool,
-    movement_mode: u8,
-) {
-    let was_moving = binding.remote_is_moving;
-    binding.remote_is_moving = is_moving;
-    binding.remote_movement_mode = movement_mode;
-
-    let prev_net = binding.last_net_pos;
-
-    // (1) Первый snapshot — телепорт в стартовую точку.
-    let Some(prev) = prev_net else {
-        binding.npc.set_position(&target_pos);
-        binding.last_net_pos = Some(target_pos);
-        binding.interp_from = target_pos;
-        binding.interp_to = target_pos;
-        binding.interp_begin = Instant::now();
-        binding.last_net_sample_at = Some(Instant::now());
-        hard_reset_remote_locomotion(binding);
-        return;
-    };
-
-    let delta = target_pos - prev;
-    let dist = (delta.x * delta.x + delta.y * delta.y).sqrt();
-
-    // (2) Реальный телепорт (loading screen / большой скачок).
-    if dist >= TELEPORT_THRESHOLD_M {
-        binding.npc.set_position(&target_pos);
-        hard_reset_remote_locomotion(binding);
-        binding.last_net_pos = Some(target_pos);
-        binding.interp_from = target_pos;
-        binding.interp_to = target_pos;
-        binding.interp_begin = Instant::now();
-        binding.last_net_sample_at = Some(Instant::now());
-        return;
-    }
-
-    let now = Instant::now();
-    let net_dt = binding
-        .last_net_sample_at
-        .as_ref()
-        .map(|t| (now - *t).as_secs_f32())
-        .unwrap_or(SNAPSHOT_DT_SECS)
-        .clamp(0.045, 0.55);
-    binding.last_net_sample_at = Some(now);
-
-    // Новый сегмент интерполяции: от прошлой сетевой цели к новой.
-    binding.interp_from = binding.interp_to;
-    binding.interp_to = target_pos;
-    binding.interp_begin = Instant::now();
-    binding.last_net_pos = Some(target_pos);
-
-    if is_moving {
-        if !was_moving {
-            hard_reset_remote_locomotion(binding);
+impl RemoteBinding {
+    /// Выставить NPC в точку буфера на текущий момент.
+    ///
+    /// См. модульный комментарий вверху файла.
+    fn tick_interpolate(&mut self, dt_secs: f32) {
+        let Some(sample) = self.motion.sample(interp_now()) else {
+            return;
+        };
+        if sample.playout == Playout::Extrapolating && self.playout != Playout::Extrapolating {
+            self.extrapolations += 1;
         }
-        if binding.move_cmd.is_none() {
-            binding.move_cmd = unsafe { binding.npc.create_move_dir_command() };
+        self.playout = sample.playout;
+        let target = from_interp(sample.position);
+        let motion = sample.payload;
+        let was_moving = self.remote_is_moving;
+        self.remote_is_moving = motion.is_moving;
+        self.remote_movement_mode = motion.movement_mode;
+
+        // Первый snapshot или скачок (loading screen, респаун) — телепорт.
+        if sample.teleported {
+            self.teleports += 1;
+            self.npc.set_position(&target);
+            hard_reset_remote_locomotion(self);
+            self.last_played = Some(target);
+            return;
         }
-        if let Some(cmd) = binding.move_cmd {
-            let applied = unsafe { cmd.apply_from_delta(prev, target_pos, net_dt) };
-            if applied {
-                unsafe { cmd.set_movement_mode_low_byte(movement_mode) };
-                let dx = target_pos.x - prev.x;
-                let dy = target_pos.y - prev.y;
-                let len = (dx * dx + dy * dy).sqrt();
-                if len > 0.025 {
-                    let _ = binding.npc.set_forward(&Vec3 {
-                        x: dx / len,
-                        y: dy / len,
-                        z: 0.0,
-                    });
-                }
-            } else {
-                binding.npc.set_position(&target_pos);
+        let prev = self.last_played.replace(target).unwrap_or(target);
+
+        if !motion.is_moving || sample.playout == Playout::Stalled {
+            if was_moving {
+                hard_reset_remote_locomotion(self);
+            }
+            self.npc.set_position(&target);
+            let dir = Vec3 {
+                x: motion.forward.x,
+                y: motion.forward.y,
+                z: motion.forward.z,
+            };
+            if dir.x * dir.x + dir.y * dir.y + dir.z * dir.z > 0.0001 {
+                let _ = self.npc.set_forward(&dir);
             }
-        } else {
-            binding.npc.set_position(&target_pos);
+            return;
         }
-    } else {
-        hard_reset_remote_locomotion(binding);
-    }
-}
 
-impl RemoteBinding {
-    fn tick_interpolate(&mut self, _dt_secs: f32) {
-        if self.last_net_pos.is_none() {
-            return;
+        if !was_moving {
+            hard_reset_remote_locomotion(self);
         }
-        if self.remote_is_moving {
+        if self.move_cmd.is_none() {
+            self.move_cmd = unsafe { self.npc.create_move_dir_command() };
+        }
+        let applied = self
+            .move_cmd
+            .is_some_and(|cmd| unsafe { cmd.apply_from_delta(prev, target, dt_secs) });
+        if !applied {
+            self.npc.set_position(&target);
             return;
         }
+        if let Some(cmd) = self.move_cmd {
+            unsafe { cmd.set_movement_mode_low_byte(motion.movement_mode) };
+        }
+        let (vx, vy) = (sample.velocity.x, sample.velocity.y);
+        let speed = (vx * vx + vy * vy).sqrt();
+        if speed > 0.1 {
+            let _ = self.npc.set_forward(&Vec3 {
+                x: vx / speed,
+                y: vy / speed,
+                z: 0.0,
+            });
+        }
 
-        let elapsed = self.interp_begin.elapsed().as_secs_f32();
-        let alpha = (elapsed / SNAPSHOT_DT_SECS).min(1.0);
-        let goal = self.interp_from.lerp(&self.interp_to, alpha);
-
-        self.npc.set_position(&goal);
-
+        // Шаги движка расходятся с сетью — подтягиваем, если ушли далеко.
         if let Some(npc_pos) = self.npc.get_position() {
-            let drift_x = npc_pos.x - self.interp_to.x;
-            let drift_y = npc_pos.y - self.interp_to.y;
-            let drift = (drift_x * drift_x + drift_y * drift_y).sqrt();
-            if drift > DRIFT_CORRECTION_M {
-                self.npc.set_position(&self.interp_to);
+            let drift_x = npc_pos.x - target.x;
+            let drift_y = npc_pos.y - target.y;
+            if (drift_x * drift_x + drift_y * drift_y).sqrt() > DRIFT_CORRECTION_M {
+                self.npc.set_position(&target);
             }
         }
     }
 }
 
 /// Применить health snapshot (death / restore).
-fn apply_health(binding: &RemoteBinding, snapshot: &NetPlayerSnapshot) {
+fn apply_health(binding: &mut RemoteBinding, snapshot: &NetPlayerSnapshot) {
+    binding.health = snapshot.health;
+    binding.is_dead = snapshot.is_dead;
     if snapshot.is_dead {
         binding.npc.set_health(0.0);
         logger::debug(&format!(
@@ -487,6 +625,130 @@ fn apply_health(binding: &RemoteBinding, snapshot: &NetPlayerSnapshot) {
     }
 }
 
+/// Что нужно нику над удалённым игроком.
+#[derive(Debug, Clone)]
+pub struct NametagTarget {
+    pub player_id: PlayerId,
+    pub name: String,
+    /// Позиция NPC (ступни).
+    pub position: Vec3,
+    pub health: f32,
+    pub is_dead: bool,
+}
+
+/// Текущие позиции proxy-NPC. Вызывается на game thread.
+pub fn nametag_targets() -> Vec<NametagTarget> {
+    let Ok(map) = bindings().lock() else {
+        return Vec::new();
+    };
+    map.values()
+        .filter_map(|b| {
+            Some(NametagTarget {
+                player_id: b.player_id,
+                name: b.player_name.clone(),
+                position: b.npc.get_position()?,
+                health: b.health,
+                is_dead: b.is_dead,
+            })
+        })
+        .collect()
+}
+
+/// Посадить proxy удалённого игрока в машину `car` на место `seat`.
+///
+/// Вызывается из `vehicles` на game thread.
+pub fn enter_vehicle(player_id: PlayerId, vehicle_id: VehicleId, car: &Car, seat: u8) {
+    let Ok(mut map) = bindings().lock() else {
+        return;
+    };
+    let Some(binding) = map.get_mut(&player_id) else {
+        return;
+    };
+    if binding.vehicle == Some((vehicle_id, seat)) {
+        return;
+    }
+
+    hard_reset_remote_locomotion(binding);
+    if binding.aim_active {
+        unsafe { HumanAim::new(binding.npc.ptr()).clear() };
+        binding.aim_active = false;
+    }
+    let ok = binding.npc.enter_vehicle(car, seat);
+    logger::info(&format!(
+        "[remote] {} (NPC '{}') enter vehicle {} seat {} ok={}",
+        binding.player_name, binding.npc_name, vehicle_id, seat, ok
+    ));
+    if ok {
+        binding.vehicle = Some((vehicle_id, seat));
+        binding.motion.clear();
+        binding.last_played = None;
+    }
+}
+
+/// Высадить proxy удалённого игрока. Следующий snapshot ставит его на
+/// место выхода (буфер пуст — первая точка телепортом).
+pub fn leave_vehicle(player_id: PlayerId) {
+    let Ok(mut map) = bindings().lock() else {
+        return;
+    };
+    let Some(binding) = map.get_mut(&player_id) else {
+        return;
+    };
+    let Some((vehicle_id, _)) = binding.vehicle.take() else {
+        return;
+    };
+    let ok = binding.npc.leave_vehicle();
+    logger::info(&format!(
+        "[remote] {} (NPC '{}') leave vehicle {} ok={}",
+        binding.player_name, binding.npc_name, vehicle_id, ok
+    ));
+    binding.motion.clear();
+    binding.last_played = None;
+}
+
+/// Позиция proxy-NPC удалённого игрока (ступни). Вызывается на game thread.
+pub fn proxy_position(player_id: PlayerId) -> Option<Vec3> {
+    bindings().lock().ok()?.get(&player_id)?.npc.get_position()
+}
+
+/// Буфер интерполяции одного удалённого игрока — для netgraph.
+#[derive(Debug, Clone)]
+pub struct InterpStats {
+    pub player_id: PlayerId,
+    pub name: String,
+    /// Snapshot'ов в буфере.
+    pub buffered: usize,
+    pub delay_ms: Option<f32>,
+    pub jitter_ms: f32,
+    pub interval_ms: Option<f32>,
+    pub playout: Playout,
+    /// Сколько раз буфер уходил в экстраполяцию.
+    pub extrapolations: u32,
+    pub teleports: u32,
+}
+
+pub fn interp_stats() -> Vec<InterpStats> {
+    let Ok(map) = bindings().lock() else {
+        return Vec::new();
+    };
+    let mut stats: Vec<_> = map
+        .values()
+        .map(|b| InterpStats {
+            player_id: b.player_id,
+            name: b.player_name.clone(),
+            buffered: b.motion.len(),
+            delay_ms: b.motion.delay().map(|d| (d * 1000.0) as f32),
+            jitter_ms: (b.motion.jitter() * 1000.0) as f32,
+            interval_ms: b.motion.interval().map(|i| (i * 1000.0) as f32),
+            playout: b.playout,
+            extrapolations: b.extrapolations,
+            teleports: b.teleports,
+        })
+        .collect();
+    stats.sort_by_key(|s| s.player_id);
+    stats
+}
+
 /// Получить имя удалённого игрока по его ID.
 #[allow(dead_code)]
 pub fn get_player_name(player_id: PlayerId) -> Option<String> {
@@ -501,9 +763,13 @@ pub fn get_player_name(player_id: PlayerId) -> Option<String> {
 ///
 /// Пока v0 делает только безопасные вещи:
 /// - Death -> hp=0
+/// - WeaponSelect / WeaponHide / WeaponReload -> оружие proxy
+/// - ShotFired -> выстрел proxy (`shots::fire_proxy`), без урона локальному игроку
+/// - EnterVehicle / LeaveVehicle — только лог: посадку ведут
+///   `ServerPacket::VehicleEnter` / `VehicleLeave` (`vehicles`)
 /// - остальное логируется
 pub fn apply_event(player_id: PlayerId, event: NetPlayerEvent) {
-    let map = match bindings().lock() {
+    let mut map = match bindings().lock() {
         Ok(m) => m,
         Err(_) => {
             logger::error("[remote] mutex poisoned in apply_event");
@@ -511,7 +777,7 @@ pub fn apply_event(player_id: PlayerId, event: NetPlayerEvent) {
         }
     };
 
-    let Some(binding) = map.get(&player_id) else {
+    let Some(binding) = map.get_mut(&player_id) else {
         return;
     };
 
@@ -524,20 +790,58 @@ pub fn apply_event(player_id: PlayerId, event: NetPlayerEvent) {
             ));
         }
 
-        NetPlayerEvent::Shot => {
+        NetPlayerEvent::ShotFired {
+            weapon_id,
+            origin,
+            direction,
+            hit_player,
+            hit_pos,
+        } => {
+            if binding.is_dead {
+                return;
+            }
+            apply_weapon(binding, Some(weapon_id));
+            if binding.weapon_in_hand != Some(weapon_id) {
+                return;
+            }
+
+            // Ствол — туда, куда стрелял игрок; снимет следующий snapshot
+            // без прицела.
+            let dir = Vec3 {
+                x: direction.x,
+                y: direction.y,
+                z: direction.z,
+            };
+            unsafe { HumanAim::new(binding.npc.ptr()).set_aim_dir(true, &dir) };
+            binding.aim_active = true;
+
+            let target = crate::shots::shot_target(origin, direction, hit_pos);
+            let ok = crate::shots::fire_proxy(&binding.npc, &target, weapon_id);
             logger::debug(&format!(
-                "[remote] {} (NPC '{}') shot",
-                binding.player_name, binding.npc_name
+                "[remote] {} (NPC '{}') shot weapon={} hit={:?} ok={}",
+                binding.player_name, binding.npc_name, weapon_id, hit_player, ok
             ));
         }
 
+        NetPlayerEvent::WeaponSelect { weapon_id } => apply_weapon(binding, Some(weapon_id)),
+        NetPlayerEvent::WeaponHide => apply_weapon(binding, None),
+
+        NetPlayerEvent::WeaponReload { weapon_id } => {
+            // Snapshot со сменой мог ещё не дойти — сначала берём оружие в руки.
+            apply_weapon(binding, Some(weapon_id));
+            if binding.weapon_in_hand == Some(weapon_id) && !binding.npc.reload_weapon() {
+                logger::debug(&format!(
+                    "[remote] {} (NPC '{}') reload failed",
+                    binding.player_name, binding.npc_name
+                ));
+            }
+        }
+
         NetPlayerEvent::EnterVehicle
         | NetPlayerEvent::EnterVehicleDone
         | NetPlayerEvent::LeaveVehicle
         | NetPlayerEvent::LeaveVehicleDone
         | NetPlayerEvent::Damage
-        | NetPlayerEvent::WeaponSelect
-        | NetPlayerEvent::WeaponHide
         | NetPlayerEvent::Fx(_) => {
             logger::debug(&format!(
                 "[remote] {} (NPC '{}'): {:?}",
@@ -0,0 +1,105 @@
+//! Серверный респаун локального игрока.
+//!
+//! Поток:
+//! - после смерти сервер шлёт `ServerPacket::RespawnPending` — HUD показывает отсчёт
+//! - по истечении задержки приходит `ServerPacket::Respawn` с точкой, здоровьем
+//!   и стартовым набором оружия
+//! - применение откладывается, пока активный игрок недоступен (загрузка)
+//!
+//! Движок сам игрока не воскрешает, поэтому сначала снимается флаг смерти
+//! (`Player::revive`), и только потом телепорт/здоровье/оружие.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use common::logger;
+use protocol::{NetVec3, NetWeapon};
+use sdk::game::Player;
+use sdk::types::Vec3;
+
+#[derive(Debug, Clone)]
+struct PendingRespawn {
+    position: NetVec3,
+    heading: f32,
+    health: f32,
+    loadout: Vec<NetWeapon>,
+}
+
+static PENDING: Mutex<Option<PendingRespawn>> = Mutex::new(None);
+
+/// Сбросить состояние (disconnect).
+pub fn reset() {
+    if let Ok(mut p) = PENDING.lock() {
+        *p = None;
+    }
+    crate::overlay::state::set_respawn_countdown(None);
+}
+
+/// Сервер зафиксировал смерть и назначил задержку.
+pub fn on_pending(delay_ms: u32) {
+    crate::overlay::state::set_respawn_countdown(Some(Duration::from_millis(delay_ms as u64)));
+}
+
+/// Сервер прислал точку респауна. Вызывается на game thread.
+pub fn on_respawn(position: NetVec3, heading: f32, health: f32, loadout: Vec<NetWeapon>) {
+    if let Ok(mut p) = PENDING.lock() {
+        *p = Some(PendingRespawn {
+            position,
+            heading,
+            health,
+            loadout,
+        });
+    }
+    apply_pending();
+}
+
+/// Применить отложенный респаун, когда игрок готов.
+///
+/// Вызывается на game thread каждый tick.
+pub fn tick_main_thread() {
+    apply_pending();
+}
+
+fn apply_pending() {
+    let Some(player) = Player::get_active() else {
+        return;
+    };
+
+    let pending = match PENDING.lock() {
+        Ok(mut p) => p.take(),
+        Err(_) => return,
+    };
+
+    let Some(r) = pending else {
+        return;
+    };
+
+    let position = Vec3::new(r.position.x, r.position.y, r.position.z);
+
+    if !player.revive() || !player.set_position(&position) {
+        logger::warn("[respawn] игрок не готов, повтор на следующем tick");
+        if let Ok(mut p) = PENDING.lock() {
+            p.get_or_insert(r);
+        }
+        return;
+    }
+
+    player.set_heading(r.heading);
+    player.set_health(r.health);
+
+    for weapon in &r.loadout {
+        if !player.add_weapon(weapon.weapon_id, weapon.ammo) {
+            logger::warn(&format!(
+                "[respawn] не удалось выдать оружие id={}",
+                weapon.weapon_id
+            ));
+        }
+    }
+
+    crate::overlay::state::set_respawn_countdown(None);
+
+    logger::info(&format!(
+        "[respawn] респаун в ({:.1}, {:.1}, {:.1}), курс {:.0}°, hp={:.0}",
+        position.x, position.y, position.z, r.heading, r.health
+    ));
+}
@@ -0,0 +1,341 @@
+//! Браузер серверов: вкладки, фильтры, сортировка, избранное и недавние.
+//!
+//! Список строится из `discovery::results()`. Избранное — в настройках
+//! клиента (`settings`), недавние — в `BROWSER_PATH`; сломанный файл не
+//! фатален — начинаем с пустых списков. Адрес мастер-сервера — там же
+//! (`master`), без него вкладка «Интернет» пустая.
+
+use std::net::SocketAddr;
+use std::sync::{LazyLock, Mutex};
+
+use common::logger;
+use protocol::PROTOCOL_VERSION;
+use protocol::master::ListFilter;
+use protocol::query::ServerInfo;
+use serde::{Deserialize, Serialize};
+
+use crate::discovery;
+use crate::overlay::state;
+use crate::settings;
+
+/// Файл недавних серверов (рядом с логами клиента).
+const BROWSER_PATH: &str = "m2mp_servers.json";
+
+/// Сколько недавних серверов помнить.
+const MAX_RECENT: usize = 10;
+
+/// Сервер в избранном / недавних.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedServer {
+    pub addr: SocketAddr,
+    /// Имя на момент сохранения — для строки, пока сервер не ответил.
+    pub name: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Saved {
+    /// `host:port` мастер-сервера.
+    master: Option<String>,
+    /// Избранное старых версий — переносится в `settings` в `init()`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    favorites: Vec<SavedServer>,
+    /// Последний — первым.
+    recent: Vec<SavedServer>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Internet,
+    Lan,
+    Favorites,
+    Recent,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [Tab::Internet, Tab::Lan, Tab::Favorites, Tab::Recent];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tab::Internet => "ИНТЕРНЕТ",
+            Tab::Lan => "ЛОКАЛЬНЫЕ",
+            Tab::Favorites => "ИЗБРАННОЕ",
+            Tab::Recent => "НЕДАВНИЕ",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Players,
+    Ping,
+    Mode,
+}
+
+/// Состояние окна браузера.
+#[derive(Debug, Clone)]
+pub struct View {
+    pub tab: Tab,
+    pub sort: SortKey,
+    pub descending: bool,
+    pub search: String,
+    pub hide_full: bool,
+    pub hide_password: bool,
+    /// Только серверы с нашей версией протокола.
+    pub compatible_only: bool,
+    pub selected: Option<SocketAddr>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            tab: Tab::Internet,
+            sort: SortKey::Players,
+            descending: true,
+            search: String::new(),
+            hide_full: false,
+            hide_password: false,
+            compatible_only: true,
+            selected: None,
+        }
+    }
+}
+
+impl View {
+    fn filter(&self) -> ListFilter {
+        let search = self.search.trim();
+        ListFilter {
+            name: (!search.is_empty()).then(|| search.to_string()),
+            hide_full: self.hide_full,
+            hide_password: self.hide_password,
+            protocol_version: self.compatible_only.then_some(PROTOCOL_VERSION),
+            ..ListFilter::default()
+        }
+    }
+
+    /// Клик по заголовку столбца: тот же столбец — сменить направление.
+    pub fn sort_by(&mut self, key: SortKey) {
+        if self.sort == key {
+            self.descending = !self.descending;
+        } else {
+            self.sort = key;
+            // Игроков интереснее сверху больше, остальное — по возрастанию.
+            self.descending = key == SortKey::Players;
+        }
+    }
+}
+
+/// Строка таблицы.
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub addr: SocketAddr,
+    pub name: String,
+    /// `None` — сервер из избранного / недавних не ответил.
+    pub info: Option<ServerInfo>,
+    pub ping_ms: Option<u32>,
+    pub favorite: bool,
+}
+
+static SAVED: LazyLock<Mutex<Saved>> = LazyLock::new(|| Mutex::new(load()));
+static VIEW: LazyLock<Mutex<View>> = LazyLock::new(|| Mutex::new(View::default()));
+
+fn load() -> Saved {
+    let text = match std::fs::read_to_string(BROWSER_PATH) {
+        Ok(t) => t,
+        Err(_) => return Saved::default(),
+    };
+    serde_json::from_str::<Saved>(&text).unwrap_or_else(|e| {
+        logger::warn(&format!("[browser] {BROWSER_PATH} повреждён: {e}"));
+        Saved::default()
+    })
+}
+
+/// Перенести избранное старых версий из `BROWSER_PATH` в настройки.
+///
+/// Вызывать после `settings::init()`: иначе перенесённое затрёт загрузка.
+pub fn init() {
+    let legacy = match SAVED.lock() {
+        Ok(mut saved) if !saved.favorites.is_empty() => {
+            let legacy = std::mem::take(&mut saved.favorites);
+            save(&saved);
+            legacy
+        }
+        _ => return,
+    };
+    logger::info(&format!(
+        "[browser] {} избранных перенесено в настройки",
+        legacy.len()
+    ));
+    settings::update(|s| {
+        for server in legacy {
+            if !s.favorites.iter().any(|f| f.addr == server.addr) {
+                s.favorites.push(server);
+            }
+        }
+    });
+}
+
+fn save(saved: &Saved) {
+    let result = serde_json::to_string_pretty(saved)
+        .map_err(|e| e.to_string())
+        .and_then(|text| std::fs::write(BROWSER_PATH, text).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        logger::warn(&format!(
+            "[browser] не удалось сохранить {BROWSER_PATH}: {e}"
+        ));
+    }
+}
+
+pub fn view() -> View {
+    VIEW.lock().map(|v| v.clone()).unwrap_or_default()
+}
+
+pub fn set_view(view: View) {
+    if let Ok(mut v) = VIEW.lock() {
+        *v = view;
+    }
+}
+
+/// Опросить мастер, LAN и все избранные / недавние адреса.
+pub fn refresh() {
+    let favorites = settings::get().favorites;
+    let (master, targets) = match SAVED.lock() {
+        Ok(saved) => {
+            let mut targets: Vec<SocketAddr> = favorites
+                .iter()
+                .chain(&saved.recent)
+                .map(|s| s.addr)
+                .collect();
+            targets.sort();
+            targets.dedup();
+            (saved.master.clone(), targets)
+        }
+        Err(_) => (None, Vec::new()),
+    };
+    discovery::refresh(master, targets);
+}
+
+pub fn toggle_favorite(addr: SocketAddr, name: &str) {
+    settings::update(|s| {
+        if s.favorites.iter().any(|f| f.addr == addr) {
+            s.favorites.retain(|f| f.addr != addr);
+        } else {
+            s.favorites.push(SavedServer {
+                addr,
+                name: name.to_string(),
+            });
+        }
+    });
+}
+
+/// Запомнить сервер, к которому подключаемся.
+pub fn add_recent(addr: SocketAddr, name: &str) {
+    let Ok(mut saved) = SAVED.lock() else {
+        return;
+    };
+    saved.recent.retain(|r| r.addr != addr);
+    saved.recent.insert(
+        0,
+        SavedServer {
+            addr,
+            name: name.to_string(),
+        },
+    );
+    saved.recent.truncate(MAX_RECENT);
+    save(&saved);
+}
+
+/// Строки текущей вкладки с фильтрами и сортировкой.
+///
+/// Не ответившие серверы избранного / недавних фильтрам не подлежат и идут
+/// в конце: про них ничего не известно, кроме имени.
+pub fn rows(view: &View) -> Vec<Row> {
+    let results = discovery::results();
+    let favorites = settings::get().favorites;
+    let recent = SAVED.lock().map(|s| s.recent.clone()).unwrap_or_default();
+    let is_favorite = |addr: SocketAddr| favorites.iter().any(|f| f.addr == addr);
+
+    let online = |server: &discovery::DiscoveredServer| Row {
+        addr: server.addr,
+        name: server.info.name.clone(),
+        info: Some(server.info.clone()),
+        ping_ms: Some(server.ping_ms),
+        favorite: is_favorite(server.addr),
+    };
+    let saved_row = |saved: &SavedServer| match results.iter().find(|r| r.addr == saved.addr) {
+        Some(server) => online(server),
+        None => Row {
+            addr: saved.addr,
+            name: saved.name.clone(),
+            info: None,
+            ping_ms: None,
+            favorite: is_favorite(saved.addr),
+        },
+    };
+
+    let mut rows: Vec<Row> = match view.tab {
+        Tab::Internet => results
+            .iter()
+            .filter(|s| s.from_master)
+            .map(online)
+            .collect(),
+        Tab::Lan => results.iter().filter(|s| s.lan).map(online).collect(),
+        Tab::Favorites => favorites.iter().map(saved_row).collect(),
+        Tab::Recent => recent.iter().map(saved_row).collect(),
+    };
+
+    let filter = view.filter();
+    rows.retain(|row| row.info.as_ref().is_none_or(|info| filter.matches(info)));
+
+    rows.sort_by(|a, b| {
+        let (Some(ia), Some(ib)) = (&a.info, &b.info) else {
+            return b.info.is_some().cmp(&a.info.is_some());
+        };
+        let order = match view.sort {
+            SortKey::Name => ia.name.to_lowercase().cmp(&ib.name.to_lowercase()),
+            SortKey::Players => ia.players.cmp(&ib.players),
+            SortKey::Ping => a.ping_ms.cmp(&b.ping_ms),
+            SortKey::Mode => ia.mode.cmp(&ib.mode),
+        };
+        if view.descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+    rows
+}
+
+/// Подключиться к серверу из браузера.
+///
+/// Сервер с паролем при пустом поле пароля не подключаем: адрес уходит в
+/// окно подключения, игрок вводит пароль там.
+pub fn join(row: &Row) {
+    let needs_password = row.info.as_ref().is_some_and(|i| i.password);
+    let (nickname, password) = {
+        let Ok(mut conn) = state::CONNECTION.lock() else {
+            return;
+        };
+        conn.ip = row.addr.ip().to_string();
+        conn.port = row.addr.port().to_string();
+        (conn.nickname.clone(), conn.password.clone())
+    };
+
+    if needs_password && password.is_empty() {
+        state::notify("Сервер защищён паролем — введите его в окне подключения");
+        state::close_browser();
+        return;
+    }
+
+    add_recent(row.addr, &row.name);
+    if crate::network::connect(
+        &row.addr.ip().to_string(),
+        row.addr.port(),
+        &nickname,
+        &password,
+    ) {
+        state::close_browser();
+        state::close_connect();
+    }
+}
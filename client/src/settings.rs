@@ -0,0 +1,179 @@
+//! Настройки клиента: последний сервер, ник, избранное, оверлей, клавиши,
+//! масштаб UI.
+//!
+//! Хранятся в `SETTINGS_PATH` рядом с игрой. Загружаются в `initialize()`
+//! (`init`), сохраняются при каждом изменении через `update`.
+//!
+//! Разбор, переживающий смену версий, — в `common::settings`: отсутствующие
+//! и битые ключи берутся по умолчанию, неизвестные остаются в `extra`.
+//! Файл, который вообще не JSON, откладывается в `.bak`.
+
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+
+use common::logger;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::server_browser::SavedServer;
+
+const SETTINGS_PATH: &str = "m2mp_client.json";
+
+/// Допустимый масштаб оверлея.
+pub const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub last_server: LastServer,
+    pub nickname: String,
+    pub favorites: Vec<SavedServer>,
+    pub overlay: OverlaySettings,
+    /// Действие -> клавиша (`"Ctrl+F2"`).
+    pub keybindings: BTreeMap<String, String>,
+    /// Масштаб оверлея (egui zoom factor).
+    pub ui_scale: f32,
+    /// `"ник@адрес"` -> токен аккаунта, выданный сервером
+    /// (`ServerPacket::AccountToken`).
+    pub account_tokens: BTreeMap<String, String>,
+    /// Ключи, которых эта версия не знает.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            last_server: LastServer::default(),
+            nickname: "Player".into(),
+            favorites: Vec::new(),
+            overlay: OverlaySettings::default(),
+            keybindings: BTreeMap::new(),
+            ui_scale: 1.0,
+            account_tokens: BTreeMap::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastServer {
+    pub ip: String,
+    pub port: u16,
+}
+
+impl Default for LastServer {
+    fn default() -> Self {
+        Self {
+            ip: "127.0.0.1".into(),
+            port: protocol::DEFAULT_PORT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlaySettings {
+    pub show_debug: bool,
+    /// Пассивный чат гаснет через несколько секунд.
+    pub chat_fade: bool,
+    pub show_netgraph: bool,
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            show_debug: true,
+            chat_fade: true,
+            show_netgraph: false,
+        }
+    }
+}
+
+static SETTINGS: LazyLock<Mutex<Settings>> = LazyLock::new(|| Mutex::new(Settings::default()));
+
+/// Загрузить файл и применить к оверлею.
+pub fn init() {
+    let settings = load();
+    logger::info(&format!(
+        "[settings] {SETTINGS_PATH}: server {}:{}, nickname '{}', scale {:.2}",
+        settings.last_server.ip, settings.last_server.port, settings.nickname, settings.ui_scale
+    ));
+    apply(&settings);
+    if let Ok(mut s) = SETTINGS.lock() {
+        *s = settings;
+    }
+}
+
+pub fn get() -> Settings {
+    SETTINGS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Изменить настройки; файл переписывается, только если что-то поменялось.
+pub fn update(change: impl FnOnce(&mut Settings)) {
+    let Ok(mut settings) = SETTINGS.lock() else {
+        return;
+    };
+    let before = settings.clone();
+    change(&mut settings);
+    settings.ui_scale = clamp_scale(settings.ui_scale);
+    if *settings != before {
+        save(&settings);
+    }
+}
+
+fn clamp_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end())
+    } else {
+        1.0
+    }
+}
+
+fn apply(settings: &Settings) {
+    if let Ok(mut c) = crate::overlay::state::CONNECTION.lock() {
+        c.ip.clone_from(&settings.last_server.ip);
+        c.port = settings.last_server.port.to_string();
+        c.nickname.clone_from(&settings.nickname);
+    }
+    crate::overlay::state::set_show_debug(settings.overlay.show_debug);
+    crate::overlay::state::set_chat_fade(settings.overlay.chat_fade);
+    crate::overlay::state::set_show_netgraph(settings.overlay.show_netgraph);
+    crate::overlay::state::set_ui_scale(settings.ui_scale);
+}
+
+fn load() -> Settings {
+    let text = match std::fs::read_to_string(SETTINGS_PATH) {
+        Ok(t) => t,
+        Err(_) => return Settings::default(),
+    };
+    match common::settings::parse::<Settings>(&text) {
+        Ok((mut settings, warnings)) => {
+            for warning in warnings {
+                logger::warn(&format!("[settings] {SETTINGS_PATH}: {warning}"));
+            }
+            settings.ui_scale = clamp_scale(settings.ui_scale);
+            settings
+        }
+        Err(e) => {
+            let backup = format!("{SETTINGS_PATH}.bak");
+            logger::warn(&format!(
+                "[settings] {SETTINGS_PATH} повреждён ({e}), отложен в {backup}"
+            ));
+            let _ = std::fs::rename(SETTINGS_PATH, backup);
+            Settings::default()
+        }
+    }
+}
+
+fn save(settings: &Settings) {
+    let result = serde_json::to_string_pretty(settings)
+        .map_err(|e| e.to_string())
+        .and_then(|text| std::fs::write(SETTINGS_PATH, text).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        logger::warn(&format!(
+            "[settings] не удалось сохранить {SETTINGS_PATH}: {e}"
+        ));
+    }
+}
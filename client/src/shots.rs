@@ -0,0 +1,266 @@
+//! Выстрелы и урон между игроками.
+//!
+//! - Выстрел локального игрока (Human Message `SHOT`) уходит событием
+//!   `ShotFired`: откуда, куда и в кого попал (по `hits`).
+//! - Чужой `ShotFired` proxy стрелка повторяет настоящим выстрелом
+//!   (`Npc::fire_weapon`) — со звуком, вспышкой и трассером.
+//! - Урон от других игроков локальный игрок получает только по
+//!   `ServerPacket::Damage`, то есть после проверки сервером, и через
+//!   движковый `ProcessDamage` (`Player::process_damage`).
+//!
+//! Пуля proxy — движковая и ранит локального игрока сама. Такой DAMAGE
+//! hook рассылки отбрасывает (`is_proxy_damage`): то же оружие, что у
+//! proxy, выстрелившего за `PROXY_DAMAGE_WINDOW`. Урон от NPC с тем же
+//! оружием в это окно тоже теряется — редкая цена за то, что одно
+//! попадание не снимает здоровье дважды.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::{NetPlayerEvent, NetVec3, PlayerId};
+use sdk::addresses::constants::weapons;
+use sdk::game::{Player, camera, npc::Npc};
+use sdk::structures::{DamageMessage, DamageTemplate};
+use sdk::types::Vec3;
+
+/// Высота ствола над ступнями стрелка.
+const MUZZLE_HEIGHT_M: f32 = 1.4;
+/// Куда целимся в proxy жертвы — грудь, над ступнями.
+const CHEST_HEIGHT_M: f32 = 1.2;
+/// Дальность выстрела «в молоко», если стрелок ни в кого не попал.
+const MISS_RANGE_M: f32 = 100.0;
+/// Сколько после выстрела proxy DAMAGE его оружием считается от него.
+const PROXY_DAMAGE_WINDOW: Duration = Duration::from_millis(250);
+/// Зона попадания «корпус» — для подтверждённого урона не в голову.
+const BODY_PART_TORSO: u32 = 0x11;
+/// DAMAGE по proxy засчитывается локальному игроку, только если он сам
+/// стрелял за это время: proxy теперь тоже стреляют и задевают друг друга.
+const LOCAL_SHOT_WINDOW: Duration = Duration::from_millis(250);
+
+#[derive(Debug)]
+struct ShotState {
+    /// Выстрелы локального игрока с прошлого tick.
+    pending: u32,
+    last_local_shot: Option<Instant>,
+    /// Недавние выстрелы proxy: когда и из какого оружия.
+    proxy_shots: Vec<(Instant, u32)>,
+    /// Последнее увиденное DAMAGE-сообщение — заготовка для своего урона.
+    damage_template: Option<DamageTemplate>,
+}
+
+static STATE: Mutex<ShotState> = Mutex::new(ShotState {
+    pending: 0,
+    last_local_shot: None,
+    proxy_shots: Vec::new(),
+    damage_template: None,
+});
+
+/// `Npc::fire_weapon` упал — больше не пробуем (и не спамим лог).
+static FIRE_BROKEN: AtomicBool = AtomicBool::new(false);
+
+/// Локальный игрок выстрелил. Вызывается из `player_events`.
+pub fn on_local_shot() {
+    if let Ok(mut s) = STATE.lock() {
+        s.pending += 1;
+        s.last_local_shot = Some(Instant::now());
+    }
+}
+
+/// Стрелял ли локальный игрок только что (см. `LOCAL_SHOT_WINDOW`).
+pub fn local_fired_recently() -> bool {
+    STATE.lock().is_ok_and(|s| {
+        s.last_local_shot
+            .is_some_and(|t| t.elapsed() <= LOCAL_SHOT_WINDOW)
+    })
+}
+
+/// Вызывается на game thread каждый tick, после `hits::flush`.
+///
+/// `victims` — кому в этом tick ушли попадания, по порядку: i-й выстрел
+/// получает i-ю жертву.
+pub fn tick_main_thread(victims: &[PlayerId]) {
+    let pending = match STATE.lock() {
+        Ok(mut s) => {
+            s.proxy_shots
+                .retain(|(t, _)| t.elapsed() <= PROXY_DAMAGE_WINDOW);
+            std::mem::take(&mut s.pending)
+        }
+        Err(_) => return,
+    };
+
+    if pending > 0 {
+        send_local_shots(pending, victims);
+    }
+}
+
+fn send_local_shots(count: u32, victims: &[PlayerId]) {
+    if !crate::network::is_connected() {
+        return;
+    }
+    let Some(player) = Player::get_active().filter(|p| p.is_ready()) else {
+        return;
+    };
+    let Some(weapon_id) = player
+        .get_weapon_in_hand_id()
+        .filter(|&id| id != weapons::EMPTY_HANDS)
+    else {
+        return;
+    };
+    let Some(position) = player.get_position() else {
+        return;
+    };
+
+    // Камера смотрит туда же, куда прицел; без неё — куда повёрнут игрок.
+    let direction = camera::current_view()
+        .map(|c| NetVec3 {
+            x: c.forward.x,
+            y: c.forward.y,
+            z: c.forward.z,
+        })
+        .or_else(|| {
+            player.get_forward_vector().map(|f| NetVec3 {
+                x: f.x,
+                y: f.y,
+                z: f.z,
+            })
+        });
+    let Some(direction) = direction.and_then(normalize) else {
+        return;
+    };
+    let origin = NetVec3 {
+        x: position.x,
+        y: position.y,
+        z: position.z + MUZZLE_HEIGHT_M,
+    };
+
+    for i in 0..count as usize {
+        let hit_player = victims.get(i).copied();
+        let hit_pos = hit_player
+            .and_then(crate::remote_players::proxy_position)
+            .map(|p| NetVec3 {
+                x: p.x,
+                y: p.y,
+                z: p.z + CHEST_HEIGHT_M,
+            });
+
+        crate::network::push_local_event(NetPlayerEvent::ShotFired {
+            weapon_id,
+            origin,
+            direction,
+            hit_player,
+            hit_pos,
+        });
+    }
+}
+
+fn normalize(v: NetVec3) -> Option<NetVec3> {
+    let len = (v.x * v.x + v.y * v.y + v.z * v.z).sqrt();
+    (len.is_finite() && len > 1e-4).then(|| NetVec3 {
+        x: v.x / len,
+        y: v.y / len,
+        z: v.z / len,
+    })
+}
+
+/// Куда стреляет proxy: в точку попадания или по направлению «в молоко».
+pub fn shot_target(origin: NetVec3, direction: NetVec3, hit_pos: Option<NetVec3>) -> Vec3 {
+    let p = hit_pos.unwrap_or(NetVec3 {
+        x: origin.x + direction.x * MISS_RANGE_M,
+        y: origin.y + direction.y * MISS_RANGE_M,
+        z: origin.z + direction.z * MISS_RANGE_M,
+    });
+    Vec3 {
+        x: p.x,
+        y: p.y,
+        z: p.z,
+    }
+}
+
+/// Выстрел proxy удалённого игрока из `weapon_id`.
+///
+/// Вызывается на game thread.
+pub fn fire_proxy(npc: &Npc, target: &Vec3, weapon_id: u32) -> bool {
+    if FIRE_BROKEN.load(Ordering::Relaxed) {
+        return false;
+    }
+    // Запоминаем до выстрела: DAMAGE приходит прямо из него.
+    if let Ok(mut s) = STATE.lock() {
+        s.proxy_shots.push((Instant::now(), weapon_id));
+    }
+
+    let ok = npc.fire_weapon(target);
+    if !ok {
+        FIRE_BROKEN.store(true, Ordering::Relaxed);
+        logger::warn("[shots] proxy не стреляют: выстрелы других игроков не видны");
+    }
+    ok
+}
+
+/// DAMAGE по локальному игроку от пули proxy (см. модульную доку).
+///
+/// `weapon_type` — `None` для сообщений без payload (`HEAD_DAMAGE`):
+/// тогда хватает любого недавнего выстрела proxy. Вызывается из hook'а.
+pub fn is_proxy_damage(weapon_type: Option<u32>) -> bool {
+    STATE.lock().is_ok_and(|s| {
+        s.proxy_shots.iter().any(|(t, weapon_id)| {
+            t.elapsed() <= PROXY_DAMAGE_WINDOW && weapon_type.is_none_or(|w| w == *weapon_id)
+        })
+    })
+}
+
+/// Запомнить DAMAGE-сообщение как заготовку. Вызывается из hook'а.
+pub fn remember_damage_message(message: &DamageMessage) {
+    let template = DamageTemplate::from_message(message);
+    if let Ok(mut s) = STATE.lock() {
+        s.damage_template = Some(template);
+    }
+}
+
+/// Сервер подтвердил попадание по локальному игроку.
+///
+/// Урон идёт через движок по заготовке DAMAGE-сообщения: смерть, реакция
+/// и `DEATH` — как от пули. Пока заготовки нет (в сессии ещё никого не
+/// ранило) — здоровье напрямую, смерть через скрипт-API.
+/// Вызывается на game thread из `network::poll_main_thread`.
+pub fn on_server_damage(attacker_id: PlayerId, weapon_id: u32, damage: f32, headshot: bool) {
+    let Some(player) = Player::get_active().filter(|p| p.is_ready()) else {
+        return;
+    };
+    if !player.is_alive().unwrap_or(false) {
+        return;
+    }
+    let Some(health) = player.get_health() else {
+        return;
+    };
+
+    logger::info(&format!(
+        "[shots] hit by player {} weapon={} dmg={:.1}{} hp={:.0}",
+        attacker_id,
+        weapon_id,
+        damage,
+        if headshot { " (head)" } else { "" },
+        health
+    ));
+
+    let template = STATE.lock().ok().and_then(|s| s.damage_template);
+    if let Some(template) = template {
+        let body_part = if headshot {
+            crate::hits::BODY_PART_HEAD
+        } else {
+            BODY_PART_TORSO
+        };
+        let mut message = template.with_damage(weapon_id, damage, body_part);
+        if player.process_damage(&mut message) {
+            return;
+        }
+    }
+
+    if health > damage {
+        player.add_health(-damage);
+    } else if !player.kill() {
+        // Без скриптовой смерти хотя бы обнуляем здоровье.
+        player.set_health(0.0);
+    }
+}
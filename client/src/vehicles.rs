@@ -0,0 +1,329 @@
+//! Машины в мультиплеере.
+//!
+//! Сервер выдаёт машине `VehicleId` и место (0 — водитель), а машина у
+//! каждого клиента своя — её находим по модели и месту (`resolve_car`).
+//! Дальше:
+//! - локальный игрок сел / вышел (`vehicle_tracker` -> `player_events`) —
+//!   `ClientPacket::VehicleEnter` с занятым местом / `VehicleLeave`;
+//! - локальный водитель шлёт `VehicleSnapshot` каждые `SNAPSHOT_INTERVAL`;
+//! - proxy удалённого игрока садится в ту же машину на то же место и
+//!   выходит из неё (`remote_players::enter_vehicle` / `leave_vehicle`);
+//! - машину удалённого водителя ведём по его snapshot'ам через
+//!   `common::interp::SnapshotBuffer`, как proxy пешком. Вращение — из
+//!   snapshot'а начала отрезка, без сглаживания.
+//!
+//! Машину, которой у нас нет (не в стриминге), не спавним: proxy тогда
+//! остаётся пешим и идёт по snapshot'ам игрока.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use common::interp::{self, InterpConfig, SnapshotBuffer};
+use common::logger;
+use protocol::{ClientPacket, NetVec3, NetVehicleSnapshot, PlayerId, VehicleId};
+use sdk::game::Player;
+use sdk::game::car::{self, Car};
+use sdk::types::Vec3;
+
+/// Как часто водитель шлёт snapshot машины.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Дальше этого наша копия машины — уже не та машина. Чуть больше
+/// серверного `MATCH_RADIUS_M`: копии расходятся из-за физики.
+const MATCH_RADIUS_M: f32 = 8.0;
+
+#[derive(Debug)]
+struct NetVehicle {
+    /// Адрес нашей копии; перед каждым использованием — `Car::from_ptr`.
+    car_addr: usize,
+    /// Кто сидит, включая локального игрока.
+    occupants: Vec<PlayerId>,
+    /// Удалённый водитель; `None` — за рулём мы или никто.
+    remote_driver: Option<PlayerId>,
+    motion: SnapshotBuffer<[f32; 4]>,
+}
+
+impl NetVehicle {
+    fn new(car_addr: usize) -> Self {
+        Self {
+            car_addr,
+            occupants: Vec::new(),
+            remote_driver: None,
+            motion: SnapshotBuffer::new(InterpConfig::default()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct VehicleState {
+    vehicles: HashMap<VehicleId, NetVehicle>,
+    /// Машина локального игрока в ожидании ответа сервера.
+    pending_local: Option<usize>,
+    /// Где сидит локальный игрок по ответу сервера.
+    local_seat: Option<(VehicleId, u8)>,
+    last_sent: Option<Instant>,
+    /// Новая сессия: если уже сидим в машине — сообщить серверу.
+    announce: bool,
+}
+
+static STATE: LazyLock<Mutex<VehicleState>> = LazyLock::new(|| Mutex::new(VehicleState::default()));
+
+/// Часы `NetVehicleSnapshot::tick` и буферов интерполяции.
+static CLOCK: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+fn now_secs() -> f64 {
+    CLOCK.elapsed().as_secs_f64()
+}
+
+fn to_net(v: Vec3) -> NetVec3 {
+    NetVec3 {
+        x: v.x,
+        y: v.y,
+        z: v.z,
+    }
+}
+
+/// Новая сессия (или её конец): все ID машин устарели.
+pub fn reset() {
+    if let Ok(mut s) = STATE.lock() {
+        *s = VehicleState {
+            announce: true,
+            ..VehicleState::default()
+        };
+    }
+}
+
+/// Локальный игрок сел в машину. Вызывается из `player_events`.
+pub fn on_local_enter(vehicle_ptr: usize) {
+    let Some(car) = Car::from_ptr(vehicle_ptr) else {
+        // Не C_Car (лодка, поезд) — не синхронизируем.
+        return;
+    };
+    let (Some(model_hash), Some(position)) = (car.name_hash(), car.get_position()) else {
+        return;
+    };
+    let Some(seat) = local_seat(&car) else {
+        return;
+    };
+    if let Ok(mut s) = STATE.lock() {
+        s.pending_local = Some(vehicle_ptr);
+        s.local_seat = None;
+    }
+    crate::network::push_vehicle_packet(ClientPacket::VehicleEnter {
+        model_hash,
+        position: to_net(position),
+        seat,
+    });
+}
+
+/// Место локального игрока. Если скрипт-API его не отдал — по числу
+/// сидящих: proxy садятся через сервер, так что порядок посадки у всех
+/// клиентов тот же.
+fn local_seat(car: &Car) -> Option<u8> {
+    Player::get_active()
+        .and_then(|p| p.get_vehicle_seat())
+        .or_else(|| {
+            let used = car.get_human_used_seat_count()?;
+            u8::try_from(used.saturating_sub(1)).ok()
+        })
+}
+
+/// Локальный игрок вышел из машины. Вызывается из `player_events`.
+pub fn on_local_leave() {
+    if let Ok(mut s) = STATE.lock() {
+        s.pending_local = None;
+        s.local_seat = None;
+    }
+    crate::network::push_vehicle_packet(ClientPacket::VehicleLeave);
+}
+
+/// Наша копия машины `model_hash`, ближайшая к `position`.
+fn resolve_car(model_hash: u64, position: NetVec3) -> Option<Car> {
+    let target = Vec3 {
+        x: position.x,
+        y: position.y,
+        z: position.z,
+    };
+    car::scan_all_cars()
+        .into_iter()
+        .filter(|c| c.name_hash() == Some(model_hash))
+        .filter_map(|c| Some((c, c.get_position()?.distance_to(&target))))
+        .filter(|(_, d)| *d <= MATCH_RADIUS_M)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(c, _)| c)
+}
+
+/// `ServerPacket::VehicleEnter`. Вызывается на game thread.
+pub fn on_enter(
+    player_id: PlayerId,
+    vehicle_id: VehicleId,
+    seat: u8,
+    model_hash: u64,
+    position: NetVec3,
+) {
+    let local = crate::network::local_player_id() == Some(player_id);
+    let Ok(mut s) = STATE.lock() else {
+        return;
+    };
+
+    // Наша копия: для себя — машина, в которую сели; для других — уже
+    // известная по ID или найденная по модели и месту.
+    let known = s
+        .vehicles
+        .get(&vehicle_id)
+        .and_then(|v| Car::from_ptr(v.car_addr));
+    let car = if local {
+        s.pending_local.and_then(Car::from_ptr)
+    } else {
+        known.or_else(|| resolve_car(model_hash, position))
+    };
+    let Some(car) = car else {
+        logger::warn(&format!(
+            "[vehicles] no local copy of vehicle {vehicle_id} (model {model_hash:#x}) for player {player_id}"
+        ));
+        return;
+    };
+
+    let vehicle = s
+        .vehicles
+        .entry(vehicle_id)
+        .or_insert_with(|| NetVehicle::new(car.addr()));
+    vehicle.car_addr = car.addr();
+    if !vehicle.occupants.contains(&player_id) {
+        vehicle.occupants.push(player_id);
+    }
+    if seat == 0 {
+        vehicle.remote_driver = (!local).then_some(player_id);
+        vehicle.motion.clear();
+    }
+
+    if local {
+        s.pending_local = None;
+        s.local_seat = Some((vehicle_id, seat));
+        logger::info(&format!(
+            "[vehicles] local player -> vehicle {vehicle_id} seat {seat}"
+        ));
+        return;
+    }
+    drop(s);
+
+    crate::remote_players::enter_vehicle(player_id, vehicle_id, &car, seat);
+}
+
+/// `ServerPacket::VehicleLeave`. Вызывается на game thread.
+pub fn on_leave(player_id: PlayerId, vehicle_id: VehicleId) {
+    let local = crate::network::local_player_id() == Some(player_id);
+    if let Ok(mut s) = STATE.lock() {
+        if let Some(vehicle) = s.vehicles.get_mut(&vehicle_id) {
+            vehicle.occupants.retain(|id| *id != player_id);
+            if vehicle.remote_driver == Some(player_id) {
+                vehicle.remote_driver = None;
+                vehicle.motion.clear();
+            }
+            if vehicle.occupants.is_empty() {
+                s.vehicles.remove(&vehicle_id);
+            }
+        }
+        if local && s.local_seat.is_some_and(|(id, _)| id == vehicle_id) {
+            s.local_seat = None;
+        }
+    }
+    if !local {
+        crate::remote_players::leave_vehicle(player_id);
+    }
+}
+
+/// `ServerPacket::VehicleSnapshot` от удалённого водителя.
+pub fn on_snapshot(snapshot: NetVehicleSnapshot) {
+    let Ok(mut s) = STATE.lock() else {
+        return;
+    };
+    let Some(vehicle) = s.vehicles.get_mut(&snapshot.vehicle_id) else {
+        return;
+    };
+    if vehicle.remote_driver.is_none() {
+        return;
+    }
+    let p = snapshot.position;
+    vehicle.motion.push(
+        snapshot.tick,
+        now_secs(),
+        interp::Vec3::new(p.x, p.y, p.z),
+        snapshot.rotation,
+    );
+}
+
+/// Вызывается на game thread каждый tick.
+pub fn tick_main_thread() {
+    if crate::network::local_player_id().is_none() {
+        return;
+    }
+    let Some(player) = Player::get_active().filter(|p| p.is_ready()) else {
+        return;
+    };
+
+    let announce = STATE
+        .lock()
+        .map(|mut s| std::mem::take(&mut s.announce))
+        .unwrap_or(false);
+    if announce && let Some(ptr) = player.get_vehicle_ptr() {
+        on_local_enter(ptr);
+    }
+
+    let Ok(mut s) = STATE.lock() else {
+        return;
+    };
+    send_local_snapshot(&mut s, &player);
+    play_remote_drivers(&mut s);
+}
+
+fn send_local_snapshot(s: &mut VehicleState, player: &Player) {
+    let Some((vehicle_id, 0)) = s.local_seat else {
+        return;
+    };
+    if s.last_sent.is_some_and(|t| t.elapsed() < SNAPSHOT_INTERVAL) {
+        return;
+    }
+    let Some(car) = player.get_vehicle_as_car() else {
+        return;
+    };
+    let (Some(position), Some(rotation)) = (car.get_position(), car.get_rotation()) else {
+        return;
+    };
+    s.last_sent = Some(Instant::now());
+
+    crate::network::push_vehicle_packet(ClientPacket::VehicleSnapshot(NetVehicleSnapshot {
+        tick: CLOCK.elapsed().as_millis() as u64,
+        vehicle_id,
+        position: to_net(position),
+        rotation,
+    }));
+}
+
+fn play_remote_drivers(s: &mut VehicleState) {
+    let now = now_secs();
+    for vehicle in s.vehicles.values_mut() {
+        if vehicle.remote_driver.is_none() {
+            continue;
+        }
+        let Some(sample) = vehicle.motion.sample(now) else {
+            continue;
+        };
+        let Some(car) = Car::from_ptr(vehicle.car_addr) else {
+            continue;
+        };
+        let p = sample.position;
+        car.set_position(&Vec3 {
+            x: p.x,
+            y: p.y,
+            z: p.z,
+        });
+        // Поворот — между теми же snapshot'ами, что и позиция.
+        let rotation = match sample.next {
+            Some(next) => interp::slerp(sample.payload, next, sample.progress),
+            None => sample.payload,
+        };
+        car.set_rotation(&rotation);
+    }
+}
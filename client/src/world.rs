@@ -0,0 +1,111 @@
+//! Серверные время суток и погода.
+//!
+//! `ServerPacket::WorldState` приходит при входе, при смене погоды и
+//! периодически. Между пакетами час двигается локально по `time_scale`;
+//! в игру он пишется раз в `TIME_APPLY_EVERY`, иначе собственный цикл
+//! суток игры успевает разойтись с сервером.
+//!
+//! Применение — через `sdk::game::environment` (Lua), поэтому только
+//! на game thread и когда Lua VM готова.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use sdk::game::{Player, environment, lua};
+
+/// Как часто переписывать время суток в игре.
+const TIME_APPLY_EVERY: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct WorldState {
+    hour: f32,
+    time_scale: f32,
+    received: Instant,
+    weather: Option<String>,
+    /// Последний применённый шаблон.
+    applied_weather: Option<String>,
+    /// `None` — время ещё не применялось после пакета.
+    time_applied_at: Option<Instant>,
+    /// Ошибку установки времени логируем один раз.
+    time_warned: bool,
+}
+
+impl WorldState {
+    fn hour_now(&self) -> f32 {
+        let elapsed = self.received.elapsed().as_secs_f32();
+        (self.hour + elapsed * self.time_scale / 3600.0).rem_euclid(24.0)
+    }
+}
+
+static STATE: Mutex<Option<WorldState>> = Mutex::new(None);
+
+/// Сбросить состояние (disconnect). Игра дальше крутит свои сутки.
+pub fn reset() {
+    if let Ok(mut s) = STATE.lock() {
+        *s = None;
+    }
+}
+
+/// Сервер прислал время и погоду. Вызывается на game thread.
+pub fn on_world_state(hour: f32, time_scale: f32, weather: Option<String>) {
+    if let Ok(mut s) = STATE.lock() {
+        let (applied_weather, time_warned) = s
+            .take()
+            .map(|old| (old.applied_weather, old.time_warned))
+            .unwrap_or_default();
+        *s = Some(WorldState {
+            hour,
+            time_scale,
+            received: Instant::now(),
+            weather,
+            applied_weather,
+            time_applied_at: None,
+            time_warned,
+        });
+    }
+    apply();
+}
+
+/// Вызывается на game thread каждый tick.
+pub fn tick_main_thread() {
+    apply();
+}
+
+fn apply() {
+    if !lua::is_ready() || Player::get_active().is_none() {
+        return;
+    }
+
+    let Ok(mut guard) = STATE.lock() else {
+        return;
+    };
+    let Some(state) = guard.as_mut() else {
+        return;
+    };
+
+    if state.weather.is_some() && state.weather != state.applied_weather {
+        if let Some(weather) = &state.weather {
+            match environment::set_weather_template(weather) {
+                Ok(()) => logger::info(&format!("[world] погода: {weather}")),
+                Err(e) => logger::warn(&format!("[world] погода '{weather}': {e}")),
+            }
+        }
+        // При ошибке не повторяем каждый tick — ждём следующей смены.
+        state.applied_weather = state.weather.clone();
+    }
+
+    let due = state
+        .time_applied_at
+        .is_none_or(|at| at.elapsed() >= TIME_APPLY_EVERY);
+    if due {
+        state.time_applied_at = Some(Instant::now());
+        let hour = state.hour_now();
+        if let Err(e) = environment::set_time_of_day(hour)
+            && !state.time_warned
+        {
+            state.time_warned = true;
+            logger::warn(&format!("[world] время суток {hour:.2}: {e}"));
+        }
+    }
+}
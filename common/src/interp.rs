@@ -0,0 +1,622 @@
+//! Буфер snapshot'ов удалённого игрока и воспроизведение с задержкой.
+//!
+//! Движок не нужен: время передаётся числом (секунды локальных часов),
+//! позиции — своим `Vec3`. Поэтому модуль тестируется на любой машине
+//! синтетическими потоками с джиттером.
+//!
+//! Модель:
+//! - snapshot'ы упорядочены по времени отправителя (`tick` — мс его часов);
+//!   устаревшие и повторные отбрасываются
+//! - задержка доставки `arrival - sent` копится в окне; её минимум — оценка
+//!   сдвига часов, разброс до 95-го перцентиля — джиттер
+//! - воспроизводим на `now - сдвиг - delay`, где `delay` — джиттер плюс
+//!   интервал между snapshot'ами; `delay` подстраивается плавно
+//!   (`delay_slew`), чтобы время воспроизведения не прыгало
+//! - между snapshot'ами — кубический Эрмит по скоростям соседних точек
+//! - если новых snapshot'ов нет — экстраполяция по последней скорости не
+//!   дольше `max_extrapolation`, дальше стоим
+//! - скачок больше `teleport_distance` — телепорт: через него не
+//!   интерполируем, а сообщаем `teleported` один раз
+//! - данные snapshot'ов (`payload`) не смешиваются: выборка отдаёт оба
+//!   конца отрезка и долю пути, а смешивать — дело вызывающего (для
+//!   поворота машины — `slerp`)
+
+use std::collections::VecDeque;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const ZERO: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dot(self, o: Self) -> f32 {
+        self.x * o.x + self.y * o.y + self.z * o.z
+    }
+
+    pub fn cross(self, o: Self) -> Self {
+        Self::new(
+            self.y * o.z - self.z * o.y,
+            self.z * o.x - self.x * o.z,
+            self.x * o.y - self.y * o.x,
+        )
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+    fn add(self, o: Self) -> Self {
+        Self::new(self.x + o.x, self.y + o.y, self.z + o.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+    fn sub(self, o: Self) -> Self {
+        Self::new(self.x - o.x, self.y - o.y, self.z - o.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Self;
+    fn mul(self, k: f32) -> Self {
+        Self::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InterpConfig {
+    /// Границы задержки воспроизведения, с.
+    pub min_delay: f64,
+    pub max_delay: f64,
+    /// Скорость подстройки задержки: секунд задержки за секунду.
+    pub delay_slew: f64,
+    /// Сколько последних задержек доставки учитывать.
+    pub jitter_window: usize,
+    /// Предел экстраполяции при потере snapshot'ов, с.
+    pub max_extrapolation: f64,
+    /// Скачок между snapshot'ами, после которого это телепорт, м.
+    pub teleport_distance: f32,
+    /// Откат часов отправителя больше этого — он перезапустился, с.
+    pub restart_after: f64,
+}
+
+impl Default for InterpConfig {
+    fn default() -> Self {
+        Self {
+            min_delay: 0.05,
+            max_delay: 0.5,
+            delay_slew: 0.1,
+            jitter_window: 40,
+            max_extrapolation: 0.25,
+            teleport_distance: 8.0,
+            restart_after: 5.0,
+        }
+    }
+}
+
+/// Что делает буфер в момент выборки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playout {
+    /// Время воспроизведения ещё не дошло до первого snapshot'а.
+    Buffering,
+    Interpolating,
+    /// Новых snapshot'ов нет, продолжаем по скорости.
+    Extrapolating,
+    /// Экстраполяция исчерпана — стоим.
+    Stalled,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sampled<T> {
+    pub position: Vec3,
+    /// м/с.
+    pub velocity: Vec3,
+    /// Данные snapshot'а, с которого начинается текущий отрезок.
+    pub payload: T,
+    /// Данные snapshot'а, которым отрезок кончается; `None` вне
+    /// интерполяции.
+    pub next: Option<T>,
+    /// Доля пройденного отрезка `payload -> next`, `0..=1`.
+    pub progress: f32,
+    pub playout: Playout,
+    /// Воспроизведение прошло телепорт (или это первая выборка) —
+    /// позицию надо выставить жёстко.
+    pub teleported: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    /// Время отправителя, с.
+    t: f64,
+    position: Vec3,
+    payload: T,
+    /// Перед этой точкой разрыв (первая точка или скачок).
+    teleport: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SnapshotBuffer<T> {
+    config: InterpConfig,
+    samples: VecDeque<Entry<T>>,
+    /// Задержки доставки `arrival - t`.
+    transits: VecDeque<f64>,
+    /// Средний интервал между snapshot'ами отправителя.
+    interval: Option<f64>,
+    delay: Option<f64>,
+    last_now: Option<f64>,
+    last_render: Option<f64>,
+    /// `t` последнего телепорта, о котором уже сообщили.
+    reported_teleport: Option<f64>,
+}
+
+impl<T: Clone> SnapshotBuffer<T> {
+    pub fn new(config: InterpConfig) -> Self {
+        Self {
+            config,
+            samples: VecDeque::new(),
+            transits: VecDeque::new(),
+            interval: None,
+            delay: None,
+            last_now: None,
+            last_render: None,
+            reported_teleport: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Текущая задержка воспроизведения, с.
+    pub fn delay(&self) -> Option<f64> {
+        self.delay
+    }
+
+    /// Разброс задержки доставки (p95 - min), с.
+    pub fn jitter(&self) -> f64 {
+        let Some(min) = self.transits.iter().copied().reduce(f64::min) else {
+            return 0.0;
+        };
+        let mut sorted: Vec<f64> = self.transits.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let rank = ((sorted.len() as f64) * 0.95).ceil() as usize;
+        sorted[rank.saturating_sub(1)] - min
+    }
+
+    /// Средний интервал между snapshot'ами отправителя, с.
+    pub fn interval(&self) -> Option<f64> {
+        self.interval
+    }
+
+    /// Добавить snapshot: `sent_ms` — часы отправителя, `arrival` —
+    /// локальные часы, с. `false` — устаревший или повтор, отброшен.
+    pub fn push(&mut self, sent_ms: u64, arrival: f64, position: Vec3, payload: T) -> bool {
+        let t = sent_ms as f64 / 1000.0;
+        if let Some(last) = self.samples.back()
+            && t <= last.t
+        {
+            if last.t - t < self.config.restart_after {
+                return false;
+            }
+            self.clear();
+        }
+
+        let teleport = match self.samples.back() {
+            Some(last) => {
+                let dt = t - last.t;
+                self.interval = Some(match self.interval {
+                    Some(i) => i + (dt.clamp(0.01, 1.0) - i) * 0.1,
+                    None => dt.clamp(0.01, 1.0),
+                });
+                (position - last.position).length() >= self.config.teleport_distance
+            }
+            None => true,
+        };
+
+        self.transits.push_back(arrival - t);
+        while self.transits.len() > self.config.jitter_window.max(1) {
+            self.transits.pop_front();
+        }
+
+        self.samples.push_back(Entry {
+            t,
+            position,
+            payload,
+            teleport,
+        });
+        true
+    }
+
+    fn target_delay(&self) -> f64 {
+        let interval = self.interval.unwrap_or(0.1);
+        (self.jitter() + interval).clamp(self.config.min_delay, self.config.max_delay)
+    }
+
+    /// Скорость в точке `i` по соседям, не пересекая телепортов.
+    fn velocity_at(&self, i: usize) -> Vec3 {
+        let s = &self.samples;
+        let prev = (i > 0 && !s[i].teleport).then(|| i - 1);
+        let next = (i + 1 < s.len() && !s[i + 1].teleport).then_some(i + 1);
+        let (from, to) = match (prev, next) {
+            (Some(p), Some(n)) => (p, n),
+            (Some(p), None) => (p, i),
+            (None, Some(n)) => (i, n),
+            (None, None) => return Vec3::ZERO,
+        };
+        let dt = (s[to].t - s[from].t) as f32;
+        if dt <= 0.0 {
+            return Vec3::ZERO;
+        }
+        (s[to].position - s[from].position) * (1.0 / dt)
+    }
+
+    /// Состояние на локальный момент `now`, с.
+    pub fn sample(&mut self, now: f64) -> Option<Sampled<T>> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let target = self.target_delay();
+        let delay = match (self.delay, self.last_now) {
+            (Some(delay), Some(last_now)) => {
+                let step = self.config.delay_slew * (now - last_now).max(0.0);
+                delay + (target - delay).clamp(-step, step)
+            }
+            _ => target,
+        };
+        self.delay = Some(delay);
+        self.last_now = Some(now);
+
+        let offset = self
+            .transits
+            .iter()
+            .copied()
+            .reduce(f64::min)
+            .unwrap_or(0.0);
+        let mut render = now - offset - delay;
+        if let Some(last) = self.last_render {
+            render = render.max(last);
+        }
+        self.last_render = Some(render);
+
+        // Последняя точка не позже `render` (или первая, если ещё не дошли).
+        let a = self
+            .samples
+            .iter()
+            .rposition(|e| e.t <= render)
+            .unwrap_or(0);
+
+        let teleport_t = self
+            .samples
+            .iter()
+            .take(a + 1)
+            .rev()
+            .find(|e| e.teleport)
+            .map(|e| e.t);
+        let teleported = match (teleport_t, self.reported_teleport) {
+            (Some(t), Some(reported)) => t > reported,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if teleported {
+            self.reported_teleport = teleport_t;
+        }
+
+        let newest = self.samples.len() - 1;
+        let mut segment = None;
+        let (position, velocity, playout) = if render < self.samples[0].t {
+            (self.samples[0].position, Vec3::ZERO, Playout::Buffering)
+        } else if a == newest {
+            let over = render - self.samples[a].t;
+            let velocity = self.velocity_at(a);
+            let dt = over.min(self.config.max_extrapolation) as f32;
+            let playout = if over <= self.config.max_extrapolation {
+                Playout::Extrapolating
+            } else {
+                Playout::Stalled
+            };
+            let v = if playout == Playout::Stalled {
+                Vec3::ZERO
+            } else {
+                velocity
+            };
+            (self.samples[a].position + velocity * dt, v, playout)
+        } else if self.samples[a + 1].teleport {
+            // Разрыв впереди: держим точку до него.
+            (self.samples[a].position, Vec3::ZERO, Playout::Interpolating)
+        } else {
+            let (p0, p1) = (&self.samples[a], &self.samples[a + 1]);
+            let h = (p1.t - p0.t) as f32;
+            let u = ((render - p0.t) as f32 / h).clamp(0.0, 1.0);
+            let (m0, m1) = (self.velocity_at(a) * h, self.velocity_at(a + 1) * h);
+            let (pos, vel) = hermite(p0.position, m0, p1.position, m1, u);
+            segment = Some((p1.payload.clone(), u));
+            (pos, vel * (1.0 / h), Playout::Interpolating)
+        };
+
+        let payload = self.samples[a].payload.clone();
+        let (next, progress) = match segment {
+            Some((next, u)) => (Some(next), u),
+            None => (None, 0.0),
+        };
+
+        // Точка перед `a` нужна для скорости в `a`.
+        for _ in 0..a.saturating_sub(1) {
+            self.samples.pop_front();
+        }
+
+        Some(Sampled {
+            position,
+            velocity,
+            payload,
+            next,
+            progress,
+            playout,
+            teleported,
+        })
+    }
+}
+
+/// Сферическая интерполяция кватернионов `[x, y, z, w]` по кратчайшей дуге.
+pub fn slerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    let mut cos = a.iter().zip(&b).map(|(x, y)| x * y).sum::<f32>();
+    // q и -q — один поворот: идём по короткой дуге.
+    let b = if cos < 0.0 {
+        cos = -cos;
+        b.map(|x| -x)
+    } else {
+        b
+    };
+    let (wa, wb) = if cos > 0.9995 {
+        // Почти совпадают — линейно, без деления на sin ≈ 0.
+        (1.0 - t, t)
+    } else {
+        let angle = cos.acos();
+        let sin = angle.sin();
+        (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+    let q: [f32; 4] = std::array::from_fn(|i| a[i] * wa + b[i] * wb);
+    let len = q.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if len > 0.0 { q.map(|x| x / len) } else { a }
+}
+
+/// Кубический Эрмит: позиция и производная по `u`.
+fn hermite(p0: Vec3, m0: Vec3, p1: Vec3, m1: Vec3, u: f32) -> (Vec3, Vec3) {
+    let u2 = u * u;
+    let u3 = u2 * u;
+    let pos = p0 * (2.0 * u3 - 3.0 * u2 + 1.0)
+        + m0 * (u3 - 2.0 * u2 + u)
+        + p1 * (-2.0 * u3 + 3.0 * u2)
+        + m1 * (u3 - u2);
+    let vel = p0 * (6.0 * u2 - 6.0 * u)
+        + m0 * (3.0 * u2 - 4.0 * u + 1.0)
+        + p1 * (-6.0 * u2 + 6.0 * u)
+        + m1 * (3.0 * u2 - 2.0 * u);
+    (pos, vel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f64 = 1.0 / 60.0;
+    const SEND_EVERY: f64 = 0.1;
+
+    /// Детерминированный шум в `[0, 1)`.
+    struct Noise(u64);
+
+    impl Noise {
+        fn next(&mut self) -> f64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    /// Поток: `(t_отправки, arrival, позиция)`, упорядочен по arrival
+    /// (TCP не переставляет, но задержки разные).
+    fn stream(
+        until: f64,
+        jitter: f64,
+        seed: u64,
+        path: impl Fn(f64) -> Vec3,
+    ) -> Vec<(u64, f64, Vec3)> {
+        let mut noise = Noise(seed);
+        let mut packets = Vec::new();
+        let mut last_arrival: f64 = 0.0;
+        let mut t = 0.0;
+        while t <= until {
+            let arrival = (t + 0.05 + jitter * noise.next()).max(last_arrival);
+            last_arrival = arrival;
+            packets.push(((t * 1000.0).round() as u64, arrival, path(t)));
+            t += SEND_EVERY;
+        }
+        packets
+    }
+
+    /// Прогнать поток кадрами по 60 Гц до `until`.
+    fn play(
+        packets: &[(u64, f64, Vec3)],
+        until: f64,
+        mut on_frame: impl FnMut(f64, &Sampled<()>, &SnapshotBuffer<()>),
+    ) {
+        let mut buffer = SnapshotBuffer::new(InterpConfig::default());
+        let mut next = 0;
+        let mut now = 0.0;
+        while now <= until {
+            while next < packets.len() && packets[next].1 <= now {
+                let (sent, arrival, pos) = packets[next];
+                buffer.push(sent, arrival, pos, ());
+                next += 1;
+            }
+            if let Some(s) = buffer.sample(now) {
+                on_frame(now, &s, &buffer);
+            }
+            now += FRAME;
+        }
+    }
+
+    fn walk(t: f64) -> Vec3 {
+        Vec3::new(2.0 * t as f32, 0.0, 0.0)
+    }
+
+    #[test]
+    fn jittery_stream_plays_smoothly_without_extrapolating() {
+        let packets = stream(10.0, 0.08, 1, walk);
+        let mut last_x = None;
+        play(&packets, 10.0, |now, s, _| {
+            if now < 3.0 {
+                return;
+            }
+            assert_eq!(s.playout, Playout::Interpolating, "at {now:.2}");
+            assert!((s.velocity.x - 2.0).abs() < 0.05, "v={:?}", s.velocity);
+            if let Some(last) = last_x {
+                let step = s.position.x - last;
+                // 2 м/с за кадр ~ 0.033 м; подстройка задержки меняет темп на ±10%.
+                assert!(step > 0.025 && step < 0.042, "step {step} at {now:.2}");
+            }
+            last_x = Some(s.position.x);
+        });
+    }
+
+    #[test]
+    fn delay_adapts_to_measured_jitter() {
+        let delay_after = |jitter: f64| {
+            let mut delay = 0.0;
+            play(&stream(15.0, jitter, 2, walk), 15.0, |_, _, b| {
+                delay = b.delay().unwrap();
+            });
+            delay
+        };
+        let calm = delay_after(0.005);
+        let stormy = delay_after(0.2);
+        assert!(calm < 0.15, "calm delay {calm}");
+        assert!(stormy > 0.25, "stormy delay {stormy}");
+        assert!(stormy <= InterpConfig::default().max_delay);
+    }
+
+    #[test]
+    fn loss_extrapolates_briefly_then_stalls() {
+        let packets = stream(2.0, 0.0, 3, walk);
+        let mut stalled_at = None;
+        let mut max_x: f32 = 0.0;
+        play(&packets, 4.0, |now, s, _| {
+            max_x = max_x.max(s.position.x);
+            if s.playout == Playout::Stalled && stalled_at.is_none() {
+                stalled_at = Some(now);
+            }
+            if stalled_at.is_some() {
+                assert_eq!(s.playout, Playout::Stalled);
+                assert_eq!(s.velocity, Vec3::ZERO);
+            }
+        });
+        assert!(stalled_at.is_some());
+        // Последняя точка x=4 плюс не больше 0.25 с при 2 м/с.
+        assert!(max_x > 4.0 && max_x <= 4.5 + 1e-3, "max x {max_x}");
+    }
+
+    #[test]
+    fn teleport_is_reported_once_and_never_interpolated() {
+        let jump = |t: f64| {
+            if t < 1.0 {
+                walk(t)
+            } else {
+                Vec3::new(100.0 + t as f32, 0.0, 0.0)
+            }
+        };
+        let packets = stream(3.0, 0.03, 4, jump);
+        let mut teleports = 0;
+        play(&packets, 3.0, |_, s, _| {
+            if s.teleported {
+                teleports += 1;
+            }
+            assert!(
+                s.position.x < 3.0 || s.position.x > 100.0,
+                "between: {:?}",
+                s.position
+            );
+        });
+        // Первая выборка и сам скачок.
+        assert_eq!(teleports, 2);
+    }
+
+    #[test]
+    fn stale_packets_are_dropped_and_sender_restart_resets() {
+        let mut buffer = SnapshotBuffer::new(InterpConfig::default());
+        assert!(buffer.push(10_000, 0.0, Vec3::ZERO, ()));
+        assert!(buffer.push(10_100, 0.1, Vec3::ZERO, ()));
+        assert!(!buffer.push(10_100, 0.2, Vec3::ZERO, ()));
+        assert!(!buffer.push(9_000, 0.2, Vec3::ZERO, ()));
+        assert_eq!(buffer.len(), 2);
+
+        // Часы отправителя начались заново.
+        assert!(buffer.push(100, 0.3, Vec3::ZERO, ()));
+        assert_eq!(buffer.len(), 1);
+    }
+
+    #[test]
+    fn segment_ends_and_progress_come_with_the_sample() {
+        let mut buffer = SnapshotBuffer::new(InterpConfig::default());
+        for i in 0..20u64 {
+            let t = i as f64 * SEND_EVERY;
+            buffer.push(i * 100, t + 0.05, walk(t), i);
+        }
+        let sample = buffer.sample(1.2).unwrap();
+        assert_eq!(sample.playout, Playout::Interpolating);
+        assert_eq!(sample.next, Some(sample.payload + 1));
+        assert!((0.0..=1.0).contains(&sample.progress));
+    }
+
+    #[test]
+    fn slerp_takes_the_short_arc() {
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let identity = [0.0, 0.0, 0.0, 1.0];
+        // 90° вокруг Z.
+        let quarter = [0.0, 0.0, half, half];
+        let close = |q: [f32; 4], r: [f32; 4]| q.iter().zip(&r).all(|(a, b)| (a - b).abs() < 1e-4);
+
+        assert!(close(slerp(identity, quarter, 0.0), identity));
+        assert!(close(slerp(identity, quarter, 1.0), quarter));
+        // Середина — 45°.
+        let eighth = (std::f32::consts::PI / 8.0).sin_cos();
+        assert!(close(
+            slerp(identity, quarter, 0.5),
+            [0.0, 0.0, eighth.0, eighth.1]
+        ));
+        // -quarter — тот же поворот: результат тот же, а не длинный путь.
+        let negated = quarter.map(|x| -x);
+        assert!(close(
+            slerp(identity, negated, 0.5),
+            [0.0, 0.0, eighth.0, eighth.1]
+        ));
+        // Совпадающие — без NaN.
+        assert!(close(slerp(quarter, quarter, 0.3), quarter));
+    }
+}
@@ -0,0 +1,510 @@
+//! Привязки горячих клавиш клиента: действие -> клавиша с модификаторами.
+//!
+//! Только разбор, печать и проверка конфликтов — без Windows API, поэтому
+//! тестируется на любой машине. Клавиша — код Windows virtual-key (`VK_*`),
+//! строковая форма — `"Ctrl+Shift+Delete"`, регистр не важен.
+//!
+//! В файле настроек хранится `id действия -> строка`. Неизвестные действия
+//! и неразбираемые строки не ломают загрузку: действие остаётся с клавишей
+//! по умолчанию, а причина попадает в предупреждения.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Что можно повесить на клавишу.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Unload,
+    ToggleOverlay,
+    ToggleDebug,
+    NetGraph,
+    Connect,
+    Players,
+    Console,
+    Chat,
+    TeamChat,
+    /// Удерживать.
+    Scoreboard,
+    DemoLoad,
+    DemoClear,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::Unload,
+        Action::ToggleOverlay,
+        Action::ToggleDebug,
+        Action::NetGraph,
+        Action::Connect,
+        Action::Players,
+        Action::Console,
+        Action::Chat,
+        Action::TeamChat,
+        Action::Scoreboard,
+        Action::DemoLoad,
+        Action::DemoClear,
+    ];
+
+    /// Ключ в файле настроек.
+    pub fn id(self) -> &'static str {
+        match self {
+            Action::Unload => "unload",
+            Action::ToggleOverlay => "toggle_overlay",
+            Action::ToggleDebug => "toggle_debug",
+            Action::NetGraph => "netgraph",
+            Action::Connect => "connect",
+            Action::Players => "players",
+            Action::Console => "console",
+            Action::Chat => "chat",
+            Action::TeamChat => "team_chat",
+            Action::Scoreboard => "scoreboard",
+            Action::DemoLoad => "demo_load",
+            Action::DemoClear => "demo_clear",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.id() == id)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Unload => "Выгрузить клиент",
+            Action::ToggleOverlay => "Показать/скрыть оверлей",
+            Action::ToggleDebug => "Панель отладки",
+            Action::NetGraph => "Сетевая статистика",
+            Action::Connect => "Меню подключения",
+            Action::Players => "Список игроков",
+            Action::Console => "Lua консоль",
+            Action::Chat => "Чат",
+            Action::TeamChat => "Командный чат",
+            Action::Scoreboard => "Скорборд (удерживать)",
+            Action::DemoLoad => "Загрузить демо-данные",
+            Action::DemoClear => "Очистить демо-данные",
+        }
+    }
+
+    fn default_binding(self) -> Binding {
+        let key = |vk| Binding::new(Mods::NONE, vk);
+        match self {
+            // Одна DELETE слишком легко нажимается случайно.
+            Action::Unload => Binding::new(
+                Mods {
+                    ctrl: true,
+                    shift: true,
+                    alt: false,
+                },
+                vk::DELETE,
+            ),
+            Action::ToggleOverlay => key(vk::F1 + 8),
+            Action::ToggleDebug => key(vk::F1 + 9),
+            Action::NetGraph => key(vk::F1 + 7),
+            Action::Connect => key(vk::F1 + 1),
+            Action::Players => key(vk::F1 + 2),
+            Action::Console => key(vk::F1 + 3),
+            Action::Chat => key(b'T'),
+            Action::TeamChat => key(b'Y'),
+            Action::Scoreboard => key(vk::TAB),
+            Action::DemoLoad => key(vk::F1 + 10),
+            Action::DemoClear => key(vk::F1 + 11),
+        }
+    }
+}
+
+/// Коды Windows virtual-key, на которые есть ссылки в коде.
+pub mod vk {
+    pub const BACK: u8 = 0x08;
+    pub const TAB: u8 = 0x09;
+    pub const ESCAPE: u8 = 0x1B;
+    pub const DELETE: u8 = 0x2E;
+    pub const F1: u8 = 0x70;
+}
+
+/// Именованные клавиши кроме букв, цифр и F1..F24.
+const NAMED_KEYS: &[(&str, u8)] = &[
+    ("Backspace", vk::BACK),
+    ("Tab", vk::TAB),
+    ("Enter", 0x0D),
+    ("Pause", 0x13),
+    ("CapsLock", 0x14),
+    ("Escape", vk::ESCAPE),
+    ("Space", 0x20),
+    ("PageUp", 0x21),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("Insert", 0x2D),
+    ("Delete", vk::DELETE),
+    ("NumpadMultiply", 0x6A),
+    ("NumpadAdd", 0x6B),
+    ("NumpadSubtract", 0x6D),
+    ("NumpadDecimal", 0x6E),
+    ("NumpadDivide", 0x6F),
+    ("Semicolon", 0xBA),
+    ("Equals", 0xBB),
+    ("Comma", 0xBC),
+    ("Minus", 0xBD),
+    ("Period", 0xBE),
+    ("Slash", 0xBF),
+    ("Backquote", 0xC0),
+    ("LeftBracket", 0xDB),
+    ("Backslash", 0xDC),
+    ("RightBracket", 0xDD),
+    ("Quote", 0xDE),
+];
+
+/// Другие написания при разборе.
+const KEY_ALIASES: &[(&str, u8)] = &[
+    ("Del", vk::DELETE),
+    ("Esc", vk::ESCAPE),
+    ("Return", 0x0D),
+    ("Ins", 0x2D),
+    ("PgUp", 0x21),
+    ("PgDn", 0x22),
+    ("Tilde", 0xC0),
+    ("Grave", 0xC0),
+];
+
+/// Имя клавиши для печати; `None` — клавишу не назначить.
+pub fn key_name(code: u8) -> Option<String> {
+    match code {
+        b'A'..=b'Z' | b'0'..=b'9' => Some((code as char).to_string()),
+        0x60..=0x69 => Some(format!("Numpad{}", code - 0x60)),
+        0x70..=0x87 => Some(format!("F{}", code - vk::F1 + 1)),
+        _ => NAMED_KEYS
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+fn parse_key(name: &str) -> Option<u8> {
+    if let [c] = name.as_bytes()
+        && c.is_ascii_alphanumeric()
+    {
+        return Some(c.to_ascii_uppercase());
+    }
+    let lower = name.to_ascii_lowercase();
+    if let Some(n) = lower.strip_prefix("numpad")
+        && let Ok(n @ 0..=9) = n.parse::<u8>()
+    {
+        return Some(0x60 + n);
+    }
+    if let Some(n) = lower.strip_prefix('f')
+        && let Ok(n @ 1..=24) = n.parse::<u8>()
+    {
+        return Some(vk::F1 + n - 1);
+    }
+    NAMED_KEYS
+        .iter()
+        .chain(KEY_ALIASES)
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, code)| *code)
+}
+
+/// Все клавиши, которые можно назначить, — для перехвата нажатия.
+pub fn bindable_keys() -> impl Iterator<Item = u8> {
+    (0..=u8::MAX).filter(|&code| code != vk::ESCAPE && key_name(code).is_some())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Mods {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Mods {
+    pub const NONE: Mods = Mods {
+        ctrl: false,
+        shift: false,
+        alt: false,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Binding {
+    pub mods: Mods,
+    /// Код virtual-key.
+    pub key: u8,
+}
+
+impl Binding {
+    pub const fn new(mods: Mods, key: u8) -> Self {
+        Self { mods, key }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (on, name) in [
+            (self.mods.ctrl, "Ctrl+"),
+            (self.mods.shift, "Shift+"),
+            (self.mods.alt, "Alt+"),
+        ] {
+            if on {
+                f.write_str(name)?;
+            }
+        }
+        match key_name(self.key) {
+            Some(name) => f.write_str(&name),
+            None => write!(f, "0x{:02X}", self.key),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    UnknownKey(String),
+    UnknownModifier(String),
+    /// Escape закрывает окна оверлея и отменяет переназначение.
+    Reserved,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "пустая привязка"),
+            Self::UnknownKey(k) => write!(f, "неизвестная клавиша '{k}'"),
+            Self::UnknownModifier(m) => write!(f, "неизвестный модификатор '{m}'"),
+            Self::Reserved => write!(f, "Escape не назначается"),
+        }
+    }
+}
+
+impl std::str::FromStr for Binding {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts
+            .pop()
+            .filter(|k| !k.is_empty())
+            .ok_or(ParseError::Empty)?;
+        let mut mods = Mods::NONE;
+        for part in parts {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => mods.ctrl = true,
+                "shift" => mods.shift = true,
+                "alt" => mods.alt = true,
+                _ => return Err(ParseError::UnknownModifier(part.to_string())),
+            }
+        }
+        let code = parse_key(key).ok_or_else(|| ParseError::UnknownKey(key.to_string()))?;
+        if code == vk::ESCAPE {
+            return Err(ParseError::Reserved);
+        }
+        Ok(Binding::new(mods, code))
+    }
+}
+
+/// Привязка уже занята другим действием.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub with: Action,
+    pub binding: Binding,
+}
+
+/// Привязки всех действий.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|a| (a, a.default_binding()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Из файла настроек. Возвращает карту и предупреждения о том, что не
+    /// применилось.
+    pub fn from_strings(saved: &BTreeMap<String, String>) -> (Keymap, Vec<String>) {
+        let mut keymap = Keymap::default();
+        let mut warnings = Vec::new();
+        for (id, text) in saved {
+            let Some(action) = Action::from_id(id) else {
+                warnings.push(format!("неизвестное действие '{id}'"));
+                continue;
+            };
+            match text.parse::<Binding>() {
+                Ok(binding) => {
+                    keymap.bindings.insert(action, binding);
+                }
+                Err(e) => warnings.push(format!("{id}: {e}, оставлено {}", keymap.get(action))),
+            }
+        }
+        for (a, b) in keymap.conflicts() {
+            warnings.push(format!(
+                "{} и {} на одной клавише {}",
+                a.id(),
+                b.id(),
+                keymap.get(a)
+            ));
+        }
+        (keymap, warnings)
+    }
+
+    /// Для файла настроек — все действия.
+    pub fn to_strings(&self) -> BTreeMap<String, String> {
+        self.bindings
+            .iter()
+            .map(|(a, b)| (a.id().to_string(), b.to_string()))
+            .collect()
+    }
+
+    pub fn get(&self, action: Action) -> Binding {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    /// Кто кроме `action` уже сидит на `binding`.
+    pub fn conflict(&self, action: Action, binding: Binding) -> Option<Conflict> {
+        self.bindings
+            .iter()
+            .find(|&(a, b)| *a != action && *b == binding)
+            .map(|(a, _)| Conflict { with: *a, binding })
+    }
+
+    /// Пары действий на одной привязке.
+    pub fn conflicts(&self) -> Vec<(Action, Action)> {
+        let mut pairs = Vec::new();
+        for (i, (a, ba)) in self.bindings.iter().enumerate() {
+            for (b, bb) in self.bindings.iter().skip(i + 1) {
+                if ba == bb {
+                    pairs.push((*a, *b));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Назначить, если привязка свободна.
+    pub fn set(&mut self, action: Action, binding: Binding) -> Result<(), Conflict> {
+        if let Some(conflict) = self.conflict(action, binding) {
+            return Err(conflict);
+        }
+        self.bindings.insert(action, binding);
+        Ok(())
+    }
+
+    /// Назначить, отдав прежнюю клавишу `action` тому, кто занимал `binding`.
+    pub fn set_swapping(&mut self, action: Action, binding: Binding) {
+        let previous = self.get(action);
+        if let Some(conflict) = self.conflict(action, binding) {
+            self.bindings.insert(conflict.with, previous);
+        }
+        self.bindings.insert(action, binding);
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        self.bindings.insert(action, action.default_binding());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn b(text: &str) -> Binding {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_print_round_trip() {
+        for text in [
+            "Ctrl+Shift+Delete",
+            "F2",
+            "T",
+            "Tab",
+            "Alt+Numpad7",
+            "Ctrl+Backquote",
+        ] {
+            assert_eq!(b(text).to_string(), text);
+        }
+        assert_eq!(b(" shift + ctrl + del "), b("Ctrl+Shift+Delete"));
+        assert_eq!(b("f12").key, vk::F1 + 11);
+        assert_eq!(b("t"), b("T"));
+        for code in bindable_keys() {
+            let binding = Binding::new(Mods::NONE, code);
+            assert_eq!(b(&binding.to_string()), binding);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Binding>(), Err(ParseError::Empty));
+        assert_eq!("Ctrl+".parse::<Binding>(), Err(ParseError::Empty));
+        assert_eq!(
+            "Hyper+F2".parse::<Binding>(),
+            Err(ParseError::UnknownModifier("Hyper".into()))
+        );
+        assert_eq!(
+            "F25".parse::<Binding>(),
+            Err(ParseError::UnknownKey("F25".into()))
+        );
+        assert_eq!("Esc".parse::<Binding>(), Err(ParseError::Reserved));
+    }
+
+    #[test]
+    fn saved_strings_fall_back_per_action() {
+        let saved: BTreeMap<String, String> = [
+            ("connect", "Ctrl+F2"),
+            ("players", "NoSuchKey"),
+            ("teleport", "F7"),
+        ]
+        .into_iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+
+        let (keymap, warnings) = Keymap::from_strings(&saved);
+        assert_eq!(keymap.get(Action::Connect), b("Ctrl+F2"));
+        assert_eq!(keymap.get(Action::Players), b("F3"));
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+
+        let (again, warnings) = Keymap::from_strings(&keymap.to_strings());
+        assert_eq!(again, keymap);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn conflicts_are_detected_and_swappable() {
+        let mut keymap = Keymap::default();
+        assert!(keymap.conflicts().is_empty());
+        assert_eq!(keymap.get(Action::Unload), b("Ctrl+Shift+Delete"));
+
+        assert_eq!(
+            keymap.set(Action::Chat, b("F2")),
+            Err(Conflict {
+                with: Action::Connect,
+                binding: b("F2")
+            })
+        );
+        // Модификатор — другая привязка.
+        assert!(keymap.set(Action::Chat, b("Ctrl+F2")).is_ok());
+        // Себе же — не конфликт.
+        assert!(keymap.set(Action::Chat, b("Ctrl+F2")).is_ok());
+
+        keymap.set_swapping(Action::Players, b("Ctrl+F2"));
+        assert_eq!(keymap.get(Action::Players), b("Ctrl+F2"));
+        assert_eq!(keymap.get(Action::Chat), b("F3"));
+        assert!(keymap.conflicts().is_empty());
+
+        let saved = BTreeMap::from([("chat".to_string(), "F2".to_string())]);
+        let (_, warnings) = Keymap::from_strings(&saved);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+    }
+}
@@ -1 +1,6 @@
-pub mod logger;
\ No newline at end of file
+pub mod interp;
+pub mod keybind;
+pub mod logger;
+pub mod projection;
+pub mod settings;
+pub mod weapons;
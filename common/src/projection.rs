@@ -0,0 +1,249 @@
+//! Проекция мировых точек на экран для оверлея (ники над игроками).
+//!
+//! Мир движка — Z вверх. Камера задаётся позицией, направлением взгляда и
+//! вертикальным FOV; крен не учитываем — камера игры его не делает. Матрицы
+//! в стиле D3D: камера смотрит вдоль +Z своего пространства, глубина
+//! `0..1`, вектор-столбец (`clip = proj * view * p`).
+
+use crate::interp::Vec3;
+
+const WORLD_UP: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+/// Ближе этого к камере точку не проецируем.
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 2000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4(pub [[f32; 4]; 4]);
+
+impl Mat4 {
+    /// Мир -> пространство камеры. `None` — направление нулевое.
+    pub fn look_to(eye: Vec3, forward: Vec3) -> Option<Mat4> {
+        let f = normalized(forward)?;
+        // Взгляд строго вверх/вниз: «верх» кадра берём по мировой Y.
+        let up = if f.cross(WORLD_UP).length() < 1e-4 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            WORLD_UP
+        };
+        // Мир правый: смотрим вдоль +Y — +X справа.
+        let r = normalized(f.cross(up))?;
+        let u = r.cross(f);
+        Some(Mat4([
+            [r.x, r.y, r.z, -r.dot(eye)],
+            [u.x, u.y, u.z, -u.dot(eye)],
+            [f.x, f.y, f.z, -f.dot(eye)],
+            [0.0, 0.0, 0.0, 1.0],
+        ]))
+    }
+
+    /// Перспектива: `fov_y` в радианах, `aspect` = ширина / высота.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let sy = 1.0 / (fov_y * 0.5).tan();
+        let sx = sy / aspect;
+        let q = far / (far - near);
+        Mat4([
+            [sx, 0.0, 0.0, 0.0],
+            [0.0, sy, 0.0, 0.0],
+            [0.0, 0.0, q, -near * q],
+            [0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    pub fn mul(&self, o: &Mat4) -> Mat4 {
+        let mut out = [[0.0; 4]; 4];
+        for (i, row) in out.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..4).map(|k| self.0[i][k] * o.0[k][j]).sum();
+            }
+        }
+        Mat4(out)
+    }
+
+    /// `[x, y, z, w]` для точки `p` (w = 1).
+    pub fn transform_point(&self, p: Vec3) -> [f32; 4] {
+        let v = [p.x, p.y, p.z, 1.0];
+        let mut out = [0.0; 4];
+        for (i, cell) in out.iter_mut().enumerate() {
+            *cell = (0..4).map(|k| self.0[i][k] * v[k]).sum();
+        }
+        out
+    }
+}
+
+fn normalized(v: Vec3) -> Option<Vec3> {
+    let len = v.length();
+    (len > 1e-6 && len.is_finite()).then(|| v * (1.0 / len))
+}
+
+/// Камера на момент кадра.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub forward: Vec3,
+    /// Вертикальный угол обзора, градусы.
+    pub fov_y_deg: f32,
+}
+
+/// Точка на экране.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projected {
+    /// Пиксели от левого верхнего угла.
+    pub x: f32,
+    pub y: f32,
+    /// Расстояние вдоль взгляда камеры, метры.
+    pub depth: f32,
+    /// Внутри кадра.
+    pub on_screen: bool,
+}
+
+impl Camera {
+    pub fn view_proj(&self, aspect: f32) -> Option<Mat4> {
+        if !(self.fov_y_deg > 0.0 && self.fov_y_deg < 180.0 && aspect > 0.0) {
+            return None;
+        }
+        let view = Mat4::look_to(self.position, self.forward)?;
+        let proj = Mat4::perspective(self.fov_y_deg.to_radians(), aspect, NEAR_PLANE, FAR_PLANE);
+        Some(proj.mul(&view))
+    }
+
+    /// Спроецировать `world` на экран `width × height`. `None` — точка за
+    /// камерой (или ближе `NEAR_PLANE`).
+    pub fn project(&self, world: Vec3, width: f32, height: f32) -> Option<Projected> {
+        self.view_proj(width / height)?
+            .project(world, width, height)
+    }
+}
+
+impl Mat4 {
+    /// Для матрицы `view_proj`: одна матрица на кадр, много точек.
+    pub fn project(&self, world: Vec3, width: f32, height: f32) -> Option<Projected> {
+        let [x, y, _, w] = self.transform_point(world);
+        if w < NEAR_PLANE || !w.is_finite() {
+            return None;
+        }
+        let (nx, ny) = (x / w, y / w);
+        Some(Projected {
+            x: (nx + 1.0) * 0.5 * width,
+            y: (1.0 - ny) * 0.5 * height,
+            depth: w,
+            on_screen: nx.abs() <= 1.0 && ny.abs() <= 1.0,
+        })
+    }
+}
+
+/// Видимость ника от расстояния: полностью до `fade_start`, линейно гаснет
+/// к `cutoff`, дальше не рисуется.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TagRange {
+    pub fade_start: f32,
+    pub cutoff: f32,
+}
+
+impl TagRange {
+    /// Прозрачность `0..=1`; 0 — не рисовать.
+    pub fn alpha(&self, distance: f32) -> f32 {
+        if !distance.is_finite() || distance >= self.cutoff {
+            0.0
+        } else if distance <= self.fade_start {
+            1.0
+        } else {
+            (self.cutoff - distance) / (self.cutoff - self.fade_start)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: f32 = 1920.0;
+    const H: f32 = 1080.0;
+
+    fn camera() -> Camera {
+        Camera {
+            position: Vec3::new(10.0, 20.0, 2.0),
+            forward: Vec3::new(0.0, 1.0, 0.0),
+            fov_y_deg: 60.0,
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.5
+    }
+
+    #[test]
+    fn center_and_directions() {
+        let cam = camera();
+        let p = cam.project(Vec3::new(10.0, 30.0, 2.0), W, H).unwrap();
+        assert!(close(p.x, W / 2.0) && close(p.y, H / 2.0), "{p:?}");
+        assert!((p.depth - 10.0).abs() < 1e-3);
+        assert!(p.on_screen);
+
+        // Смотрим вдоль +Y, Z вверх: +X — вправо.
+        let right = cam.project(Vec3::new(12.0, 30.0, 2.0), W, H).unwrap();
+        assert!(right.x > W / 2.0 && close(right.y, H / 2.0));
+        let up = cam.project(Vec3::new(10.0, 30.0, 4.0), W, H).unwrap();
+        assert!(up.y < H / 2.0 && close(up.x, W / 2.0));
+    }
+
+    #[test]
+    fn fov_edge_maps_to_screen_edge() {
+        let cam = camera();
+        let half = (30.0f32).to_radians().tan();
+        let top = cam
+            .project(Vec3::new(10.0, 30.0, 2.0 + 10.0 * half), W, H)
+            .unwrap();
+        assert!(close(top.y, 0.0), "{top:?}");
+
+        let aspect = W / H;
+        let edge = cam
+            .project(Vec3::new(10.0 + 10.0 * half * aspect, 30.0, 2.0), W, H)
+            .unwrap();
+        assert!(close(edge.x, W), "{edge:?}");
+
+        let beyond = cam
+            .project(Vec3::new(10.0 + 20.0 * half * aspect, 30.0, 2.0), W, H)
+            .unwrap();
+        assert!(!beyond.on_screen);
+    }
+
+    #[test]
+    fn behind_and_degenerate() {
+        let cam = camera();
+        assert!(cam.project(Vec3::new(10.0, 10.0, 2.0), W, H).is_none());
+        assert!(cam.project(cam.position, W, H).is_none());
+
+        let zero = Camera {
+            forward: Vec3::ZERO,
+            ..cam
+        };
+        assert!(zero.project(Vec3::new(10.0, 30.0, 2.0), W, H).is_none());
+
+        let down = Camera {
+            forward: Vec3::new(0.0, 0.0, -1.0),
+            ..cam
+        };
+        let p = down.project(Vec3::new(10.0, 20.0, -5.0), W, H).unwrap();
+        assert!(close(p.x, W / 2.0) && close(p.y, H / 2.0));
+
+        let bad_fov = Camera {
+            fov_y_deg: 0.0,
+            ..cam
+        };
+        assert!(bad_fov.project(Vec3::new(10.0, 30.0, 2.0), W, H).is_none());
+    }
+
+    #[test]
+    fn tag_fades_with_distance() {
+        let range = TagRange {
+            fade_start: 30.0,
+            cutoff: 60.0,
+        };
+        assert_eq!(range.alpha(5.0), 1.0);
+        assert_eq!(range.alpha(30.0), 1.0);
+        assert!((range.alpha(45.0) - 0.5).abs() < 1e-6);
+        assert_eq!(range.alpha(60.0), 0.0);
+        assert_eq!(range.alpha(f32::NAN), 0.0);
+    }
+}
@@ -0,0 +1,130 @@
+//! Разбор файла настроек клиента так, чтобы он переживал смену версий.
+//!
+//! Только JSON и serde — без файловой системы, поэтому тестируется на любой
+//! машине. Отсутствующие ключи берутся по умолчанию, неизвестные ключи
+//! верхнего уровня остаются в `#[serde(flatten)]`-карте типа, ключ с
+//! неподходящим типом сбрасывается к умолчанию без потери остальных — а
+//! причина попадает в предупреждения.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Разобрать текст файла настроек.
+///
+/// `Err` — текст вообще не JSON: такой файл вызывающий откладывает, а не
+/// перезаписывает молча.
+pub fn parse<T>(text: &str) -> Result<(T, Vec<String>), serde_json::Error>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let value = serde_json::from_str::<Value>(text)?;
+    Ok(from_value(value))
+}
+
+/// Разобрать уже прочитанный JSON; ключ, который не подходит по типу,
+/// остаётся по умолчанию, остальные применяются.
+pub fn from_value<T>(value: Value) -> (T, Vec<String>)
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    if let Ok(settings) = serde_json::from_value(value.clone()) {
+        return (settings, Vec::new());
+    }
+    let Value::Object(file) = value else {
+        return (T::default(), vec!["ожидался объект".to_string()]);
+    };
+    let Ok(Value::Object(mut merged)) = serde_json::to_value(T::default()) else {
+        return (T::default(), vec!["умолчания не сериализуются".to_string()]);
+    };
+
+    let mut warnings = Vec::new();
+    for (key, value) in file {
+        let previous = merged.insert(key.clone(), value);
+        if serde_json::from_value::<T>(Value::Object(merged.clone())).is_err() {
+            warnings.push(format!("'{key}' не разобран, по умолчанию"));
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    let settings = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+    (settings, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::{Map, json};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Demo {
+        nickname: String,
+        port: u16,
+        tags: Vec<String>,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    }
+
+    impl Default for Demo {
+        fn default() -> Self {
+            Self {
+                nickname: "Player".into(),
+                port: 7788,
+                tags: Vec::new(),
+                extra: Map::new(),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_keys_take_defaults() {
+        let (demo, warnings) = parse::<Demo>(r#"{ "nickname": "vito" }"#).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(demo.nickname, "vito");
+        assert_eq!(demo.port, 7788);
+        assert_eq!(parse::<Demo>("{}").unwrap().0, Demo::default());
+    }
+
+    #[test]
+    fn unknown_keys_are_kept_for_the_next_save() {
+        let (demo, warnings) =
+            parse::<Demo>(r#"{ "port": 1, "future": { "a": [1, 2] } }"#).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(demo.extra.get("future"), Some(&json!({ "a": [1, 2] })));
+
+        let saved = serde_json::to_value(&demo).unwrap();
+        assert_eq!(saved["future"], json!({ "a": [1, 2] }));
+        assert_eq!(saved["port"], json!(1));
+    }
+
+    #[test]
+    fn wrongly_typed_key_resets_only_itself() {
+        let (demo, warnings) = parse::<Demo>(
+            r#"{ "nickname": "joe", "port": "seven", "tags": ["a"], "future": true }"#,
+        )
+        .unwrap();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("port"), "{warnings:?}");
+        assert_eq!(demo.nickname, "joe");
+        assert_eq!(demo.port, 7788);
+        assert_eq!(demo.tags, vec!["a".to_string()]);
+        assert_eq!(demo.extra.get("future"), Some(&json!(true)));
+    }
+
+    #[test]
+    fn non_object_json_gives_defaults() {
+        let (demo, warnings) = parse::<Demo>("[1, 2, 3]").unwrap();
+        assert_eq!(demo, Demo::default());
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn non_json_is_an_error() {
+        assert!(parse::<Demo>("nickname = vito").is_err());
+        assert!(parse::<Demo>("").is_err());
+    }
+}
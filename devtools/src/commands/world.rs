@@ -69,8 +69,9 @@ pub fn init_map(season: &str, weather: &str) {
         _ => sdk::game::sds::activate_stream_map_line("free_summer_load"),
     };
 
-    let weather_cmd = format!("game.gfx:SetWeatherTemplate(\"{}\")", weather);
-    sdk::game::lua::exec(&weather_cmd).ok();
+    if let Err(e) = sdk::game::environment::set_weather_template(weather) {
+        logger::warn(&format!("[mapping] погода '{weather}': {e}"));
+    }
     sdk::game::sds::activate_stream_map_line("free_joe_load");
     sdk::game::lua::exec("game.traffic:OpenSeason(140)").ok();
 
@@ -0,0 +1,177 @@
+//! Мастер-сервер: реестр публичных выделенных серверов.
+//!
+//! Протокол — `protocol::master`. Каждое TCP-соединение: одна строка
+//! запроса, одна строка ответа, закрытие.
+//!
+//! - `Register` проверяется обратным UDP query (`verify`), только потом
+//!   сервер попадает в реестр; каждый heartbeat заново опрашивает сервер,
+//!   так что число игроков в списке свежее не больше чем на период heartbeat
+//! - отдельный поток выкидывает серверы без heartbeat дольше `stale_after`
+
+pub mod registry;
+pub mod verify;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::master::{
+    DEFAULT_HEARTBEAT_SECS, MAX_MASTER_REQUEST_BYTES, MasterRequest, MasterResponse,
+};
+
+use registry::Registry;
+
+/// Сколько ждать строку запроса от клиента.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Настройки мастер-сервера.
+#[derive(Debug, Clone)]
+pub struct MasterConfig {
+    /// Без heartbeat дольше этого — сервер выкидывается из списка.
+    pub stale_after: Duration,
+    /// Сколько ждать ответ на проверочный query.
+    pub verify_timeout: Duration,
+}
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        Self {
+            stale_after: Duration::from_secs(DEFAULT_HEARTBEAT_SECS * 3),
+            verify_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+struct Master {
+    config: MasterConfig,
+    registry: Mutex<Registry>,
+}
+
+/// Обслуживать `listener` до ошибки accept-цикла (т.е. вечно).
+pub fn run(listener: TcpListener, config: MasterConfig) {
+    let master = Arc::new(Master {
+        registry: Mutex::new(Registry::new(config.stale_after)),
+        config,
+    });
+
+    {
+        let master = Arc::clone(&master);
+        thread::spawn(move || evict_loop(master));
+    }
+
+    for incoming in listener.incoming() {
+        match incoming {
+            Ok(stream) => {
+                let master = Arc::clone(&master);
+                thread::spawn(move || handle_connection(stream, &master));
+            }
+            Err(e) => logger::warn(&format!("[master] accept failed: {e}")),
+        }
+    }
+}
+
+fn evict_loop(master: Arc<Master>) {
+    let interval = (master.config.stale_after / 4).max(Duration::from_millis(100));
+    loop {
+        thread::sleep(interval);
+        let evicted = match master.registry.lock() {
+            Ok(mut registry) => registry.evict_stale(Instant::now()),
+            Err(_) => continue,
+        };
+        for addr in evicted {
+            logger::info(&format!("[master] {addr} evicted: no heartbeat"));
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, master: &Master) {
+    let peer = match stream.peer_addr() {
+        Ok(a) => a,
+        Err(_) => return,
+    };
+    if let Err(e) = stream.set_read_timeout(Some(REQUEST_TIMEOUT)) {
+        logger::debug(&format!("[master] {peer}: set_read_timeout failed: {e}"));
+        return;
+    }
+
+    let response = match read_request(&stream) {
+        Ok(request) => handle_request(request, peer, master),
+        Err(reason) => {
+            logger::debug(&format!("[master] {peer}: {reason}"));
+            MasterResponse::Error { reason }
+        }
+    };
+
+    let mut writer = &stream;
+    if let Ok(mut line) = serde_json::to_string(&response) {
+        line.push('\n');
+        let _ = writer.write_all(line.as_bytes());
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Result<MasterRequest, String> {
+    let mut reader = BufReader::new(stream.take(MAX_MASTER_REQUEST_BYTES as u64 + 1));
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|e| format!("read failed: {e}"))?;
+
+    if line.len() > MAX_MASTER_REQUEST_BYTES {
+        return Err("запрос слишком длинный".to_string());
+    }
+    serde_json::from_str(line.trim_end()).map_err(|e| format!("некорректный запрос: {e}"))
+}
+
+fn handle_request(request: MasterRequest, peer: SocketAddr, master: &Master) -> MasterResponse {
+    match request {
+        MasterRequest::Register { port } => register(SocketAddr::new(peer.ip(), port), master),
+        MasterRequest::List { filter } => {
+            let servers = match master.registry.lock() {
+                Ok(registry) => registry.list(&filter),
+                Err(_) => Vec::new(),
+            };
+            MasterResponse::Servers { servers }
+        }
+    }
+}
+
+fn register(addr: SocketAddr, master: &Master) -> MasterResponse {
+    // IP берём из соединения, порт проверяем query — без лока реестра.
+    let info = match verify::query(addr, master.config.verify_timeout) {
+        Ok(info) => info,
+        Err(e) => {
+            logger::warn(&format!("[master] {addr} failed verification: {e}"));
+            return MasterResponse::Error {
+                reason: format!("сервер не прошёл проверку: {e}"),
+            };
+        }
+    };
+
+    let Ok(mut registry) = master.registry.lock() else {
+        return MasterResponse::Error {
+            reason: "внутренняя ошибка".to_string(),
+        };
+    };
+    let known = registry.contains(addr);
+    match registry.register(addr, info.clone(), Instant::now()) {
+        Ok(()) => {
+            if !known {
+                logger::info(&format!(
+                    "[master] {addr} registered: '{}' ({} servers)",
+                    info.name,
+                    registry.len()
+                ));
+            }
+            MasterResponse::Registered
+        }
+        Err(e) => {
+            logger::warn(&format!("[master] {addr} rejected: {e}"));
+            MasterResponse::Error {
+                reason: e.to_string(),
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Мастер-сервер списка серверов.
+//!
+//! ```text
+//! master [--port <tcp-порт>] [--stale-secs <секунды>]
+//! ```
+
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use common::logger;
+use master::MasterConfig;
+use protocol::master::DEFAULT_MASTER_PORT;
+
+fn main() -> ExitCode {
+    if let Err(e) = logger::init(
+        logger::Level::Debug,
+        logger::Target::Both,
+        Some("logs/master.log"),
+    ) {
+        eprintln!("Logger init failed: {e}");
+    }
+
+    let mut port = DEFAULT_MASTER_PORT;
+    let mut config = MasterConfig::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match arg.as_str() {
+            "--port" => value.and_then(|v| v.parse().ok()).map(|v| port = v),
+            "--stale-secs" => value
+                .and_then(|v| v.parse().ok())
+                .map(|v| config.stale_after = Duration::from_secs(v)),
+            _ => None,
+        };
+        if parsed.is_none() {
+            eprintln!("usage: master [--port <tcp-порт>] [--stale-secs <секунды>]");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(l) => l,
+        Err(e) => {
+            logger::error(&format!("[master] bind {port} failed: {e}"));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    logger::info(&format!(
+        "[master] listening on 0.0.0.0:{port}, stale after {}s",
+        config.stale_after.as_secs()
+    ));
+    master::run(listener, config);
+    ExitCode::SUCCESS
+}
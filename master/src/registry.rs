@@ -0,0 +1,249 @@
+//! Реестр зарегистрированных серверов. Чистая логика, без сети.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use protocol::master::{ListFilter, MasterServer};
+use protocol::query::ServerInfo;
+
+/// Сколько серверов может висеть на одном IP.
+pub const MAX_SERVERS_PER_IP: usize = 16;
+
+/// Почему регистрация отклонена.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterError {
+    /// Порт в ответе query не совпал с заявленным.
+    PortMismatch { claimed: u16, reported: u16 },
+    /// С этого IP уже `MAX_SERVERS_PER_IP` серверов.
+    TooManyServers,
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PortMismatch { claimed, reported } => write!(
+                f,
+                "порт {claimed} не совпадает с портом из query ({reported})"
+            ),
+            Self::TooManyServers => write!(f, "слишком много серверов с одного IP"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    info: ServerInfo,
+    last_seen: Instant,
+}
+
+#[derive(Debug)]
+pub struct Registry {
+    entries: HashMap<SocketAddr, Entry>,
+    /// Без heartbeat дольше этого — сервер выкидывается.
+    stale_after: Duration,
+}
+
+impl Registry {
+    pub fn new(stale_after: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            stale_after,
+        }
+    }
+
+    /// Добавить или обновить проверенный сервер.
+    ///
+    /// `addr` — IP из TCP-соединения и заявленный порт, `info` — ответ на
+    /// проверочный query по этому адресу.
+    pub fn register(
+        &mut self,
+        addr: SocketAddr,
+        info: ServerInfo,
+        now: Instant,
+    ) -> Result<(), RegisterError> {
+        if info.port != addr.port() {
+            return Err(RegisterError::PortMismatch {
+                claimed: addr.port(),
+                reported: info.port,
+            });
+        }
+
+        if !self.entries.contains_key(&addr) {
+            let same_ip = self.entries.keys().filter(|a| a.ip() == addr.ip()).count();
+            if same_ip >= MAX_SERVERS_PER_IP {
+                return Err(RegisterError::TooManyServers);
+            }
+        }
+
+        self.entries.insert(
+            addr,
+            Entry {
+                info,
+                last_seen: now,
+            },
+        );
+        Ok(())
+    }
+
+    /// Выкинуть серверы без heartbeat. Возвращает их адреса.
+    pub fn evict_stale(&mut self, now: Instant) -> Vec<SocketAddr> {
+        let stale: Vec<SocketAddr> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| now.saturating_duration_since(e.last_seen) > self.stale_after)
+            .map(|(addr, _)| *addr)
+            .collect();
+        for addr in &stale {
+            self.entries.remove(addr);
+        }
+        stale
+    }
+
+    /// Серверы под фильтр: сначала с игроками, затем по имени.
+    pub fn list(&self, filter: &ListFilter) -> Vec<MasterServer> {
+        let mut servers: Vec<MasterServer> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| filter.matches(&e.info))
+            .map(|(addr, e)| MasterServer {
+                addr: *addr,
+                info: e.info.clone(),
+            })
+            .collect();
+        servers.sort_by(|a, b| {
+            b.info
+                .players
+                .cmp(&a.info.players)
+                .then_with(|| a.info.name.cmp(&b.info.name))
+                .then_with(|| a.addr.cmp(&b.addr))
+        });
+        servers
+    }
+
+    pub fn contains(&self, addr: SocketAddr) -> bool {
+        self.entries.contains_key(&addr)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(name: &str, port: u16, players: u32) -> ServerInfo {
+        ServerInfo {
+            name: name.to_string(),
+            mode: "freeroam".to_string(),
+            players,
+            max_players: 4,
+            port,
+            ..ServerInfo::default()
+        }
+    }
+
+    fn addr(ip: [u8; 4], port: u16) -> SocketAddr {
+        SocketAddr::from((ip, port))
+    }
+
+    #[test]
+    fn heartbeat_keeps_entry_and_silence_evicts_it() {
+        let t0 = Instant::now();
+        let mut registry = Registry::new(Duration::from_secs(90));
+        let a = addr([10, 0, 0, 1], 7788);
+        let b = addr([10, 0, 0, 2], 7788);
+
+        registry.register(a, info("A", 7788, 0), t0).unwrap();
+        registry.register(b, info("B", 7788, 0), t0).unwrap();
+
+        let t1 = t0 + Duration::from_secs(60);
+        registry.register(a, info("A", 7788, 1), t1).unwrap();
+
+        let t2 = t0 + Duration::from_secs(120);
+        assert_eq!(registry.evict_stale(t2), vec![b]);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.list(&ListFilter::default())[0].info.players, 1);
+    }
+
+    #[test]
+    fn rejects_port_mismatch_and_ip_flood() {
+        let t0 = Instant::now();
+        let mut registry = Registry::new(Duration::from_secs(90));
+
+        assert_eq!(
+            registry.register(addr([10, 0, 0, 1], 7788), info("A", 7789, 0), t0),
+            Err(RegisterError::PortMismatch {
+                claimed: 7788,
+                reported: 7789
+            })
+        );
+
+        for i in 0..MAX_SERVERS_PER_IP as u16 {
+            let port = 8000 + i;
+            registry
+                .register(addr([10, 0, 0, 1], port), info("A", port, 0), t0)
+                .unwrap();
+        }
+        assert_eq!(
+            registry.register(addr([10, 0, 0, 1], 9000), info("A", 9000, 0), t0),
+            Err(RegisterError::TooManyServers)
+        );
+        // Heartbeat уже известного сервера проходит.
+        assert!(
+            registry
+                .register(addr([10, 0, 0, 1], 8000), info("A", 8000, 0), t0)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn list_applies_filter_and_sorts_by_players() {
+        let t0 = Instant::now();
+        let mut registry = Registry::new(Duration::from_secs(90));
+        registry
+            .register(addr([10, 0, 0, 1], 1), info("Alpha", 1, 0), t0)
+            .unwrap();
+        registry
+            .register(addr([10, 0, 0, 2], 2), info("Beta", 2, 4), t0)
+            .unwrap();
+        let mut locked = info("Gamma", 3, 2);
+        locked.password = true;
+        registry
+            .register(addr([10, 0, 0, 3], 3), locked, t0)
+            .unwrap();
+
+        let names = |filter: &ListFilter| -> Vec<String> {
+            registry
+                .list(filter)
+                .into_iter()
+                .map(|s| s.info.name)
+                .collect()
+        };
+
+        assert_eq!(names(&ListFilter::default()), ["Beta", "Gamma", "Alpha"]);
+        assert_eq!(
+            names(&ListFilter {
+                hide_full: true,
+                hide_empty: true,
+                ..ListFilter::default()
+            }),
+            ["Gamma"]
+        );
+        assert_eq!(
+            names(&ListFilter {
+                name: Some("AL".into()),
+                hide_password: true,
+                ..ListFilter::default()
+            }),
+            ["Alpha"]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Проверка сервера обратным UDP query.
+//!
+//! Регистрацию принимаем, только если по `IP соединения : заявленный порт`
+//! отвечает наш query с нашим nonce. Так нельзя внести в список чужой адрес
+//! или несуществующий сервер.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use protocol::query::{QUERY_MAGIC, QueryRequest, QueryResponse, ServerInfo};
+
+/// Максимальный размер ответа.
+const MAX_RESPONSE_BYTES: usize = 8 * 1024;
+
+static NONCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Непредсказуемый снаружи nonce: время + счётчик.
+fn next_nonce() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let n = NONCE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    nanos ^ n.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+/// Опросить `addr` и вернуть его `ServerInfo`. Ответ с другого IP или с
+/// другим nonce игнорируется.
+pub fn query(addr: SocketAddr, timeout: Duration) -> io::Result<ServerInfo> {
+    let bind: IpAddr = match addr {
+        SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+        SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+    };
+    let socket = UdpSocket::bind((bind, 0))?;
+
+    let nonce = next_nonce();
+    let request = serde_json::to_vec(&QueryRequest::new(nonce)).map_err(io::Error::other)?;
+    socket.send_to(&request, addr)?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = vec![0u8; MAX_RESPONSE_BYTES];
+
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "сервер не ответил на query",
+            ));
+        }
+        socket.set_read_timeout(Some(left))?;
+
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(r) => r,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if from.ip() != addr.ip() {
+            continue;
+        }
+
+        let Ok(response) = serde_json::from_slice::<QueryResponse>(&buf[..len]) else {
+            continue;
+        };
+        if response.magic == QUERY_MAGIC && response.nonce == nonce {
+            return Ok(response.info);
+        }
+    }
+}
@@ -9,6 +9,10 @@
 
 use serde::{Deserialize, Serialize};
 
+pub mod master;
+pub mod query;
+pub mod recording;
+
 /// Версия протокола.
 ///
 /// v4: добавлены поля `is_aiming` / `aim_dir` в `NetPlayerSnapshot`
@@ -17,7 +21,32 @@ use serde::{Deserialize, Serialize};
 ///      между snapshot'ами — фикс walking-on-spot при стоянии).
 /// v6: добавлено поле `movement_mode` — сырой байт режима шага (DE), см. SDK
 ///     `Player::get_movement_mode_byte` / `fields::shuman_command_move_dir`.
-pub const PROTOCOL_VERSION: u32 = 6;
+/// v7: серверная экономика — `ClientPacket::MoneyReport`, `ServerPacket::MoneySet`,
+///     `ServerPacket::SystemMessage` (ответы на чат-команды вроде `/pay`).
+/// v8: серверный респаун — `ServerPacket::RespawnPending`, `ServerPacket::Respawn`.
+/// v9: попадания и deathmatch — `ClientPacket::Hit`, `ServerPacket::Kill`,
+///     `ServerPacket::ScoreUpdate`, `ServerPacket::RoundStart` / `RoundEnd`.
+/// v10: гонки — `ServerPacket::RaceTrack`, `RaceCountdown`, `RaceStart`,
+///      `RaceCheckpoint`, `RaceStandings`, `RaceFinish`, `RaceResults`.
+/// v11: команды — `ServerPacket::TeamList`, `TeamAssigned`, командный чат
+///      `ClientPacket::TeamChat` / `ServerPacket::TeamChat`.
+/// v12: пароль сервера — поле `password` в `ClientPacket::Connect`;
+///      UDP query-протокол (`query`).
+/// v13: серверные время суток и погода — `ServerPacket::WorldState`.
+/// v14: `NetPlayerSnapshot::tick` — миллисекунды монотонных часов
+///      отправителя вместо счётчика (по ним строится буфер интерполяции).
+/// v15: замер RTT — `ClientPacket::Ping` / `ServerPacket::Pong`.
+/// v16: оружие в руках — `NetPlayerSnapshot::weapon_id`; события
+///      `WeaponSelect { weapon_id }`, `WeaponHide`, `WeaponReload`.
+/// v17: внешний вид — `ClientPacket::Appearance`, `ServerPacket::PlayerAppearance`.
+/// v18: выстрелы — событие `ShotFired` вместо `Shot`; подтверждённый сервером
+///      урон `ServerPacket::Damage`.
+/// v19: машины — сетевые `VehicleId` и места: `ClientPacket::VehicleEnter` /
+///      `VehicleLeave` / `VehicleSnapshot` и одноимённые `ServerPacket`.
+/// v20: аккаунты по токену — поле `account_token` в `ClientPacket::Connect`,
+///      `ServerPacket::AccountToken`.
+/// v21: место, которое игрок занял сам, — `seat` в `ClientPacket::VehicleEnter`.
+pub const PROTOCOL_VERSION: u32 = 21;
 
 /// Порт сервера по умолчанию.
 pub const DEFAULT_PORT: u16 = 7788;
@@ -28,6 +57,9 @@ pub const MAX_PLAYERS: usize = 32;
 /// Идентификатор игрока на сервере.
 pub type PlayerId = u16;
 
+/// Сетевой идентификатор машины, выдаёт сервер при первой посадке.
+pub type VehicleId = u32;
+
 /// Простой сетевой Vec3.
 #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct NetVec3 {
@@ -36,12 +68,60 @@ pub struct NetVec3 {
     pub z: f32,
 }
 
+/// Внешний вид игрока: модель и вариант одежды.
+///
+/// `model` — имя из model descriptor (`Player::get_model_name`), то же, что
+/// в `/sds/player/<model>.sds`. `appearance_id` — `Player::get_appearance_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetAppearance {
+    pub model: String,
+    #[serde(default)]
+    pub appearance_id: Option<u32>,
+}
+
+impl NetAppearance {
+    pub const MAX_MODEL_LEN: usize = 64;
+
+    /// Имя модели годится для загрузки: непустое, не длиннее
+    /// `MAX_MODEL_LEN`, только ASCII буквы, цифры, `_` и `-` (оно уходит
+    /// в путь к SDS и в Lua).
+    pub fn is_valid(&self) -> bool {
+        !self.model.is_empty()
+            && self.model.len() <= Self::MAX_MODEL_LEN
+            && self
+                .model
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+    }
+}
+
+/// Оружие с патронами (стартовый набор при респауне и т.п.).
+///
+/// `weapon_id` — из `sdk::addresses::constants::weapons`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetWeapon {
+    pub weapon_id: u32,
+    pub ammo: u32,
+}
+
+/// Snapshot машины. Шлёт только водитель (место 0).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NetVehicleSnapshot {
+    /// Как `NetPlayerSnapshot::tick` — мс монотонных часов водителя.
+    pub tick: u64,
+    pub vehicle_id: VehicleId,
+    pub position: NetVec3,
+    /// Кватернион `[x, y, z, w]` (`Car::get_rotation`).
+    pub rotation: [f32; 4],
+}
+
 /// Snapshot игрока.
 ///
 /// Минимальный multiplayer-useful набор подтверждённых reverse'ом данных.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetPlayerSnapshot {
-    /// Локальный tick/sequence number отправителя.
+    /// Время отправки, мс монотонных часов отправителя (начало отсчёта
+    /// произвольное, но одно на сессию).
     pub tick: u64,
 
     /// ID игрока в сессии.
@@ -103,6 +183,11 @@ pub struct NetPlayerSnapshot {
     /// Старые клиенты без поля получают `0` (`serde(default)`).
     #[serde(default)]
     pub movement_mode: u8,
+
+    /// Оружие в руках (`constants::weapons`). `None` — руки пусты или
+    /// оружие убрано в кобуру.
+    #[serde(default)]
+    pub weapon_id: Option<u32>,
 }
 
 /// Высокоуровневые события игрока.
@@ -116,19 +201,83 @@ pub enum NetPlayerEvent {
     Damage,
     Death,
 
-    Shot,
-    WeaponSelect,
+    /// Выстрел из `weapon_id`: из `origin` в направлении `direction`
+    /// (нормализован). `hit_player` / `hit_pos` — в кого и куда попал по
+    /// данным стрелка; урон всё равно идёт только через `ClientPacket::Hit`.
+    ShotFired {
+        weapon_id: u32,
+        origin: NetVec3,
+        direction: NetVec3,
+        #[serde(default)]
+        hit_player: Option<PlayerId>,
+        #[serde(default)]
+        hit_pos: Option<NetVec3>,
+    },
+    /// Достал оружие `weapon_id` (или сменил одно на другое).
+    WeaponSelect {
+        weapon_id: u32,
+    },
+    /// Убрал оружие в кобуру.
     WeaponHide,
+    /// Перезарядил оружие в руках.
+    WeaponReload {
+        weapon_id: u32,
+    },
 
     /// Триггер PlayerFx-style события.
     Fx(u16),
 }
 
+/// Почему закончился раунд.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundEndReason {
+    /// Кто-то набрал лимит фрагов.
+    ScoreLimit,
+    /// Истёк лимит времени.
+    TimeLimit,
+}
+
+/// Чекпоинт гоночной трассы.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NetCheckpoint {
+    pub position: NetVec3,
+    pub radius: f32,
+}
+
+/// Итог гонщика. `place = None` — не финишировал.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetRaceResult {
+    pub player_id: PlayerId,
+    pub place: Option<u32>,
+    pub total_ms: Option<u32>,
+    pub best_lap_ms: Option<u32>,
+}
+
+/// Команда, как её видит клиент.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetTeam {
+    pub id: String,
+    pub name: String,
+    /// RGB.
+    pub color: [u8; 3],
+}
+
 /// Пакет от клиента к серверу.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientPacket {
     /// Первый пакет после подключения.
-    Connect { name: String, version: u32 },
+    ///
+    /// `password` — если сервер закрыт паролем (`ServerInfo::password`).
+    /// `account_token` — выданный этим сервером при прошлом входе с этим
+    /// ником (`ServerPacket::AccountToken`).
+    Connect {
+        name: String,
+        version: u32,
+        #[serde(default)]
+        password: Option<String>,
+        #[serde(default)]
+        account_token: Option<String>,
+    },
 
     /// Явное отключение.
     Disconnect,
@@ -140,7 +289,78 @@ pub enum ClientPacket {
     Event(NetPlayerEvent),
 
     /// Сообщение чата.
+    ///
+    /// Текст, начинающийся с `/`, сервер трактует как команду
+    /// (например `/pay <id> <сумма>`) и в общий чат не рассылает.
     ChatMessage { text: String },
+
+    /// Сообщение командного чата (только своей команде).
+    TeamChat { text: String },
+
+    /// Изменение кошелька, замеченное клиентом (`PlayerEvent::MoneyChanged`).
+    ///
+    /// Это только заявка: сервер сверяет её со своим балансом и при
+    /// несогласии отвечает `ServerPacket::MoneySet`. Суммы в центах.
+    MoneyReport { old_cents: i64, new_cents: i64 },
+
+    /// Локальный игрок попал в proxy удалённого игрока `victim_id`.
+    ///
+    /// Стрелок — всегда отправитель. `weapon_id` — из `constants::weapons`,
+    /// `headshot` — пришёл `human_messages::HEAD_DAMAGE`. Сервер проверяет
+    /// заявку и запоминает её для атрибуции убийства.
+    Hit {
+        victim_id: PlayerId,
+        weapon_id: u32,
+        damage: f32,
+        headshot: bool,
+    },
+
+    /// Замер RTT: сервер сразу отвечает `ServerPacket::Pong` с тем же
+    /// `sent_ms` (мс часов клиента).
+    Ping { sent_ms: u64 },
+
+    /// Внешний вид локального игрока: сразу после `ConnectAccepted` и при
+    /// каждой смене (гардероб).
+    Appearance(NetAppearance),
+
+    /// Локальный игрок сел в машину модели `model_hash` (`Car::name_hash`),
+    /// стоящую в `position`, на место `seat` (0 — водитель). Сервер находит
+    /// уже известную машину рядом со свободным `seat` (так пассажир попадает
+    /// в машину водителя) или заводит новую и отвечает всем
+    /// `ServerPacket::VehicleEnter` с ID. Если место рядом уже занято —
+    /// пакет отбрасывается.
+    VehicleEnter {
+        model_hash: u64,
+        position: NetVec3,
+        seat: u8,
+    },
+
+    /// Локальный игрок вышел из машины.
+    VehicleLeave,
+
+    /// Snapshot машины, которой управляет локальный игрок.
+    VehicleSnapshot(NetVehicleSnapshot),
+}
+
+impl ClientPacket {
+    /// Имя варианта — для логов и метрик.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Connect { .. } => "Connect",
+            Self::Disconnect { .. } => "Disconnect",
+            Self::Snapshot { .. } => "Snapshot",
+            Self::Event { .. } => "Event",
+            Self::ChatMessage { .. } => "ChatMessage",
+            Self::TeamChat { .. } => "TeamChat",
+            Self::MoneyReport { .. } => "MoneyReport",
+            Self::Hit { .. } => "Hit",
+            Self::Ping { .. } => "Ping",
+            Self::Appearance { .. } => "Appearance",
+            Self::VehicleEnter { .. } => "VehicleEnter",
+            Self::VehicleLeave { .. } => "VehicleLeave",
+            Self::VehicleSnapshot { .. } => "VehicleSnapshot",
+        }
+    }
 }
 
 /// Пакет от сервера к клиенту.
@@ -152,6 +372,10 @@ pub enum ServerPacket {
     /// Подключение отвергнуто.
     ConnectRejected { reason: String },
 
+    /// Токен аккаунта для ника, под которым принято подключение. Клиент
+    /// хранит его и предъявляет в следующих `Connect` к этому серверу.
+    AccountToken { token: String },
+
     /// Спавн удалённого игрока.
     PlayerSpawn { player_id: PlayerId, name: String },
 
@@ -169,4 +393,198 @@ pub enum ServerPacket {
 
     /// Чат-сообщение.
     ChatMessage { player_id: PlayerId, text: String },
+
+    /// Системное сообщение сервера (ответы на команды, объявления).
+    SystemMessage { text: String },
+
+    /// Игрок мёртв, респаун через `delay_ms` (для отсчёта в HUD).
+    RespawnPending { delay_ms: u32 },
+
+    /// Возродить локального игрока.
+    ///
+    /// `heading` — курс в градусах по часовой стрелке от +Y (0 = "север").
+    Respawn {
+        position: NetVec3,
+        heading: f32,
+        health: f32,
+        loadout: Vec<NetWeapon>,
+    },
+
+    /// Авторитетный баланс игрока в центах.
+    ///
+    /// Клиент обязан выставить кошелёк в это значение (`Player::set_money`).
+    MoneySet { balance_cents: i64 },
+
+    /// Игрок погиб. `killer_id = None` — смерть без атрибуции
+    /// (падение, машина, самоубийство).
+    Kill {
+        killer_id: Option<PlayerId>,
+        victim_id: PlayerId,
+        weapon_id: Option<u32>,
+        headshot: bool,
+    },
+
+    /// Сервер принял попадание по локальному игроку — клиент снимает
+    /// `damage` здоровья. Единственный источник урона от других игроков.
+    Damage {
+        attacker_id: PlayerId,
+        weapon_id: u32,
+        damage: f32,
+        headshot: bool,
+    },
+
+    /// Счёт игрока в текущем раунде.
+    ScoreUpdate {
+        player_id: PlayerId,
+        kills: u32,
+        deaths: u32,
+    },
+
+    /// Начался раунд. Клиент обнуляет счёт. Нулевой лимит — без ограничения.
+    RoundStart {
+        mode: String,
+        score_limit: u32,
+        time_limit_secs: u32,
+    },
+
+    /// Раунд закончен; следующий начнётся через `next_round_in_secs`.
+    RoundEnd {
+        winner_id: Option<PlayerId>,
+        reason: RoundEndReason,
+        next_round_in_secs: u32,
+    },
+
+    /// Трасса текущей гонки. Последний чекпоинт — финиш круга.
+    RaceTrack {
+        name: String,
+        laps: u32,
+        checkpoints: Vec<NetCheckpoint>,
+    },
+
+    /// Получатель записан в заезд, старт через `seconds`.
+    RaceCountdown { seconds: u32 },
+
+    /// Старт заезда. Время сплитов считается от этого момента.
+    RaceStart,
+
+    /// Получатель прошёл чекпоинт `checkpoint` на круге `lap` (с единицы).
+    /// `lap_ms` — если чекпоинт закрыл круг.
+    RaceCheckpoint {
+        checkpoint: u32,
+        lap: u32,
+        split_ms: u32,
+        lap_ms: Option<u32>,
+    },
+
+    /// Текущий порядок гонщиков (первый — лидер).
+    RaceStandings { order: Vec<PlayerId> },
+
+    /// Игрок финишировал.
+    RaceFinish {
+        player_id: PlayerId,
+        place: u32,
+        total_ms: u32,
+    },
+
+    /// Заезд окончен.
+    RaceResults {
+        results: Vec<NetRaceResult>,
+        next_race_in_secs: u32,
+    },
+
+    /// Команды сервера. Пустой список — без команд.
+    TeamList { teams: Vec<NetTeam> },
+
+    /// Игрок сменил команду (`None` — без команды).
+    TeamAssigned {
+        player_id: PlayerId,
+        team: Option<String>,
+    },
+
+    /// Сообщение командного чата от союзника.
+    TeamChat { player_id: PlayerId, text: String },
+
+    /// Время суток и погода мира.
+    ///
+    /// `hour` — час в `[0, 24)` на момент отправки; дальше клиент сам
+    /// двигает его со скоростью `time_scale` (игровых секунд в реальной
+    /// секунде, `0` — время стоит). `weather` — шаблон погоды
+    /// (`game.gfx:SetWeatherTemplate`), `None` — погоду не трогать.
+    WorldState {
+        hour: f32,
+        time_scale: f32,
+        weather: Option<String>,
+    },
+
+    /// Ответ на `ClientPacket::Ping`.
+    Pong { sent_ms: u64 },
+
+    /// Внешний вид удалённого игрока. Новичку приходит после `PlayerSpawn`
+    /// для уже известных игроков, остальным — при каждой смене.
+    PlayerAppearance {
+        player_id: PlayerId,
+        appearance: NetAppearance,
+    },
+
+    /// Игрок сел в машину `vehicle_id` на место `seat` (0 — водитель).
+    /// Приходит и самому севшему — так он узнаёт ID и место. Новичку
+    /// приходит после `PlayerSpawn` для каждого, кто уже в машине.
+    VehicleEnter {
+        player_id: PlayerId,
+        vehicle_id: VehicleId,
+        seat: u8,
+        model_hash: u64,
+        /// Где машина была при посадке (или по последнему snapshot'у).
+        position: NetVec3,
+    },
+
+    /// Игрок вышел из машины `vehicle_id`.
+    VehicleLeave {
+        player_id: PlayerId,
+        vehicle_id: VehicleId,
+    },
+
+    /// Snapshot машины от её водителя.
+    VehicleSnapshot(NetVehicleSnapshot),
+}
+
+impl ServerPacket {
+    /// Имя варианта — для логов и метрик.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ConnectAccepted { .. } => "ConnectAccepted",
+            Self::ConnectRejected { .. } => "ConnectRejected",
+            Self::AccountToken { .. } => "AccountToken",
+            Self::PlayerSpawn { .. } => "PlayerSpawn",
+            Self::PlayerDespawn { .. } => "PlayerDespawn",
+            Self::Snapshot { .. } => "Snapshot",
+            Self::Event { .. } => "Event",
+            Self::ChatMessage { .. } => "ChatMessage",
+            Self::SystemMessage { .. } => "SystemMessage",
+            Self::RespawnPending { .. } => "RespawnPending",
+            Self::Respawn { .. } => "Respawn",
+            Self::MoneySet { .. } => "MoneySet",
+            Self::Kill { .. } => "Kill",
+            Self::Damage { .. } => "Damage",
+            Self::ScoreUpdate { .. } => "ScoreUpdate",
+            Self::RoundStart { .. } => "RoundStart",
+            Self::RoundEnd { .. } => "RoundEnd",
+            Self::RaceTrack { .. } => "RaceTrack",
+            Self::RaceCountdown { .. } => "RaceCountdown",
+            Self::RaceStart { .. } => "RaceStart",
+            Self::RaceCheckpoint { .. } => "RaceCheckpoint",
+            Self::RaceStandings { .. } => "RaceStandings",
+            Self::RaceFinish { .. } => "RaceFinish",
+            Self::RaceResults { .. } => "RaceResults",
+            Self::TeamList { .. } => "TeamList",
+            Self::TeamAssigned { .. } => "TeamAssigned",
+            Self::TeamChat { .. } => "TeamChat",
+            Self::WorldState { .. } => "WorldState",
+            Self::Pong { .. } => "Pong",
+            Self::PlayerAppearance { .. } => "PlayerAppearance",
+            Self::VehicleEnter { .. } => "VehicleEnter",
+            Self::VehicleLeave { .. } => "VehicleLeave",
+            Self::VehicleSnapshot { .. } => "VehicleSnapshot",
+        }
+    }
 }
@@ -0,0 +1,103 @@
+//! Протокол мастер-сервера (список публичных серверов).
+//!
+//! TCP, один JSON-запрос строкой (`\n`) и один ответ на соединение.
+//!
+//! - выделенный сервер раз в `DEFAULT_HEARTBEAT_SECS` шлёт `Register` со своим
+//!   игровым портом; мастер берёт IP из TCP-соединения и проверяет сервер
+//!   обратным UDP query (`query`) на этот IP и порт — зарегистрировать чужой
+//!   адрес нельзя
+//! - клиент шлёт `List` с фильтром и получает подходящие серверы
+//! - сервер без heartbeat дольше таймаута мастер выкидывает сам
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::query::ServerInfo;
+
+/// TCP-порт мастер-сервера по умолчанию.
+pub const DEFAULT_MASTER_PORT: u16 = 7790;
+
+/// Период heartbeat по умолчанию.
+pub const DEFAULT_HEARTBEAT_SECS: u64 = 30;
+
+/// Максимальная длина строки запроса.
+pub const MAX_MASTER_REQUEST_BYTES: usize = 4 * 1024;
+
+/// Запрос к мастер-серверу.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum MasterRequest {
+    /// Регистрация / heartbeat. `port` — игровой порт (он же UDP query).
+    Register { port: u16 },
+    /// Список серверов.
+    List {
+        #[serde(default)]
+        filter: ListFilter,
+    },
+}
+
+/// Ответ мастер-сервера.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum MasterResponse {
+    /// Сервер проверен и в списке.
+    Registered,
+    Servers {
+        servers: Vec<MasterServer>,
+    },
+    Error {
+        reason: String,
+    },
+}
+
+/// Сервер в списке мастера.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasterServer {
+    /// Адрес для TCP-подключения.
+    pub addr: SocketAddr,
+    /// Ответ на последний проверочный query.
+    pub info: ServerInfo,
+}
+
+/// Фильтр списка. Пустой фильтр пропускает всё.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListFilter {
+    /// Подстрока имени без учёта регистра.
+    pub name: Option<String>,
+    /// Точное имя режима.
+    pub mode: Option<String>,
+    pub hide_full: bool,
+    pub hide_empty: bool,
+    pub hide_password: bool,
+    /// Только серверы с этой версией протокола.
+    pub protocol_version: Option<u32>,
+}
+
+impl ListFilter {
+    pub fn matches(&self, info: &ServerInfo) -> bool {
+        if let Some(name) = &self.name
+            && !info.name.to_lowercase().contains(&name.to_lowercase())
+        {
+            return false;
+        }
+        if let Some(mode) = &self.mode
+            && !info.mode.eq_ignore_ascii_case(mode)
+        {
+            return false;
+        }
+        if let Some(version) = self.protocol_version
+            && info.protocol_version != version
+        {
+            return false;
+        }
+        if self.hide_full && info.players >= info.max_players {
+            return false;
+        }
+        if self.hide_empty && info.players == 0 {
+            return false;
+        }
+        !(self.hide_password && info.password)
+    }
+}
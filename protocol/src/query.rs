@@ -0,0 +1,73 @@
+//! Query-протокол: информация о сервере по UDP.
+//!
+//! Один JSON-объект на датаграмму, без `\n`. Сервер слушает UDP на том же
+//! номере порта, что и игровой TCP. LAN discovery — тот же запрос на
+//! broadcast-адрес: ответят все серверы в сети.
+//!
+//! Против амплификации:
+//! - запрос должен быть не короче `MIN_QUERY_REQUEST_BYTES` (поле `pad`),
+//!   поэтому ответ больше запроса не более чем в несколько раз
+//! - список имён игроков обрезан до `MAX_QUERY_PLAYER_NAMES`
+//! - сервер ограничивает частоту ответов на IP и суммарно
+
+use serde::{Deserialize, Serialize};
+
+/// Маркер, отличающий наш запрос от случайного UDP-мусора.
+pub const QUERY_MAGIC: &str = "M2MP";
+
+/// Минимальная длина запроса в байтах (с паддингом).
+pub const MIN_QUERY_REQUEST_BYTES: usize = 256;
+
+/// Максимальная длина датаграммы запроса.
+pub const MAX_QUERY_REQUEST_BYTES: usize = 1024;
+
+/// Сколько имён игроков максимум попадает в ответ.
+pub const MAX_QUERY_PLAYER_NAMES: usize = 32;
+
+/// Запрос информации о сервере.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryRequest {
+    pub magic: String,
+    /// Возвращается в ответе: сопоставление ответа запросу и замер пинга.
+    pub nonce: u64,
+    /// Паддинг до `MIN_QUERY_REQUEST_BYTES`.
+    #[serde(default)]
+    pub pad: String,
+}
+
+impl QueryRequest {
+    /// Запрос, уже дополненный до минимальной длины при JSON-кодировании.
+    pub fn new(nonce: u64) -> Self {
+        Self {
+            magic: QUERY_MAGIC.to_string(),
+            nonce,
+            // Одного паддинга хватает на минимальную длину.
+            pad: "x".repeat(MIN_QUERY_REQUEST_BYTES),
+        }
+    }
+}
+
+/// Ответ сервера.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryResponse {
+    pub magic: String,
+    pub nonce: u64,
+    pub info: ServerInfo,
+}
+
+/// Что сервер сообщает о себе.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub name: String,
+    pub map: String,
+    pub mode: String,
+    pub players: u32,
+    pub max_players: u32,
+    /// Не больше `MAX_QUERY_PLAYER_NAMES`.
+    pub player_names: Vec<String>,
+    pub protocol_version: u32,
+    /// Нужен ли пароль для входа.
+    pub password: bool,
+    /// TCP-порт для подключения.
+    pub port: u16,
+}
@@ -0,0 +1,95 @@
+//! Формат записи сессии сервера (`.m2rec`).
+//!
+//! Текст, одна JSON-строка на запись:
+//! - первая строка — `RecordingHeader`
+//! - дальше `Record` в порядке времени: принятый `ClientPacket` (`in`),
+//!   записанный в сокет `ServerPacket` (`out`) или закрытие соединения
+//!   (`close`)
+//!
+//! Ключи записей короткие: файл пишется на каждый пакет каждого игрока.
+//!
+//! Секреты в файл не попадают: пароль и токен аккаунта в `Connect` и токен
+//! в `AccountToken` заменены на `REDACTED` — записью можно делиться.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClientPacket, PlayerId, ServerPacket};
+
+/// Расширение файлов записи.
+pub const RECORDING_EXTENSION: &str = "m2rec";
+
+/// Версия формата записи (не протокола).
+pub const RECORDING_FORMAT: u32 = 1;
+
+/// Чем в записи заменены пароли и токены.
+pub const REDACTED: &str = "<redacted>";
+
+/// Первая строка файла.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub format: u32,
+    /// `PROTOCOL_VERSION` сервера, который писал.
+    pub protocol_version: u32,
+    /// Unix-время начала записи в миллисекундах.
+    pub started_unix_ms: u64,
+    /// Имя сервера из конфига.
+    pub server: String,
+}
+
+/// Одна запись.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    /// Миллисекунды от начала записи.
+    pub t: u64,
+    /// Соединение (provisional `PlayerId`, выданный при accept).
+    pub p: PlayerId,
+    #[serde(flatten)]
+    pub entry: Entry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "d", content = "k")]
+pub enum Entry {
+    /// Принятый от клиента пакет.
+    #[serde(rename = "in")]
+    In(ClientPacket),
+    /// Пакет, записанный клиенту в сокет.
+    #[serde(rename = "out")]
+    Out(ServerPacket),
+    /// Соединение закрыто (любой причиной).
+    #[serde(rename = "close")]
+    Closed,
+}
+
+impl Entry {
+    /// Та же запись без секретов (см. `REDACTED`).
+    pub fn redacted(self) -> Self {
+        let hide = |secret: Option<String>| secret.map(|_| REDACTED.to_string());
+        match self {
+            Self::In(ClientPacket::Connect {
+                name,
+                version,
+                password,
+                account_token,
+            }) => Self::In(ClientPacket::Connect {
+                name,
+                version,
+                password: hide(password),
+                account_token: hide(account_token),
+            }),
+            Self::Out(ServerPacket::AccountToken { .. }) => Self::Out(ServerPacket::AccountToken {
+                token: REDACTED.to_string(),
+            }),
+            other => other,
+        }
+    }
+
+    /// Имя варианта пакета (`"close"` для закрытия).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::In(packet) => packet.kind(),
+            Self::Out(packet) => packet.kind(),
+            Self::Closed => "close",
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! Повтор записи на живом сервере и сравнение ответов.
+//!
+//! Каждое записанное соединение становится TCP-соединением к серверу:
+//! открывается на первом его входящем пакете, закрывается на `close`.
+//! Входящие пакеты уходят с исходными интервалами (с поправкой на
+//! `speed`), ответы сервера копятся по соединениям.
+//!
+//! `PlayerId` живого сервера почти всегда другие, поэтому перед сравнением
+//! id в ответах переводятся в записанные по парам `ConnectAccepted`.
+//!
+//! Пароль в записи скрыт (`recording::REDACTED`), для сервера с паролем
+//! его подставляет `FeedOptions::password`.
+
+use std::collections::{HashMap, hash_map};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use protocol::recording::Entry;
+use protocol::{ClientPacket, PlayerId, ServerPacket};
+use serde_json::Value;
+
+use crate::Recording;
+
+/// Пакеты, которые зависят от таймеров тика или склеиваются в очереди
+/// отправки (и `AccountToken` — токены случайные), — побайтово не
+/// повторяются, при сравнении пропускаются.
+pub const TIMING_DEPENDENT: &[&str] = &[
+    "Snapshot",
+    "Respawn",
+    "RaceCountdown",
+    "RaceStart",
+    "WorldState",
+    "AccountToken",
+];
+
+/// Поля с `PlayerId`, которые переводятся при сравнении.
+const ID_FIELDS: &[&str] = &["player_id", "victim_id", "killer_id", "winner_id"];
+
+#[derive(Debug, Clone)]
+pub struct FeedOptions {
+    /// Множитель скорости: 2.0 — вдвое быстрее записи.
+    pub speed: f32,
+    /// Сколько ждать ответов после последнего пакета.
+    pub settle: Duration,
+    /// Пароль вместо скрытого в записанных `Connect`.
+    pub password: Option<String>,
+}
+
+impl Default for FeedOptions {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            settle: Duration::from_secs(1),
+            password: None,
+        }
+    }
+}
+
+/// Соединение повтора.
+#[derive(Debug, Clone)]
+pub struct FedConnection {
+    /// Id соединения в записи.
+    pub recorded_id: PlayerId,
+    /// Id, выданный живым сервером (`None` — `ConnectAccepted` не пришёл).
+    pub live_id: Option<PlayerId>,
+    pub received: Vec<ServerPacket>,
+}
+
+struct Live {
+    stream: TcpStream,
+    reader: JoinHandle<Vec<ServerPacket>>,
+}
+
+/// Подставить `password` в `Connect`, где пароль был (в записи он скрыт).
+fn with_password(packet: &ClientPacket, password: Option<&str>) -> ClientPacket {
+    match (packet, password) {
+        (
+            ClientPacket::Connect {
+                name,
+                version,
+                password: Some(_),
+                account_token,
+            },
+            Some(password),
+        ) => ClientPacket::Connect {
+            name: name.clone(),
+            version: *version,
+            password: Some(password.to_string()),
+            account_token: account_token.clone(),
+        },
+        _ => packet.clone(),
+    }
+}
+
+/// Подать входящие пакеты записи серверу `server`.
+pub fn feed(
+    recording: &Recording,
+    server: SocketAddr,
+    options: &FeedOptions,
+) -> io::Result<Vec<FedConnection>> {
+    let speed = options.speed.max(0.01) as f64;
+    let started = Instant::now();
+    let mut live: HashMap<PlayerId, Live> = HashMap::new();
+    let mut order = Vec::new();
+
+    for record in &recording.records {
+        let packet = match &record.entry {
+            Entry::In(packet) => packet,
+            Entry::Closed => {
+                if let Some(conn) = live.get(&record.p) {
+                    let _ = conn.stream.shutdown(Shutdown::Write);
+                }
+                continue;
+            }
+            Entry::Out(_) => continue,
+        };
+
+        let at = started + Duration::from_secs_f64(record.t as f64 / 1000.0 / speed);
+        if let Some(wait) = at.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+
+        let conn = match live.entry(record.p) {
+            hash_map::Entry::Occupied(e) => e.into_mut(),
+            hash_map::Entry::Vacant(e) => {
+                order.push(record.p);
+                e.insert(connect(server)?)
+            }
+        };
+        let packet = with_password(packet, options.password.as_deref());
+        let mut line = serde_json::to_string(&packet).map_err(io::Error::other)?;
+        line.push('\n');
+        // Сервер мог уже закрыть соединение (как и в записи) — не ошибка.
+        let _ = conn.stream.write_all(line.as_bytes());
+    }
+
+    thread::sleep(options.settle);
+
+    let mut fed = Vec::new();
+    for recorded_id in order {
+        let Some(conn) = live.remove(&recorded_id) else {
+            continue;
+        };
+        let _ = conn.stream.shutdown(Shutdown::Write);
+        let received = conn.reader.join().unwrap_or_default();
+        let live_id = received.iter().find_map(|p| match p {
+            ServerPacket::ConnectAccepted { player_id } => Some(*player_id),
+            _ => None,
+        });
+        fed.push(FedConnection {
+            recorded_id,
+            live_id,
+            received,
+        });
+    }
+    Ok(fed)
+}
+
+fn connect(server: SocketAddr) -> io::Result<Live> {
+    let stream = TcpStream::connect(server)?;
+    // Страховка от сервера, который не закрывает соединение.
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let reader_stream = stream.try_clone()?;
+    let reader = thread::spawn(move || {
+        let mut received = Vec::new();
+        for line in BufReader::new(reader_stream).lines() {
+            let Ok(line) = line else { break };
+            if let Ok(packet) = serde_json::from_str(&line) {
+                received.push(packet);
+            }
+        }
+        received
+    });
+    Ok(Live { stream, reader })
+}
+
+/// Расхождение ответа сервера с записью.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    pub recorded_id: PlayerId,
+    /// Номер пакета среди сравниваемых (без пропущенных видов).
+    pub index: usize,
+    /// `None` — пакета не было.
+    pub expected: Option<Value>,
+    pub actual: Option<Value>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<Value>| v.as_ref().map_or("<none>".to_string(), Value::to_string);
+        write!(
+            f,
+            "#{} packet {}: expected {}, got {}",
+            self.recorded_id,
+            self.index,
+            show(&self.expected),
+            show(&self.actual)
+        )
+    }
+}
+
+/// Сравнить ответы живого сервера с записанными, пропуская виды из
+/// `ignore`. По каждому соединению — первое расхождение.
+pub fn compare(recording: &Recording, fed: &[FedConnection], ignore: &[&str]) -> Vec<Mismatch> {
+    let keep = |packet: &ServerPacket| !ignore.contains(&packet.kind());
+
+    let mut expected: HashMap<PlayerId, Vec<Value>> = HashMap::new();
+    for record in &recording.records {
+        if let Entry::Out(packet) = &record.entry
+            && keep(packet)
+            && let Ok(value) = serde_json::to_value(packet)
+        {
+            expected.entry(record.p).or_default().push(value);
+        }
+    }
+
+    // live id -> записанный id (записанный — из `ConnectAccepted` записи).
+    let ids: HashMap<PlayerId, PlayerId> = fed
+        .iter()
+        .filter_map(|conn| Some((conn.live_id?, recorded_accept(recording, conn.recorded_id)?)))
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for conn in fed {
+        let expected = expected.remove(&conn.recorded_id).unwrap_or_default();
+        let actual: Vec<Value> = conn
+            .received
+            .iter()
+            .filter(|p| keep(p))
+            .filter_map(|p| serde_json::to_value(p).ok())
+            .map(|mut v| {
+                remap_ids(&mut v, &ids);
+                v
+            })
+            .collect();
+
+        let len = expected.len().max(actual.len());
+        if let Some(index) = (0..len).find(|&i| expected.get(i) != actual.get(i)) {
+            mismatches.push(Mismatch {
+                recorded_id: conn.recorded_id,
+                index,
+                expected: expected.get(index).cloned(),
+                actual: actual.get(index).cloned(),
+            });
+        }
+    }
+    mismatches
+}
+
+fn recorded_accept(recording: &Recording, recorded_id: PlayerId) -> Option<PlayerId> {
+    recording.records.iter().find_map(|r| match &r.entry {
+        Entry::Out(ServerPacket::ConnectAccepted { player_id }) if r.p == recorded_id => {
+            Some(*player_id)
+        }
+        _ => None,
+    })
+}
+
+fn remap_ids(value: &mut Value, ids: &HashMap<PlayerId, PlayerId>) {
+    let remap = |v: &mut Value| {
+        if let Some(id) = v.as_u64().and_then(|id| PlayerId::try_from(id).ok())
+            && let Some(recorded) = ids.get(&id)
+        {
+            *v = Value::from(*recorded);
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if ID_FIELDS.contains(&key.as_str()) {
+                    remap(v);
+                } else if key == "order"
+                    && let Value::Array(items) = v
+                {
+                    items.iter_mut().for_each(remap);
+                } else {
+                    remap_ids(v, ids);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| remap_ids(v, ids)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::recording::{Record, RecordingHeader};
+
+    use super::*;
+
+    fn recording(records: Vec<Record>) -> Recording {
+        Recording {
+            header: RecordingHeader {
+                format: 1,
+                protocol_version: protocol::PROTOCOL_VERSION,
+                started_unix_ms: 0,
+                server: String::new(),
+            },
+            records,
+        }
+    }
+
+    fn out(p: PlayerId, packet: ServerPacket) -> Record {
+        Record {
+            t: 0,
+            p,
+            entry: Entry::Out(packet),
+        }
+    }
+
+    #[test]
+    fn compare_translates_live_ids_and_skips_ignored_kinds() {
+        let recorded = recording(vec![
+            out(1, ServerPacket::ConnectAccepted { player_id: 1 }),
+            out(2, ServerPacket::ConnectAccepted { player_id: 2 }),
+            out(
+                2,
+                ServerPacket::ChatMessage {
+                    player_id: 1,
+                    text: "hi".into(),
+                },
+            ),
+        ]);
+        // Живой сервер выдал 7 и 8 и прислал лишний Respawn.
+        let fed = vec![
+            FedConnection {
+                recorded_id: 1,
+                live_id: Some(7),
+                received: vec![ServerPacket::ConnectAccepted { player_id: 7 }],
+            },
+            FedConnection {
+                recorded_id: 2,
+                live_id: Some(8),
+                received: vec![
+                    ServerPacket::ConnectAccepted { player_id: 8 },
+                    ServerPacket::Respawn {
+                        position: Default::default(),
+                        heading: 0.0,
+                        health: 720.0,
+                        loadout: Vec::new(),
+                    },
+                    ServerPacket::ChatMessage {
+                        player_id: 7,
+                        text: "hi".into(),
+                    },
+                ],
+            },
+        ];
+        assert!(compare(&recorded, &fed, TIMING_DEPENDENT).is_empty());
+
+        let mut changed = fed.clone();
+        changed[1].received[2] = ServerPacket::ChatMessage {
+            player_id: 7,
+            text: "bye".into(),
+        };
+        let mismatches = compare(&recorded, &changed, TIMING_DEPENDENT);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!((mismatches[0].recorded_id, mismatches[0].index), (2, 1));
+    }
+
+    #[test]
+    fn password_replaces_only_a_recorded_one() {
+        let connect = |password: Option<&str>| ClientPacket::Connect {
+            name: "vito".to_string(),
+            version: 1,
+            password: password.map(str::to_string),
+            account_token: None,
+        };
+        let password = |packet: ClientPacket| match packet {
+            ClientPacket::Connect { password, .. } => password,
+            _ => None,
+        };
+        let redacted = connect(Some(protocol::recording::REDACTED));
+
+        assert_eq!(
+            password(with_password(&redacted, Some("secret"))).as_deref(),
+            Some("secret")
+        );
+        assert_eq!(
+            password(with_password(&redacted, None)).as_deref(),
+            Some(protocol::recording::REDACTED)
+        );
+        assert_eq!(
+            password(with_password(&connect(None), Some("secret"))),
+            None
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Записи сессий сервера (`.m2rec`): чтение, лента событий, повтор.
+//!
+//! - `timeline` — фильтр и текстовая лента записи
+//! - `feed` — подать входящие пакеты записи живому серверу с исходными
+//!   интервалами и собрать его ответы
+//! - `compare` — сравнить ответы живого сервера с записанными: так реальная
+//!   сессия превращается в регрессионный тест
+
+pub mod feed;
+pub mod timeline;
+
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use protocol::recording::{RECORDING_FORMAT, Record, RecordingHeader};
+
+pub use feed::{FedConnection, FeedOptions, Mismatch, compare, feed};
+pub use timeline::Filter;
+
+/// Загруженная запись.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub records: Vec<Record>,
+}
+
+impl Recording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .map_err(|e| format!("cannot open {}: {e}", path.display()))?;
+        Self::parse(file).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Разобрать запись. Оборванная последняя строка (сервер убит посреди
+    /// записи) отбрасывается, битая строка в середине — ошибка.
+    pub fn parse(input: impl Read) -> Result<Self, String> {
+        let mut lines = BufReader::new(input).lines();
+
+        let header_line = lines
+            .next()
+            .ok_or("empty recording")?
+            .map_err(|e| format!("read failed: {e}"))?;
+        let header: RecordingHeader =
+            serde_json::from_str(&header_line).map_err(|e| format!("invalid header: {e}"))?;
+        if header.format != RECORDING_FORMAT {
+            return Err(format!(
+                "unsupported recording format {} (expected {RECORDING_FORMAT})",
+                header.format
+            ));
+        }
+
+        let mut records = Vec::new();
+        let mut broken: Option<(usize, String)> = None;
+        for (i, line) in lines.enumerate() {
+            let line = line.map_err(|e| format!("read failed: {e}"))?;
+            if let Some((at, e)) = broken.take() {
+                return Err(format!("line {}: {e}", at + 2));
+            }
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => broken = Some((i, e.to_string())),
+            }
+        }
+
+        Ok(Self { header, records })
+    }
+
+    /// Длительность записи в миллисекундах.
+    pub fn duration_ms(&self) -> u64 {
+        self.records.last().map_or(0, |r| r.t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str =
+        r#"{"format":1,"protocol_version":12,"started_unix_ms":0,"server":"Test"}"#;
+
+    #[test]
+    fn truncated_tail_is_dropped_but_broken_middle_is_an_error() {
+        let good = r#"{"t":5,"p":1,"d":"close"}"#;
+
+        let truncated = format!("{HEADER}\n{good}\n{{\"t\":9,\"p\"");
+        let recording = Recording::parse(truncated.as_bytes()).unwrap();
+        assert_eq!(recording.records.len(), 1);
+        assert_eq!(recording.duration_ms(), 5);
+
+        let broken = format!("{HEADER}\n{{oops\n{good}\n");
+        assert!(Recording::parse(broken.as_bytes()).is_err());
+
+        let future = HEADER.replace(r#""format":1"#, r#""format":99"#);
+        assert!(Recording::parse(future.as_bytes()).is_err());
+    }
+}
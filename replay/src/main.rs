@@ -0,0 +1,165 @@
+//! Просмотр и повтор записей сессий сервера.
+//!
+//! ```text
+//! replay timeline <file.m2rec> [--player <id>] [--type <Kind>]... [--dir in|out]
+//!                              [--from <сек>] [--to <сек>]
+//! replay feed <file.m2rec> --server <host:port> [--speed <x>] [--password <pw>] [--check]
+//! ```
+//!
+//! Пароль в записи скрыт: для сервера с паролем его передаёт `--password`.
+//! `feed --check` сравнивает ответы сервера с записанными (без пакетов из
+//! `TIMING_DEPENDENT`) и завершается с ошибкой при расхождении.
+
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::process::ExitCode;
+
+use replay::feed::TIMING_DEPENDENT;
+use replay::timeline::{self, Direction};
+use replay::{FeedOptions, Filter, Recording};
+
+const USAGE: &str = "usage:
+  replay timeline <file.m2rec> [--player <id>] [--type <Kind>]... [--dir in|out] [--from <сек>] [--to <сек>]
+  replay feed <file.m2rec> --server <host:port> [--speed <x>] [--password <pw>] [--check]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("timeline") => run_timeline(&args[1..]),
+        Some("feed") => run_feed(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Пары `--flag value`.
+type Flags<'a> = Vec<(&'a str, &'a str)>;
+
+/// `<file> [--flag value]...` -> файл и пары флагов. Флаги без значения
+/// (`--check`) получают пустую строку.
+fn split_args(args: &[String]) -> Result<(Recording, Flags<'_>), String> {
+    let (file, rest) = args.split_first().ok_or(USAGE)?;
+    let recording = Recording::load(file)?;
+
+    let mut flags = Vec::new();
+    let mut i = 0;
+    while i < rest.len() {
+        let flag = rest[i].as_str();
+        if flag == "--check" {
+            flags.push((flag, ""));
+            i += 1;
+            continue;
+        }
+        let value = rest.get(i + 1).ok_or(USAGE)?;
+        flags.push((flag, value.as_str()));
+        i += 2;
+    }
+    Ok((recording, flags))
+}
+
+fn parse<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {flag}: {value}"))
+}
+
+fn seconds_to_ms(flag: &str, value: &str) -> Result<u64, String> {
+    let secs: f64 = parse(flag, value)?;
+    Ok((secs * 1000.0) as u64)
+}
+
+fn run_timeline(args: &[String]) -> Result<ExitCode, String> {
+    let (recording, flags) = split_args(args)?;
+
+    let mut filter = Filter::default();
+    for (flag, value) in flags {
+        match flag {
+            "--player" => filter.player = Some(parse(flag, value)?),
+            "--type" => filter.kinds.push(value.to_string()),
+            "--dir" => {
+                filter.direction = Some(match value {
+                    "in" => Direction::In,
+                    "out" => Direction::Out,
+                    _ => return Err(format!("invalid value for --dir: {value}")),
+                })
+            }
+            "--from" => filter.from_ms = Some(seconds_to_ms(flag, value)?),
+            "--to" => filter.to_ms = Some(seconds_to_ms(flag, value)?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let header = &recording.header;
+    println!(
+        "# '{}', protocol v{}, {} records, {:.1}s",
+        header.server,
+        header.protocol_version,
+        recording.records.len(),
+        recording.duration_ms() as f64 / 1000.0
+    );
+    for record in recording.records.iter().filter(|r| filter.matches(r)) {
+        println!("{}", timeline::format_record(record));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_feed(args: &[String]) -> Result<ExitCode, String> {
+    let (recording, flags) = split_args(args)?;
+
+    let mut server: Option<SocketAddr> = None;
+    let mut options = FeedOptions::default();
+    let mut check = false;
+    for (flag, value) in flags {
+        match flag {
+            "--server" => {
+                server = value
+                    .to_socket_addrs()
+                    .map_err(|e| format!("cannot resolve {value}: {e}"))?
+                    .next()
+            }
+            "--speed" => options.speed = parse(flag, value)?,
+            "--password" => options.password = Some(value.to_string()),
+            "--check" => check = true,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let server = server.ok_or(USAGE)?;
+
+    if recording.header.protocol_version != protocol::PROTOCOL_VERSION {
+        eprintln!(
+            "warning: recorded with protocol v{}, this build speaks v{}",
+            recording.header.protocol_version,
+            protocol::PROTOCOL_VERSION
+        );
+    }
+
+    let fed = replay::feed(&recording, server, &options)
+        .map_err(|e| format!("feed to {server} failed: {e}"))?;
+    for conn in &fed {
+        println!(
+            "#{} -> live #{}: {} packets received",
+            conn.recorded_id,
+            conn.live_id.map_or("?".to_string(), |id| id.to_string()),
+            conn.received.len()
+        );
+    }
+
+    if !check {
+        return Ok(ExitCode::SUCCESS);
+    }
+    let mismatches = replay::compare(&recording, &fed, TIMING_DEPENDENT);
+    for mismatch in &mismatches {
+        println!("MISMATCH {mismatch}");
+    }
+    if mismatches.is_empty() {
+        println!("OK: server output matches the recording");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
@@ -0,0 +1,121 @@
+//! Текстовая лента записи с фильтрами.
+
+use protocol::PlayerId;
+use protocol::recording::{Entry, Record};
+
+/// Направление пакета.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+/// Какие записи показывать. Пустые поля не фильтруют.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub player: Option<PlayerId>,
+    /// Имена вариантов (`Snapshot`, `ChatMessage`, `close`...).
+    pub kinds: Vec<String>,
+    pub direction: Option<Direction>,
+    /// Миллисекунды от начала записи, включительно.
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+}
+
+impl Filter {
+    pub fn matches(&self, record: &Record) -> bool {
+        let direction = match record.entry {
+            Entry::In(_) | Entry::Closed => Direction::In,
+            Entry::Out(_) => Direction::Out,
+        };
+        self.player.is_none_or(|p| p == record.p)
+            && (self.kinds.is_empty() || self.kinds.iter().any(|k| k == record.entry.kind()))
+            && self.direction.is_none_or(|d| d == direction)
+            && self.from_ms.is_none_or(|from| record.t >= from)
+            && self.to_ms.is_none_or(|to| record.t <= to)
+    }
+}
+
+/// Строка ленты: `   12.345s  #3  -> ChatMessage {"text":"hi"}`.
+pub fn format_record(record: &Record) -> String {
+    let (arrow, body) = match &record.entry {
+        Entry::In(packet) => ("->", serde_json::to_value(packet).ok()),
+        Entry::Out(packet) => ("<-", serde_json::to_value(packet).ok()),
+        Entry::Closed => ("--", None),
+    };
+    // Внешний тег варианта уже в `kind`, печатаем только поля.
+    let fields = body
+        .and_then(|value| match value {
+            serde_json::Value::Object(map) => map.into_iter().next().map(|(_, v)| v),
+            _ => None,
+        })
+        .map(|v| format!(" {v}"))
+        .unwrap_or_default();
+
+    format!(
+        "{:>9.3}s  #{:<3} {arrow} {}{fields}",
+        record.t as f64 / 1000.0,
+        record.p,
+        record.entry.kind()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::{ClientPacket, ServerPacket};
+
+    use super::*;
+
+    fn record(t: u64, p: PlayerId, entry: Entry) -> Record {
+        Record { t, p, entry }
+    }
+
+    #[test]
+    fn filter_by_player_kind_direction_and_time() {
+        let chat_in = record(
+            1_000,
+            1,
+            Entry::In(ClientPacket::ChatMessage { text: "hi".into() }),
+        );
+        let chat_out = record(
+            1_010,
+            2,
+            Entry::Out(ServerPacket::ChatMessage {
+                player_id: 1,
+                text: "hi".into(),
+            }),
+        );
+        let closed = record(5_000, 1, Entry::Closed);
+
+        let by_player = Filter {
+            player: Some(1),
+            ..Filter::default()
+        };
+        assert!(by_player.matches(&chat_in) && !by_player.matches(&chat_out));
+
+        let by_kind = Filter {
+            kinds: vec!["close".into()],
+            ..Filter::default()
+        };
+        assert!(by_kind.matches(&closed) && !by_kind.matches(&chat_in));
+
+        let outgoing = Filter {
+            direction: Some(Direction::Out),
+            ..Filter::default()
+        };
+        assert!(outgoing.matches(&chat_out) && !outgoing.matches(&chat_in));
+
+        let window = Filter {
+            from_ms: Some(1_005),
+            to_ms: Some(2_000),
+            ..Filter::default()
+        };
+        assert!(window.matches(&chat_out) && !window.matches(&chat_in));
+        assert!(!window.matches(&closed));
+
+        assert_eq!(
+            format_record(&chat_out),
+            r#"    1.010s  #2   <- ChatMessage {"player_id":1,"text":"hi"}"#
+        );
+    }
+}
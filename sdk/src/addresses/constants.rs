@@ -1,36 +1,9 @@
 //! Игровые константы: ID оружия, машин, слотов, и т.д.
 
-/// ID оружия для `Player::add_weapon`.
+/// ID оружия для `Player::add_weapon` — из `common::weapons`, их же
+/// сверяет сервер.
 pub mod weapons {
-    // ПИСТОЛЕТЫ / РЕВОЛЬВЕРЫ
-    pub const EMPTY_HANDS: u32 = 1;
-    pub const MODEL_12_REVOLVER: u32 = 2; // ".38 MP2"
-    pub const MAUSER_C96: u32 = 3; // "Mauser C-96"
-    pub const COLT_M1911A1: u32 = 4; // "Colt 1911"
-    pub const COLT_M1911_SPECIAL: u32 = 5; // "Colt 1911 Ext"
-    pub const MODEL_19_REVOLVER: u32 = 6; // ".357 Magnum"
-
-    // ДРОБОВИКИ / ПП
-    pub const REMINGTON_870: u32 = 8; // "Remington 870"
-    pub const M3_GREASE_GUN: u32 = 9; // "M3 Grease gun"
-    pub const MP40: u32 = 10; // "MP 40"
-    pub const THOMPSON_1928: u32 = 11; // "Thompson 1928"
-    pub const M1A1_THOMPSON: u32 = 12; // "Thomson M1A1"
-    pub const BERETTA_38A: u32 = 13; // "Beretta 38A"
-
-    // ВИНТОВКИ / ТЯЖЁЛЫЕ
-    pub const M1_GARAND: u32 = 15; // "M1 Garand"
-    pub const KAR98K: u32 = 17; // "Mauser 98k"
-    pub const MG42: u32 = 14; // "MG42"
-    pub const KAR98K_SNIPER: u32 = 18; // "Mauser 98k sniper"
-    pub const BAZOOKA: u32 = 19; // "Bazooka"
-
-    // ГРАНАТЫ
-    pub const GRENADE_SICILY: u32 = 7; // "Grenade Sicily"
-    pub const MK2_FRAG_GRENADE: u32 = 20; // "Grenade MkII"
-    pub const MOLOTOV_COCKTAIL: u32 = 21; // "Molotov"
-    pub const STIELHANDGRANATE: u32 = 16; // "Stielhandgranate 24"
-    pub const TANK_EXP: u32 = 125; // "TankExp"
+    pub use common::weapons::*;
 }
 
 pub mod items {
@@ -47,14 +20,10 @@ pub mod items {
     pub const C4_CHARGE: u32 = 83; // "Charge"
     pub const C4_DETONATOR: u32 = 88; // "Roznetka"
 
-    // БЛИЖНИЙ БОЙ
-    pub const KNIFE: u32 = 22; // "Knife"
-    pub const KNUCKLES: u32 = 23; // "Knuckleduster"
-    pub const WRENCH: u32 = 24; // "Wrench"
-    pub const PIPE: u32 = 25; // "Pipe"
-    pub const CROWBAR: u32 = 27; // "Crowbar"
-    pub const BASEBALL_BAT: u32 = 32; // "Baseball bat"
-    pub const HAMMER: u32 = 116; // "kladivo"
+    // БЛИЖНИЙ БОЙ (и лопата из инструментов) — оружие, см. `weapons`
+    pub use common::weapons::{
+        BASEBALL_BAT, CROWBAR, HAMMER, KNIFE, KNUCKLES, PIPE, SHOVEL, WRENCH,
+    };
 
     // ЕДА / ПИТЬЁ / СИГАРЕТЫ
     pub const HOTDOG: u32 = 48; // "HotDog"
@@ -69,7 +38,6 @@ pub mod items {
 
     // ИНСТРУМЕНТЫ / БЫТОВУХА
     pub const GLASS_CUTTER: u32 = 96; // "glass-cutter"
-    pub const SHOVEL: u32 = 34; // "Shovel"
 }
 
 /// Индексы слотов инвентаря.
@@ -61,9 +61,13 @@
 //! - Инициализация: `sub_141008230` -> `sub_140E767E0` -> `sub_140E75CC0` -> `sub_140E7D020`
 //! - Runtime скан подтвердил все индексы (значения 61..72 в ожидаемых позициях)
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use super::base;
 use crate::{addresses, memory};
+use common::interp::Vec3;
 use common::logger;
+use common::projection;
 
 use addresses::constants::camera_params;
 use addresses::fields::{camera_manager as cm, camera_view as cv};
@@ -368,6 +372,57 @@ pub fn set_car_camera_param(params_offset: usize, param_index: usize, value: f32
     unsafe { memory::write(addr, value) }
 }
 
+// =============================================================================
+//  Текущий кадр
+// =============================================================================
+
+/// Поза камеры из Lua. Матрица `C_Camera` в памяти ещё не отреверсена,
+/// а `GetPos`/`GetDir` главной камеры игрока отдаёт скрипт-API.
+const CURRENT_VIEW_LUA: &str = r#"
+local cam = game.cameramanager:GetPlayerMainCamera(0)
+if cam == nil then return nil end
+local p, d = cam:GetPos(), cam:GetDir()
+return string.format("%f %f %f %f %f %f", p.x, p.y, p.z, d.x, d.y, d.z)
+"#;
+
+/// Lua-путь упал — больше не пробуем (и не спамим лог каждый кадр).
+static CURRENT_VIEW_BROKEN: AtomicBool = AtomicBool::new(false);
+
+/// Камера текущего кадра для проекции на экран — приближение, а не
+/// матрицы движка.
+///
+/// ⚠️ Известное ограничение: view/projection-матрицы `C_Camera` не
+/// отреверсены. Поза — из Lua `GetPos`/`GetDir`, FOV — Exterier default.
+/// Зум прицела, катсцены и интерьеры меняют FOV мимо этого значения —
+/// проекция там смещается. Вызывать только из main thread (Lua).
+pub fn current_view() -> Option<projection::Camera> {
+    if CURRENT_VIEW_BROKEN.load(Ordering::Relaxed) {
+        return None;
+    }
+    let fov_y_deg = get_exterier_fov().filter(|f| is_valid_fov(*f))?;
+    let text = match super::lua::eval_chunk_named(CURRENT_VIEW_LUA, "=m2mp_camera") {
+        Ok(Some(text)) => text,
+        Ok(None) => return None,
+        Err(e) => {
+            CURRENT_VIEW_BROKEN.store(true, Ordering::Relaxed);
+            logger::warn(&format!("[camera] поза камеры недоступна: {e}"));
+            return None;
+        }
+    };
+    let v: Vec<f32> = text
+        .split_whitespace()
+        .filter_map(|s| s.parse().ok())
+        .collect();
+    let [px, py, pz, dx, dy, dz] = v[..] else {
+        return None;
+    };
+    Some(projection::Camera {
+        position: Vec3::new(px, py, pz),
+        forward: Vec3::new(dx, dy, dz),
+        fov_y_deg,
+    })
+}
+
 // =============================================================================
 //  Диагностика
 // =============================================================================
@@ -0,0 +1,44 @@
+//! Время суток и погода.
+//!
+//! Обе настройки живут в `M2DE_g_GfxEnvEffSystem`
+//! ([`addresses::globals::GFX_ENV_EFF_SYSTEM`](crate::addresses::globals::GFX_ENV_EFF_SYSTEM)),
+//! но раскладка `WeatherSystem` / `DateTimeBuffers` не разобрана, поэтому
+//! меняем их через Lua-биндинги `game.gfx`, как скрипты freeride.
+//!
+//! Вызывать с game thread, когда [`lua::is_ready`] — Lua VM не
+//! потокобезопасна.
+
+use super::lua;
+
+/// Смена шаблона погоды (`DT_...` из SDS погоды). Подтверждён в devtools
+/// (`init_map`).
+const SET_WEATHER_TEMPLATE: &str = "game.gfx:SetWeatherTemplate";
+
+/// Час суток. ⚠️ Имя и аргумент (час как float) взяты из скриптов, в IDA
+/// не проверены — ошибка Lua возвращается вызывающему.
+const SET_DAY_TIME: &str = "game.gfx:SetDayTime";
+
+/// Допустимо ли имя шаблона: подставляется в Lua-строку как есть.
+pub fn is_valid_weather_template(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Выставить шаблон погоды.
+pub fn set_weather_template(name: &str) -> Result<(), String> {
+    if !is_valid_weather_template(name) {
+        return Err(format!("недопустимое имя шаблона погоды: {name:?}"));
+    }
+    lua::exec_named(
+        &format!("{SET_WEATHER_TEMPLATE}(\"{name}\")"),
+        "=m2mp_weather",
+    )
+}
+
+/// Выставить время суток: `hour` в `[0, 24)`.
+pub fn set_time_of_day(hour: f32) -> Result<(), String> {
+    if !hour.is_finite() {
+        return Err(format!("недопустимый час: {hour}"));
+    }
+    let hour = hour.rem_euclid(24.0);
+    lua::exec_named(&format!("{SET_DAY_TIME}({hour:.3})"), "=m2mp_daytime")
+}
@@ -14,6 +14,7 @@ pub mod car;
 pub mod entity;
 pub mod entity_ref;
 pub mod entity_types;
+pub mod environment;
 pub mod game_input_module;
 mod hash;
 pub mod lua;
@@ -12,6 +12,7 @@
 //! native функции по RVA через `memory::fn_at()`.
 
 use crate::game::{
+    car::Car,
     entity,
     entity_ref::EntityRef,
     entity_types::{EntityType, FactoryType},
@@ -201,6 +202,120 @@ impl Npc {
         true
     }
 
+    // =========================================================================
+    //  Оружие (через Lua wrapper)
+    // =========================================================================
+
+    /// Выполнить `code` с `human` = Lua wrapper этого NPC.
+    ///
+    /// Native-выбора оружия для NPC нет (holster у NPC пишется прямо в
+    /// behavior, см. `human_messages::HOLSTER_WEAPON`), поэтому идём через
+    /// скрипт-API. Вызывать только из game thread.
+    fn exec_on_wrapper(&self, code: &str, chunk_name: &str) -> bool {
+        let Some(guid) = self.entity.table_id() else {
+            return false;
+        };
+        let chunk = format!(
+            "local human = game.entitywrapper:GetEntityByGUID({guid})\n\
+             if human == nil then return end\n{code}"
+        );
+        match super::lua::exec_named(&chunk, chunk_name) {
+            Ok(()) => true,
+            Err(e) => {
+                logger::warn(&format!("[npc] {}: {chunk_name}: {e}", self.name));
+                false
+            }
+        }
+    }
+
+    /// Взять в руки оружие `weapon_id` (должно быть в инвентаре, см.
+    /// `add_weapon`). `constants::weapons::EMPTY_HANDS` — убрать оружие.
+    pub fn select_weapon(&self, weapon_id: u32) -> bool {
+        self.exec_on_wrapper(
+            &format!("human:InventorySelect({weapon_id}, true)"),
+            "=m2mp_npc_select",
+        )
+    }
+
+    /// Убрать оружие в кобуру.
+    pub fn holster_weapon(&self) -> bool {
+        self.select_weapon(crate::addresses::constants::weapons::EMPTY_HANDS)
+    }
+
+    /// Перезарядить оружие в руках.
+    ///
+    /// ⚠️ Имя метода wrapper'а не подтверждено reverse'ом — при ошибке
+    /// `exec_on_wrapper` пишет warn, вызывающему стоит перестать пробовать.
+    pub fn reload_weapon(&self) -> bool {
+        self.exec_on_wrapper("human:InventoryReload()", "=m2mp_npc_reload")
+    }
+
+    /// Выстрелить из оружия в руках в точку `target`.
+    ///
+    /// Пуля настоящая: попадает и ранит как выстрел NPC. Вызывающий сам
+    /// отбрасывает урон, которого быть не должно (DAMAGE в hook'е рассылки).
+    /// ⚠️ Метод wrapper'а не подтверждён reverse'ом (см. `reload_weapon`).
+    pub fn fire_weapon(&self, target: &Vec3) -> bool {
+        self.exec_on_wrapper(
+            &format!(
+                "human:ShootAt(Math:newVector({:.3}, {:.3}, {:.3}))",
+                target.x, target.y, target.z
+            ),
+            "=m2mp_npc_fire",
+        )
+    }
+
+    /// Сменить модель NPC (`/sds/player/<model>.sds`) и вариант одежды.
+    ///
+    /// `model` уходит в Lua как есть — вызывающий проверяет имя
+    /// (`protocol::NetAppearance::is_valid`). Неизвестная модель даёт
+    /// ошибку скрипта, NPC остаётся в прежнем виде.
+    /// ⚠️ Метод wrapper'а не подтверждён reverse'ом (см. `reload_weapon`).
+    pub fn set_model(&self, model: &str, appearance_id: Option<u32>) -> bool {
+        let code = match appearance_id {
+            Some(id) => format!("human:SetModel(\"{model}\", {id})"),
+            None => format!("human:SetModel(\"{model}\")"),
+        };
+        self.exec_on_wrapper(&code, "=m2mp_npc_model")
+    }
+
+    // =========================================================================
+    //  Транспорт (через Lua wrapper)
+    // =========================================================================
+
+    /// Машина, в которой сидит NPC (`CHuman.actor.owner`, как у
+    /// `Player::get_vehicle_ptr`).
+    pub fn vehicle_ptr(&self) -> Option<usize> {
+        let owner = unsafe { self.human()?.actor.owner as usize };
+        crate::memory::is_valid_ptr(owner).then_some(owner)
+    }
+
+    /// Сесть в машину `car` на место `seat` (0 — водитель), сразу, без
+    /// подхода к двери.
+    ///
+    /// ⚠️ Метод wrapper'а не подтверждён reverse'ом (см. `reload_weapon`);
+    /// сел ли NPC на самом деле — проверять по `vehicle_ptr`.
+    pub fn enter_vehicle(&self, car: &Car, seat: u8) -> bool {
+        let Some(car_guid) = car.get_table_id() else {
+            return false;
+        };
+        self.exec_on_wrapper(
+            &format!(
+                "local car = game.entitywrapper:GetEntityByGUID({car_guid})\n\
+                 if car == nil then return end\n\
+                 human:GetInCar(car, {seat}, true)"
+            ),
+            "=m2mp_npc_enter",
+        )
+    }
+
+    /// Выйти из машины.
+    ///
+    /// ⚠️ Метод wrapper'а не подтверждён reverse'ом (см. `enter_vehicle`).
+    pub fn leave_vehicle(&self) -> bool {
+        self.exec_on_wrapper("human:GetOutCar(true)", "=m2mp_npc_leave")
+    }
+
     /// Вывести инфо в лог.
     pub fn log_info(&self) {
         let pos = self.get_position().unwrap_or_default();
@@ -2,6 +2,7 @@
 
 use crate::addresses;
 use crate::memory;
+use crate::structures::{CHuman, DamageMessage, DamageTemplate};
 use common::logger;
 
 use super::{Player, base};
@@ -72,6 +73,62 @@ impl Player {
         unsafe { self.human().map(|h| h.is_alive()) }
     }
 
+    /// Снять флаг смерти и восстановить здоровье.
+    ///
+    /// Движок не умеет «оживлять» сам: после смерти `CHuman.is_dead`
+    /// остаётся выставленным. Сбрасываем его и выдаём полное здоровье —
+    /// вызывать до телепорта на точку респауна.
+    pub fn revive(&self) -> bool {
+        if !self.ptr.is_valid() {
+            return false;
+        }
+        unsafe {
+            (&raw mut (*self.ptr.raw()).base.is_dead).write(0);
+        }
+        self.heal_full()
+    }
+
+    /// Убить игрока через скрипт-API: смерть с анимацией и `DEATH`,
+    /// как от урона движка (`set_health(0.0)` её не запускает).
+    ///
+    /// ⚠️ Метод wrapper'а не подтверждён reverse'ом — при ошибке скрипта
+    /// возвращает `false`, вызывающий решает, чем заменить.
+    /// Вызывать только из game thread.
+    pub fn kill(&self) -> bool {
+        if !self.ptr.is_valid() {
+            return false;
+        }
+        match crate::game::lua::exec_named(
+            "game.game:GetActivePlayer():Kill()",
+            "=m2mp_player_kill",
+        ) {
+            Ok(()) => true,
+            Err(e) => {
+                logger::warn(&format!("[player] kill: {e}"));
+                false
+            }
+        }
+    }
+
+    /// Нанести урон через движковый `C_Human::ProcessDamage` (vtable[82]):
+    /// здоровье, реакция и смерть с `DEATH` — как от настоящей пули.
+    /// Неуязвимость и полубог соблюдаются движком.
+    ///
+    /// `message` — DAMAGE-сообщение из [`DamageTemplate`], со своим уроном.
+    ///
+    /// ⚠️ Полный размер объекта сообщения reverse'ом не подтверждён (см.
+    /// [`DamageTemplate`]). Вызывать только из game thread.
+    pub fn process_damage(&self, message: &mut DamageTemplate) -> bool {
+        if !self.ptr.is_valid() {
+            return false;
+        }
+        type ProcessDamageFn = unsafe extern "C" fn(*mut CHuman, *mut DamageMessage) -> u8;
+        let process: ProcessDamageFn =
+            unsafe { memory::fn_at(base() + addresses::functions::human::PROCESS_DAMAGE) };
+        unsafe { process(&raw mut (*self.ptr.raw()).base, message.as_mut_ptr()) };
+        true
+    }
+
     /// Флаг неуязвимости.
     pub fn is_invulnerable(&self) -> Option<bool> {
         unsafe { self.human().map(|h| h.is_invulnerable()) }
@@ -118,6 +118,28 @@ impl Player {
     pub fn get_forward_vector(&self) -> Option<Vec3> {
         self.get_forward()
     }
+
+    /// Установить вращение через vtable[34] SetRot. Кватернион `[x, y, z, w]`.
+    pub fn set_rotation(&self, quat: &[f32; 4]) -> bool {
+        if !quat.iter().all(|c| c.is_finite()) {
+            return false;
+        }
+        unsafe {
+            let Some(vt) = self.vtable() else {
+                return false;
+            };
+            (vt.set_rot)(self.this_mut(), quat);
+        }
+        true
+    }
+
+    /// Развернуть персонажа по курсу: градусы по часовой стрелке от +Y.
+    ///
+    /// Поворот вокруг оси Z (вверх) на `-heading`.
+    pub fn set_heading(&self, heading_deg: f32) -> bool {
+        let half = -heading_deg.to_radians() * 0.5;
+        self.set_rotation(&[0.0, 0.0, half.sin(), half.cos()])
+    }
 }
 
 // =============================================================================
@@ -1,5 +1,7 @@
 //! Транспорт.
 
+use common::logger;
+
 use crate::game::car::Car;
 use crate::memory;
 
@@ -36,4 +38,29 @@ impl Player {
     pub fn get_vehicle_as_car(&self) -> Option<Car> {
         Car::from_ptr(self.get_vehicle_ptr()?)
     }
-}
\ No newline at end of file
+
+    /// Место игрока в машине (0 — водитель).
+    ///
+    /// ⚠️ `GetOwner` / `GetSeatIdx` wrapper'а не подтверждены reverse'ом —
+    /// при ошибке скрипта `None`, вызывающий решает, чем заменить.
+    /// Вызывать только из game thread.
+    pub fn get_vehicle_seat(&self) -> Option<u8> {
+        if !self.ptr.is_valid() {
+            return None;
+        }
+        match crate::game::lua::eval_chunk_named(VEHICLE_SEAT_LUA, "=m2mp_player_seat") {
+            Ok(text) => text?.trim().parse().ok(),
+            Err(e) => {
+                logger::warn(&format!("[player] vehicle seat: {e}"));
+                None
+            }
+        }
+    }
+}
+
+const VEHICLE_SEAT_LUA: &str = r#"
+local human = game.game:GetActivePlayer()
+local car = human:GetOwner()
+if car == nil then return nil end
+return tostring(car:GetSeatIdx(human))
+"#;
@@ -85,6 +85,45 @@ pub struct DamageMessage {
     pub payload: DamageMessagePayload,
 }
 
+/// Своё DAMAGE-сообщение, собранное по подтверждённому layout'у.
+///
+/// С нуля его не собрать: тип движок берёт по vtable, адреса которой у нас
+/// нет. Поэтому из живого сообщения берём только поля [`DamageMessage`]
+/// (0x30 байт; vtable — статическая, не владеющая), payload ставим свой.
+/// Сколько весит объект сообщения целиком, reverse'ом не подтверждено —
+/// за ним идёт обнулённый запас, чтобы чтение хвоста движком не попало в
+/// чужие данные стека.
+#[repr(C, align(8))]
+#[derive(Clone, Copy)]
+pub struct DamageTemplate {
+    message: DamageMessage,
+    _reserve: [u8; 0x50],
+}
+
+impl DamageTemplate {
+    /// Заготовка по живому DAMAGE-сообщению.
+    pub fn from_message(message: &DamageMessage) -> Self {
+        Self {
+            message: *message,
+            _reserve: [0; 0x50],
+        }
+    }
+
+    /// Сообщение с нашим уроном, оружием и зоной попадания.
+    pub fn with_damage(&self, weapon_type: u32, damage: f32, body_part: u32) -> Self {
+        let mut copy = *self;
+        copy.message.payload.weapon_type = weapon_type;
+        copy.message.payload.damage_amount = damage;
+        copy.message.payload.body_part = body_part;
+        copy
+    }
+
+    /// Указатель на сообщение для движковых обработчиков.
+    pub fn as_mut_ptr(&mut self) -> *mut DamageMessage {
+        &raw mut self.message
+    }
+}
+
 /// Payload DEATH-сообщения (0xD0014).
 ///
 /// Подтверждено по M2DE_HumanEntity_ProcessDeath.
@@ -175,6 +214,10 @@ assert_field_offsets!(DamageMessage {
     payload == 0x20,
 });
 
+const _: () = {
+    assert!(std::mem::size_of::<DamageMessage>() == 0x30);
+};
+
 assert_layout!(DeathMessagePayload, size = 0x1C, {
     pos_x            == 0x00,
     pos_y            == 0x04,
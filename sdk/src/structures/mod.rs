@@ -71,8 +71,8 @@ pub use callbacks::{
 };
 
 pub use messages::{
-    DamageMessage, DamageMessagePayload, DeathMessage, DeathMessagePayload, EntityMessageHeader,
-    StanceMessage, StanceMessagePayload, WeaponMessage, WeaponMessagePayload,
+    DamageMessage, DamageMessagePayload, DamageTemplate, DeathMessage, DeathMessagePayload,
+    EntityMessageHeader, StanceMessage, StanceMessagePayload, WeaponMessage, WeaponMessagePayload,
 };
 
 pub use police_script_owner::{PoliceScriptOwner, PoliceScriptOwnerNode};
@@ -0,0 +1,52 @@
+//! Внешний вид игроков (модель и одежда).
+//!
+//! Клиент сообщает свой вид после входа и при каждой смене
+//! (`ClientPacket::Appearance`), сервер хранит последний и раздаёт его
+//! соседям по измерению. Новичок и сменивший измерение получают вид уже
+//! известных игроков сразу после их `PlayerSpawn`.
+
+use common::logger;
+use protocol::{NetAppearance, PlayerId, ServerPacket};
+
+use crate::SharedServer;
+
+/// Обработать `ClientPacket::Appearance` от `player_id`.
+pub fn handle(shared: &SharedServer, player_id: PlayerId, appearance: NetAppearance) {
+    if !appearance.is_valid() {
+        logger::warn(&format!(
+            "[appearance] player {player_id} sent invalid model {:?}",
+            appearance.model
+        ));
+        return;
+    }
+
+    let Ok(mut appearances) = shared.appearances.lock() else {
+        return;
+    };
+    if appearances.get(&player_id) == Some(&appearance) {
+        return;
+    }
+    logger::info(&format!(
+        "[appearance] player {player_id} model={} appearance={:?}",
+        appearance.model, appearance.appearance_id
+    ));
+    appearances.insert(player_id, appearance.clone());
+    drop(appearances);
+
+    shared.broadcast_dimension(
+        player_id,
+        ServerPacket::PlayerAppearance {
+            player_id,
+            appearance,
+        },
+    );
+}
+
+/// `PlayerAppearance` игрока `player_id`, если он уже сообщал свой вид.
+pub fn packet(shared: &SharedServer, player_id: PlayerId) -> Option<ServerPacket> {
+    let appearance = shared.appearances.lock().ok()?.get(&player_id).cloned()?;
+    Some(ServerPacket::PlayerAppearance {
+        player_id,
+        appearance,
+    })
+}
@@ -0,0 +1,780 @@
+//! Попадания, атрибуция убийств и счёт K/D.
+//!
+//! Выстрел (`NetPlayerEvent::ShotFired`) рассылается соседям, только если
+//! сходится с последним snapshot'ом стрелка: он жив, держит это оружие и
+//! стреляет рядом с собой (`check_shot`).
+//!
+//! Клиент стрелка сообщает о попадании в proxy (`ClientPacket::Hit`).
+//! Сервер проверяет заявку (стрелок жив и по snapshot'у держит заявленное
+//! оружие, урон не выше потолка этого оружия — `max_hit_damage`),
+//! запоминает последнее попадание по жертве и отправляет жертве `ServerPacket::Damage` — урон от других игроков
+//! клиент применяет только по нему.
+//! Когда жертва сама сообщает о смерти (event `Death` / `is_dead` в snapshot),
+//! убийство засчитывается последнему стрелку, если он попал не раньше
+//! `KILL_CREDIT_WINDOW` назад.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use common::logger;
+use common::weapons;
+use protocol::{NetPlayerSnapshot, NetVec3, PlayerId, ServerPacket};
+
+use crate::SharedServer;
+
+/// Сколько попадание даёт право на фраг.
+pub const KILL_CREDIT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Максимальный урон одного попадания (базука по голове с запасом).
+pub const MAX_HIT_DAMAGE: f32 = 1_000.0;
+
+/// Потолок урона одного попадания из оружия `weapon_id` — с запасом на
+/// голову. `None` — такого оружия нет.
+pub fn max_hit_damage(weapon_id: u32) -> Option<f32> {
+    let cap = match weapon_id {
+        weapons::EMPTY_HANDS | weapons::KNUCKLES => 100.0,
+        weapons::KNIFE => 300.0,
+        weapons::WRENCH
+        | weapons::PIPE
+        | weapons::CROWBAR
+        | weapons::BASEBALL_BAT
+        | weapons::SHOVEL
+        | weapons::HAMMER => 250.0,
+        weapons::MODEL_12_REVOLVER
+        | weapons::MAUSER_C96
+        | weapons::COLT_M1911A1
+        | weapons::COLT_M1911_SPECIAL
+        | weapons::MODEL_19_REVOLVER => 400.0,
+        // Вся дробь одним сообщением.
+        weapons::REMINGTON_870 => 800.0,
+        weapons::M3_GREASE_GUN
+        | weapons::MP40
+        | weapons::THOMPSON_1928
+        | weapons::M1A1_THOMPSON
+        | weapons::BERETTA_38A => 300.0,
+        weapons::MG42 => 400.0,
+        weapons::M1_GARAND | weapons::KAR98K => 600.0,
+        weapons::KAR98K_SNIPER => 800.0,
+        weapons::BAZOOKA
+        | weapons::GRENADE_SICILY
+        | weapons::STIELHANDGRANATE
+        | weapons::MK2_FRAG_GRENADE
+        | weapons::MOLOTOV_COCKTAIL => MAX_HIT_DAMAGE,
+        _ => return None,
+    };
+    Some(cap)
+}
+
+/// Дальше этого расстояния попадание считаем подделкой.
+pub const MAX_HIT_DISTANCE_M: f32 = 400.0;
+
+/// Дальше этого от позиции в последнем snapshot'е выстрел не выпустить:
+/// ствол над ступнями плюс бег между snapshot'ами.
+pub const MAX_SHOT_ORIGIN_OFFSET_M: f32 = 6.0;
+
+/// Допуск на длину `direction` выстрела (должна быть единичной).
+const DIRECTION_LENGTH_TOLERANCE: f32 = 0.05;
+
+/// Почему выстрел не разослан.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShotRejected {
+    /// Snapshot'а стрелка ещё не было — сверять не с чем.
+    NoSnapshot,
+    ShooterDead,
+    /// В последнем snapshot'е в руках другое оружие (или ничего).
+    WrongWeapon,
+    OriginTooFar,
+    BadDirection,
+}
+
+impl ShotRejected {
+    /// Метка для метрик.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NoSnapshot => "no_snapshot",
+            Self::ShooterDead => "shooter_dead",
+            Self::WrongWeapon => "wrong_weapon",
+            Self::OriginTooFar => "origin_too_far",
+            Self::BadDirection => "bad_direction",
+        }
+    }
+}
+
+/// Сверить выстрел с последним snapshot'ом стрелка.
+pub fn validate_shot(
+    snapshot: Option<&NetPlayerSnapshot>,
+    weapon_id: u32,
+    origin: NetVec3,
+    direction: NetVec3,
+) -> Result<(), ShotRejected> {
+    let Some(snapshot) = snapshot else {
+        return Err(ShotRejected::NoSnapshot);
+    };
+    if snapshot.is_dead {
+        return Err(ShotRejected::ShooterDead);
+    }
+    if snapshot.weapon_id != Some(weapon_id) {
+        return Err(ShotRejected::WrongWeapon);
+    }
+    let p = snapshot.position;
+    let (dx, dy, dz) = (origin.x - p.x, origin.y - p.y, origin.z - p.z);
+    let offset_sq = dx * dx + dy * dy + dz * dz;
+    if !offset_sq.is_finite() || offset_sq > MAX_SHOT_ORIGIN_OFFSET_M * MAX_SHOT_ORIGIN_OFFSET_M {
+        return Err(ShotRejected::OriginTooFar);
+    }
+    let length =
+        (direction.x * direction.x + direction.y * direction.y + direction.z * direction.z).sqrt();
+    if !length.is_finite() || (length - 1.0).abs() > DIRECTION_LENGTH_TOLERANCE {
+        return Err(ShotRejected::BadDirection);
+    }
+    Ok(())
+}
+
+/// Заявка о попадании после подстановки стрелка сервером.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitReport {
+    pub attacker: PlayerId,
+    pub victim: PlayerId,
+    pub weapon_id: u32,
+    pub damage: f32,
+    pub headshot: bool,
+}
+
+/// Почему заявка о попадании отклонена.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitRejected {
+    SelfHit,
+    /// Стрелка нет в бою (не в игре) или от него ещё не было snapshot'а.
+    UnknownAttacker,
+    AttackerDead,
+    UnknownVictim,
+    VictimDead,
+    UnknownWeapon,
+    /// В последнем snapshot'е у стрелка в руках другое оружие.
+    WrongWeapon,
+    /// Урон не число, не положительный или выше потолка оружия.
+    BadDamage,
+    TooFar,
+    /// Попадание по союзнику при выключенном friendly fire.
+    FriendlyFire,
+    /// Жертва в другом измерении — стрелок её не видит.
+    OtherDimension,
+}
+
+impl HitRejected {
+    /// Метка для метрик.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::SelfHit => "self_hit",
+            Self::UnknownAttacker => "unknown_attacker",
+            Self::AttackerDead => "attacker_dead",
+            Self::UnknownVictim => "unknown_victim",
+            Self::VictimDead => "victim_dead",
+            Self::UnknownWeapon => "unknown_weapon",
+            Self::WrongWeapon => "wrong_weapon",
+            Self::BadDamage => "bad_damage",
+            Self::TooFar => "too_far",
+            Self::FriendlyFire => "friendly_fire",
+            Self::OtherDimension => "other_dimension",
+        }
+    }
+}
+
+/// Кто убил и чем.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KillCredit {
+    pub killer: PlayerId,
+    pub weapon_id: u32,
+    pub headshot: bool,
+}
+
+/// Счёт игрока в раунде.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LastHit {
+    credit: KillCredit,
+    at: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct CombatLog {
+    last_hits: HashMap<PlayerId, LastHit>,
+    dead: HashSet<PlayerId>,
+    scores: HashMap<PlayerId, Score>,
+}
+
+impl CombatLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_player(&mut self, player_id: PlayerId) {
+        self.scores.insert(player_id, Score::default());
+    }
+
+    pub fn remove_player(&mut self, player_id: PlayerId) {
+        self.scores.remove(&player_id);
+        self.dead.remove(&player_id);
+        self.last_hits.remove(&player_id);
+        self.last_hits
+            .retain(|_, hit| hit.credit.killer != player_id);
+    }
+
+    pub fn is_dead(&self, player_id: PlayerId) -> bool {
+        self.dead.contains(&player_id)
+    }
+
+    /// Проверить заявку. `attacker` — последний snapshot стрелка (его
+    /// позиция и оружие в руках), `victim_pos` — из snapshot'а жертвы.
+    ///
+    /// `friendly` — стрелок и жертва в одной команде, а friendly fire
+    /// правилами выключен (`Teams::blocks_hit`).
+    pub fn validate(
+        &self,
+        report: &HitReport,
+        attacker: Option<&NetPlayerSnapshot>,
+        victim_pos: Option<NetVec3>,
+        friendly: bool,
+    ) -> Result<(), HitRejected> {
+        if report.attacker == report.victim {
+            return Err(HitRejected::SelfHit);
+        }
+        if friendly {
+            return Err(HitRejected::FriendlyFire);
+        }
+        let Some(attacker) = attacker.filter(|_| self.scores.contains_key(&report.attacker)) else {
+            return Err(HitRejected::UnknownAttacker);
+        };
+        if attacker.is_dead || self.is_dead(report.attacker) {
+            return Err(HitRejected::AttackerDead);
+        }
+        if !self.scores.contains_key(&report.victim) {
+            return Err(HitRejected::UnknownVictim);
+        }
+        if self.is_dead(report.victim) {
+            return Err(HitRejected::VictimDead);
+        }
+        let Some(cap) = max_hit_damage(report.weapon_id) else {
+            return Err(HitRejected::UnknownWeapon);
+        };
+        if attacker.weapon_id.unwrap_or(weapons::EMPTY_HANDS) != report.weapon_id {
+            return Err(HitRejected::WrongWeapon);
+        }
+        if !report.damage.is_finite() || report.damage <= 0.0 || report.damage > cap {
+            return Err(HitRejected::BadDamage);
+        }
+        if let Some(v) = victim_pos {
+            let a = attacker.position;
+            let dx = a.x - v.x;
+            let dy = a.y - v.y;
+            let dz = a.z - v.z;
+            if dx * dx + dy * dy + dz * dz > MAX_HIT_DISTANCE_M * MAX_HIT_DISTANCE_M {
+                return Err(HitRejected::TooFar);
+            }
+        }
+        Ok(())
+    }
+
+    /// Запомнить принятое попадание.
+    pub fn record_hit(&mut self, report: &HitReport, now: Instant) {
+        self.last_hits.insert(
+            report.victim,
+            LastHit {
+                credit: KillCredit {
+                    killer: report.attacker,
+                    weapon_id: report.weapon_id,
+                    headshot: report.headshot,
+                },
+                at: now,
+            },
+        );
+    }
+
+    /// Зарегистрировать смерть. `None` — повторный сигнал о той же смерти.
+    ///
+    /// Иначе `Some(credit)`, где `credit` — убийца, если он есть.
+    /// Счёт обновляется только при `tally = true` (раунд идёт).
+    pub fn on_death(
+        &mut self,
+        victim: PlayerId,
+        now: Instant,
+        tally: bool,
+    ) -> Option<Option<KillCredit>> {
+        if !self.scores.contains_key(&victim) || !self.dead.insert(victim) {
+            return None;
+        }
+
+        let credit = self
+            .last_hits
+            .remove(&victim)
+            .filter(|hit| now.duration_since(hit.at) <= KILL_CREDIT_WINDOW)
+            .map(|hit| hit.credit)
+            .filter(|credit| self.scores.contains_key(&credit.killer));
+
+        if tally {
+            if let Some(score) = self.scores.get_mut(&victim) {
+                score.deaths += 1;
+            }
+            if let Some(credit) = credit
+                && let Some(score) = self.scores.get_mut(&credit.killer)
+            {
+                score.kills += 1;
+            }
+        }
+
+        Some(credit)
+    }
+
+    /// Игрок снова жив (snapshot с `is_dead = false`).
+    pub fn on_alive(&mut self, player_id: PlayerId) {
+        self.dead.remove(&player_id);
+    }
+
+    pub fn score(&self, player_id: PlayerId) -> Score {
+        self.scores.get(&player_id).copied().unwrap_or_default()
+    }
+
+    pub fn scores(&self) -> Vec<(PlayerId, Score)> {
+        let mut all: Vec<_> = self.scores.iter().map(|(id, s)| (*id, *s)).collect();
+        all.sort_unstable_by_key(|(id, _)| *id);
+        all
+    }
+
+    /// Забыть попадания игрока и по нему: он ушёл в другое измерение.
+    pub fn forget_hits(&mut self, player_id: PlayerId) {
+        self.last_hits
+            .retain(|victim, hit| *victim != player_id && hit.credit.killer != player_id);
+    }
+
+    /// Обнулить счёт участников нового раунда.
+    pub fn reset_scores(&mut self, players: &[PlayerId]) {
+        for (id, score) in &mut self.scores {
+            if players.contains(id) {
+                *score = Score::default();
+            }
+        }
+        self.last_hits.retain(|victim, _| !players.contains(victim));
+    }
+}
+
+/// Проверить `ShotFired` от `shooter` перед рассылкой.
+///
+/// `false` — выстрел отклонён (метрика и warn уже записаны).
+pub fn check_shot(
+    shared: &SharedServer,
+    shooter: PlayerId,
+    weapon_id: u32,
+    origin: NetVec3,
+    direction: NetVec3,
+) -> bool {
+    let snapshot = shared.get_snapshot(shooter);
+    match validate_shot(snapshot.as_ref(), weapon_id, origin, direction) {
+        Ok(()) => true,
+        Err(reason) => {
+            shared.metrics.shot_rejected(reason.label());
+            logger::warn(&format!(
+                "[combat] rejected shot of {} weapon={}: {:?}",
+                shooter, weapon_id, reason
+            ));
+            false
+        }
+    }
+}
+
+/// Обработать `ClientPacket::Hit` от `attacker`.
+pub fn handle_hit(shared: &SharedServer, report: HitReport) {
+    if !shared.same_dimension(report.attacker, report.victim) {
+        shared
+            .metrics
+            .hit_rejected(HitRejected::OtherDimension.label());
+        logger::warn(&format!(
+            "[combat] rejected hit {} -> {}: {:?}",
+            report.attacker,
+            report.victim,
+            HitRejected::OtherDimension
+        ));
+        return;
+    }
+
+    let attacker = shared.get_snapshot(report.attacker);
+    let victim_pos = shared.get_snapshot(report.victim).map(|s| s.position);
+    let friendly = shared
+        .teams
+        .lock()
+        .is_ok_and(|t| t.blocks_hit(report.attacker, report.victim));
+
+    let verdict = {
+        let Ok(mut combat) = shared.combat.lock() else {
+            return;
+        };
+        let verdict = combat.validate(&report, attacker.as_ref(), victim_pos, friendly);
+        if verdict.is_ok() {
+            combat.record_hit(&report, Instant::now());
+        }
+        verdict
+    };
+
+    match verdict {
+        Ok(()) => {
+            logger::debug(&format!(
+                "[combat] hit {} -> {} weapon={} dmg={:.1}{}",
+                report.attacker,
+                report.victim,
+                report.weapon_id,
+                report.damage,
+                if report.headshot { " (head)" } else { "" }
+            ));
+            shared.send_to(
+                report.victim,
+                ServerPacket::Damage {
+                    attacker_id: report.attacker,
+                    weapon_id: report.weapon_id,
+                    damage: report.damage,
+                    headshot: report.headshot,
+                },
+            );
+        }
+        Err(reason) => {
+            shared.metrics.hit_rejected(reason.label());
+            logger::warn(&format!(
+                "[combat] rejected hit {} -> {}: {:?}",
+                report.attacker, report.victim, reason
+            ));
+        }
+    }
+}
+
+/// Зарегистрировать смерть, разослать kill feed и новый счёт.
+pub fn handle_death(shared: &SharedServer, victim: PlayerId, now: Instant) {
+    let Some(dimension) = shared.dimensions.lock().ok().and_then(|d| d.get(victim)) else {
+        return;
+    };
+    let tally = shared.mode.lock().is_ok_and(|m| m.is_scoring(dimension));
+
+    let (credit, victim_score, killer_score) = {
+        let Ok(mut combat) = shared.combat.lock() else {
+            return;
+        };
+        let Some(credit) = combat.on_death(victim, now, tally) else {
+            return;
+        };
+        let killer_score = credit.map(|c| (c.killer, combat.score(c.killer)));
+        (credit, combat.score(victim), killer_score)
+    };
+
+    logger::info(&format!(
+        "[combat] player {victim} killed by {:?}",
+        credit.map(|c| c.killer)
+    ));
+
+    shared.broadcast_to_dimension(
+        dimension,
+        ServerPacket::Kill {
+            killer_id: credit.map(|c| c.killer),
+            victim_id: victim,
+            weapon_id: credit.map(|c| c.weapon_id),
+            headshot: credit.is_some_and(|c| c.headshot),
+        },
+    );
+
+    if !tally {
+        return;
+    }
+
+    shared.broadcast_to_dimension(dimension, score_packet(victim, victim_score));
+
+    if let Some((killer, score)) = killer_score {
+        shared.broadcast_to_dimension(dimension, score_packet(killer, score));
+        crate::mode::on_kill(shared, killer, score, now);
+    }
+}
+
+pub fn score_packet(player_id: PlayerId, score: Score) -> ServerPacket {
+    ServerPacket::ScoreUpdate {
+        player_id,
+        kills: score.kills,
+        deaths: score.deaths,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(attacker: PlayerId, victim: PlayerId) -> HitReport {
+        HitReport {
+            attacker,
+            victim,
+            weapon_id: 4,
+            damage: 50.0,
+            headshot: false,
+        }
+    }
+
+    fn log_with(ids: &[PlayerId]) -> CombatLog {
+        let mut log = CombatLog::new();
+        for id in ids {
+            log.add_player(*id);
+        }
+        log
+    }
+
+    #[test]
+    fn kill_is_credited_to_last_recent_attacker() {
+        let mut log = log_with(&[1, 2, 3]);
+        let now = Instant::now();
+
+        log.record_hit(&hit(2, 1), now);
+        log.record_hit(&hit(3, 1), now + Duration::from_secs(1));
+
+        let credit = log.on_death(1, now + Duration::from_secs(2), true).unwrap();
+        assert_eq!(credit.map(|c| c.killer), Some(3));
+        assert_eq!(
+            log.score(3),
+            Score {
+                kills: 1,
+                deaths: 0
+            }
+        );
+        assert_eq!(
+            log.score(1),
+            Score {
+                kills: 0,
+                deaths: 1
+            }
+        );
+        assert_eq!(log.score(2), Score::default());
+    }
+
+    #[test]
+    fn stale_hit_gives_no_credit() {
+        let mut log = log_with(&[1, 2]);
+        let now = Instant::now();
+
+        log.record_hit(&hit(2, 1), now);
+        let credit = log.on_death(1, now + KILL_CREDIT_WINDOW * 2, true).unwrap();
+        assert!(credit.is_none());
+        assert_eq!(log.score(1).deaths, 1);
+    }
+
+    #[test]
+    fn duplicate_death_counted_once() {
+        let mut log = log_with(&[1]);
+        let now = Instant::now();
+
+        assert!(log.on_death(1, now, true).is_some());
+        assert!(log.on_death(1, now, true).is_none());
+        assert_eq!(log.score(1).deaths, 1);
+
+        log.on_alive(1);
+        assert!(log.on_death(1, now, true).is_some());
+        assert_eq!(log.score(1).deaths, 2);
+    }
+
+    #[test]
+    fn validation_rejects_bogus_reports() {
+        let mut log = log_with(&[1, 2]);
+        let armed = armed_at(0.0, Some(4));
+        let near = NetVec3::default();
+        let far = NetVec3 {
+            x: 1_000.0,
+            y: 0.0,
+            z: 0.0,
+        };
+
+        assert_eq!(
+            log.validate(&hit(1, 1), Some(&armed), None, false),
+            Err(HitRejected::SelfHit)
+        );
+        assert_eq!(
+            log.validate(&hit(1, 9), Some(&armed), None, false),
+            Err(HitRejected::UnknownVictim)
+        );
+        assert_eq!(
+            log.validate(&hit(1, 2), Some(&armed), Some(far), false),
+            Err(HitRejected::TooFar)
+        );
+
+        let mut bad = hit(1, 2);
+        bad.damage = f32::NAN;
+        assert_eq!(
+            log.validate(&bad, Some(&armed), None, false),
+            Err(HitRejected::BadDamage)
+        );
+
+        assert_eq!(
+            log.validate(&hit(1, 2), Some(&armed), Some(near), false),
+            Ok(())
+        );
+        assert_eq!(
+            log.validate(&hit(1, 2), Some(&armed), Some(near), true),
+            Err(HitRejected::FriendlyFire)
+        );
+
+        log.on_death(2, Instant::now(), true);
+        assert_eq!(
+            log.validate(&hit(1, 2), Some(&armed), None, false),
+            Err(HitRejected::VictimDead)
+        );
+    }
+
+    #[test]
+    fn validation_checks_attacker_and_weapon() {
+        let mut log = log_with(&[1, 2]);
+        let armed = armed_at(0.0, Some(weapons::COLT_M1911A1));
+
+        // Нет snapshot'а или стрелок не в игре.
+        assert_eq!(
+            log.validate(&hit(1, 2), None, None, false),
+            Err(HitRejected::UnknownAttacker)
+        );
+        assert_eq!(
+            log.validate(&hit(7, 2), Some(&armed), None, false),
+            Err(HitRejected::UnknownAttacker)
+        );
+
+        let mut unknown = hit(1, 2);
+        unknown.weapon_id = 99;
+        assert_eq!(
+            log.validate(&unknown, Some(&armed), None, false),
+            Err(HitRejected::UnknownWeapon)
+        );
+
+        // Заявлен дробовик, а в руках кольт; кулаки — при пустых руках.
+        let mut shotgun = hit(1, 2);
+        shotgun.weapon_id = weapons::REMINGTON_870;
+        assert_eq!(
+            log.validate(&shotgun, Some(&armed), None, false),
+            Err(HitRejected::WrongWeapon)
+        );
+        let mut fists = hit(1, 2);
+        fists.weapon_id = weapons::EMPTY_HANDS;
+        assert_eq!(
+            log.validate(&fists, Some(&armed_at(0.0, None)), None, false),
+            Ok(())
+        );
+
+        // Потолок — свой у каждого оружия.
+        let mut strong = hit(1, 2);
+        strong.damage = 500.0;
+        assert_eq!(
+            log.validate(&strong, Some(&armed), None, false),
+            Err(HitRejected::BadDamage)
+        );
+        shotgun.damage = 500.0;
+        assert_eq!(
+            log.validate(
+                &shotgun,
+                Some(&armed_at(0.0, Some(weapons::REMINGTON_870))),
+                None,
+                false
+            ),
+            Ok(())
+        );
+
+        let mut dead = armed.clone();
+        dead.is_dead = true;
+        assert_eq!(
+            log.validate(&hit(1, 2), Some(&dead), None, false),
+            Err(HitRejected::AttackerDead)
+        );
+        log.on_death(1, Instant::now(), true);
+        assert_eq!(
+            log.validate(&hit(1, 2), Some(&armed), None, false),
+            Err(HitRejected::AttackerDead)
+        );
+    }
+
+    #[test]
+    fn melee_hits_are_accepted() {
+        let log = log_with(&[1, 2]);
+        for weapon in [weapons::KNIFE, weapons::BASEBALL_BAT] {
+            let mut melee = hit(1, 2);
+            melee.weapon_id = weapon;
+            melee.damage = 150.0;
+            assert_eq!(
+                log.validate(&melee, Some(&armed_at(0.0, Some(weapon))), None, false),
+                Ok(()),
+                "weapon {weapon}"
+            );
+        }
+    }
+
+    fn armed_at(x: f32, weapon_id: Option<u32>) -> NetPlayerSnapshot {
+        NetPlayerSnapshot {
+            tick: 1,
+            player_id: 1,
+            position: NetVec3 { x, y: 0.0, z: 0.0 },
+            forward: NetVec3::default(),
+            health: 100.0,
+            is_dead: false,
+            state_code: 0,
+            car_wrapper_state: 0,
+            ctrl_style_mask: 0,
+            sub45c_state: 0,
+            in_vehicle: false,
+            is_aiming: false,
+            aim_dir: None,
+            is_moving: false,
+            movement_mode: 0,
+            weapon_id,
+        }
+    }
+
+    #[test]
+    fn shot_must_match_shooter_snapshot() {
+        let muzzle = NetVec3 {
+            x: 0.0,
+            y: 0.0,
+            z: 1.4,
+        };
+        let forward = NetVec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        };
+        let armed = armed_at(0.0, Some(4));
+
+        assert_eq!(validate_shot(Some(&armed), 4, muzzle, forward), Ok(()));
+        assert_eq!(
+            validate_shot(None, 4, muzzle, forward),
+            Err(ShotRejected::NoSnapshot)
+        );
+        assert_eq!(
+            validate_shot(Some(&armed), 12, muzzle, forward),
+            Err(ShotRejected::WrongWeapon)
+        );
+        assert_eq!(
+            validate_shot(Some(&armed_at(0.0, None)), 4, muzzle, forward),
+            Err(ShotRejected::WrongWeapon)
+        );
+        assert_eq!(
+            validate_shot(Some(&armed_at(50.0, Some(4))), 4, muzzle, forward),
+            Err(ShotRejected::OriginTooFar)
+        );
+        let nan = NetVec3 {
+            x: f32::NAN,
+            ..muzzle
+        };
+        assert_eq!(
+            validate_shot(Some(&armed), 4, nan, forward),
+            Err(ShotRejected::OriginTooFar)
+        );
+        assert_eq!(
+            validate_shot(Some(&armed), 4, muzzle, NetVec3::default()),
+            Err(ShotRejected::BadDirection)
+        );
+
+        let mut dead = armed.clone();
+        dead.is_dead = true;
+        assert_eq!(
+            validate_shot(Some(&dead), 4, muzzle, forward),
+            Err(ShotRejected::ShooterDead)
+        );
+    }
+}
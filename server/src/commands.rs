@@ -0,0 +1,271 @@
+//! Чат-команды игроков.
+//!
+//! Любое сообщение чата, начинающееся с `/`, сюда, а не в общий чат.
+//! Ответы уходят только автору команды через `ServerPacket::SystemMessage`.
+//! Команды над другими игроками — только администраторам (`admins` в
+//! конфиге).
+
+use std::time::Instant;
+
+use protocol::{PlayerId, ServerPacket};
+
+use crate::SharedServer;
+use crate::dimension::{self, DimensionId};
+use crate::economy::{self, format_money};
+use crate::{teams, world};
+
+/// Является ли текст чата командой.
+pub fn is_command(text: &str) -> bool {
+    text.trim_start().starts_with('/')
+}
+
+/// Выполнить команду игрока `player_id`.
+pub fn handle(shared: &SharedServer, player_id: PlayerId, text: &str) {
+    let mut parts = text.trim().trim_start_matches('/').split_whitespace();
+    let Some(name) = parts.next() else {
+        return;
+    };
+    let args: Vec<&str> = parts.collect();
+
+    match name.to_ascii_lowercase().as_str() {
+        "help" => reply(
+            shared,
+            player_id,
+            "Команды: /money — баланс, /pay <id> <сумма> — перевод, /team [id] — команда, /time — время и погода, /dimension [id] — измерение; админам: /dimension <id> <игрок>",
+        ),
+        "money" | "balance" => cmd_money(shared, player_id),
+        "pay" => cmd_pay(shared, player_id, &args),
+        "team" => cmd_team(shared, player_id, &args),
+        "time" | "weather" => cmd_time(shared, player_id),
+        "dimension" | "dim" => cmd_dimension(shared, player_id, &args),
+        other => reply(
+            shared,
+            player_id,
+            &format!("Неизвестная команда /{other}. Список: /help"),
+        ),
+    }
+}
+
+fn cmd_money(shared: &SharedServer, player_id: PlayerId) {
+    match shared.balance(player_id) {
+        Some(balance) => reply(
+            shared,
+            player_id,
+            &format!("На счету: {}", format_money(balance)),
+        ),
+        None => reply(shared, player_id, "Кошелёк недоступен"),
+    }
+}
+
+/// Администратор ли игрок (ник в `admins`).
+fn is_admin(shared: &SharedServer, player_id: PlayerId) -> bool {
+    shared
+        .get_name(player_id)
+        .is_some_and(|name| shared.config.admins.contains(&name))
+}
+
+fn cmd_dimension(shared: &SharedServer, player_id: PlayerId, args: &[&str]) {
+    const USAGE: &str = "Использование: /dimension [номер]";
+
+    let current = shared.dimensions.lock().ok().and_then(|d| d.get(player_id));
+    match args {
+        [] => {
+            if let Some(current) = current {
+                reply(shared, player_id, &format!("Вы в измерении {current}"));
+            }
+        }
+        [id] => {
+            let Ok(to) = id.parse::<DimensionId>() else {
+                reply(shared, player_id, USAGE);
+                return;
+            };
+            if !is_admin(shared, player_id) {
+                if !shared.config.dimensions.self_service {
+                    reply(shared, player_id, "Смена измерения на сервере отключена");
+                    return;
+                }
+                let busy = current.is_some_and(|dimension| {
+                    shared
+                        .mode
+                        .lock()
+                        .is_ok_and(|m| m.holds(dimension, player_id))
+                });
+                if busy {
+                    reply(
+                        shared,
+                        player_id,
+                        "Нельзя сменить измерение посреди раунда или заезда",
+                    );
+                    return;
+                }
+            }
+            match dimension::move_player(shared, player_id, to) {
+                Ok(_) => reply(shared, player_id, &format!("Вы перешли в измерение {to}")),
+                Err(e) => reply(shared, player_id, &format!("Переход не выполнен: {e}")),
+            }
+        }
+        [id, target] => {
+            if !is_admin(shared, player_id) {
+                reply(
+                    shared,
+                    player_id,
+                    "Переводить других игроков может только администратор",
+                );
+                return;
+            }
+            let (Ok(to), Ok(target_id)) = (
+                id.parse::<DimensionId>(),
+                target.trim_start_matches('#').parse::<PlayerId>(),
+            ) else {
+                reply(
+                    shared,
+                    player_id,
+                    "Использование: /dimension <номер> <id игрока>",
+                );
+                return;
+            };
+            match dimension::move_player(shared, target_id, to) {
+                Ok(_) => {
+                    reply(
+                        shared,
+                        player_id,
+                        &format!("Игрок #{target_id} переведён в измерение {to}"),
+                    );
+                    reply(
+                        shared,
+                        target_id,
+                        &format!("Администратор перевёл вас в измерение {to}"),
+                    );
+                }
+                Err(e) => reply(shared, player_id, &format!("Переход не выполнен: {e}")),
+            }
+        }
+        _ => reply(shared, player_id, USAGE),
+    }
+}
+
+fn cmd_time(shared: &SharedServer, player_id: PlayerId) {
+    let text = match shared.world.lock() {
+        Ok(w) => {
+            let now = Instant::now();
+            match w.weather(now) {
+                Some(weather) => format!(
+                    "Время: {}, погода: {weather}",
+                    world::format_hour(w.hour(now))
+                ),
+                None => format!("Время: {}", world::format_hour(w.hour(now))),
+            }
+        }
+        Err(_) => return,
+    };
+    reply(shared, player_id, &text);
+}
+
+fn cmd_team(shared: &SharedServer, player_id: PlayerId, args: &[&str]) {
+    if let [team_id] = args {
+        if let Err(e) = teams::join(shared, player_id, team_id) {
+            reply(
+                shared,
+                player_id,
+                &format!("Смена команды не выполнена: {e}"),
+            );
+        }
+        return;
+    }
+
+    let text = match shared.teams.lock() {
+        Ok(t) if t.is_enabled() => {
+            let list = t
+                .definitions()
+                .iter()
+                .map(|d| format!("{} ({})", d.id, d.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let current = t.team_of(player_id).unwrap_or("нет");
+            format!("Ваша команда: {current}. Команды: {list}. Смена: /team <id>")
+        }
+        Ok(_) => "На сервере нет команд".to_string(),
+        Err(_) => return,
+    };
+    reply(shared, player_id, &text);
+}
+
+fn cmd_pay(shared: &SharedServer, player_id: PlayerId, args: &[&str]) {
+    let [target, amount] = args else {
+        reply(shared, player_id, "Использование: /pay <id> <сумма>");
+        return;
+    };
+
+    let Some(target_id) = target.trim_start_matches('#').parse::<PlayerId>().ok() else {
+        reply(
+            shared,
+            player_id,
+            &format!("Некорректный id игрока: {target}"),
+        );
+        return;
+    };
+
+    let Some(amount_cents) = economy::parse_dollars(amount) else {
+        reply(shared, player_id, &format!("Некорректная сумма: {amount}"));
+        return;
+    };
+
+    let result = match shared.economy.lock() {
+        Ok(mut eco) => eco.transfer(player_id, target_id, amount_cents),
+        Err(_) => return,
+    };
+
+    match result {
+        Ok((from_balance, to_balance)) => {
+            let from_name = shared
+                .get_name(player_id)
+                .unwrap_or_else(|| format!("Player#{player_id}"));
+            let to_name = shared
+                .get_name(target_id)
+                .unwrap_or_else(|| format!("Player#{target_id}"));
+
+            shared.send_to(
+                player_id,
+                ServerPacket::MoneySet {
+                    balance_cents: from_balance,
+                },
+            );
+            shared.send_to(
+                target_id,
+                ServerPacket::MoneySet {
+                    balance_cents: to_balance,
+                },
+            );
+            reply(
+                shared,
+                player_id,
+                &format!(
+                    "Вы перевели {} игроку {to_name}",
+                    format_money(amount_cents)
+                ),
+            );
+            reply(
+                shared,
+                target_id,
+                &format!("{from_name} перевёл вам {}", format_money(amount_cents)),
+            );
+
+            common::logger::info(&format!(
+                "[economy] transfer {} -> {}: {}",
+                player_id,
+                target_id,
+                format_money(amount_cents)
+            ));
+        }
+        Err(e) => reply(shared, player_id, &format!("Перевод не выполнен: {e}")),
+    }
+}
+
+fn reply(shared: &SharedServer, player_id: PlayerId, text: &str) {
+    shared.send_to(
+        player_id,
+        ServerPacket::SystemMessage {
+            text: text.to_string(),
+        },
+    );
+}
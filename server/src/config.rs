@@ -0,0 +1,357 @@
+//! Конфигурация сервера (`server.json` рядом с бинарником).
+//!
+//! Все поля необязательны: отсутствующий файл или ключ — значение по умолчанию.
+//! Неизвестные ключи игнорируются, чтобы конфиг переживал смену версий.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use common::logger;
+use protocol::master::DEFAULT_HEARTBEAT_SECS;
+use protocol::{DEFAULT_PORT, NetVec3, NetWeapon};
+use serde::{Deserialize, Serialize};
+
+use crate::dimension::DimensionId;
+use crate::track::DEFAULT_TRACKS_DIR;
+
+/// Путь к конфигу по умолчанию.
+pub const DEFAULT_CONFIG_PATH: &str = "server.json";
+
+/// Режим по умолчанию, если в конфиге не указан другой.
+pub const DEFAULT_MODE: &str = "freeroam";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Имя сервера в браузере серверов.
+    pub name: String,
+    /// Карта (для браузера серверов).
+    pub map: String,
+    /// Пароль на вход. `None` — открытый сервер.
+    pub password: Option<String>,
+    /// Игровой TCP-порт (и UDP query на том же номере).
+    pub port: u16,
+    /// Регистрация на мастер-сервере.
+    pub master: MasterConfig,
+    /// HTTP-эндпоинт метрик.
+    pub metrics: MetricsConfig,
+    /// Запись сессии для `replay`.
+    pub recording: RecordingConfig,
+    /// Режим измерения новичков (`DEFAULT_DIMENSION`). Используется и
+    /// для фильтрации точек спавна.
+    pub mode: String,
+    /// Режимы остальных измерений и переходы между ними.
+    pub dimensions: DimensionsConfig,
+    /// Ники администраторов: им доступны команды над другими игроками.
+    /// Ник закрепляется за первым вошедшим (токен аккаунта), так что
+    /// администратору стоит зайти раньше, чем ник займут.
+    pub admins: Vec<String>,
+    /// Отправлять ли `Respawn` сразу после подключения (стартовая точка).
+    pub spawn_on_connect: bool,
+    /// Точки спавна.
+    pub spawn_points: Vec<SpawnPointConfig>,
+    /// Правила респауна.
+    pub respawn: RespawnConfig,
+    /// Правила режима `deathmatch`.
+    pub deathmatch: DeathmatchConfig,
+    /// Правила режима `race`.
+    pub race: RaceConfig,
+    /// Команды. Пустой список — без команд.
+    pub teams: TeamsConfig,
+    /// Время суток и погода.
+    pub world: WorldConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            name: "Mafia II: DE Multiplayer".to_string(),
+            map: "Empire Bay".to_string(),
+            password: None,
+            port: DEFAULT_PORT,
+            master: MasterConfig::default(),
+            metrics: MetricsConfig::default(),
+            recording: RecordingConfig::default(),
+            mode: DEFAULT_MODE.to_string(),
+            dimensions: DimensionsConfig::default(),
+            admins: Vec::new(),
+            spawn_on_connect: false,
+            spawn_points: Vec::new(),
+            respawn: RespawnConfig::default(),
+            deathmatch: DeathmatchConfig::default(),
+            race: RaceConfig::default(),
+            teams: TeamsConfig::default(),
+            world: WorldConfig::default(),
+        }
+    }
+}
+
+/// Точка спавна.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPointConfig {
+    pub position: NetVec3,
+    /// Курс в градусах по часовой стрелке от +Y.
+    #[serde(default)]
+    pub heading: f32,
+    /// Команда, для которой точка предназначена. `None` — любая.
+    #[serde(default)]
+    pub team: Option<String>,
+    /// Режим, для которого точка предназначена. `None` — любой.
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// Правила респауна.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RespawnConfig {
+    /// Включён ли серверный респаун вообще.
+    pub enabled: bool,
+    /// Базовая задержка после смерти.
+    pub delay_ms: u32,
+    /// Добавка к задержке за каждую смерть в окне `streak_window_ms`
+    /// (штраф за частые смерти / спавн-кемпинг).
+    pub delay_per_recent_death_ms: u32,
+    /// Потолок задержки.
+    pub max_delay_ms: u32,
+    /// Окно подсчёта недавних смертей.
+    pub streak_window_ms: u32,
+    /// Здоровье после респауна. 720.0 — полное на нормальной сложности.
+    pub health: f32,
+    /// Стартовый набор оружия.
+    pub loadout: Vec<NetWeapon>,
+}
+
+impl Default for RespawnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay_ms: 5_000,
+            delay_per_recent_death_ms: 2_000,
+            max_delay_ms: 15_000,
+            streak_window_ms: 60_000,
+            health: 720.0,
+            loadout: vec![NetWeapon {
+                weapon_id: common::weapons::COLT_M1911A1,
+                ammo: 48,
+            }],
+        }
+    }
+}
+
+/// Измерения.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DimensionsConfig {
+    /// Режим измерения по номеру (`"1": "race"`). Без записи — freeroam.
+    pub modes: BTreeMap<DimensionId, String>,
+    /// Может ли игрок сам сменить измерение (`/dimension <id>`). Даже
+    /// если может — не посреди раунда или заезда, в котором участвует.
+    pub self_service: bool,
+}
+
+impl Default for DimensionsConfig {
+    fn default() -> Self {
+        Self {
+            modes: BTreeMap::new(),
+            self_service: true,
+        }
+    }
+}
+
+/// Правила deathmatch. Нулевой лимит — без ограничения.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeathmatchConfig {
+    /// Фрагов для победы.
+    pub score_limit: u32,
+    /// Длительность раунда.
+    pub time_limit_secs: u32,
+    /// Пауза между раундами.
+    pub intermission_secs: u32,
+    /// Награда за фраг в центах (зачисляется в кошелёк).
+    pub kill_reward_cents: i64,
+}
+
+impl Default for DeathmatchConfig {
+    fn default() -> Self {
+        Self {
+            score_limit: 20,
+            time_limit_secs: 600,
+            intermission_secs: 10,
+            kill_reward_cents: 10_000,
+        }
+    }
+}
+
+/// Правила гонки. Нулевой лимит времени — без ограничения.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RaceConfig {
+    /// Имя трассы (`<tracks_dir>/<track>.json`).
+    pub track: String,
+    /// Каталог с трассами.
+    pub tracks_dir: String,
+    /// Отсчёт перед стартом.
+    pub countdown_secs: u32,
+    /// Сколько игроков нужно для старта.
+    pub min_players: u32,
+    /// Сколько ждать остальных после первого финишировавшего.
+    pub finish_timeout_secs: u32,
+    /// Максимальная длительность заезда.
+    pub time_limit_secs: u32,
+    /// Показ результатов перед следующим заездом.
+    pub results_secs: u32,
+}
+
+impl Default for RaceConfig {
+    fn default() -> Self {
+        Self {
+            track: "default".to_string(),
+            tracks_dir: DEFAULT_TRACKS_DIR.to_string(),
+            countdown_secs: 5,
+            min_players: 1,
+            finish_timeout_secs: 60,
+            time_limit_secs: 900,
+            results_secs: 15,
+        }
+    }
+}
+
+/// Команды и правила между ними.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamsConfig {
+    pub teams: Vec<TeamConfig>,
+    /// Назначать новичков в меньшую команду и не давать перекашивать составы.
+    pub auto_balance: bool,
+    /// Засчитывать ли попадания по своим.
+    pub friendly_fire: bool,
+}
+
+impl Default for TeamsConfig {
+    fn default() -> Self {
+        Self {
+            teams: Vec::new(),
+            auto_balance: true,
+            friendly_fire: false,
+        }
+    }
+}
+
+/// Время суток и погода, общие для всех игроков.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    /// Час при старте сервера, `[0, 24)`.
+    pub start_hour: f32,
+    /// Игровых секунд в реальной секунде. `0` — время заблокировано.
+    pub time_scale: f32,
+    /// Шаблоны погоды по кругу, первый — стартовый. Пусто — погоду
+    /// клиентов не трогаем.
+    pub weather: Vec<String>,
+    /// Смена погоды раз в столько секунд. `0` — погода заблокирована
+    /// на первом шаблоне.
+    pub weather_cycle_secs: u32,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            start_hour: 12.0,
+            // Сутки за 48 минут.
+            time_scale: 30.0,
+            weather: Vec::new(),
+            weather_cycle_secs: 0,
+        }
+    }
+}
+
+/// Мастер-сервер списка серверов.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MasterConfig {
+    /// `host:port` мастера. `None` — сервер не публикуется.
+    pub addr: Option<String>,
+    /// Период heartbeat.
+    pub heartbeat_secs: u64,
+}
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        Self {
+            addr: None,
+            heartbeat_secs: DEFAULT_HEARTBEAT_SECS,
+        }
+    }
+}
+
+/// HTTP-эндпоинт метрик (`/metrics`, `/health`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Адрес HTTP. `None` — выключено. По умолчанию только localhost.
+    pub addr: Option<String>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            addr: Some("127.0.0.1:9788".to_string()),
+        }
+    }
+}
+
+/// Запись сессии (`recording`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+    /// Каталог для `.m2rec`. `None` — запись выключена.
+    pub dir: Option<String>,
+}
+
+/// Команда. Её точки спавна — `spawn_points` с `team == id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamConfig {
+    pub id: String,
+    pub name: String,
+    /// RGB для ников, списка игроков и скорборда.
+    #[serde(default = "default_team_color")]
+    pub color: [u8; 3],
+}
+
+fn default_team_color() -> [u8; 3] {
+    [200, 200, 200]
+}
+
+impl ServerConfig {
+    /// Загрузить конфиг. Ошибки чтения/разбора не фатальны — берём дефолты.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(_) => {
+                logger::info(&format!(
+                    "[config] {} не найден, используются значения по умолчанию",
+                    path.display()
+                ));
+                return Self::default();
+            }
+        };
+
+        match serde_json::from_str::<Self>(&text) {
+            Ok(cfg) => {
+                logger::info(&format!("[config] загружен {}", path.display()));
+                cfg
+            }
+            Err(e) => {
+                logger::error(&format!(
+                    "[config] ошибка разбора {}: {e}; используются значения по умолчанию",
+                    path.display()
+                ));
+                Self::default()
+            }
+        }
+    }
+}
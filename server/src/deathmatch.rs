@@ -0,0 +1,183 @@
+//! Deathmatch: каждый сам за себя, раунд до лимита фрагов или времени.
+//!
+//! Модуль не знает про сеть — только фазы раунда. Рассылку делает `mode`.
+
+use std::time::{Duration, Instant};
+
+use protocol::{PlayerId, RoundEndReason};
+
+use crate::combat::Score;
+use crate::config::DeathmatchConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Running { started: Instant },
+    Intermission { next_round_at: Instant },
+}
+
+/// Смена фазы раунда.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundChange {
+    Started,
+    Ended {
+        winner: Option<PlayerId>,
+        reason: RoundEndReason,
+    },
+}
+
+#[derive(Debug)]
+pub struct Deathmatch {
+    rules: DeathmatchConfig,
+    phase: Phase,
+}
+
+impl Deathmatch {
+    /// Раунд начинается сразу.
+    pub fn new(rules: DeathmatchConfig, now: Instant) -> Self {
+        Self {
+            rules,
+            phase: Phase::Running { started: now },
+        }
+    }
+
+    pub fn rules(&self) -> &DeathmatchConfig {
+        &self.rules
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.phase, Phase::Running { .. })
+    }
+
+    /// Сколько осталось до конца раунда. `None` — лимита нет или раунд не идёт.
+    pub fn time_left(&self, now: Instant) -> Option<Duration> {
+        match self.phase {
+            Phase::Running { started } if self.rules.time_limit_secs > 0 => {
+                let limit = Duration::from_secs(self.rules.time_limit_secs as u64);
+                Some(limit.saturating_sub(now.duration_since(started)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Фраг засчитан: не пора ли заканчивать.
+    pub fn on_kill(&mut self, killer: PlayerId, kills: u32, now: Instant) -> Option<RoundChange> {
+        if !self.is_running() || self.rules.score_limit == 0 || kills < self.rules.score_limit {
+            return None;
+        }
+
+        self.enter_intermission(now);
+        Some(RoundChange::Ended {
+            winner: Some(killer),
+            reason: RoundEndReason::ScoreLimit,
+        })
+    }
+
+    /// Таймеры: лимит времени и конец перерыва.
+    pub fn tick(&mut self, now: Instant, scores: &[(PlayerId, Score)]) -> Option<RoundChange> {
+        match self.phase {
+            Phase::Running { .. } => {
+                if self.time_left(now)? > Duration::ZERO {
+                    return None;
+                }
+                self.enter_intermission(now);
+                Some(RoundChange::Ended {
+                    winner: leader(scores),
+                    reason: RoundEndReason::TimeLimit,
+                })
+            }
+            Phase::Intermission { next_round_at } => {
+                if now < next_round_at {
+                    return None;
+                }
+                self.phase = Phase::Running { started: now };
+                Some(RoundChange::Started)
+            }
+        }
+    }
+
+    fn enter_intermission(&mut self, now: Instant) {
+        self.phase = Phase::Intermission {
+            next_round_at: now + Duration::from_secs(self.rules.intermission_secs as u64),
+        };
+    }
+}
+
+/// Лидер по фрагам, при равенстве — по меньшему числу смертей.
+/// Полная ничья — победителя нет.
+pub fn leader(scores: &[(PlayerId, Score)]) -> Option<PlayerId> {
+    let rank = |s: &Score| (s.kills, std::cmp::Reverse(s.deaths));
+
+    let (best_id, best) = scores.iter().max_by_key(|(_, s)| rank(s))?;
+    let tied = scores.iter().filter(|(_, s)| rank(s) == rank(best)).count();
+
+    (tied == 1 && best.kills > 0).then_some(*best_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> DeathmatchConfig {
+        DeathmatchConfig {
+            score_limit: 3,
+            time_limit_secs: 60,
+            intermission_secs: 5,
+            ..DeathmatchConfig::default()
+        }
+    }
+
+    fn score(kills: u32, deaths: u32) -> Score {
+        Score { kills, deaths }
+    }
+
+    #[test]
+    fn score_limit_ends_round() {
+        let now = Instant::now();
+        let mut dm = Deathmatch::new(rules(), now);
+
+        assert_eq!(dm.on_kill(7, 2, now), None);
+        assert_eq!(
+            dm.on_kill(7, 3, now),
+            Some(RoundChange::Ended {
+                winner: Some(7),
+                reason: RoundEndReason::ScoreLimit
+            })
+        );
+        assert!(!dm.is_running());
+        // В перерыве фраги раунд не заканчивают повторно.
+        assert_eq!(dm.on_kill(7, 4, now), None);
+    }
+
+    #[test]
+    fn time_limit_then_intermission_then_new_round() {
+        let now = Instant::now();
+        let mut dm = Deathmatch::new(rules(), now);
+        let scores = [(1, score(2, 0)), (2, score(1, 3))];
+
+        assert_eq!(dm.tick(now + Duration::from_secs(59), &scores), None);
+
+        let end = now + Duration::from_secs(60);
+        assert_eq!(
+            dm.tick(end, &scores),
+            Some(RoundChange::Ended {
+                winner: Some(1),
+                reason: RoundEndReason::TimeLimit
+            })
+        );
+
+        assert_eq!(dm.tick(end + Duration::from_secs(4), &scores), None);
+        assert_eq!(
+            dm.tick(end + Duration::from_secs(5), &scores),
+            Some(RoundChange::Started)
+        );
+        assert!(dm.is_running());
+    }
+
+    #[test]
+    fn leader_breaks_ties_by_deaths() {
+        assert_eq!(leader(&[(1, score(3, 2)), (2, score(3, 1))]), Some(2));
+        assert_eq!(leader(&[(1, score(3, 1)), (2, score(3, 1))]), None);
+        assert_eq!(leader(&[(1, score(0, 0))]), None);
+        assert_eq!(leader(&[]), None);
+    }
+}
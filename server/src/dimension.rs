@@ -0,0 +1,233 @@
+//! Измерения (виртуальные миры).
+//!
+//! Каждый игрок живёт в одном измерении; snapshot'ы, события, общий чат и
+//! spawn/despawn ходят только внутри него. Новички попадают в
+//! `DEFAULT_DIMENSION`. При переходе старые соседи и переходящий получают
+//! `PlayerDespawn` друг о друге, новые — `PlayerSpawn`, так что клиентам
+//! знать об измерениях не нужно.
+//!
+//! У измерения свой режим (`mode::Modes`): переходящий выбывает из режима
+//! старого измерения и получает состояние нового.
+//!
+//! Переход — из кода сервера через [`move_player`] или чат-командой
+//! `/dimension` (сам игрок — если разрешено конфигом и он не занят
+//! раундом или заездом; администратор — любого игрока).
+
+use std::collections::HashMap;
+
+use common::logger;
+use protocol::{PlayerId, ServerPacket};
+
+use crate::{SharedServer, appearance, mode, vehicles};
+
+pub type DimensionId = u32;
+
+/// Измерение новичков.
+pub const DEFAULT_DIMENSION: DimensionId = 0;
+
+/// Почему переход не выполнен.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DimensionError {
+    UnknownPlayer,
+    AlreadyThere,
+}
+
+impl std::fmt::Display for DimensionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownPlayer => write!(f, "игрок не в игре"),
+            Self::AlreadyThere => write!(f, "игрок уже в этом измерении"),
+        }
+    }
+}
+
+/// Результат перехода: кого переходящий покинул и к кому пришёл.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub from: DimensionId,
+    pub to: DimensionId,
+    pub left: Vec<PlayerId>,
+    pub joined: Vec<PlayerId>,
+}
+
+#[derive(Debug, Default)]
+pub struct Dimensions {
+    of: HashMap<PlayerId, DimensionId>,
+}
+
+impl Dimensions {
+    pub fn add_player(&mut self, player_id: PlayerId) {
+        self.of.insert(player_id, DEFAULT_DIMENSION);
+    }
+
+    /// Убрать игрока; возвращает измерение, в котором он был.
+    pub fn remove_player(&mut self, player_id: PlayerId) -> Option<DimensionId> {
+        self.of.remove(&player_id)
+    }
+
+    pub fn get(&self, player_id: PlayerId) -> Option<DimensionId> {
+        self.of.get(&player_id).copied()
+    }
+
+    /// Видят ли игроки друг друга.
+    pub fn same(&self, a: PlayerId, b: PlayerId) -> bool {
+        matches!((self.get(a), self.get(b)), (Some(x), Some(y)) if x == y)
+    }
+
+    /// Игроки измерения `dimension`, по возрастанию id.
+    pub fn members(&self, dimension: DimensionId) -> Vec<PlayerId> {
+        let mut members: Vec<PlayerId> = self
+            .of
+            .iter()
+            .filter(|&(_, d)| *d == dimension)
+            .map(|(id, _)| *id)
+            .collect();
+        members.sort_unstable();
+        members
+    }
+
+    /// Соседи игрока по измерению (без него самого).
+    pub fn neighbours(&self, player_id: PlayerId) -> Vec<PlayerId> {
+        let Some(dimension) = self.get(player_id) else {
+            return Vec::new();
+        };
+        let mut members = self.members(dimension);
+        members.retain(|id| *id != player_id);
+        members
+    }
+
+    pub fn move_player(
+        &mut self,
+        player_id: PlayerId,
+        to: DimensionId,
+    ) -> Result<Move, DimensionError> {
+        let from = self.get(player_id).ok_or(DimensionError::UnknownPlayer)?;
+        if from == to {
+            return Err(DimensionError::AlreadyThere);
+        }
+        let left = self.neighbours(player_id);
+        self.of.insert(player_id, to);
+        Ok(Move {
+            from,
+            to,
+            left,
+            joined: self.neighbours(player_id),
+        })
+    }
+}
+
+/// Перевести игрока в измерение `to` и разослать spawn/despawn.
+pub fn move_player(
+    shared: &SharedServer,
+    player_id: PlayerId,
+    to: DimensionId,
+) -> Result<Move, DimensionError> {
+    // Машина остаётся в старом измерении.
+    if shared
+        .dimensions
+        .lock()
+        .is_ok_and(|d| d.get(player_id).is_some_and(|from| from != to))
+    {
+        vehicles::handle_leave(shared, player_id);
+    }
+
+    let moved = shared
+        .dimensions
+        .lock()
+        .map_err(|_| DimensionError::UnknownPlayer)?
+        .move_player(player_id, to)?;
+
+    let name = shared.get_name(player_id).unwrap_or_default();
+    for &other in &moved.left {
+        shared.send_to(other, ServerPacket::PlayerDespawn { player_id });
+        shared.send_to(player_id, ServerPacket::PlayerDespawn { player_id: other });
+    }
+    for &other in &moved.joined {
+        shared.send_to(
+            other,
+            ServerPacket::PlayerSpawn {
+                player_id,
+                name: name.clone(),
+            },
+        );
+        if let Some(packet) = appearance::packet(shared, player_id) {
+            shared.send_to(other, packet);
+        }
+        if let Some(other_name) = shared.get_name(other) {
+            shared.send_to(
+                player_id,
+                ServerPacket::PlayerSpawn {
+                    player_id: other,
+                    name: other_name,
+                },
+            );
+            if let Some(packet) = appearance::packet(shared, other) {
+                shared.send_to(player_id, packet);
+            }
+            if let Some(packet) = vehicles::enter_packet(shared, other) {
+                shared.send_to(player_id, packet);
+            }
+        }
+        // Последняя позиция — чтобы proxy не стоял в нуле до следующего snapshot.
+        if let Some(snapshot) = shared.get_snapshot(other) {
+            shared.send_to(player_id, ServerPacket::Snapshot(snapshot));
+        }
+    }
+    if let Some(snapshot) = shared.get_snapshot(player_id) {
+        for &other in &moved.joined {
+            shared.send_to(other, ServerPacket::Snapshot(snapshot.clone()));
+        }
+    }
+
+    if let Ok(mut combat) = shared.combat.lock() {
+        combat.forget_hits(player_id);
+    }
+    mode::on_dimension_change(shared, player_id, moved.from);
+
+    logger::info(&format!(
+        "[dimension] player {player_id} ('{name}') {} -> {}",
+        moved.from, moved.to
+    ));
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_reports_old_and_new_neighbours() {
+        let mut dims = Dimensions::default();
+        for id in 1..=4 {
+            dims.add_player(id);
+        }
+        assert!(dims.same(1, 2));
+
+        let moved = dims.move_player(3, 7).unwrap();
+        assert_eq!(moved.left, vec![1, 2, 4]);
+        assert!(moved.joined.is_empty());
+        assert!(!dims.same(1, 3));
+
+        let moved = dims.move_player(4, 7).unwrap();
+        assert_eq!((moved.from, moved.to), (DEFAULT_DIMENSION, 7));
+        assert_eq!(moved.left, vec![1, 2]);
+        assert_eq!(moved.joined, vec![3]);
+        assert_eq!(dims.neighbours(3), vec![4]);
+        assert_eq!(dims.members(DEFAULT_DIMENSION), vec![1, 2]);
+    }
+
+    #[test]
+    fn unknown_or_same_dimension_is_an_error() {
+        let mut dims = Dimensions::default();
+        dims.add_player(1);
+        assert_eq!(
+            dims.move_player(1, DEFAULT_DIMENSION),
+            Err(DimensionError::AlreadyThere)
+        );
+        assert_eq!(dims.move_player(9, 1), Err(DimensionError::UnknownPlayer));
+
+        dims.remove_player(1);
+        assert!(!dims.same(1, 1));
+        assert!(dims.neighbours(1).is_empty());
+    }
+}
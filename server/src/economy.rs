@@ -0,0 +1,661 @@
+//! Серверная экономика — авторитетные балансы игроков.
+//!
+//! Модель:
+//! - сервер хранит баланс каждого игрока (в центах, как кошелёк игры)
+//! - клиент только *сообщает* об изменениях кошелька (`ClientPacket::MoneyReport`)
+//! - сервер проверяет заявку и либо принимает её, либо шлёт коррекцию
+//!   (`ServerPacket::MoneySet`), которую клиент применяет через `Player::set_money`
+//! - переводы между игроками (`/pay`) проходят только через сервер
+//!
+//! Балансы привязаны к нику и сохраняются в JSON-файл, чтобы переживать
+//! реконнекты и перезапуски сервера. Ник сам по себе ничего не доказывает,
+//! поэтому аккаунт закрепляется токеном: сервер выдаёт его при первом входе
+//! (`ServerPacket::AccountToken`), клиент предъявляет в `Connect`. Аккаунт
+//! без токена (из файла старой версии) достаётся первому, кто зайдёт.
+//!
+//! Файл пишется не на каждое изменение, а из `tick_loop` (`tick`) — не чаще
+//! `SAVE_INTERVAL` и вне блокировки реестра; при остановке сервера
+//! несохранённое дописывает `flush`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::PlayerId;
+use serde::{Deserialize, Serialize};
+
+use crate::SharedServer;
+
+/// Стартовый баланс нового аккаунта ($500.00).
+pub const STARTING_BALANCE_CENTS: i64 = 50_000;
+
+/// Максимальный заработок в одной заявке ($2 000.00).
+///
+/// Самые крупные легальные поступления в freeroam (продажа машины,
+/// ограбление кассы) заметно меньше.
+pub const MAX_EARN_PER_REPORT_CENTS: i64 = 200_000;
+
+/// Максимальный заработок за окно `EARN_WINDOW` ($5 000.00).
+pub const MAX_EARN_PER_WINDOW_CENTS: i64 = 500_000;
+
+/// Окно, в котором считается суммарный заработок.
+pub const EARN_WINDOW: Duration = Duration::from_secs(60);
+
+/// Файл с сохранёнными балансами по умолчанию.
+pub const DEFAULT_ACCOUNTS_PATH: &str = "data/economy.json";
+
+/// Как часто изменённые балансы сбрасываются на диск.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Результат проверки заявки клиента.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportOutcome {
+    /// Заявка принята, баланс сервера обновлён.
+    Accepted { balance_cents: i64 },
+    /// Заявка отклонена — клиенту нужно выставить этот баланс.
+    Corrected { balance_cents: i64 },
+}
+
+/// Почему кошелёк не открыт.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+    /// С этим ником уже кто-то играет.
+    AlreadyOnline,
+    /// Аккаунт закреплён за другим токеном.
+    WrongToken,
+    /// Генератор случайных чисел ОС недоступен — токен не выдать.
+    NoRandomness,
+}
+
+impl std::fmt::Display for AccountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyOnline => write!(f, "игрок с таким ником уже на сервере"),
+            Self::WrongToken => write!(f, "ник занят другим игроком"),
+            Self::NoRandomness => write!(f, "сервер не может выдать токен аккаунта"),
+        }
+    }
+}
+
+/// Ошибки перевода между игроками.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferError {
+    InvalidAmount,
+    SelfTransfer,
+    UnknownPlayer(PlayerId),
+    InsufficientFunds { balance_cents: i64 },
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidAmount => write!(f, "сумма должна быть больше нуля"),
+            Self::SelfTransfer => write!(f, "нельзя перевести деньги самому себе"),
+            Self::UnknownPlayer(id) => write!(f, "игрок #{id} не найден"),
+            Self::InsufficientFunds { balance_cents } => {
+                write!(
+                    f,
+                    "недостаточно средств (на счету {})",
+                    format_money(*balance_cents)
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Wallet {
+    account: String,
+    balance_cents: i64,
+    /// Принятые поступления внутри `EARN_WINDOW` (время, сумма).
+    recent_earnings: Vec<(Instant, i64)>,
+}
+
+impl Wallet {
+    fn earned_in_window(&mut self, now: Instant) -> i64 {
+        self.recent_earnings
+            .retain(|(at, _)| now.duration_since(*at) < EARN_WINDOW);
+        self.recent_earnings.iter().map(|(_, c)| *c).sum()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AccountsFile {
+    accounts: HashMap<String, i64>,
+    /// Ник -> токен владельца. В файлах старых версий отсутствует.
+    #[serde(default)]
+    tokens: HashMap<String, String>,
+}
+
+/// Реестр балансов.
+#[derive(Debug)]
+pub struct Economy {
+    /// Кошельки подключённых игроков.
+    wallets: HashMap<PlayerId, Wallet>,
+    /// Сохранённые балансы по нику (включая отключившихся).
+    accounts: HashMap<String, i64>,
+    tokens: HashMap<String, String>,
+    /// Куда сохранять балансы. `None` — только в памяти.
+    path: Option<PathBuf>,
+    /// Есть изменения, которых нет в файле.
+    dirty: bool,
+    last_save: Option<Instant>,
+}
+
+impl Economy {
+    /// Экономика без сохранения на диск.
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            wallets: HashMap::new(),
+            accounts: HashMap::new(),
+            tokens: HashMap::new(),
+            path: None,
+            dirty: false,
+            last_save: None,
+        }
+    }
+
+    /// Загрузить балансы из файла. Отсутствующий или битый файл — пустой реестр.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+
+        let file = match std::fs::read_to_string(&path) {
+            Ok(text) => match serde_json::from_str::<AccountsFile>(&text) {
+                Ok(file) => file,
+                Err(e) => {
+                    logger::warn(&format!(
+                        "[economy] не удалось разобрать {}: {e}",
+                        path.display()
+                    ));
+                    AccountsFile::default()
+                }
+            },
+            Err(_) => AccountsFile::default(),
+        };
+        let AccountsFile { accounts, tokens } = file;
+
+        logger::info(&format!(
+            "[economy] загружено {} аккаунт(ов) из {}",
+            accounts.len(),
+            path.display()
+        ));
+
+        Self {
+            wallets: HashMap::new(),
+            accounts,
+            tokens,
+            path: Some(path),
+            dirty: false,
+            last_save: None,
+        }
+    }
+
+    /// Открыть кошелёк подключившегося игрока.
+    ///
+    /// `token` — то, что клиент получил при прошлом входе с этим ником.
+    /// Возвращает баланс и токен аккаунта (новый, если его ещё не было).
+    pub fn open_wallet(
+        &mut self,
+        player_id: PlayerId,
+        account: &str,
+        token: Option<&str>,
+    ) -> Result<(i64, String), AccountError> {
+        if self.wallets.values().any(|w| w.account == account) {
+            return Err(AccountError::AlreadyOnline);
+        }
+        let token = match self.tokens.get(account) {
+            Some(owner) if Some(owner.as_str()) != token => return Err(AccountError::WrongToken),
+            Some(owner) => owner.clone(),
+            None => {
+                let issued = new_token().ok_or(AccountError::NoRandomness)?;
+                self.tokens.insert(account.to_string(), issued.clone());
+                self.dirty = true;
+                issued
+            }
+        };
+
+        let balance_cents = *self
+            .accounts
+            .entry(account.to_string())
+            .or_insert(STARTING_BALANCE_CENTS);
+
+        self.wallets.insert(
+            player_id,
+            Wallet {
+                account: account.to_string(),
+                balance_cents,
+                recent_earnings: Vec::new(),
+            },
+        );
+
+        Ok((balance_cents, token))
+    }
+
+    /// Закрыть кошелёк отключившегося игрока; его баланс уйдёт в файл со
+    /// следующим `take_dirty`.
+    pub fn close_wallet(&mut self, player_id: PlayerId) {
+        if let Some(wallet) = self.wallets.remove(&player_id) {
+            self.accounts.insert(wallet.account, wallet.balance_cents);
+            self.dirty = true;
+        }
+    }
+
+    /// Баланс подключённого игрока.
+    pub fn balance(&self, player_id: PlayerId) -> Option<i64> {
+        self.wallets.get(&player_id).map(|w| w.balance_cents)
+    }
+
+    /// Начислить/списать сумму от имени сервера (без лимитов заработка).
+    ///
+    /// Баланс не уходит ниже нуля. Возвращает новый баланс.
+    pub fn grant(&mut self, player_id: PlayerId, delta_cents: i64) -> Option<i64> {
+        let wallet = self.wallets.get_mut(&player_id)?;
+        wallet.balance_cents = wallet.balance_cents.saturating_add(delta_cents).max(0);
+        let balance = wallet.balance_cents;
+        self.sync_account(player_id);
+        Some(balance)
+    }
+
+    /// Проверить заявку клиента об изменении кошелька.
+    ///
+    /// Правила:
+    /// - `old_cents` должен совпадать с балансом сервера, иначе клиент
+    ///   рассинхронизирован и получает коррекцию
+    /// - трата принимается, если баланс не уходит в минус
+    /// - заработок принимается в пределах `MAX_EARN_PER_REPORT_CENTS`
+    ///   и `MAX_EARN_PER_WINDOW_CENTS`
+    pub fn apply_report(
+        &mut self,
+        player_id: PlayerId,
+        old_cents: i64,
+        new_cents: i64,
+        now: Instant,
+    ) -> Option<ReportOutcome> {
+        let wallet = self.wallets.get_mut(&player_id)?;
+        let balance = wallet.balance_cents;
+
+        if old_cents != balance || new_cents < 0 {
+            return Some(ReportOutcome::Corrected {
+                balance_cents: balance,
+            });
+        }
+
+        let delta = new_cents - old_cents;
+
+        if delta > 0 {
+            let earned = wallet.earned_in_window(now);
+            if delta > MAX_EARN_PER_REPORT_CENTS || earned + delta > MAX_EARN_PER_WINDOW_CENTS {
+                logger::warn(&format!(
+                    "[economy] player {} ('{}'): отклонён заработок {} (за окно уже {})",
+                    player_id,
+                    wallet.account,
+                    format_money(delta),
+                    format_money(earned)
+                ));
+                return Some(ReportOutcome::Corrected {
+                    balance_cents: balance,
+                });
+            }
+            wallet.recent_earnings.push((now, delta));
+        }
+
+        wallet.balance_cents = new_cents;
+        self.sync_account(player_id);
+
+        Some(ReportOutcome::Accepted {
+            balance_cents: new_cents,
+        })
+    }
+
+    /// Перевод между двумя подключёнными игроками.
+    ///
+    /// Возвращает новые балансы `(from, to)`.
+    pub fn transfer(
+        &mut self,
+        from: PlayerId,
+        to: PlayerId,
+        amount_cents: i64,
+    ) -> Result<(i64, i64), TransferError> {
+        if amount_cents <= 0 {
+            return Err(TransferError::InvalidAmount);
+        }
+        if from == to {
+            return Err(TransferError::SelfTransfer);
+        }
+        if !self.wallets.contains_key(&to) {
+            return Err(TransferError::UnknownPlayer(to));
+        }
+        let from_balance = self
+            .balance(from)
+            .ok_or(TransferError::UnknownPlayer(from))?;
+        if from_balance < amount_cents {
+            return Err(TransferError::InsufficientFunds {
+                balance_cents: from_balance,
+            });
+        }
+
+        let from_new = from_balance - amount_cents;
+        let to_new = self.balance(to).unwrap_or(0).saturating_add(amount_cents);
+
+        if let Some(w) = self.wallets.get_mut(&from) {
+            w.balance_cents = from_new;
+        }
+        if let Some(w) = self.wallets.get_mut(&to) {
+            w.balance_cents = to_new;
+        }
+        self.sync_account(from);
+        self.sync_account(to);
+
+        Ok((from_new, to_new))
+    }
+
+    /// Перенести баланс кошелька в `accounts` и пометить файл устаревшим.
+    fn sync_account(&mut self, player_id: PlayerId) {
+        if let Some(wallet) = self.wallets.get(&player_id) {
+            self.accounts
+                .insert(wallet.account.clone(), wallet.balance_cents);
+            self.dirty = true;
+        }
+    }
+
+    /// Что записать в файл, если пора: есть изменения и с прошлой записи
+    /// прошло `SAVE_INTERVAL`. Сама запись — у вызывающего, без блокировки.
+    fn take_dirty(&mut self, now: Instant) -> Option<(PathBuf, AccountsFile)> {
+        if self
+            .last_save
+            .is_some_and(|t| now.duration_since(t) < SAVE_INTERVAL)
+        {
+            return None;
+        }
+        self.take_unsaved(now)
+    }
+
+    /// Несохранённые изменения — без оглядки на `SAVE_INTERVAL`.
+    fn take_unsaved(&mut self, now: Instant) -> Option<(PathBuf, AccountsFile)> {
+        if !self.dirty {
+            return None;
+        }
+        let path = self.path.clone()?;
+        self.dirty = false;
+        self.last_save = Some(now);
+        Some((
+            path,
+            AccountsFile {
+                accounts: self.accounts.clone(),
+                tokens: self.tokens.clone(),
+            },
+        ))
+    }
+}
+
+/// Вызывается из `tick_loop`: сбросить изменённые балансы на диск.
+pub fn tick(shared: &SharedServer, now: Instant) {
+    let pending = shared
+        .economy
+        .lock()
+        .ok()
+        .and_then(|mut eco| eco.take_dirty(now));
+    if let Some((path, file)) = pending {
+        save(&path, &file);
+    }
+}
+
+/// Сохранить всё несохранённое сейчас — при остановке сервера.
+pub fn flush(shared: &SharedServer) {
+    let pending = shared
+        .economy
+        .lock()
+        .ok()
+        .and_then(|mut eco| eco.take_unsaved(Instant::now()));
+    if let Some((path, file)) = pending {
+        save(&path, &file);
+    }
+}
+
+fn save(path: &std::path::Path, file: &AccountsFile) {
+    let json = match serde_json::to_string_pretty(file) {
+        Ok(s) => s,
+        Err(e) => {
+            logger::error(&format!("[economy] сериализация не удалась: {e}"));
+            return;
+        }
+    };
+
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+
+    if let Err(e) = std::fs::write(path, json) {
+        logger::error(&format!(
+            "[economy] не удалось сохранить {}: {e}",
+            path.display()
+        ));
+    }
+}
+
+/// Случайный токен аккаунта: 128 бит из генератора ОС, в hex.
+///
+/// `None` — ОС не дала случайности: угадываемый токен хуже отказа во входе.
+fn new_token() -> Option<String> {
+    let mut bytes = [0u8; 16];
+    if let Err(e) = getrandom::fill(&mut bytes) {
+        logger::error(&format!("[economy] нет случайности ОС для токена: {e}"));
+        return None;
+    }
+    Some(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Разобрать сумму в долларах (`"25"`, `"25.5"`, `"$25.50"`) в центы.
+pub fn parse_dollars(text: &str) -> Option<i64> {
+    let text = text.trim().trim_start_matches('$');
+    let (whole, frac) = match text.split_once('.') {
+        Some((w, f)) => (w, f),
+        None => (text, ""),
+    };
+
+    if whole.is_empty() || frac.len() > 2 {
+        return None;
+    }
+    if !whole.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let dollars: i64 = whole.parse().ok()?;
+    let cents: i64 = match frac.len() {
+        0 => 0,
+        1 => frac.parse::<i64>().ok()? * 10,
+        _ => frac.parse().ok()?,
+    };
+
+    dollars.checked_mul(100)?.checked_add(cents)
+}
+
+/// Формат "$ 600.00" — как в `sdk::game::player::money`.
+pub fn format_money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let abs = cents.abs();
+    format!("{sign}$ {}.{:02}", abs / 100, abs % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn economy_with(players: &[(PlayerId, &str)]) -> Economy {
+        let mut eco = Economy::in_memory();
+        for (id, name) in players {
+            eco.open_wallet(*id, name, None).unwrap();
+        }
+        eco
+    }
+
+    #[test]
+    fn spending_and_small_earnings_are_accepted() {
+        let mut eco = economy_with(&[(1, "Vito")]);
+        let now = Instant::now();
+
+        let start = STARTING_BALANCE_CENTS;
+        assert_eq!(
+            eco.apply_report(1, start, start - 1_500, now),
+            Some(ReportOutcome::Accepted {
+                balance_cents: start - 1_500
+            })
+        );
+        assert_eq!(
+            eco.apply_report(1, start - 1_500, start + 10_000, now),
+            Some(ReportOutcome::Accepted {
+                balance_cents: start + 10_000
+            })
+        );
+    }
+
+    #[test]
+    fn desynced_or_excessive_reports_are_corrected() {
+        let mut eco = economy_with(&[(1, "Vito")]);
+        let now = Instant::now();
+        let start = STARTING_BALANCE_CENTS;
+
+        // Клиент думает, что у него другой баланс.
+        assert_eq!(
+            eco.apply_report(1, start + 5, start + 100, now),
+            Some(ReportOutcome::Corrected {
+                balance_cents: start
+            })
+        );
+
+        // Слишком крупное поступление за раз.
+        assert_eq!(
+            eco.apply_report(1, start, start + MAX_EARN_PER_REPORT_CENTS + 1, now),
+            Some(ReportOutcome::Corrected {
+                balance_cents: start
+            })
+        );
+
+        // Лимит окна: несколько допустимых поступлений подряд.
+        let mut balance = start;
+        let step = MAX_EARN_PER_REPORT_CENTS;
+        while balance - start + step <= MAX_EARN_PER_WINDOW_CENTS {
+            let next = balance + step;
+            assert!(matches!(
+                eco.apply_report(1, balance, next, now),
+                Some(ReportOutcome::Accepted { .. })
+            ));
+            balance = next;
+        }
+        assert_eq!(
+            eco.apply_report(1, balance, balance + step, now),
+            Some(ReportOutcome::Corrected {
+                balance_cents: balance
+            })
+        );
+    }
+
+    #[test]
+    fn transfer_moves_money_between_wallets() {
+        let mut eco = economy_with(&[(1, "Vito"), (2, "Joe")]);
+        let start = STARTING_BALANCE_CENTS;
+
+        assert_eq!(
+            eco.transfer(1, 2, 2_500),
+            Ok((start - 2_500, start + 2_500))
+        );
+        assert_eq!(eco.transfer(1, 1, 100), Err(TransferError::SelfTransfer));
+        assert_eq!(
+            eco.transfer(1, 3, 100),
+            Err(TransferError::UnknownPlayer(3))
+        );
+        assert_eq!(eco.transfer(1, 2, 0), Err(TransferError::InvalidAmount));
+        assert_eq!(
+            eco.transfer(1, 2, start * 10),
+            Err(TransferError::InsufficientFunds {
+                balance_cents: start - 2_500
+            })
+        );
+    }
+
+    #[test]
+    fn balance_survives_reconnect_with_account_token() {
+        let mut eco = Economy::in_memory();
+        let (_, token) = eco.open_wallet(1, "Vito", None).unwrap();
+        eco.grant(1, 1_234);
+        eco.close_wallet(1);
+
+        assert_eq!(
+            eco.open_wallet(7, "Vito", Some(&token)),
+            Ok((STARTING_BALANCE_CENTS + 1_234, token))
+        );
+        assert_eq!(
+            eco.open_wallet(8, "Henry", None).map(|(b, _)| b),
+            Ok(STARTING_BALANCE_CENTS)
+        );
+    }
+
+    #[test]
+    fn tokens_are_random_128_bit_hex() {
+        let a = new_token().unwrap();
+        let b = new_token().unwrap();
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()), "{a}");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn account_cannot_be_taken_by_nickname_alone() {
+        let mut eco = Economy::in_memory();
+        let (_, token) = eco.open_wallet(1, "Vito", None).unwrap();
+
+        // Вторая сессия с тем же ником — даже с верным токеном.
+        assert_eq!(
+            eco.open_wallet(2, "Vito", Some(&token)),
+            Err(AccountError::AlreadyOnline)
+        );
+
+        eco.close_wallet(1);
+        assert_eq!(
+            eco.open_wallet(3, "Vito", None),
+            Err(AccountError::WrongToken)
+        );
+        assert_eq!(
+            eco.open_wallet(3, "Vito", Some("forged")),
+            Err(AccountError::WrongToken)
+        );
+        assert!(eco.open_wallet(3, "Vito", Some(&token)).is_ok());
+    }
+
+    #[test]
+    fn changes_are_flushed_at_most_once_per_interval() {
+        let mut eco = Economy::in_memory();
+        eco.path = Some(PathBuf::from("unused.json"));
+        let now = Instant::now();
+        assert!(eco.take_dirty(now).is_none());
+
+        eco.open_wallet(1, "Vito", None).unwrap();
+        eco.grant(1, 100);
+        let (_, file) = eco.take_dirty(now).unwrap();
+        assert_eq!(file.accounts["Vito"], STARTING_BALANCE_CENTS + 100);
+        assert!(file.tokens.contains_key("Vito"));
+
+        eco.grant(1, 100);
+        assert!(eco.take_dirty(now + SAVE_INTERVAL / 2).is_none());
+        assert!(eco.take_dirty(now + SAVE_INTERVAL).is_some());
+        assert!(eco.take_dirty(now + SAVE_INTERVAL * 3).is_none());
+
+        // При остановке интервал не ждём.
+        eco.grant(1, 100);
+        let (_, file) = eco.take_unsaved(now + SAVE_INTERVAL).unwrap();
+        assert_eq!(file.accounts["Vito"], STARTING_BALANCE_CENTS + 300);
+        assert!(eco.take_unsaved(now + SAVE_INTERVAL).is_none());
+    }
+
+    #[test]
+    fn parse_dollars_accepts_common_forms() {
+        assert_eq!(parse_dollars("25"), Some(2_500));
+        assert_eq!(parse_dollars("$25.5"), Some(2_550));
+        assert_eq!(parse_dollars("0.05"), Some(5));
+        assert_eq!(parse_dollars("-5"), None);
+        assert_eq!(parse_dollars("1.234"), None);
+        assert_eq!(parse_dollars("abc"), None);
+    }
+}
@@ -1,43 +1,140 @@
+mod appearance;
+mod combat;
+mod commands;
+mod config;
+mod deathmatch;
+mod dimension;
+mod economy;
+mod master;
+mod metrics;
+mod mode;
+mod query;
+mod race;
+mod recording;
+mod respawn;
+mod send_queue;
+mod spawn;
+mod teams;
+mod track;
+mod vehicles;
+mod world;
+
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Write};
-use std::net::{Shutdown, TcpListener, TcpStream};
-use std::sync::atomic::{AtomicU16, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, mpsc};
-use std::thread;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use common::logger;
-use protocol::{ClientPacket, DEFAULT_PORT, MAX_PLAYERS, PROTOCOL_VERSION, PlayerId, ServerPacket};
+use protocol::{
+    ClientPacket, MAX_PLAYERS, NetAppearance, NetPlayerEvent, NetPlayerSnapshot, PROTOCOL_VERSION,
+    PlayerId, ServerPacket,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+
+use combat::{CombatLog, HitReport};
+use config::ServerConfig;
+use dimension::{DimensionId, Dimensions};
+use economy::{AccountError, Economy, ReportOutcome};
+use metrics::Metrics;
+use mode::Modes;
+use recording::{Entry, Recorder};
+use respawn::RespawnTracker;
+use send_queue::{SendQueue, WriteEnd};
+use spawn::SpawnSelector;
+use teams::Teams;
+use vehicles::Vehicles;
+use world::WorldClock;
+
+/// Период серверного тика (респауны, таймеры режимов).
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Следующий выдаваемый PlayerId.
 static NEXT_PLAYER_ID: AtomicU16 = AtomicU16::new(1);
 
-/// Сколько snapshot-ов всего сервер принял.
-static SNAPSHOT_COUNT: AtomicU64 = AtomicU64::new(0);
+/// Очередь исходящих пакетов клиента (см. `send_queue`).
+#[derive(Clone)]
+struct ClientSender {
+    queue: Arc<SendQueue>,
+}
+
+impl ClientSender {
+    fn new() -> Self {
+        Self {
+            queue: Arc::new(SendQueue::new(send_queue::MAX_RELIABLE)),
+        }
+    }
+
+    /// Переполненная очередь пакет не примет — клиента уже отключают.
+    fn send(&self, packet: ServerPacket) {
+        self.queue.push(packet);
+    }
 
-/// Сколько event-ов всего сервер принял.
-static EVENT_COUNT: AtomicU64 = AtomicU64::new(0);
+    fn depth(&self) -> usize {
+        self.queue.len()
+    }
+}
 
 #[derive(Clone)]
 struct ClientHandle {
     #[allow(dead_code)]
     player_id: PlayerId,
-    sender: mpsc::Sender<ServerPacket>,
+    sender: ClientSender,
 }
 
 struct SharedServer {
+    config: ServerConfig,
     clients: Mutex<HashMap<PlayerId, ClientHandle>>,
     names: Mutex<HashMap<PlayerId, String>>,
+    /// Последний принятый snapshot каждого игрока.
+    snapshots: Mutex<HashMap<PlayerId, NetPlayerSnapshot>>,
+    /// Последний сообщённый внешний вид (см. `appearance`).
+    appearances: Mutex<HashMap<PlayerId, NetAppearance>>,
+    economy: Mutex<Economy>,
+    respawn: Mutex<RespawnTracker>,
+    spawns: Mutex<SpawnSelector>,
+    combat: Mutex<CombatLog>,
+    /// Режимы по измерениям.
+    mode: Mutex<Modes>,
+    teams: Mutex<Teams>,
+    dimensions: Mutex<Dimensions>,
+    vehicles: Mutex<Vehicles>,
+    world: Mutex<WorldClock>,
+    metrics: Metrics,
+    /// `None` — запись сессии выключена.
+    recorder: Option<Recorder>,
 }
 
 impl SharedServer {
-    fn new() -> Self {
+    fn new(config: ServerConfig, economy: Economy, recorder: Option<Recorder>) -> Self {
+        let respawn = RespawnTracker::new(config.respawn.clone());
+        let spawns = SpawnSelector::new(config.spawn_points.clone());
+        let mode = Modes::from_config(&config, Instant::now());
+        let teams = Teams::new(config.teams.clone());
+        let world = WorldClock::new(config.world.clone(), Instant::now());
+
         Self {
+            config,
             clients: Mutex::new(HashMap::new()),
             names: Mutex::new(HashMap::new()),
+            snapshots: Mutex::new(HashMap::new()),
+            appearances: Mutex::new(HashMap::new()),
+            economy: Mutex::new(economy),
+            respawn: Mutex::new(respawn),
+            spawns: Mutex::new(spawns),
+            combat: Mutex::new(CombatLog::new()),
+            mode: Mutex::new(mode),
+            teams: Mutex::new(teams),
+            dimensions: Mutex::new(Dimensions::default()),
+            vehicles: Mutex::new(Vehicles::default()),
+            world: Mutex::new(world),
+            metrics: Metrics::new(Instant::now()),
+            recorder,
         }
     }
 
-    fn insert_client(&self, player_id: PlayerId, sender: mpsc::Sender<ServerPacket>) {
+    fn insert_client(&self, player_id: PlayerId, sender: ClientSender) {
         if let Ok(mut clients) = self.clients.lock() {
             clients.insert(player_id, ClientHandle { player_id, sender });
         }
@@ -50,6 +147,37 @@ impl SharedServer {
         if let Ok(mut names) = self.names.lock() {
             names.remove(&player_id);
         }
+        if let Ok(mut snapshots) = self.snapshots.lock() {
+            snapshots.remove(&player_id);
+        }
+        if let Ok(mut appearances) = self.appearances.lock() {
+            appearances.remove(&player_id);
+        }
+        if let Ok(mut economy) = self.economy.lock() {
+            economy.close_wallet(player_id);
+        }
+        if let Ok(mut respawn) = self.respawn.lock() {
+            respawn.remove_player(player_id);
+        }
+        if let Ok(mut combat) = self.combat.lock() {
+            combat.remove_player(player_id);
+        }
+        let dimension = self
+            .dimensions
+            .lock()
+            .ok()
+            .and_then(|mut d| d.remove_player(player_id));
+        if let Some(dimension) = dimension
+            && let Ok(mut mode) = self.mode.lock()
+        {
+            mode.remove_player(dimension, player_id);
+        }
+        if let Ok(mut teams) = self.teams.lock() {
+            teams.remove_player(player_id);
+        }
+        if let Ok(mut vehicles) = self.vehicles.lock() {
+            vehicles.leave(player_id);
+        }
     }
 
     fn set_name(&self, player_id: PlayerId, name: String) {
@@ -69,7 +197,27 @@ impl SharedServer {
             .unwrap_or_default()
     }
 
-    #[allow(dead_code)]
+    fn set_snapshot(&self, snapshot: NetPlayerSnapshot) {
+        if let Ok(mut snapshots) = self.snapshots.lock() {
+            snapshots.insert(snapshot.player_id, snapshot);
+        }
+    }
+
+    fn get_snapshot(&self, player_id: PlayerId) -> Option<NetPlayerSnapshot> {
+        self.snapshots.lock().ok()?.get(&player_id).cloned()
+    }
+
+    fn list_snapshots(&self) -> Vec<NetPlayerSnapshot> {
+        self.snapshots
+            .lock()
+            .map(|m| m.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn balance(&self, player_id: PlayerId) -> Option<i64> {
+        self.economy.lock().ok()?.balance(player_id)
+    }
+
     fn send_to(&self, player_id: PlayerId, packet: ServerPacket) {
         let sender = {
             let clients = match self.clients.lock() {
@@ -80,7 +228,7 @@ impl SharedServer {
         };
 
         if let Some(tx) = sender {
-            let _ = tx.send(packet);
+            tx.send(packet);
         }
     }
 
@@ -104,12 +252,83 @@ impl SharedServer {
         };
 
         for tx in senders {
-            let _ = tx.send(packet.clone());
+            tx.send(packet.clone());
+        }
+    }
+
+    /// Разослать соседям `player_id` по измерению (см. `dimension`).
+    fn broadcast_dimension(&self, player_id: PlayerId, packet: ServerPacket) {
+        let neighbours = match self.dimensions.lock() {
+            Ok(d) => d.neighbours(player_id),
+            Err(_) => return,
+        };
+        let senders = {
+            let clients = match self.clients.lock() {
+                Ok(c) => c,
+                Err(_) => return,
+            };
+            neighbours
+                .iter()
+                .filter_map(|id| clients.get(id).map(|c| c.sender.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        for tx in senders {
+            tx.send(packet.clone());
+        }
+    }
+
+    /// Всем игрокам измерения `dimension`.
+    fn broadcast_to_dimension(&self, dimension: DimensionId, packet: ServerPacket) {
+        let members = match self.dimensions.lock() {
+            Ok(d) => d.members(dimension),
+            Err(_) => return,
+        };
+        let senders = {
+            let clients = match self.clients.lock() {
+                Ok(c) => c,
+                Err(_) => return,
+            };
+            members
+                .iter()
+                .filter_map(|id| clients.get(id).map(|c| c.sender.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        for tx in senders {
+            tx.send(packet.clone());
         }
     }
+
+    /// Видят ли игроки друг друга (одно измерение).
+    fn same_dimension(&self, a: PlayerId, b: PlayerId) -> bool {
+        self.dimensions.lock().is_ok_and(|d| d.same(a, b))
+    }
+
+    /// Записать в `.m2rec`, если запись включена. `entry` строится только тогда.
+    fn record(&self, player_id: PlayerId, entry: impl FnOnce() -> Entry) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(player_id, entry());
+        }
+    }
+
+    /// Глубина очереди отправки каждого клиента.
+    fn queue_depths(&self) -> Vec<(PlayerId, usize)> {
+        let mut depths: Vec<(PlayerId, usize)> = self
+            .clients
+            .lock()
+            .map(|c| c.iter().map(|(id, h)| (*id, h.sender.depth())).collect())
+            .unwrap_or_default();
+        depths.sort_unstable();
+        depths
+    }
 }
 
-fn main() {
+/// Весь сетевой ввод-вывод — соединения, тик, query, метрики, heartbeat
+/// мастеру — крутится задачами одного потока. Игровая логика синхронная:
+/// блокировки `SharedServer` никогда не держатся через `.await`.
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
     if let Err(e) = logger::init(
         logger::Level::Debug,
         logger::Target::Both,
@@ -118,14 +337,17 @@ fn main() {
         eprintln!("Logger init failed: {e}");
     }
 
+    let config = ServerConfig::load(config::DEFAULT_CONFIG_PATH);
+    let port = config.port;
+
     logger::info("=============================================================================");
     logger::info("  Mafia II: DE Multiplayer Server");
     logger::info(&format!("  Protocol v{}", PROTOCOL_VERSION));
     logger::info(&format!("  Max players: {}", MAX_PLAYERS));
-    logger::info(&format!("  Port: {}", DEFAULT_PORT));
+    logger::info(&format!("  Port: {}", port));
     logger::info("=============================================================================");
 
-    let listener = match TcpListener::bind(("0.0.0.0", DEFAULT_PORT)) {
+    let listener = match TcpListener::bind(("0.0.0.0", port)).await {
         Ok(l) => l,
         Err(e) => {
             logger::error(&format!("Bind failed: {e}"));
@@ -133,24 +355,135 @@ fn main() {
         }
     };
 
-    logger::info(&format!("Listening on 0.0.0.0:{DEFAULT_PORT}"));
+    logger::info(&format!("Listening on 0.0.0.0:{port}"));
+
+    logger::info(&format!(
+        "[server] mode='{}', spawn points: {}",
+        config.mode,
+        config.spawn_points.len()
+    ));
+
+    let recorder =
+        config
+            .recording
+            .dir
+            .as_ref()
+            .and_then(|dir| match Recorder::create(dir, &config.name) {
+                Ok(r) => {
+                    logger::info(&format!("[recording] writing {}", r.path().display()));
+                    Some(r)
+                }
+                Err(e) => {
+                    logger::error(&format!("[recording] cannot record into {dir}: {e}"));
+                    None
+                }
+            });
+
+    let shared = Arc::new(SharedServer::new(
+        config,
+        Economy::load(economy::DEFAULT_ACCOUNTS_PATH),
+        recorder,
+    ));
 
-    let shared = Arc::new(SharedServer::new());
+    tokio::spawn(tick_loop(Arc::clone(&shared)));
 
-    for incoming in listener.incoming() {
-        match incoming {
-            Ok(stream) => {
-                let shared = Arc::clone(&shared);
-                thread::spawn(move || handle_client(stream, shared));
+    query::spawn(Arc::clone(&shared), port).await;
+    master::spawn(&shared.config.master, port);
+    if let Some(addr) = shared.config.metrics.addr.clone() {
+        metrics::spawn(Arc::clone(&shared), &addr).await;
+    }
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_client(stream, Arc::clone(&shared)));
+                }
+                Err(e) => {
+                    logger::warn(&format!("Accept failed: {e}"));
+                }
+            },
+            () = &mut shutdown => break,
+        }
+    }
+
+    // Балансы пишутся раз в `SAVE_INTERVAL` — дописываем хвост.
+    logger::info("[server] остановка");
+    economy::flush(&shared);
+    if let Some(recorder) = &shared.recorder {
+        recorder.flush();
+    }
+}
+
+/// Ctrl+C или SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            logger::warn(&format!("[server] Ctrl+C не перехватывается: {e}"));
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut term) => {
+                term.recv().await;
             }
             Err(e) => {
-                logger::warn(&format!("Accept failed: {e}"));
+                logger::warn(&format!("[server] SIGTERM не перехватывается: {e}"));
+                std::future::pending::<()>().await;
             }
         }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
+/// Серверный тик: всё, что зависит от времени, а не от входящих пакетов.
+async fn tick_loop(shared: Arc<SharedServer>) {
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // Первый тик `interval` срабатывает сразу.
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let now = Instant::now();
+        respawn::tick(&shared, now);
+        economy::tick(&shared, now);
+        mode::tick(&shared, now);
+        world::tick(&shared, now);
+        shared.metrics.record_tick(now, now.elapsed());
+        if let Some(recorder) = &shared.recorder {
+            recorder.flush();
+        }
+    }
+}
+
+/// Игрок погиб (event `Death` или `is_dead` в snapshot). Повторы отсекаются.
+fn player_died(shared: &SharedServer, player_id: PlayerId) {
+    let now = Instant::now();
+    combat::handle_death(shared, player_id, now);
+    respawn::handle_death(shared, player_id, now);
+}
+
+/// Snapshot с `is_dead = false`.
+fn player_alive(shared: &SharedServer, player_id: PlayerId) {
+    if let Ok(mut combat) = shared.combat.lock() {
+        combat.on_alive(player_id);
+    }
+    if let Ok(mut respawn) = shared.respawn.lock() {
+        respawn.on_alive(player_id);
     }
 }
 
-fn handle_client(stream: TcpStream, shared: Arc<SharedServer>) {
+async fn handle_client(stream: TcpStream, shared: Arc<SharedServer>) {
     let peer = match stream.peer_addr() {
         Ok(a) => a.to_string(),
         Err(_) => "<unknown>".to_string(),
@@ -162,32 +495,36 @@ fn handle_client(stream: TcpStream, shared: Arc<SharedServer>) {
         "[server] accepted connection from {peer}, provisional player_id={player_id}"
     ));
 
-    let reader_stream = match stream.try_clone() {
-        Ok(s) => s,
-        Err(e) => {
-            logger::error(&format!("[server] try_clone failed: {e}"));
-            return;
-        }
-    };
+    let (reader_stream, writer_stream) = stream.into_split();
 
-    let writer_stream = stream;
-
-    let (tx, rx) = mpsc::channel::<ServerPacket>();
+    let tx = ClientSender::new();
     shared.insert_client(player_id, tx.clone());
 
-    // Writer thread
-    let writer_handle = thread::spawn(move || {
-        writer_thread(writer_stream, rx, player_id);
-    });
+    let mut writer = {
+        let shared = Arc::clone(&shared);
+        let queue = Arc::clone(&tx.queue);
+        tokio::spawn(async move {
+            writer_task(writer_stream, &queue, player_id, &shared).await;
+        })
+    };
 
-    // Reader loop
-    let result = reader_loop(reader_stream, player_id, &shared, tx.clone());
+    // Задача записи сама завершается только при ошибке (переполнение,
+    // таймаут) — тогда бросаем и чтение.
+    let (result, writer_done) = tokio::select! {
+        result = reader_loop(reader_stream, player_id, &shared, tx.clone()) => (result, false),
+        _ = &mut writer => (Err("send failed".to_string()), true),
+    };
 
-    // Cleanup
+    // Cleanup. Соединение без Connect другим игрокам не показывалось —
+    // despawn для него не шлём. Соседей по измерению берём до удаления.
+    let name = shared.get_name(player_id);
+    if name.is_some() {
+        vehicles::handle_leave(&shared, player_id);
+        shared.broadcast_dimension(player_id, ServerPacket::PlayerDespawn { player_id });
+    }
     shared.remove_client(player_id);
-    shared.broadcast_except(Some(player_id), ServerPacket::PlayerDespawn { player_id });
 
-    if let Some(name) = shared.get_name(player_id) {
+    if let Some(name) = name {
         logger::info(&format!(
             "[server] player {} ('{}') disconnected",
             player_id, name
@@ -196,8 +533,11 @@ fn handle_client(stream: TcpStream, shared: Arc<SharedServer>) {
         logger::info(&format!("[server] player {} disconnected", player_id));
     }
 
-    drop(tx);
-    let _ = writer_handle.join();
+    tx.queue.close();
+    if !writer_done {
+        let _ = writer.await;
+    }
+    shared.record(player_id, || Entry::Closed);
 
     if let Err(e) = result {
         logger::warn(&format!(
@@ -207,11 +547,11 @@ fn handle_client(stream: TcpStream, shared: Arc<SharedServer>) {
     }
 }
 
-fn reader_loop(
-    stream: TcpStream,
+async fn reader_loop(
+    stream: OwnedReadHalf,
     player_id: PlayerId,
     shared: &Arc<SharedServer>,
-    tx: mpsc::Sender<ServerPacket>,
+    tx: ClientSender,
 ) -> Result<(), String> {
     let mut reader = BufReader::new(stream);
     let mut line = String::new();
@@ -222,6 +562,7 @@ fn reader_loop(
 
         let read = reader
             .read_line(&mut line)
+            .await
             .map_err(|e| format!("read_line failed: {e}"))?;
 
         if read == 0 {
@@ -236,11 +577,28 @@ fn reader_loop(
             continue;
         }
 
-        let packet = serde_json::from_str::<ClientPacket>(&line)
-            .map_err(|e| format!("invalid client packet json: {e}; line={line}"))?;
+        let packet = match serde_json::from_str::<ClientPacket>(&line) {
+            Ok(p) => p,
+            Err(e) => {
+                shared.metrics.record_in("invalid", read);
+                shared.metrics.kicked("invalid_packet");
+                // Саму строку не логируем: в ней может быть пароль.
+                return Err(format!(
+                    "invalid client packet json ({} bytes): {e}",
+                    line.len()
+                ));
+            }
+        };
+        let received = shared.metrics.record_in(packet.kind(), read);
+        shared.record(player_id, || Entry::In(packet.clone()));
 
         match packet {
-            ClientPacket::Connect { name, version } => {
+            ClientPacket::Connect {
+                name,
+                version,
+                password,
+                account_token,
+            } => {
                 if welcomed {
                     logger::warn(&format!(
                         "[server] player {} sent duplicate Connect",
@@ -250,7 +608,8 @@ fn reader_loop(
                 }
 
                 if version != PROTOCOL_VERSION {
-                    let _ = tx.send(ServerPacket::ConnectRejected {
+                    shared.metrics.connect_rejected("protocol_mismatch");
+                    tx.send(ServerPacket::ConnectRejected {
                         reason: format!(
                             "Protocol mismatch: client={} server={}",
                             version, PROTOCOL_VERSION
@@ -259,31 +618,102 @@ fn reader_loop(
                     return Ok(());
                 }
 
+                if let Some(expected) = &shared.config.password
+                    && password.as_deref() != Some(expected.as_str())
+                {
+                    logger::warn(&format!(
+                        "[server] player {} rejected: wrong password",
+                        player_id
+                    ));
+                    shared.metrics.connect_rejected("wrong_password");
+                    tx.send(ServerPacket::ConnectRejected {
+                        reason: "Неверный пароль".to_string(),
+                    });
+                    return Ok(());
+                }
+
+                // Ник — ключ аккаунта: второй сессии с тем же ником и входу
+                // без токена владельца отказываем.
+                let wallet = match shared.economy.lock() {
+                    Ok(mut eco) => eco.open_wallet(player_id, &name, account_token.as_deref()),
+                    Err(_) => return Err("economy mutex poisoned".to_string()),
+                };
+                let (balance_cents, token) = match wallet {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        logger::warn(&format!(
+                            "[server] player {} rejected as '{}': {e}",
+                            player_id, name
+                        ));
+                        shared.metrics.connect_rejected(match e {
+                            AccountError::AlreadyOnline => "name_online",
+                            AccountError::WrongToken => "wrong_token",
+                            AccountError::NoRandomness => "no_randomness",
+                        });
+                        tx.send(ServerPacket::ConnectRejected {
+                            reason: e.to_string(),
+                        });
+                        return Ok(());
+                    }
+                };
+
                 shared.set_name(player_id, name.clone());
 
                 // Welcome
-                let _ = tx.send(ServerPacket::ConnectAccepted { player_id });
+                tx.send(ServerPacket::ConnectAccepted { player_id });
+                tx.send(ServerPacket::AccountToken { token });
+
+                // Кошелёк: сервер сразу навязывает клиенту свой баланс.
+                tx.send(ServerPacket::MoneySet { balance_cents });
+
+                if let Ok(mut combat) = shared.combat.lock() {
+                    combat.add_player(player_id);
+                }
+                if let Ok(mut respawn) = shared.respawn.lock() {
+                    respawn.add_player(player_id);
+                    if shared.config.spawn_on_connect {
+                        respawn.force_respawn(player_id, Instant::now());
+                    }
+                }
+
+                if let Ok(mut dimensions) = shared.dimensions.lock() {
+                    dimensions.add_player(player_id);
+                }
 
                 // Existing players -> newcomer
                 for (other_id, other_name) in shared.list_named_players() {
-                    if other_id == player_id {
+                    if other_id == player_id || !shared.same_dimension(player_id, other_id) {
                         continue;
                     }
-                    let _ = tx.send(ServerPacket::PlayerSpawn {
+                    tx.send(ServerPacket::PlayerSpawn {
                         player_id: other_id,
                         name: other_name,
                     });
+                    if let Some(packet) = appearance::packet(shared, other_id) {
+                        tx.send(packet);
+                    }
+                    if let Some(packet) = vehicles::enter_packet(shared, other_id) {
+                        tx.send(packet);
+                    }
                 }
 
                 // Newcomer -> others
-                shared.broadcast_except(
-                    Some(player_id),
+                shared.broadcast_dimension(
+                    player_id,
                     ServerPacket::PlayerSpawn {
                         player_id,
                         name: name.clone(),
                     },
                 );
 
+                world::send_state(shared, player_id);
+                teams::send_state(shared, player_id);
+                mode::send_state(shared, player_id);
+                shared.broadcast_dimension(
+                    player_id,
+                    combat::score_packet(player_id, combat::Score::default()),
+                );
+
                 welcomed = true;
 
                 logger::info(&format!(
@@ -304,15 +734,27 @@ fn reader_loop(
                 // Никогда не доверяем player_id клиента.
                 snapshot.player_id = player_id;
 
-                let n = SNAPSHOT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
-                if n % 20 == 0 {
+                if received.is_multiple_of(20) {
                     logger::debug(&format!(
                         "[server] snapshot count={} from player {}",
-                        n, player_id
+                        received, player_id
                     ));
                 }
 
-                shared.broadcast_except(Some(player_id), ServerPacket::Snapshot(snapshot));
+                if snapshot.is_dead {
+                    player_died(shared, player_id);
+                } else {
+                    player_alive(shared, player_id);
+                    mode::on_position(shared, player_id, snapshot.position, Instant::now());
+                }
+                if snapshot.in_vehicle
+                    && let Ok(mut vehicles) = shared.vehicles.lock()
+                {
+                    vehicles.driver_moved(player_id, snapshot.position);
+                }
+
+                shared.set_snapshot(snapshot.clone());
+                shared.broadcast_dimension(player_id, ServerPacket::Snapshot(snapshot));
             }
 
             ClientPacket::Event(event) => {
@@ -320,13 +762,27 @@ fn reader_loop(
                     continue;
                 }
 
-                let n = EVENT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
                 logger::debug(&format!(
                     "[server] event #{} from player {}: {:?}",
-                    n, player_id, event
+                    received, player_id, event
                 ));
 
-                shared.broadcast_except(Some(player_id), ServerPacket::Event { player_id, event });
+                if let NetPlayerEvent::ShotFired {
+                    weapon_id,
+                    origin,
+                    direction,
+                    ..
+                } = event
+                    && !combat::check_shot(shared, player_id, weapon_id, origin, direction)
+                {
+                    continue;
+                }
+
+                if matches!(event, NetPlayerEvent::Death) {
+                    player_died(shared, player_id);
+                }
+
+                shared.broadcast_dimension(player_id, ServerPacket::Event { player_id, event });
             }
 
             ClientPacket::ChatMessage { text } => {
@@ -334,43 +790,152 @@ fn reader_loop(
                     continue;
                 }
 
-                shared.broadcast_except(
-                    Some(player_id),
-                    ServerPacket::ChatMessage { player_id, text },
+                if commands::is_command(&text) {
+                    commands::handle(shared, player_id, &text);
+                    continue;
+                }
+
+                shared
+                    .broadcast_dimension(player_id, ServerPacket::ChatMessage { player_id, text });
+            }
+
+            ClientPacket::TeamChat { text } => {
+                if !welcomed {
+                    continue;
+                }
+
+                teams::handle_chat(shared, player_id, text);
+            }
+
+            ClientPacket::MoneyReport {
+                old_cents,
+                new_cents,
+            } => {
+                if !welcomed {
+                    continue;
+                }
+
+                let outcome = match shared.economy.lock() {
+                    Ok(mut eco) => {
+                        eco.apply_report(player_id, old_cents, new_cents, Instant::now())
+                    }
+                    Err(_) => None,
+                };
+
+                if let Some(ReportOutcome::Corrected { balance_cents }) = outcome {
+                    logger::debug(&format!(
+                        "[server] player {} money report {} -> {} corrected to {}",
+                        player_id, old_cents, new_cents, balance_cents
+                    ));
+                    tx.send(ServerPacket::MoneySet { balance_cents });
+                }
+            }
+
+            ClientPacket::Hit {
+                victim_id,
+                weapon_id,
+                damage,
+                headshot,
+            } => {
+                if !welcomed {
+                    continue;
+                }
+
+                combat::handle_hit(
+                    shared,
+                    HitReport {
+                        attacker: player_id,
+                        victim: victim_id,
+                        weapon_id,
+                        damage,
+                        headshot,
+                    },
                 );
             }
+
+            ClientPacket::Ping { sent_ms } => {
+                tx.send(ServerPacket::Pong { sent_ms });
+            }
+
+            ClientPacket::Appearance(appearance) => {
+                if !welcomed {
+                    continue;
+                }
+
+                appearance::handle(shared, player_id, appearance);
+            }
+
+            ClientPacket::VehicleEnter {
+                model_hash,
+                position,
+                seat,
+            } => {
+                if !welcomed {
+                    continue;
+                }
+
+                vehicles::handle_enter(shared, player_id, model_hash, position, seat);
+            }
+
+            ClientPacket::VehicleLeave => {
+                if !welcomed {
+                    continue;
+                }
+
+                vehicles::handle_leave(shared, player_id);
+            }
+
+            ClientPacket::VehicleSnapshot(snapshot) => {
+                if !welcomed {
+                    continue;
+                }
+
+                vehicles::handle_snapshot(shared, player_id, snapshot);
+            }
         }
     }
 }
 
-fn writer_thread(mut stream: TcpStream, rx: mpsc::Receiver<ServerPacket>, player_id: PlayerId) {
-    for packet in rx {
-        let json = match serde_json::to_string(&packet) {
-            Ok(s) => s,
-            Err(e) => {
-                logger::warn(&format!(
-                    "[server] failed to serialize packet for player {}: {}",
-                    player_id, e
-                ));
-                continue;
-            }
-        };
-
-        if let Err(e) = stream.write_all(json.as_bytes()) {
+async fn writer_task(
+    mut stream: OwnedWriteHalf,
+    queue: &SendQueue,
+    player_id: PlayerId,
+    shared: &SharedServer,
+) {
+    let end = send_queue::write_loop(
+        &mut stream,
+        queue,
+        send_queue::WRITE_TIMEOUT,
+        |packet, bytes| {
+            shared.metrics.record_out(packet.kind(), bytes);
+            shared.record(player_id, || Entry::Out(packet.clone()));
+        },
+    )
+    .await;
+
+    match end {
+        WriteEnd::Closed => {}
+        WriteEnd::Overflowed => {
             logger::warn(&format!(
-                "[server] write failed for player {}: {}",
-                player_id, e
+                "[server] player {} disconnected: send queue overflow",
+                player_id
+            ));
+            shared.metrics.kicked("send_queue_overflow");
+        }
+        WriteEnd::WriteFailed(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            logger::warn(&format!(
+                "[server] player {} disconnected: write timed out",
+                player_id
             ));
-            break;
+            shared.metrics.kicked("write_timeout");
         }
-        if let Err(e) = stream.write_all(b"\n") {
+        WriteEnd::WriteFailed(e) => {
             logger::warn(&format!(
-                "[server] write newline failed for player {}: {}",
+                "[server] write failed for player {}: {}",
                 player_id, e
             ));
-            break;
         }
     }
 
-    let _ = stream.shutdown(Shutdown::Both);
+    let _ = stream.shutdown().await;
 }
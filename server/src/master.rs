@@ -0,0 +1,82 @@
+//! Публикация на мастер-сервере: `Register` раз в `heartbeat_secs`.
+//!
+//! Мастер на каждый heartbeat опрашивает нас UDP query, поэтому query
+//! должен быть запущен раньше. В лог пишутся только смены состояния, чтобы
+//! недоступный мастер не засыпал лог каждые полминуты.
+
+use std::time::Duration;
+
+use common::logger;
+use protocol::master::{MasterRequest, MasterResponse};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use crate::config::MasterConfig;
+
+/// Таймаут подключения к мастеру.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Таймаут ответа: мастер успевает опросить нас query.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Запустить heartbeat, если мастер указан в конфиге.
+pub fn spawn(config: &MasterConfig, port: u16) {
+    let Some(addr) = config.addr.clone() else {
+        return;
+    };
+    let interval = Duration::from_secs(config.heartbeat_secs.max(1));
+
+    logger::info(&format!(
+        "[master] publishing to {addr} every {}s",
+        interval.as_secs()
+    ));
+    tokio::spawn(async move { heartbeat_loop(&addr, port, interval).await });
+}
+
+async fn heartbeat_loop(addr: &str, port: u16, interval: Duration) {
+    let mut registered = None;
+    loop {
+        let result = register(addr, port).await;
+        let ok = result.is_ok();
+        if registered != Some(ok) {
+            match result {
+                Ok(()) => logger::info(&format!("[master] registered on {addr}")),
+                Err(e) => logger::warn(&format!("[master] register on {addr} failed: {e}")),
+            }
+            registered = Some(ok);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn register(addr: &str, port: u16) -> Result<(), String> {
+    let mut stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+        .await
+        .map_err(|_| "connect timed out".to_string())?
+        .map_err(|e| format!("connect failed: {e}"))?;
+
+    let mut line = serde_json::to_string(&MasterRequest::Register { port })
+        .map_err(|e| format!("serialize failed: {e}"))?;
+    line.push('\n');
+    stream
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("write failed: {e}"))?;
+
+    let mut reply = String::new();
+    timeout(
+        RESPONSE_TIMEOUT,
+        BufReader::new(&mut stream).read_line(&mut reply),
+    )
+    .await
+    .map_err(|_| "read timed out".to_string())?
+    .map_err(|e| format!("read failed: {e}"))?;
+
+    match serde_json::from_str::<MasterResponse>(reply.trim_end()) {
+        Ok(MasterResponse::Registered) => Ok(()),
+        Ok(MasterResponse::Error { reason }) => Err(reason),
+        Ok(other) => Err(format!("unexpected response: {other:?}")),
+        Err(e) => Err(format!("invalid response: {e}")),
+    }
+}
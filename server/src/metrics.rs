@@ -0,0 +1,430 @@
+//! Метрики сервера и HTTP-эндпоинт для них.
+//!
+//! `GET /metrics` — текстовый формат Prometheus, `GET /health` — JSON со
+//! статусом: 503, если серверный тик не отрабатывал дольше `TICK_STALL`.
+//! Слушаем `metrics.addr` из конфига (по умолчанию только localhost).
+//!
+//! Счётчики пакетов — по имени варианта (`ClientPacket::kind`), байты — вместе
+//! с `\n`. Глубина очереди отправки берётся из `ClientSender` каждого клиента.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::PlayerId;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::SharedServer;
+
+/// Тик молчит дольше — `/health` отвечает 503.
+const TICK_STALL: Duration = Duration::from_secs(2);
+
+/// Сколько ждать строку запроса HTTP.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Максимальная длина строки запроса HTTP.
+const MAX_REQUEST_LINE: u64 = 1024;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Traffic {
+    packets: u64,
+    bytes: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct TickStats {
+    count: u64,
+    total: Duration,
+    last: Duration,
+    max: Duration,
+    last_at: Option<Instant>,
+}
+
+/// Счётчики сервера. Всё под своими мьютексами — пишут задачи клиентов.
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    inbound: Mutex<BTreeMap<&'static str, Traffic>>,
+    outbound: Mutex<BTreeMap<&'static str, Traffic>>,
+    connects_rejected: Mutex<BTreeMap<&'static str, u64>>,
+    kicks: Mutex<BTreeMap<&'static str, u64>>,
+    hits_rejected: Mutex<BTreeMap<&'static str, u64>>,
+    shots_rejected: Mutex<BTreeMap<&'static str, u64>>,
+    tick: Mutex<TickStats>,
+}
+
+impl Metrics {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            inbound: Mutex::new(BTreeMap::new()),
+            outbound: Mutex::new(BTreeMap::new()),
+            connects_rejected: Mutex::new(BTreeMap::new()),
+            kicks: Mutex::new(BTreeMap::new()),
+            hits_rejected: Mutex::new(BTreeMap::new()),
+            shots_rejected: Mutex::new(BTreeMap::new()),
+            tick: Mutex::new(TickStats::default()),
+        }
+    }
+
+    /// Принят пакет. Возвращает, сколько пакетов этого типа принято всего.
+    pub fn record_in(&self, kind: &'static str, bytes: usize) -> u64 {
+        add_traffic(&self.inbound, kind, bytes)
+    }
+
+    /// Пакет записан в сокет.
+    pub fn record_out(&self, kind: &'static str, bytes: usize) {
+        add_traffic(&self.outbound, kind, bytes);
+    }
+
+    pub fn connect_rejected(&self, reason: &'static str) {
+        increment(&self.connects_rejected, reason);
+    }
+
+    /// Сервер сам разорвал соединение с клиентом.
+    pub fn kicked(&self, reason: &'static str) {
+        increment(&self.kicks, reason);
+    }
+
+    pub fn hit_rejected(&self, reason: &'static str) {
+        increment(&self.hits_rejected, reason);
+    }
+
+    /// `ShotFired` не сошёлся со snapshot'ом стрелка и не разослан.
+    pub fn shot_rejected(&self, reason: &'static str) {
+        increment(&self.shots_rejected, reason);
+    }
+
+    /// Тик, начавшийся в `started`, занял `duration`.
+    pub fn record_tick(&self, started: Instant, duration: Duration) {
+        if let Ok(mut tick) = self.tick.lock() {
+            tick.count += 1;
+            tick.total += duration;
+            tick.last = duration;
+            tick.max = tick.max.max(duration);
+            tick.last_at = Some(started);
+        }
+    }
+
+    /// Тик отрабатывает. До первого тика даём `TICK_STALL` на старт.
+    pub fn is_ticking(&self, now: Instant) -> bool {
+        let last = self
+            .tick
+            .lock()
+            .ok()
+            .and_then(|t| t.last_at)
+            .unwrap_or(self.started);
+        now.saturating_duration_since(last) < TICK_STALL
+    }
+
+    pub fn uptime(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+
+    /// Текстовый формат Prometheus.
+    ///
+    /// `connections` — открытые TCP-соединения, `players` — прошедшие
+    /// `Connect`, `queues` — глубина очереди отправки по игрокам.
+    pub fn render(
+        &self,
+        now: Instant,
+        connections: usize,
+        players: usize,
+        queues: &[(PlayerId, usize)],
+    ) -> String {
+        let mut out = String::new();
+
+        gauge(
+            &mut out,
+            "m2mp_connections",
+            "Open TCP connections.",
+            connections,
+        );
+        gauge(
+            &mut out,
+            "m2mp_players",
+            "Players that completed Connect.",
+            players,
+        );
+        gauge(
+            &mut out,
+            "m2mp_uptime_seconds",
+            "Seconds since server start.",
+            self.uptime(now).as_secs(),
+        );
+
+        let inbound = snapshot(&self.inbound);
+        let outbound = snapshot(&self.outbound);
+        traffic(&mut out, "received", &inbound);
+        traffic(&mut out, "sent", &outbound);
+
+        header(
+            &mut out,
+            "m2mp_send_queue_depth",
+            "Packets queued for a client but not yet written.",
+            "gauge",
+        );
+        for (player, depth) in queues {
+            let _ = writeln!(out, "m2mp_send_queue_depth{{player=\"{player}\"}} {depth}");
+        }
+        gauge(
+            &mut out,
+            "m2mp_send_queue_depth_max",
+            "Deepest send queue among clients.",
+            queues.iter().map(|(_, d)| *d).max().unwrap_or(0),
+        );
+
+        let tick = self.tick.lock().map(|t| *t).unwrap_or_default();
+        header(
+            &mut out,
+            "m2mp_tick_duration_seconds",
+            "Server tick duration.",
+            "summary",
+        );
+        let _ = writeln!(
+            out,
+            "m2mp_tick_duration_seconds_sum {}",
+            tick.total.as_secs_f64()
+        );
+        let _ = writeln!(out, "m2mp_tick_duration_seconds_count {}", tick.count);
+        gauge(
+            &mut out,
+            "m2mp_tick_duration_last_seconds",
+            "Duration of the latest tick.",
+            tick.last.as_secs_f64(),
+        );
+        gauge(
+            &mut out,
+            "m2mp_tick_duration_max_seconds",
+            "Longest tick since start.",
+            tick.max.as_secs_f64(),
+        );
+
+        counter_by_reason(
+            &mut out,
+            "m2mp_connects_rejected_total",
+            "Connect attempts refused by the server.",
+            &snapshot(&self.connects_rejected),
+        );
+        counter_by_reason(
+            &mut out,
+            "m2mp_kicks_total",
+            "Connections closed by the server.",
+            &snapshot(&self.kicks),
+        );
+        counter_by_reason(
+            &mut out,
+            "m2mp_hits_rejected_total",
+            "Hit reports that failed validation.",
+            &snapshot(&self.hits_rejected),
+        );
+        counter_by_reason(
+            &mut out,
+            "m2mp_shots_rejected_total",
+            "Shot events that did not match the shooter's last snapshot.",
+            &snapshot(&self.shots_rejected),
+        );
+
+        out
+    }
+}
+
+fn add_traffic(
+    map: &Mutex<BTreeMap<&'static str, Traffic>>,
+    kind: &'static str,
+    bytes: usize,
+) -> u64 {
+    let Ok(mut map) = map.lock() else {
+        return 0;
+    };
+    let entry = map.entry(kind).or_default();
+    entry.packets += 1;
+    entry.bytes += bytes as u64;
+    entry.packets
+}
+
+fn increment(map: &Mutex<BTreeMap<&'static str, u64>>, key: &'static str) {
+    if let Ok(mut map) = map.lock() {
+        *map.entry(key).or_default() += 1;
+    }
+}
+
+fn snapshot<T: Clone>(map: &Mutex<BTreeMap<&'static str, T>>) -> BTreeMap<&'static str, T> {
+    map.lock().map(|m| m.clone()).unwrap_or_default()
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
+    header(out, name, help, "gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn traffic(out: &mut String, direction: &str, map: &BTreeMap<&'static str, Traffic>) {
+    let packets = format!("m2mp_packets_{direction}_total");
+    header(
+        out,
+        &packets,
+        &format!("Packets {direction} by type."),
+        "counter",
+    );
+    for (kind, t) in map {
+        let _ = writeln!(out, "{packets}{{type=\"{kind}\"}} {}", t.packets);
+    }
+    let bytes = format!("m2mp_bytes_{direction}_total");
+    header(
+        out,
+        &bytes,
+        &format!("Bytes {direction} by packet type."),
+        "counter",
+    );
+    for (kind, t) in map {
+        let _ = writeln!(out, "{bytes}{{type=\"{kind}\"}} {}", t.bytes);
+    }
+}
+
+fn counter_by_reason(out: &mut String, name: &str, help: &str, map: &BTreeMap<&'static str, u64>) {
+    header(out, name, help, "counter");
+    for (reason, n) in map {
+        let _ = writeln!(out, "{name}{{reason=\"{reason}\"}} {n}");
+    }
+}
+
+/// Запустить HTTP-эндпоинт на `addr` отдельной задачей.
+pub async fn spawn(shared: Arc<SharedServer>, addr: &str) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            logger::error(&format!("[metrics] HTTP bind {addr} failed: {e}"));
+            return;
+        }
+    };
+
+    logger::info(&format!("[metrics] http://{addr}/metrics, /health"));
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_http(stream, Arc::clone(&shared)));
+                }
+                Err(e) => logger::debug(&format!("[metrics] accept failed: {e}")),
+            }
+        }
+    });
+}
+
+async fn handle_http(mut stream: TcpStream, shared: Arc<SharedServer>) {
+    let mut line = String::new();
+    let mut reader = BufReader::new((&mut stream).take(MAX_REQUEST_LINE));
+    let read = tokio::time::timeout(HTTP_TIMEOUT, reader.read_line(&mut line)).await;
+    if !matches!(read, Ok(Ok(_))) {
+        return;
+    }
+
+    let (status, content_type, body) = route(&line, &shared, Instant::now());
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+fn route(
+    request_line: &str,
+    shared: &SharedServer,
+    now: Instant,
+) -> (&'static str, &'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return ("400 Bad Request", "text/plain", "bad request\n".to_string());
+    };
+    if method != "GET" {
+        return (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".to_string(),
+        );
+    }
+
+    let players = shared.list_named_players().len();
+    match path {
+        "/metrics" => {
+            let queues = shared.queue_depths();
+            let body = shared.metrics.render(now, queues.len(), players, &queues);
+            ("200 OK", "text/plain; version=0.0.4", body)
+        }
+        "/health" => {
+            let ticking = shared.metrics.is_ticking(now);
+            let body = serde_json::json!({
+                "status": if ticking { "ok" } else { "tick_stalled" },
+                "players": players,
+                "uptime_secs": shared.metrics.uptime(now).as_secs(),
+            })
+            .to_string();
+            let status = if ticking {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            (status, "application/json", body)
+        }
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_exports_counters_in_prometheus_format() {
+        let t0 = Instant::now();
+        let metrics = Metrics::new(t0);
+
+        assert_eq!(metrics.record_in("Snapshot", 100), 1);
+        assert_eq!(metrics.record_in("Snapshot", 120), 2);
+        metrics.record_out("ChatMessage", 40);
+        metrics.connect_rejected("wrong_password");
+        metrics.kicked("invalid_packet");
+        metrics.record_tick(t0, Duration::from_millis(3));
+
+        let text = metrics.render(t0, 2, 1, &[(1, 0), (2, 5)]);
+        for line in [
+            "# TYPE m2mp_players gauge",
+            "m2mp_connections 2",
+            "m2mp_players 1",
+            "m2mp_packets_received_total{type=\"Snapshot\"} 2",
+            "m2mp_bytes_received_total{type=\"Snapshot\"} 220",
+            "m2mp_packets_sent_total{type=\"ChatMessage\"} 1",
+            "m2mp_send_queue_depth{player=\"2\"} 5",
+            "m2mp_send_queue_depth_max 5",
+            "m2mp_tick_duration_seconds_count 1",
+            "m2mp_connects_rejected_total{reason=\"wrong_password\"} 1",
+            "m2mp_kicks_total{reason=\"invalid_packet\"} 1",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing `{line}` in:\n{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn health_fails_when_tick_stalls() {
+        let t0 = Instant::now();
+        let metrics = Metrics::new(t0);
+        // Даём время на первый тик.
+        assert!(metrics.is_ticking(t0 + Duration::from_secs(1)));
+        assert!(!metrics.is_ticking(t0 + TICK_STALL));
+
+        metrics.record_tick(t0 + Duration::from_secs(5), Duration::from_millis(1));
+        assert!(metrics.is_ticking(t0 + Duration::from_secs(6)));
+        assert!(!metrics.is_ticking(t0 + Duration::from_secs(5) + TICK_STALL));
+    }
+}
@@ -0,0 +1,535 @@
+//! Игровые режимы измерений и рассылка их состояния.
+//!
+//! У каждого измерения свой режим: измерение новичков берёт ключ `mode` в
+//! `server.json`, остальные — `dimensions.modes`, без записи — freeroam.
+//! Freeroam считает K/D без ограничений; deathmatch добавляет раунды с
+//! лимитами; race — заезды по чекпоинтам без счёта фрагов.
+//!
+//! Участники режима — игроки его измерения, и всё состояние режима
+//! (раунды, заезды, счёт) рассылается только им.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::{NetCheckpoint, NetRaceResult, NetVec3, PlayerId, RoundEndReason, ServerPacket};
+
+use crate::SharedServer;
+use crate::combat::{self, Score};
+use crate::config::{DEFAULT_MODE, ServerConfig};
+use crate::deathmatch::{Deathmatch, RoundChange};
+use crate::dimension::{DEFAULT_DIMENSION, DimensionId};
+use crate::race::{Race, RaceChange, RaceEvent, RaceResult};
+use crate::track::Track;
+
+#[derive(Debug)]
+pub enum GameMode {
+    Freeroam,
+    Deathmatch(Deathmatch),
+    Race(Race),
+}
+
+impl GameMode {
+    /// Режим `name` с правилами из конфига.
+    pub fn from_config(name: &str, config: &ServerConfig, now: Instant) -> Self {
+        match name {
+            "deathmatch" => Self::Deathmatch(Deathmatch::new(config.deathmatch.clone(), now)),
+            "race" => {
+                let rules = &config.race;
+                match Track::load_named(&rules.tracks_dir, &rules.track) {
+                    Ok(track) => {
+                        logger::info(&format!(
+                            "[mode] трасса '{}': {} чекпоинтов, {} кр.",
+                            track.name,
+                            track.checkpoints.len(),
+                            track.laps
+                        ));
+                        Self::Race(Race::new(rules.clone(), track))
+                    }
+                    Err(e) => {
+                        logger::error(&format!(
+                            "[mode] трасса '{}' не загружена: {e}; используется {DEFAULT_MODE}",
+                            rules.track
+                        ));
+                        Self::Freeroam
+                    }
+                }
+            }
+            DEFAULT_MODE => Self::Freeroam,
+            other => {
+                logger::warn(&format!(
+                    "[mode] неизвестный режим '{other}', используется {DEFAULT_MODE}"
+                ));
+                Self::Freeroam
+            }
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Freeroam => DEFAULT_MODE,
+            Self::Deathmatch(_) => "deathmatch",
+            Self::Race(_) => "race",
+        }
+    }
+
+    /// Засчитываются ли сейчас фраги и смерти.
+    pub fn is_scoring(&self) -> bool {
+        match self {
+            Self::Freeroam => true,
+            Self::Deathmatch(dm) => dm.is_running(),
+            Self::Race(_) => false,
+        }
+    }
+
+    /// Игрок отключился или ушёл в другое измерение.
+    pub fn remove_player(&mut self, player_id: PlayerId) {
+        if let Self::Race(race) = self {
+            race.remove_player(player_id);
+        }
+    }
+
+    /// Занят ли игрок режимом: идёт раунд или он в заезде.
+    pub fn holds(&self, player_id: PlayerId) -> bool {
+        match self {
+            Self::Freeroam => false,
+            Self::Deathmatch(dm) => dm.is_running(),
+            Self::Race(race) => race.is_racing(player_id),
+        }
+    }
+
+    /// `RoundStart` для текущего раунда (оставшееся время — как лимит).
+    fn round_start_packet(&self, now: Instant) -> Option<ServerPacket> {
+        match self {
+            Self::Freeroam | Self::Race(_) => None,
+            Self::Deathmatch(dm) if dm.is_running() => Some(ServerPacket::RoundStart {
+                mode: self.name().to_string(),
+                score_limit: dm.rules().score_limit,
+                time_limit_secs: dm.time_left(now).map_or(0, |d| d.as_secs() as u32),
+            }),
+            Self::Deathmatch(_) => None,
+        }
+    }
+}
+
+/// Режимы по измерениям.
+#[derive(Debug)]
+pub struct Modes {
+    /// Измерения без записи — freeroam.
+    by_dimension: BTreeMap<DimensionId, GameMode>,
+}
+
+impl Modes {
+    pub fn from_config(config: &ServerConfig, now: Instant) -> Self {
+        let mut by_dimension = BTreeMap::new();
+        for (&dimension, name) in &config.dimensions.modes {
+            if dimension == DEFAULT_DIMENSION {
+                logger::warn(&format!(
+                    "[mode] режим измерения {DEFAULT_DIMENSION} задаёт ключ mode, '{name}' пропущен"
+                ));
+                continue;
+            }
+            by_dimension.insert(dimension, GameMode::from_config(name, config, now));
+        }
+        by_dimension.insert(
+            DEFAULT_DIMENSION,
+            GameMode::from_config(&config.mode, config, now),
+        );
+        Self { by_dimension }
+    }
+
+    pub fn get(&self, dimension: DimensionId) -> Option<&GameMode> {
+        self.by_dimension.get(&dimension)
+    }
+
+    pub fn get_mut(&mut self, dimension: DimensionId) -> Option<&mut GameMode> {
+        self.by_dimension.get_mut(&dimension)
+    }
+
+    /// Имя режима измерения.
+    pub fn name(&self, dimension: DimensionId) -> &'static str {
+        self.get(dimension).map_or(DEFAULT_MODE, GameMode::name)
+    }
+
+    /// Засчитываются ли фраги и смерти в измерении.
+    pub fn is_scoring(&self, dimension: DimensionId) -> bool {
+        self.get(dimension).is_none_or(GameMode::is_scoring)
+    }
+
+    /// Занят ли игрок режимом своего измерения (см. `GameMode::holds`).
+    pub fn holds(&self, dimension: DimensionId, player_id: PlayerId) -> bool {
+        self.get(dimension).is_some_and(|m| m.holds(player_id))
+    }
+
+    /// Игрок покинул измерение `dimension`.
+    pub fn remove_player(&mut self, dimension: DimensionId, player_id: PlayerId) {
+        if let Some(mode) = self.get_mut(dimension) {
+            mode.remove_player(player_id);
+        }
+    }
+
+    /// Измерения с режимом (включая freeroam из конфига), по возрастанию.
+    pub fn dimensions(&self) -> Vec<DimensionId> {
+        self.by_dimension.keys().copied().collect()
+    }
+}
+
+fn dimension_of(shared: &SharedServer, player_id: PlayerId) -> Option<DimensionId> {
+    shared.dimensions.lock().ok()?.get(player_id)
+}
+
+/// Имя режима измерения игрока — для точек спавна.
+pub fn name_for(shared: &SharedServer, player_id: PlayerId) -> &'static str {
+    let dimension = dimension_of(shared, player_id).unwrap_or(DEFAULT_DIMENSION);
+    shared
+        .mode
+        .lock()
+        .map_or(DEFAULT_MODE, |m| m.name(dimension))
+}
+
+/// Подключённые игроки измерения — участники его режима.
+fn participants(shared: &SharedServer, dimension: DimensionId) -> Vec<PlayerId> {
+    let members = match shared.dimensions.lock() {
+        Ok(d) => d.members(dimension),
+        Err(_) => return Vec::new(),
+    };
+    let named: Vec<PlayerId> = shared
+        .list_named_players()
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    members
+        .into_iter()
+        .filter(|id| named.contains(id))
+        .collect()
+}
+
+/// Счёт участников измерения.
+fn scores_of(shared: &SharedServer, players: &[PlayerId]) -> Vec<(PlayerId, Score)> {
+    shared
+        .combat
+        .lock()
+        .map(|c| c.scores())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(id, _)| players.contains(id))
+        .collect()
+}
+
+/// Отправить игроку, пришедшему в измерение, текущий раунд и счёт его
+/// соседей.
+pub fn send_state(shared: &SharedServer, player_id: PlayerId) {
+    let Some(dimension) = dimension_of(shared, player_id) else {
+        return;
+    };
+    let start = shared.mode.lock().ok().and_then(|m| {
+        m.get(dimension)
+            .and_then(|mode| mode.round_start_packet(Instant::now()))
+    });
+    if let Some(packet) = start {
+        shared.send_to(player_id, packet);
+    }
+
+    send_race_state(shared, dimension, player_id);
+
+    let players = participants(shared, dimension);
+    for (id, score) in scores_of(shared, &players) {
+        shared.send_to(player_id, combat::score_packet(id, score));
+    }
+}
+
+/// Игрок перешёл из измерения `from`: выбывает из его режима и получает
+/// состояние нового.
+pub fn on_dimension_change(shared: &SharedServer, player_id: PlayerId, from: DimensionId) {
+    if let Ok(mut modes) = shared.mode.lock() {
+        modes.remove_player(from, player_id);
+    }
+    send_state(shared, player_id);
+}
+
+/// Засчитан фраг: награда и проверка лимита.
+pub fn on_kill(shared: &SharedServer, killer: PlayerId, score: Score, now: Instant) {
+    let Some(dimension) = dimension_of(shared, killer) else {
+        return;
+    };
+    let (change, reward) = match shared.mode.lock() {
+        Ok(mut modes) => match modes.get_mut(dimension) {
+            None | Some(GameMode::Freeroam | GameMode::Race(_)) => (None, 0),
+            Some(GameMode::Deathmatch(dm)) => (
+                dm.on_kill(killer, score.kills, now),
+                dm.rules().kill_reward_cents,
+            ),
+        },
+        Err(_) => return,
+    };
+
+    if reward > 0 {
+        let balance = shared
+            .economy
+            .lock()
+            .ok()
+            .and_then(|mut eco| eco.grant(killer, reward));
+        if let Some(balance_cents) = balance {
+            shared.send_to(killer, ServerPacket::MoneySet { balance_cents });
+        }
+    }
+
+    if let Some(change) = change {
+        apply_change(shared, dimension, change, now);
+    }
+}
+
+/// Таймеры режимов всех измерений. Вызывается из серверного тика.
+pub fn tick(shared: &SharedServer, now: Instant) {
+    let dimensions = match shared.mode.lock() {
+        Ok(m) => m.dimensions(),
+        Err(_) => return,
+    };
+    for dimension in dimensions {
+        dimension_tick(shared, dimension, now);
+    }
+}
+
+fn dimension_tick(shared: &SharedServer, dimension: DimensionId, now: Instant) {
+    let players = participants(shared, dimension);
+    let is_race = shared
+        .mode
+        .lock()
+        .is_ok_and(|m| matches!(m.get(dimension), Some(GameMode::Race(_))));
+    if is_race {
+        race_tick(shared, dimension, &players, now);
+        return;
+    }
+
+    let scores = scores_of(shared, &players);
+    let change = match shared.mode.lock() {
+        Ok(mut modes) => match modes.get_mut(dimension) {
+            Some(GameMode::Deathmatch(dm)) => dm.tick(now, &scores),
+            _ => None,
+        },
+        Err(_) => return,
+    };
+
+    if let Some(change) = change {
+        apply_change(shared, dimension, change, now);
+    }
+}
+
+fn apply_change(shared: &SharedServer, dimension: DimensionId, change: RoundChange, now: Instant) {
+    let players = participants(shared, dimension);
+    match change {
+        RoundChange::Ended { winner, reason } => {
+            let next_round_in_secs = shared.config.deathmatch.intermission_secs;
+            announce_end(shared, dimension, winner, reason);
+            shared.broadcast_to_dimension(
+                dimension,
+                ServerPacket::RoundEnd {
+                    winner_id: winner,
+                    reason,
+                    next_round_in_secs,
+                },
+            );
+        }
+
+        RoundChange::Started => {
+            if let Ok(mut combat) = shared.combat.lock() {
+                combat.reset_scores(&players);
+            }
+
+            let start = shared.mode.lock().ok().and_then(|m| {
+                m.get(dimension)
+                    .and_then(|mode| mode.round_start_packet(now))
+            });
+            if let Some(packet) = start {
+                shared.broadcast_to_dimension(dimension, packet);
+            }
+
+            // Новый раунд — все участники с точек спавна.
+            if let Ok(mut respawn) = shared.respawn.lock() {
+                for &player_id in &players {
+                    respawn.force_respawn(player_id, now);
+                }
+            }
+
+            logger::info(&format!("[mode] раунд в измерении {dimension} начался"));
+        }
+    }
+}
+
+fn announce_end(
+    shared: &SharedServer,
+    dimension: DimensionId,
+    winner: Option<PlayerId>,
+    reason: RoundEndReason,
+) {
+    let why = match reason {
+        RoundEndReason::ScoreLimit => "лимит фрагов",
+        RoundEndReason::TimeLimit => "время вышло",
+    };
+    let who = winner
+        .and_then(|id| shared.get_name(id))
+        .unwrap_or_else(|| "ничья".to_string());
+
+    logger::info(&format!(
+        "[mode] раунд в измерении {dimension} окончен ({why}), победитель: {who}"
+    ));
+}
+
+/// Новичку — трасса, а если идёт отсчёт, то и место в заезде.
+fn send_race_state(shared: &SharedServer, dimension: DimensionId, player_id: PlayerId) {
+    let now = Instant::now();
+    let (track, countdown) = match shared.mode.lock() {
+        Ok(mut modes) => match modes.get_mut(dimension) {
+            Some(GameMode::Race(race)) => {
+                let countdown = race
+                    .countdown_left(now)
+                    .filter(|_| race.join(player_id, now));
+                (track_packet(race.track()), countdown)
+            }
+            _ => return,
+        },
+        Err(_) => return,
+    };
+
+    shared.send_to(player_id, track);
+    if let Some(left) = countdown {
+        shared.send_to(
+            player_id,
+            ServerPacket::RaceCountdown {
+                seconds: left.as_secs_f32().ceil() as u32,
+            },
+        );
+    }
+}
+
+/// Позиция из snapshot'а живого игрока.
+pub fn on_position(shared: &SharedServer, player_id: PlayerId, position: NetVec3, now: Instant) {
+    let Some(dimension) = dimension_of(shared, player_id) else {
+        return;
+    };
+    let events = match shared.mode.lock() {
+        Ok(mut modes) => match modes.get_mut(dimension) {
+            Some(GameMode::Race(race)) => race.on_position(player_id, position, now),
+            _ => return,
+        },
+        Err(_) => return,
+    };
+
+    for event in events {
+        match event {
+            RaceEvent::Checkpoint {
+                player,
+                index,
+                lap,
+                split,
+                lap_time,
+            } => shared.send_to(
+                player,
+                ServerPacket::RaceCheckpoint {
+                    checkpoint: index as u32,
+                    lap,
+                    split_ms: millis(split),
+                    lap_ms: lap_time.map(millis),
+                },
+            ),
+
+            RaceEvent::Finished {
+                player,
+                place,
+                total,
+            } => {
+                let who = shared
+                    .get_name(player)
+                    .unwrap_or_else(|| format!("#{player}"));
+                logger::info(&format!(
+                    "[race] {who} финишировал {place}-м за {:.3} с",
+                    total.as_secs_f32()
+                ));
+                shared.broadcast_to_dimension(
+                    dimension,
+                    ServerPacket::RaceFinish {
+                        player_id: player,
+                        place,
+                        total_ms: millis(total),
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn race_tick(shared: &SharedServer, dimension: DimensionId, players: &[PlayerId], now: Instant) {
+    let (change, standings) = match shared.mode.lock() {
+        Ok(mut modes) => match modes.get_mut(dimension) {
+            Some(GameMode::Race(race)) => (race.tick(now, players), race.standings_changed()),
+            _ => return,
+        },
+        Err(_) => return,
+    };
+
+    if let Some(order) = standings {
+        shared.broadcast_to_dimension(dimension, ServerPacket::RaceStandings { order });
+    }
+
+    match change {
+        Some(RaceChange::Countdown { seconds }) => {
+            // Все игроки измерения записаны в заезд — ставим их на старт.
+            if let Ok(mut respawn) = shared.respawn.lock() {
+                for id in players {
+                    respawn.force_respawn(*id, now);
+                }
+            }
+            shared.broadcast_to_dimension(dimension, ServerPacket::RaceCountdown { seconds });
+            logger::info(&format!(
+                "[race] измерение {dimension}: отсчёт {seconds} с, гонщиков: {}",
+                players.len()
+            ));
+        }
+        Some(RaceChange::Started) => {
+            shared.broadcast_to_dimension(dimension, ServerPacket::RaceStart);
+            logger::info(&format!("[race] измерение {dimension}: старт"));
+        }
+        Some(RaceChange::Ended { results }) => {
+            logger::info(&format!(
+                "[race] измерение {dimension}: заезд окончен, финишировали: {}",
+                results.iter().filter(|r| r.place.is_some()).count()
+            ));
+            shared.broadcast_to_dimension(
+                dimension,
+                ServerPacket::RaceResults {
+                    results: results.iter().map(net_result).collect(),
+                    next_race_in_secs: shared.config.race.results_secs,
+                },
+            );
+        }
+        None => {}
+    }
+}
+
+fn track_packet(track: &Track) -> ServerPacket {
+    ServerPacket::RaceTrack {
+        name: track.name.clone(),
+        laps: track.laps,
+        checkpoints: track
+            .checkpoints
+            .iter()
+            .map(|cp| NetCheckpoint {
+                position: cp.position,
+                radius: cp.radius,
+            })
+            .collect(),
+    }
+}
+
+fn net_result(r: &RaceResult) -> NetRaceResult {
+    NetRaceResult {
+        player_id: r.player,
+        place: r.place,
+        total_ms: r.total.map(millis),
+        best_lap_ms: r.best_lap.map(millis),
+    }
+}
+
+fn millis(d: Duration) -> u32 {
+    d.as_millis().min(u32::MAX as u128) as u32
+}
@@ -0,0 +1,335 @@
+//! UDP query: ответы на `protocol::query::QueryRequest`.
+//!
+//! Слушаем `0.0.0.0:<порт игры>`, поэтому LAN broadcast-запросы приходят
+//! сюда же. Частота ответов ограничена на IP и суммарно (token bucket),
+//! чтобы сервер нельзя было использовать как усилитель UDP-флуда; число
+//! запоминаемых IP тоже ограничено.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::query::{
+    MAX_QUERY_PLAYER_NAMES, MAX_QUERY_REQUEST_BYTES, MIN_QUERY_REQUEST_BYTES, QUERY_MAGIC,
+    QueryRequest, QueryResponse, ServerInfo,
+};
+use protocol::{MAX_PLAYERS, PROTOCOL_VERSION};
+use tokio::net::UdpSocket;
+
+use crate::SharedServer;
+use crate::dimension::DEFAULT_DIMENSION;
+
+/// Ответов в секунду на один IP (и запас burst).
+const PER_IP_RATE: f32 = 2.0;
+const PER_IP_BURST: f32 = 5.0;
+
+/// Ответов в секунду суммарно.
+const GLOBAL_RATE: f32 = 100.0;
+const GLOBAL_BURST: f32 = 200.0;
+
+/// Забывать IP, от которых давно ничего не было.
+const IDLE_FORGET: Duration = Duration::from_secs(60);
+
+/// Сколько IP помнить; при переполнении вытесняются самые давние.
+const MAX_TRACKED_IPS: usize = 4096;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f32,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(burst: f32, now: Instant) -> Self {
+        Self {
+            tokens: burst,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, rate: f32, burst: f32, now: Instant) {
+        let dt = now.saturating_duration_since(self.updated).as_secs_f32();
+        self.tokens = (self.tokens + dt * rate).min(burst);
+        self.updated = now;
+    }
+
+    fn take(&mut self, rate: f32, burst: f32, now: Instant) -> bool {
+        self.refill(rate, burst, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Ограничитель частоты ответов.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_ip: HashMap<IpAddr, Bucket>,
+    global: Bucket,
+    last_prune: Instant,
+    /// Предел `per_ip` (`MAX_TRACKED_IPS`).
+    capacity: usize,
+}
+
+impl RateLimiter {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            per_ip: HashMap::new(),
+            global: Bucket::full(GLOBAL_BURST, now),
+            last_prune: now,
+            capacity: MAX_TRACKED_IPS,
+        }
+    }
+
+    /// Можно ли ответить `ip` прямо сейчас.
+    ///
+    /// Общий лимит проверяется первым: флуд, упёршийся в него, не тратит
+    /// токены и не заводит записи отдельных IP.
+    pub fn allow(&mut self, ip: IpAddr, now: Instant) -> bool {
+        if now.saturating_duration_since(self.last_prune) >= IDLE_FORGET {
+            self.forget_idle(now);
+        }
+
+        self.global.refill(GLOBAL_RATE, GLOBAL_BURST, now);
+        if self.global.tokens < 1.0 {
+            return false;
+        }
+
+        if !self.per_ip.contains_key(&ip) && self.per_ip.len() >= self.capacity {
+            self.evict_oldest(now);
+        }
+        let bucket = self
+            .per_ip
+            .entry(ip)
+            .or_insert_with(|| Bucket::full(PER_IP_BURST, now));
+        if !bucket.take(PER_IP_RATE, PER_IP_BURST, now) {
+            return false;
+        }
+        self.global.tokens -= 1.0;
+        true
+    }
+
+    fn forget_idle(&mut self, now: Instant) {
+        self.per_ip
+            .retain(|_, b| now.saturating_duration_since(b.updated) < IDLE_FORGET);
+        self.last_prune = now;
+    }
+
+    /// Освободить место: сначала простаивающие IP, потом самые давние —
+    /// восьмую часть предела, чтобы не вытеснять по одному на каждый пакет.
+    fn evict_oldest(&mut self, now: Instant) {
+        self.forget_idle(now);
+        if self.per_ip.len() < self.capacity {
+            return;
+        }
+        let keep = self.capacity - (self.capacity / 8).max(1);
+        let mut updated: Vec<Instant> = self.per_ip.values().map(|b| b.updated).collect();
+        let cut = updated.len() - keep;
+        let (_, oldest_kept, _) = updated.select_nth_unstable(cut);
+        let oldest_kept = *oldest_kept;
+        self.per_ip.retain(|_, b| b.updated >= oldest_kept);
+        // Равные отметки времени могли оставить лишнее.
+        while self.per_ip.len() > keep {
+            let Some(ip) = self.per_ip.keys().next().copied() else {
+                break;
+            };
+            self.per_ip.remove(&ip);
+        }
+    }
+}
+
+/// Разобрать датаграмму. `None` — не наш запрос или слишком короткий.
+pub fn parse_request(data: &[u8]) -> Option<QueryRequest> {
+    if data.len() < MIN_QUERY_REQUEST_BYTES || data.len() > MAX_QUERY_REQUEST_BYTES {
+        return None;
+    }
+    let request: QueryRequest = serde_json::from_slice(data).ok()?;
+    (request.magic == QUERY_MAGIC).then_some(request)
+}
+
+/// Текущее состояние сервера для ответа.
+pub fn server_info(shared: &SharedServer, port: u16) -> ServerInfo {
+    let mut names: Vec<String> = shared
+        .list_named_players()
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    let players = names.len() as u32;
+    names.sort();
+    names.truncate(MAX_QUERY_PLAYER_NAMES);
+
+    // Браузеру — режим, в который попадает новичок.
+    let mode = shared
+        .mode
+        .lock()
+        .map(|m| m.name(DEFAULT_DIMENSION).to_string())
+        .unwrap_or_default();
+
+    ServerInfo {
+        name: shared.config.name.clone(),
+        map: shared.config.map.clone(),
+        mode,
+        players,
+        max_players: MAX_PLAYERS as u32,
+        player_names: names,
+        protocol_version: PROTOCOL_VERSION,
+        password: shared.config.password.is_some(),
+        port,
+    }
+}
+
+/// Запустить UDP query на `port` отдельной задачей.
+pub async fn spawn(shared: Arc<SharedServer>, port: u16) {
+    let socket = match UdpSocket::bind(("0.0.0.0", port)).await {
+        Ok(s) => s,
+        Err(e) => {
+            logger::error(&format!("[query] UDP bind {port} failed: {e}"));
+            return;
+        }
+    };
+
+    logger::info(&format!("[query] UDP query on 0.0.0.0:{port}"));
+    tokio::spawn(serve(socket, shared, port));
+}
+
+async fn serve(socket: UdpSocket, shared: Arc<SharedServer>, port: u16) {
+    let mut limiter = RateLimiter::new(Instant::now());
+    let mut buf = [0u8; MAX_QUERY_REQUEST_BYTES + 1];
+
+    loop {
+        let (len, from) = match socket.recv_from(&mut buf).await {
+            Ok(r) => r,
+            Err(e) => {
+                logger::warn(&format!("[query] recv failed: {e}"));
+                continue;
+            }
+        };
+
+        let Some(request) = parse_request(&buf[..len]) else {
+            continue;
+        };
+        if !limiter.allow(from.ip(), Instant::now()) {
+            continue;
+        }
+
+        respond(&socket, from, request.nonce, server_info(&shared, port)).await;
+    }
+}
+
+async fn respond(socket: &UdpSocket, to: SocketAddr, nonce: u64, info: ServerInfo) {
+    let response = QueryResponse {
+        magic: QUERY_MAGIC.to_string(),
+        nonce,
+        info,
+    };
+    let Ok(bytes) = serde_json::to_vec(&response) else {
+        return;
+    };
+    if let Err(e) = socket.send_to(&bytes, to).await {
+        logger::debug(&format!("[query] send to {to} failed: {e}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_ip_limit_refills_over_time() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::new(t0);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+
+        for _ in 0..PER_IP_BURST as usize {
+            assert!(limiter.allow(ip, t0));
+        }
+        assert!(!limiter.allow(ip, t0));
+        // Другой IP не страдает.
+        assert!(limiter.allow(other, t0));
+
+        // Через секунду — ещё PER_IP_RATE ответов.
+        let t1 = t0 + Duration::from_secs(1);
+        assert!(limiter.allow(ip, t1));
+        assert!(limiter.allow(ip, t1));
+        assert!(!limiter.allow(ip, t1));
+    }
+
+    #[test]
+    fn global_limit_caps_spoofed_sources() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::new(t0);
+
+        let allowed = (0..1_000u32)
+            .filter(|i| limiter.allow(IpAddr::from(i.to_be_bytes()), t0))
+            .count();
+        assert_eq!(allowed, GLOBAL_BURST as usize);
+    }
+
+    #[test]
+    fn exhausted_global_limit_spends_no_per_ip_tokens() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::new(t0);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        limiter.global.tokens = 0.0;
+        for _ in 0..10 {
+            assert!(!limiter.allow(ip, t0));
+        }
+        assert!(limiter.per_ip.is_empty());
+
+        // Когда общий лимит восстановился, у IP полный запас.
+        let t1 = t0 + Duration::from_secs(1);
+        for _ in 0..PER_IP_BURST as usize {
+            assert!(limiter.allow(ip, t1));
+        }
+        assert!(!limiter.allow(ip, t1));
+    }
+
+    #[test]
+    fn tracked_ips_are_capped_evicting_the_oldest() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::new(t0);
+        limiter.capacity = 16;
+
+        for i in 0..16u32 {
+            let at = t0 + Duration::from_millis(u64::from(i));
+            assert!(limiter.allow(IpAddr::from(i.to_be_bytes()), at));
+        }
+        assert_eq!(limiter.per_ip.len(), 16);
+
+        let newcomer = IpAddr::from(100u32.to_be_bytes());
+        assert!(limiter.allow(newcomer, t0 + Duration::from_millis(20)));
+        assert!(limiter.per_ip.len() <= 16);
+        assert!(limiter.per_ip.contains_key(&newcomer));
+        // Вытеснены самые давние, свежие остались.
+        assert!(
+            !limiter
+                .per_ip
+                .contains_key(&IpAddr::from(0u32.to_be_bytes()))
+        );
+        assert!(
+            limiter
+                .per_ip
+                .contains_key(&IpAddr::from(15u32.to_be_bytes()))
+        );
+    }
+
+    #[test]
+    fn short_or_foreign_requests_are_ignored() {
+        let ok = serde_json::to_vec(&QueryRequest::new(7)).unwrap();
+        assert_eq!(parse_request(&ok).map(|r| r.nonce), Some(7));
+
+        let short = br#"{"magic":"M2MP","nonce":7}"#;
+        assert!(parse_request(short).is_none());
+
+        let mut foreign = QueryRequest::new(7);
+        foreign.magic = "XXXX".into();
+        assert!(parse_request(&serde_json::to_vec(&foreign).unwrap()).is_none());
+    }
+}
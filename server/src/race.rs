@@ -0,0 +1,503 @@
+//! Гонка по чекпоинтам.
+//!
+//! Фазы: ожидание игроков → отсчёт → гонка → результаты → снова отсчёт.
+//! Модуль чистый: позиции приходят из snapshot'ов, наружу уходят события,
+//! рассылкой занимается `mode`.
+//!
+//! Чекпоинт засчитывается, только если он следующий по порядку и игрок
+//! действительно в него въехал. Скачок позиции быстрее `MAX_RACE_SPEED_MPS`
+//! (телепорт, подмена snapshot'а) на этом сэмпле чекпоинт не засчитывает.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use protocol::{NetVec3, PlayerId};
+
+use crate::config::RaceConfig;
+use crate::track::{Track, distance_sq};
+
+/// Максимальная правдоподобная скорость (~400 км/ч).
+pub const MAX_RACE_SPEED_MPS: f32 = 110.0;
+
+/// Допуск на задержку/джиттер snapshot'ов.
+const TELEPORT_SLACK_M: f32 = 15.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    Countdown {
+        go_at: Instant,
+    },
+    Running {
+        started: Instant,
+        /// Дедлайн после первого финишировавшего.
+        finish_deadline: Option<Instant>,
+    },
+    Results {
+        next_at: Instant,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Racer {
+    /// Индекс следующего чекпоинта.
+    next_checkpoint: usize,
+    /// Пройдено полных кругов.
+    laps_done: u32,
+    lap_started: Instant,
+    best_lap: Option<Duration>,
+    finished: Option<(u32, Duration)>,
+    last_sample: Option<(NetVec3, Instant)>,
+}
+
+impl Racer {
+    fn new(now: Instant) -> Self {
+        Self {
+            next_checkpoint: 0,
+            laps_done: 0,
+            lap_started: now,
+            best_lap: None,
+            finished: None,
+            last_sample: None,
+        }
+    }
+}
+
+/// Событие прогресса отдельного гонщика.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaceEvent {
+    /// Пройден чекпоинт `index`; `lap_time` — если он закрыл круг.
+    Checkpoint {
+        player: PlayerId,
+        index: usize,
+        lap: u32,
+        split: Duration,
+        lap_time: Option<Duration>,
+    },
+    Finished {
+        player: PlayerId,
+        place: u32,
+        total: Duration,
+    },
+}
+
+/// Итог гонщика.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaceResult {
+    pub player: PlayerId,
+    pub place: Option<u32>,
+    pub total: Option<Duration>,
+    pub best_lap: Option<Duration>,
+}
+
+/// Смена фазы гонки.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceChange {
+    Countdown { seconds: u32 },
+    Started,
+    Ended { results: Vec<RaceResult> },
+}
+
+#[derive(Debug)]
+pub struct Race {
+    rules: RaceConfig,
+    track: Track,
+    phase: Phase,
+    racers: HashMap<PlayerId, Racer>,
+    finished_count: u32,
+    last_standings: Vec<PlayerId>,
+}
+
+impl Race {
+    pub fn new(rules: RaceConfig, track: Track) -> Self {
+        Self {
+            rules,
+            track,
+            phase: Phase::Idle,
+            racers: HashMap::new(),
+            finished_count: 0,
+            last_standings: Vec::new(),
+        }
+    }
+
+    pub fn track(&self) -> &Track {
+        &self.track
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.phase, Phase::Running { .. })
+    }
+
+    /// Осталось до старта, если идёт отсчёт.
+    pub fn countdown_left(&self, now: Instant) -> Option<Duration> {
+        match self.phase {
+            Phase::Countdown { go_at } => Some(go_at.saturating_duration_since(now)),
+            _ => None,
+        }
+    }
+
+    /// Записать опоздавшего в гонку, пока идёт отсчёт.
+    pub fn join(&mut self, player: PlayerId, now: Instant) -> bool {
+        if !matches!(self.phase, Phase::Countdown { .. }) {
+            return false;
+        }
+        self.racers.insert(player, Racer::new(now));
+        true
+    }
+
+    /// Записан ли игрок в заезд, который ещё не кончился для него.
+    pub fn is_racing(&self, player: PlayerId) -> bool {
+        matches!(self.phase, Phase::Countdown { .. } | Phase::Running { .. })
+            && self
+                .racers
+                .get(&player)
+                .is_some_and(|r| r.finished.is_none())
+    }
+
+    pub fn remove_player(&mut self, player: PlayerId) {
+        self.racers.remove(&player);
+    }
+
+    /// Таймеры фаз. `players` — подключённые игроки.
+    pub fn tick(&mut self, now: Instant, players: &[PlayerId]) -> Option<RaceChange> {
+        match self.phase {
+            Phase::Idle => {
+                if players.len() < self.rules.min_players.max(1) as usize {
+                    return None;
+                }
+                Some(self.begin_countdown(now, players))
+            }
+
+            Phase::Countdown { go_at } => {
+                if now < go_at {
+                    return None;
+                }
+                for racer in self.racers.values_mut() {
+                    *racer = Racer::new(now);
+                }
+                self.finished_count = 0;
+                self.phase = Phase::Running {
+                    started: now,
+                    finish_deadline: None,
+                };
+                Some(RaceChange::Started)
+            }
+
+            Phase::Running {
+                started,
+                finish_deadline,
+            } => {
+                let all_done = self.racers.values().all(|r| r.finished.is_some());
+                let time_up = self.rules.time_limit_secs > 0
+                    && now.duration_since(started)
+                        >= Duration::from_secs(self.rules.time_limit_secs as u64);
+                let deadline = finish_deadline.is_some_and(|d| now >= d);
+
+                if !(all_done || time_up || deadline) {
+                    return None;
+                }
+
+                let results = self.results();
+                self.phase = Phase::Results {
+                    next_at: now + Duration::from_secs(self.rules.results_secs as u64),
+                };
+                Some(RaceChange::Ended { results })
+            }
+
+            Phase::Results { next_at } => {
+                if now < next_at {
+                    return None;
+                }
+                self.phase = Phase::Idle;
+                self.racers.clear();
+                self.last_standings.clear();
+                None
+            }
+        }
+    }
+
+    fn begin_countdown(&mut self, now: Instant, players: &[PlayerId]) -> RaceChange {
+        self.racers = players.iter().map(|id| (*id, Racer::new(now))).collect();
+        self.finished_count = 0;
+        self.last_standings.clear();
+        self.phase = Phase::Countdown {
+            go_at: now + Duration::from_secs(self.rules.countdown_secs as u64),
+        };
+        RaceChange::Countdown {
+            seconds: self.rules.countdown_secs,
+        }
+    }
+
+    /// Новая позиция игрока из snapshot'а.
+    pub fn on_position(&mut self, player: PlayerId, pos: NetVec3, now: Instant) -> Vec<RaceEvent> {
+        let Phase::Running { started, .. } = self.phase else {
+            return Vec::new();
+        };
+        let Some(racer) = self.racers.get_mut(&player) else {
+            return Vec::new();
+        };
+        if racer.finished.is_some() {
+            return Vec::new();
+        }
+
+        let plausible = match racer.last_sample {
+            Some((prev, at)) => {
+                let dt = now.duration_since(at).as_secs_f32();
+                let max = MAX_RACE_SPEED_MPS * dt + TELEPORT_SLACK_M;
+                distance_sq(&prev, &pos) <= max * max
+            }
+            None => true,
+        };
+        racer.last_sample = Some((pos, now));
+
+        let checkpoint = &self.track.checkpoints[racer.next_checkpoint];
+        if !plausible || !checkpoint.contains(&pos) {
+            return Vec::new();
+        }
+
+        let index = racer.next_checkpoint;
+        let last = self.track.checkpoints.len() - 1;
+        let mut lap_time = None;
+
+        if index == last {
+            let time = now.duration_since(racer.lap_started);
+            racer.laps_done += 1;
+            racer.lap_started = now;
+            racer.best_lap = Some(racer.best_lap.map_or(time, |b| b.min(time)));
+            racer.next_checkpoint = 0;
+            lap_time = Some(time);
+        } else {
+            racer.next_checkpoint += 1;
+        }
+
+        let split = now.duration_since(started);
+        let mut events = vec![RaceEvent::Checkpoint {
+            player,
+            index,
+            lap: racer.laps_done + u32::from(lap_time.is_none()),
+            split,
+            lap_time,
+        }];
+
+        if racer.laps_done >= self.track.laps {
+            self.finished_count += 1;
+            let place = self.finished_count;
+            racer.finished = Some((place, split));
+            events.push(RaceEvent::Finished {
+                player,
+                place,
+                total: split,
+            });
+
+            if place == 1
+                && let Phase::Running {
+                    finish_deadline, ..
+                } = &mut self.phase
+            {
+                *finish_deadline =
+                    Some(now + Duration::from_secs(self.rules.finish_timeout_secs as u64));
+            }
+        }
+
+        events
+    }
+
+    /// Порядок гонщиков: финишировавшие по месту, остальные по прогрессу,
+    /// при равном прогрессе — ближе к следующему чекпоинту.
+    pub fn standings(&self) -> Vec<PlayerId> {
+        let per_lap = self.track.checkpoints.len() as u32;
+
+        let mut order: Vec<_> = self
+            .racers
+            .iter()
+            .map(|(id, r)| {
+                let progress = r.laps_done * per_lap + r.next_checkpoint as u32;
+                let distance = r
+                    .last_sample
+                    .map(|(p, _)| {
+                        distance_sq(&p, &self.track.checkpoints[r.next_checkpoint].position)
+                    })
+                    .unwrap_or(f32::INFINITY);
+                (*id, r.finished.map(|(place, _)| place), progress, distance)
+            })
+            .collect();
+
+        order.sort_by(|a, b| match (a.1, b.1) {
+            (Some(pa), Some(pb)) => pa.cmp(&pb),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => b.2.cmp(&a.2).then(a.3.total_cmp(&b.3)).then(a.0.cmp(&b.0)),
+        });
+
+        order.into_iter().map(|(id, ..)| id).collect()
+    }
+
+    /// Порядок, если он изменился с прошлого вызова (для рассылки).
+    pub fn standings_changed(&mut self) -> Option<Vec<PlayerId>> {
+        if !self.is_running() {
+            return None;
+        }
+        let now = self.standings();
+        if now == self.last_standings {
+            return None;
+        }
+        self.last_standings = now.clone();
+        Some(now)
+    }
+
+    fn results(&self) -> Vec<RaceResult> {
+        self.standings()
+            .into_iter()
+            .filter_map(|id| {
+                let r = self.racers.get(&id)?;
+                Some(RaceResult {
+                    player: id,
+                    place: r.finished.map(|(place, _)| place),
+                    total: r.finished.map(|(_, total)| total),
+                    best_lap: r.best_lap,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::track::Checkpoint;
+
+    fn v(x: f32) -> NetVec3 {
+        NetVec3 { x, y: 0.0, z: 0.0 }
+    }
+
+    fn race(laps: u32) -> Race {
+        let track = Track {
+            name: "test".into(),
+            laps,
+            checkpoints: vec![
+                Checkpoint {
+                    position: v(100.0),
+                    radius: 10.0,
+                },
+                Checkpoint {
+                    position: v(0.0),
+                    radius: 10.0,
+                },
+            ],
+        };
+        Race::new(
+            RaceConfig {
+                countdown_secs: 3,
+                finish_timeout_secs: 30,
+                results_secs: 5,
+                ..RaceConfig::default()
+            },
+            track,
+        )
+    }
+
+    /// Запустить гонку для `players`, вернуть момент старта.
+    fn start(race: &mut Race, players: &[PlayerId], t0: Instant) -> Instant {
+        assert_eq!(
+            race.tick(t0, players),
+            Some(RaceChange::Countdown { seconds: 3 })
+        );
+        let go = t0 + Duration::from_secs(3);
+        assert_eq!(race.tick(go, players), Some(RaceChange::Started));
+        go
+    }
+
+    /// Проехать от x=0 к x=100 и обратно шагами по 1 с.
+    fn drive_lap(race: &mut Race, player: PlayerId, from: Instant) -> (Vec<RaceEvent>, Instant) {
+        let mut events = Vec::new();
+        let mut t = from;
+        for x in [0.0, 50.0, 100.0, 50.0, 0.0] {
+            t += Duration::from_secs(1);
+            events.extend(race.on_position(player, v(x), t));
+        }
+        (events, t)
+    }
+
+    #[test]
+    fn laps_and_finish_order() {
+        let mut r = race(2);
+        let go = start(&mut r, &[1, 2], Instant::now());
+
+        let (ev1, t1) = drive_lap(&mut r, 1, go);
+        assert!(matches!(
+            ev1.last(),
+            Some(RaceEvent::Checkpoint {
+                index: 1,
+                lap_time: Some(_),
+                ..
+            })
+        ));
+
+        let (ev2, _) = drive_lap(&mut r, 1, t1);
+        assert!(ev2.contains(&RaceEvent::Finished {
+            player: 1,
+            place: 1,
+            total: Duration::from_secs(10)
+        }));
+        assert!(!r.is_racing(1));
+        assert!(r.is_racing(2));
+
+        assert_eq!(r.standings(), vec![1, 2]);
+    }
+
+    #[test]
+    fn checkpoints_must_be_taken_in_order() {
+        let mut r = race(1);
+        let go = start(&mut r, &[1], Instant::now());
+
+        // Сразу в финишный чекпоинт — не засчитывается.
+        assert!(
+            r.on_position(1, v(0.0), go + Duration::from_secs(1))
+                .is_empty()
+        );
+        assert!(
+            r.on_position(1, v(50.0), go + Duration::from_secs(2))
+                .is_empty()
+        );
+        assert_eq!(
+            r.on_position(1, v(100.0), go + Duration::from_secs(3))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn teleport_does_not_count() {
+        let mut r = race(1);
+        let go = start(&mut r, &[1], Instant::now());
+
+        r.on_position(1, v(-5_000.0), go + Duration::from_secs(1));
+        // 5 км за 100 мс — подозрительно.
+        assert!(
+            r.on_position(1, v(100.0), go + Duration::from_millis(1_100))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn race_ends_after_finish_timeout_and_restarts() {
+        let mut r = race(1);
+        let go = start(&mut r, &[1, 2], Instant::now());
+        let (_, t) = drive_lap(&mut r, 1, go);
+
+        assert_eq!(r.tick(t + Duration::from_secs(29), &[1, 2]), None);
+        let Some(RaceChange::Ended { results }) = r.tick(t + Duration::from_secs(30), &[1, 2])
+        else {
+            panic!("гонка должна закончиться");
+        };
+        assert_eq!(results[0].place, Some(1));
+        assert_eq!(results[1].place, None);
+
+        let after = t + Duration::from_secs(35);
+        assert_eq!(r.tick(after, &[1, 2]), None);
+        assert_eq!(
+            r.tick(after, &[1, 2]),
+            Some(RaceChange::Countdown { seconds: 3 })
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Запись сессии в `.m2rec` (формат — `protocol::recording`).
+//!
+//! Включается `recording.dir` в конфиге: на каждый запуск сервера — новый
+//! файл `session-<unix_ms>.m2rec`. Пишется через буфер, сбрасывается на
+//! каждом тике. Ошибка записи выключает запись до перезапуска, сервер
+//! продолжает работать. Пароли и токены пишутся скрытыми
+//! (`Entry::redacted`).
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use common::logger;
+use protocol::recording::{RECORDING_EXTENSION, RECORDING_FORMAT, Record, RecordingHeader};
+use protocol::{PROTOCOL_VERSION, PlayerId};
+
+pub use protocol::recording::Entry;
+
+pub struct Recorder {
+    started: Instant,
+    path: PathBuf,
+    /// `None` — запись сломалась и выключена.
+    out: Mutex<Option<BufWriter<File>>>,
+}
+
+impl Recorder {
+    /// Создать файл записи в `dir` и записать заголовок.
+    pub fn create(dir: impl AsRef<Path>, server: &str) -> io::Result<Self> {
+        let started_unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        std::fs::create_dir_all(&dir)?;
+        let path = dir
+            .as_ref()
+            .join(format!("session-{started_unix_ms}.{RECORDING_EXTENSION}"));
+        let mut out = BufWriter::new(File::create(&path)?);
+
+        let header = RecordingHeader {
+            format: RECORDING_FORMAT,
+            protocol_version: PROTOCOL_VERSION,
+            started_unix_ms,
+            server: server.to_string(),
+        };
+        serde_json::to_writer(&mut out, &header)?;
+        out.write_all(b"\n")?;
+
+        Ok(Self {
+            started: Instant::now(),
+            path,
+            out: Mutex::new(Some(out)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, player_id: PlayerId, entry: Entry) {
+        let record = Record {
+            t: self.started.elapsed().as_millis() as u64,
+            p: player_id,
+            entry: entry.redacted(),
+        };
+        self.with_writer(|out| {
+            serde_json::to_writer(&mut *out, &record)?;
+            out.write_all(b"\n")
+        });
+    }
+
+    pub fn flush(&self) {
+        self.with_writer(|out| out.flush());
+    }
+
+    fn with_writer(&self, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) {
+        let Ok(mut out) = self.out.lock() else {
+            return;
+        };
+        let Some(writer) = out.as_mut() else {
+            return;
+        };
+        if let Err(e) = write(writer) {
+            logger::error(&format!(
+                "[recording] запись в {} прервана: {e}",
+                self.path.display()
+            ));
+            *out = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::recording::REDACTED;
+    use protocol::{ClientPacket, ServerPacket};
+
+    use super::*;
+
+    #[test]
+    fn writes_header_and_records_as_json_lines() {
+        let dir = std::env::temp_dir().join(format!("m2mp-recorder-{}", std::process::id()));
+        let recorder = Recorder::create(&dir, "Test").unwrap();
+        recorder.record(
+            3,
+            Entry::In(ClientPacket::ChatMessage {
+                text: "hi".to_string(),
+            }),
+        );
+        recorder.record(
+            3,
+            Entry::Out(ServerPacket::ConnectAccepted { player_id: 3 }),
+        );
+        recorder.record(3, Entry::Closed);
+        recorder.flush();
+
+        let text = std::fs::read_to_string(recorder.path()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut lines = text.lines();
+
+        let header: RecordingHeader = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(header.server, "Test");
+
+        let records: Vec<Record> = lines.map(|l| serde_json::from_str(l).unwrap()).collect();
+        let kinds: Vec<_> = records.iter().map(|r| (r.p, r.entry.kind())).collect();
+        assert_eq!(
+            kinds,
+            [(3, "ChatMessage"), (3, "ConnectAccepted"), (3, "close")]
+        );
+        assert!(records.windows(2).all(|w| w[0].t <= w[1].t));
+    }
+
+    #[test]
+    fn secrets_are_not_recorded() {
+        let dir = std::env::temp_dir().join(format!("m2mp-redact-{}", std::process::id()));
+        let recorder = Recorder::create(&dir, "Test").unwrap();
+        recorder.record(
+            3,
+            Entry::In(ClientPacket::Connect {
+                name: "vito".to_string(),
+                version: PROTOCOL_VERSION,
+                password: Some("hunter2".to_string()),
+                account_token: Some("tok-in".to_string()),
+            }),
+        );
+        recorder.record(
+            3,
+            Entry::Out(ServerPacket::AccountToken {
+                token: "tok-out".to_string(),
+            }),
+        );
+        recorder.record(
+            4,
+            Entry::In(ClientPacket::Connect {
+                name: "joe".to_string(),
+                version: PROTOCOL_VERSION,
+                password: None,
+                account_token: None,
+            }),
+        );
+        recorder.flush();
+
+        let text = std::fs::read_to_string(recorder.path()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        for secret in ["hunter2", "tok-in", "tok-out"] {
+            assert!(!text.contains(secret), "{secret} leaked: {text}");
+        }
+
+        let records: Vec<Record> = text
+            .lines()
+            .skip(1)
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        match &records[0].entry {
+            Entry::In(ClientPacket::Connect {
+                name,
+                password,
+                account_token,
+                ..
+            }) => {
+                assert_eq!(name, "vito");
+                assert_eq!(password.as_deref(), Some(REDACTED));
+                assert_eq!(account_token.as_deref(), Some(REDACTED));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        // Отсутствующий секрет так и остаётся отсутствующим.
+        assert!(matches!(
+            &records[2].entry,
+            Entry::In(ClientPacket::Connect {
+                password: None,
+                account_token: None,
+                ..
+            })
+        ));
+    }
+}
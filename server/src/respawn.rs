@@ -0,0 +1,316 @@
+//! Жизненный цикл игрока: смерть → ожидание → респаун.
+//!
+//! Источники смерти — `NetPlayerEvent::Death` и `is_dead` в snapshot'е.
+//! Когда задержка истекла, сервер шлёт `ServerPacket::Respawn` и ждёт
+//! snapshot с `is_dead = false`. Если клиент так и не ожил (пакет потерялся
+//! на загрузке, игрок был в меню), респаун повторяется.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common::logger;
+use protocol::{NetVec3, PlayerId, ServerPacket};
+
+use crate::SharedServer;
+use crate::config::RespawnConfig;
+
+/// Через сколько повторить `Respawn`, если клиент не подтвердил его.
+pub const RESPAWN_RESEND_AFTER: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Life {
+    Alive,
+    Dead { respawn_at: Instant },
+    Respawning { sent_at: Instant },
+}
+
+#[derive(Debug)]
+struct LifeRecord {
+    life: Life,
+    recent_deaths: Vec<Instant>,
+}
+
+#[derive(Debug)]
+pub struct RespawnTracker {
+    rules: RespawnConfig,
+    lives: HashMap<PlayerId, LifeRecord>,
+}
+
+impl RespawnTracker {
+    pub fn new(rules: RespawnConfig) -> Self {
+        Self {
+            rules,
+            lives: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &RespawnConfig {
+        &self.rules
+    }
+
+    pub fn add_player(&mut self, player_id: PlayerId) {
+        self.lives.insert(
+            player_id,
+            LifeRecord {
+                life: Life::Alive,
+                recent_deaths: Vec::new(),
+            },
+        );
+    }
+
+    pub fn remove_player(&mut self, player_id: PlayerId) {
+        self.lives.remove(&player_id);
+    }
+
+    /// Мёртв ли игрок с точки зрения сервера (ждёт респауна).
+    #[cfg(test)]
+    pub fn is_dead(&self, player_id: PlayerId) -> bool {
+        self.lives
+            .get(&player_id)
+            .is_some_and(|r| !matches!(r.life, Life::Alive))
+    }
+
+    /// Зарегистрировать смерть. Возвращает задержку до респауна,
+    /// если это новая смерть (повторные сигналы игнорируются).
+    pub fn on_death(&mut self, player_id: PlayerId, now: Instant) -> Option<Duration> {
+        if !self.rules.enabled {
+            return None;
+        }
+
+        let window = Duration::from_millis(self.rules.streak_window_ms as u64);
+        let record = self.lives.get_mut(&player_id)?;
+
+        if record.life != Life::Alive {
+            return None;
+        }
+
+        record
+            .recent_deaths
+            .retain(|at| now.duration_since(*at) < window);
+
+        let extra = self.rules.delay_per_recent_death_ms as u64 * record.recent_deaths.len() as u64;
+        let delay_ms = (self.rules.delay_ms as u64 + extra).min(self.rules.max_delay_ms as u64);
+        let delay = Duration::from_millis(delay_ms);
+
+        record.recent_deaths.push(now);
+        record.life = Life::Dead {
+            respawn_at: now + delay,
+        };
+
+        Some(delay)
+    }
+
+    /// Snapshot с `is_dead = false`: подтверждение респауна.
+    pub fn on_alive(&mut self, player_id: PlayerId) {
+        if let Some(record) = self.lives.get_mut(&player_id)
+            && matches!(record.life, Life::Respawning { .. })
+        {
+            record.life = Life::Alive;
+        }
+    }
+
+    /// Игроки, которым пора (повторно) отправить `Respawn`.
+    pub fn take_due(&mut self, now: Instant) -> Vec<PlayerId> {
+        let mut due = Vec::new();
+
+        for (id, record) in self.lives.iter_mut() {
+            let ready = match record.life {
+                Life::Alive => false,
+                Life::Dead { respawn_at } => now >= respawn_at,
+                Life::Respawning { sent_at } => now.duration_since(sent_at) >= RESPAWN_RESEND_AFTER,
+            };
+
+            if ready {
+                record.life = Life::Respawning { sent_at: now };
+                due.push(*id);
+            }
+        }
+
+        due.sort_unstable();
+        due
+    }
+
+    /// Принудительно поставить игрока в очередь на немедленный респаун
+    /// (стартовый спавн, смена режима).
+    pub fn force_respawn(&mut self, player_id: PlayerId, now: Instant) {
+        if let Some(record) = self.lives.get_mut(&player_id) {
+            record.life = Life::Dead { respawn_at: now };
+        }
+    }
+}
+
+/// Зарегистрировать смерть игрока и сообщить ему задержку.
+pub fn handle_death(shared: &SharedServer, player_id: PlayerId, now: Instant) {
+    let delay = match shared.respawn.lock() {
+        Ok(mut respawn) => respawn.on_death(player_id, now),
+        Err(_) => return,
+    };
+
+    if let Some(delay) = delay {
+        logger::info(&format!(
+            "[server] player {player_id} died, respawn in {} ms",
+            delay.as_millis()
+        ));
+        shared.send_to(
+            player_id,
+            ServerPacket::RespawnPending {
+                delay_ms: delay.as_millis() as u32,
+            },
+        );
+    }
+}
+
+/// Отправить `Respawn` всем, у кого истекла задержка.
+pub fn tick(shared: &SharedServer, now: Instant) {
+    let due = match shared.respawn.lock() {
+        Ok(mut respawn) => respawn.take_due(now),
+        Err(_) => return,
+    };
+
+    for player_id in due {
+        send_respawn(shared, player_id);
+    }
+}
+
+fn send_respawn(shared: &SharedServer, player_id: PlayerId) {
+    let rules = match shared.respawn.lock() {
+        Ok(respawn) => respawn.rules().clone(),
+        Err(_) => return,
+    };
+
+    // Держимся подальше от живых противников; союзники не мешают.
+    let (team, allies) = match shared.teams.lock() {
+        Ok(t) => (
+            t.team_of(player_id).map(str::to_string),
+            t.teammates(player_id),
+        ),
+        Err(_) => (None, Vec::new()),
+    };
+
+    // Игроки других измерений этому миру не мешают.
+    let neighbours = shared
+        .dimensions
+        .lock()
+        .map(|d| d.neighbours(player_id))
+        .unwrap_or_default();
+    let others: Vec<NetVec3> = shared
+        .list_snapshots()
+        .into_iter()
+        .filter(|s| {
+            neighbours.contains(&s.player_id) && !s.is_dead && !allies.contains(&s.player_id)
+        })
+        .map(|s| s.position)
+        .collect();
+
+    let mode = crate::mode::name_for(shared, player_id);
+    let point = shared
+        .spawns
+        .lock()
+        .ok()
+        .and_then(|mut spawns| spawns.pick(team.as_deref(), mode, &others));
+
+    // Без точек спавна — воскрешаем на месте смерти.
+    let (position, heading) = match point {
+        Some(p) => (p.position, p.heading),
+        None => match shared.get_snapshot(player_id) {
+            Some(s) => (s.position, heading_from_forward(&s.forward)),
+            None => {
+                logger::warn(&format!(
+                    "[server] no spawn point and no position for player {player_id}"
+                ));
+                return;
+            }
+        },
+    };
+
+    shared.send_to(
+        player_id,
+        ServerPacket::Respawn {
+            position,
+            heading,
+            health: rules.health,
+            loadout: rules.loadout,
+        },
+    );
+}
+
+/// Курс в градусах по часовой стрелке от +Y.
+fn heading_from_forward(forward: &NetVec3) -> f32 {
+    forward.x.atan2(forward.y).to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> RespawnConfig {
+        RespawnConfig {
+            delay_ms: 1_000,
+            delay_per_recent_death_ms: 500,
+            max_delay_ms: 1_800,
+            streak_window_ms: 10_000,
+            ..RespawnConfig::default()
+        }
+    }
+
+    #[test]
+    fn delay_grows_with_recent_deaths_and_is_capped() {
+        let mut t = RespawnTracker::new(rules());
+        t.add_player(1);
+        let now = Instant::now();
+
+        let mut delays = Vec::new();
+        for i in 0..3 {
+            let at = now + Duration::from_secs(i * 3);
+            delays.push(t.on_death(1, at).unwrap().as_millis());
+            // Респаун + подтверждение.
+            let due = t.take_due(at + Duration::from_secs(2));
+            assert_eq!(due, vec![1]);
+            t.on_alive(1);
+        }
+
+        assert_eq!(delays, vec![1_000, 1_500, 1_800]);
+    }
+
+    #[test]
+    fn duplicate_death_signals_are_ignored() {
+        let mut t = RespawnTracker::new(rules());
+        t.add_player(1);
+        let now = Instant::now();
+
+        assert!(t.on_death(1, now).is_some());
+        // Event Death + snapshot is_dead — одна и та же смерть.
+        assert!(t.on_death(1, now).is_none());
+        assert!(t.is_dead(1));
+    }
+
+    #[test]
+    fn respawn_is_resent_until_acknowledged() {
+        let mut t = RespawnTracker::new(rules());
+        t.add_player(1);
+        let now = Instant::now();
+
+        t.on_death(1, now);
+        assert!(t.take_due(now).is_empty());
+
+        let first = now + Duration::from_millis(1_000);
+        assert_eq!(t.take_due(first), vec![1]);
+        assert!(t.take_due(first + Duration::from_secs(1)).is_empty());
+        assert_eq!(t.take_due(first + RESPAWN_RESEND_AFTER), vec![1]);
+
+        t.on_alive(1);
+        assert!(!t.is_dead(1));
+        assert!(t.take_due(first + RESPAWN_RESEND_AFTER * 3).is_empty());
+    }
+
+    #[test]
+    fn disabled_rules_never_schedule() {
+        let mut t = RespawnTracker::new(RespawnConfig {
+            enabled: false,
+            ..rules()
+        });
+        t.add_player(1);
+        assert!(t.on_death(1, Instant::now()).is_none());
+        assert!(!t.is_dead(1));
+    }
+}
@@ -0,0 +1,457 @@
+//! Очередь отправки клиенту с ограничением размера.
+//!
+//! - `Snapshot` ненадёжен: в очереди держим только последний snapshot
+//!   каждого удалённого игрока, новый заменяет ещё не отправленный на его
+//!   месте в очереди; так же — `VehicleSnapshot` каждой машины
+//! - всё остальное (spawn/despawn, чат, события, счёт) надёжно: порядок
+//!   сохраняется, ничего не выбрасывается
+//! - если надёжных пакетов накопилось больше `max_reliable`, клиент не
+//!   успевает читать — очередь переполняется и соединение закрывается
+//! - запись в сокет с таймаутом: клиент, который совсем не читает, не держит
+//!   задачу записи дольше `write_timeout`
+//!
+//! `push` синхронный — его зовут из игровой логики под любыми блокировками;
+//! ждёт пакетов только `pop` в задаче записи.
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use protocol::{NetPlayerSnapshot, NetVehicleSnapshot, PlayerId, ServerPacket, VehicleId};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::Notify;
+
+/// Сколько надёжных пакетов может ждать отправки.
+pub const MAX_RELIABLE: usize = 2048;
+
+/// Таймаут одной записи в сокет.
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+enum Item {
+    Reliable(ServerPacket),
+    /// Место в очереди; сам snapshot — в `State::snapshots`.
+    Snapshot(PlayerId),
+    /// То же для snapshot'а машины.
+    VehicleSnapshot(VehicleId),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    items: VecDeque<Item>,
+    snapshots: HashMap<PlayerId, NetPlayerSnapshot>,
+    vehicle_snapshots: HashMap<VehicleId, NetVehicleSnapshot>,
+    reliable: usize,
+    closed: bool,
+    overflowed: bool,
+}
+
+/// Результат `SendQueue::pop`.
+#[derive(Debug)]
+pub enum Pop {
+    Packet(ServerPacket),
+    /// Очередь закрыта и пуста.
+    Closed,
+    /// Клиент не успевал читать — соединение пора закрыть.
+    Overflowed,
+}
+
+#[derive(Debug)]
+pub struct SendQueue {
+    state: Mutex<State>,
+    /// Читатель один (задача записи), поэтому хватает `notify_one`:
+    /// разбуживание без ждущего сохраняется до следующего `pop`.
+    ready: Notify,
+    max_reliable: usize,
+}
+
+impl SendQueue {
+    pub fn new(max_reliable: usize) -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            ready: Notify::new(),
+            max_reliable,
+        }
+    }
+
+    /// Поставить пакет в очередь. `false` — очередь закрыта или переполнена.
+    pub fn push(&self, packet: ServerPacket) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if state.closed || state.overflowed {
+            return false;
+        }
+
+        match packet {
+            ServerPacket::Snapshot(snapshot) => {
+                let player = snapshot.player_id;
+                if state.snapshots.insert(player, snapshot).is_none() {
+                    state.items.push_back(Item::Snapshot(player));
+                }
+            }
+            ServerPacket::VehicleSnapshot(snapshot) => {
+                let vehicle = snapshot.vehicle_id;
+                if state.vehicle_snapshots.insert(vehicle, snapshot).is_none() {
+                    state.items.push_back(Item::VehicleSnapshot(vehicle));
+                }
+            }
+            packet => {
+                if state.reliable >= self.max_reliable {
+                    // Память освобождаем сразу, не дожидаясь потока записи.
+                    state.overflowed = true;
+                    state.items.clear();
+                    state.snapshots.clear();
+                    state.vehicle_snapshots.clear();
+                    state.reliable = 0;
+                    self.ready.notify_one();
+                    return false;
+                }
+                // Snapshot ушедшего игрока отправлять уже незачем.
+                if let ServerPacket::PlayerDespawn { player_id } = &packet
+                    && state.snapshots.remove(player_id).is_some()
+                {
+                    let player_id = *player_id;
+                    state
+                        .items
+                        .retain(|i| !matches!(i, Item::Snapshot(p) if *p == player_id));
+                }
+                state.reliable += 1;
+                state.items.push_back(Item::Reliable(packet));
+            }
+        }
+
+        self.ready.notify_one();
+        true
+    }
+
+    /// Больше пакетов не будет: задача записи дописывает остаток и выходит.
+    pub fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+        }
+        self.ready.notify_one();
+    }
+
+    /// Пакетов в очереди (snapshot'ы — по одному на игрока).
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|s| s.items.len()).unwrap_or(0)
+    }
+
+    /// Следующий пакет; ждёт, пока очередь пуста и открыта.
+    pub async fn pop(&self) -> Pop {
+        loop {
+            if let Some(pop) = self.try_pop() {
+                return pop;
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    /// `None` — очередь пуста и открыта.
+    fn try_pop(&self) -> Option<Pop> {
+        let Ok(mut state) = self.state.lock() else {
+            return Some(Pop::Closed);
+        };
+        if state.overflowed {
+            return Some(Pop::Overflowed);
+        }
+        while let Some(item) = state.items.pop_front() {
+            let packet = match item {
+                Item::Reliable(packet) => {
+                    state.reliable -= 1;
+                    packet
+                }
+                Item::Snapshot(player) => match state.snapshots.remove(&player) {
+                    Some(snapshot) => ServerPacket::Snapshot(snapshot),
+                    None => continue,
+                },
+                Item::VehicleSnapshot(vehicle) => match state.vehicle_snapshots.remove(&vehicle) {
+                    Some(snapshot) => ServerPacket::VehicleSnapshot(snapshot),
+                    None => continue,
+                },
+            };
+            return Some(Pop::Packet(packet));
+        }
+        state.closed.then_some(Pop::Closed)
+    }
+}
+
+/// Чем закончилась запись клиенту.
+#[derive(Debug)]
+pub enum WriteEnd {
+    /// Очередь закрыта и дописана.
+    Closed,
+    Overflowed,
+    WriteFailed(io::Error),
+}
+
+/// Писать пакеты из `queue` в `stream`, пока есть что писать.
+///
+/// `on_written(packet, bytes)` — после каждой успешной записи.
+pub async fn write_loop(
+    stream: &mut (impl AsyncWrite + Unpin),
+    queue: &SendQueue,
+    write_timeout: Duration,
+    mut on_written: impl FnMut(&ServerPacket, usize),
+) -> WriteEnd {
+    loop {
+        let packet = match queue.pop().await {
+            Pop::Packet(p) => p,
+            Pop::Closed => return WriteEnd::Closed,
+            Pop::Overflowed => return WriteEnd::Overflowed,
+        };
+
+        let mut line = match serde_json::to_string(&packet) {
+            Ok(s) => s,
+            Err(e) => return WriteEnd::WriteFailed(io::Error::other(e)),
+        };
+        line.push('\n');
+
+        match tokio::time::timeout(write_timeout, stream.write_all(line.as_bytes())).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return WriteEnd::WriteFailed(e),
+            Err(_) => return WriteEnd::WriteFailed(io::ErrorKind::TimedOut.into()),
+        }
+        on_written(&packet, line.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    use protocol::NetVec3;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+
+    fn snapshot(player_id: PlayerId, x: f32) -> ServerPacket {
+        ServerPacket::Snapshot(NetPlayerSnapshot {
+            tick: 0,
+            player_id,
+            position: NetVec3 { x, y: 0.0, z: 0.0 },
+            forward: NetVec3::default(),
+            health: 100.0,
+            is_dead: false,
+            state_code: 0,
+            car_wrapper_state: 0,
+            ctrl_style_mask: 0,
+            sub45c_state: 0,
+            in_vehicle: false,
+            is_aiming: false,
+            aim_dir: None,
+            is_moving: false,
+            movement_mode: 0,
+            weapon_id: None,
+        })
+    }
+
+    fn chat(text: &str) -> ServerPacket {
+        ServerPacket::SystemMessage {
+            text: text.to_string(),
+        }
+    }
+
+    async fn drain(queue: &SendQueue) -> Vec<ServerPacket> {
+        queue.close();
+        let mut out = Vec::new();
+        while let Pop::Packet(p) = queue.pop().await {
+            out.push(p);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn snapshots_coalesce_and_reliable_packets_keep_order() {
+        let queue = SendQueue::new(16);
+        queue.push(snapshot(1, 1.0));
+        queue.push(chat("a"));
+        queue.push(snapshot(2, 1.0));
+        queue.push(snapshot(1, 2.0));
+        queue.push(snapshot(1, 3.0));
+        queue.push(chat("b"));
+        assert_eq!(queue.len(), 4);
+
+        let sent: Vec<String> = drain(&queue)
+            .await
+            .into_iter()
+            .map(|p| match p {
+                ServerPacket::Snapshot(s) => format!("s{}@{}", s.player_id, s.position.x),
+                ServerPacket::SystemMessage { text } => text,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(sent, ["s1@3", "a", "s2@1", "b"]);
+    }
+
+    #[tokio::test]
+    async fn vehicle_snapshots_coalesce_per_vehicle() {
+        let queue = SendQueue::new(16);
+        let vehicle = |vehicle_id, x| {
+            ServerPacket::VehicleSnapshot(NetVehicleSnapshot {
+                tick: 0,
+                vehicle_id,
+                position: NetVec3 { x, y: 0.0, z: 0.0 },
+                rotation: [0.0, 0.0, 0.0, 1.0],
+            })
+        };
+        queue.push(vehicle(7, 1.0));
+        queue.push(snapshot(1, 1.0));
+        queue.push(vehicle(8, 1.0));
+        queue.push(vehicle(7, 2.0));
+        assert_eq!(queue.len(), 3);
+
+        let sent: Vec<String> = drain(&queue)
+            .await
+            .into_iter()
+            .map(|p| match p {
+                ServerPacket::VehicleSnapshot(s) => format!("v{}@{}", s.vehicle_id, s.position.x),
+                ServerPacket::Snapshot(s) => format!("s{}@{}", s.player_id, s.position.x),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(sent, ["v7@2", "s1@1", "v8@1"]);
+    }
+
+    #[tokio::test]
+    async fn despawn_drops_pending_snapshot() {
+        let queue = SendQueue::new(16);
+        queue.push(snapshot(1, 1.0));
+        queue.push(ServerPacket::PlayerDespawn { player_id: 1 });
+        queue.push(snapshot(2, 1.0));
+
+        let sent = drain(&queue).await;
+        assert_eq!(sent.len(), 2);
+        assert!(matches!(
+            sent[0],
+            ServerPacket::PlayerDespawn { player_id: 1 }
+        ));
+        assert!(matches!(&sent[1], ServerPacket::Snapshot(s) if s.player_id == 2));
+    }
+
+    #[tokio::test]
+    async fn reliable_overflow_closes_queue() {
+        let queue = SendQueue::new(3);
+        for i in 0..3 {
+            assert!(queue.push(chat(&i.to_string())));
+        }
+        // Snapshot'ы в лимит надёжных не входят.
+        assert!(queue.push(snapshot(1, 0.0)));
+        assert!(!queue.push(chat("overflow")));
+        assert_eq!(queue.len(), 0);
+        assert!(matches!(queue.pop().await, Pop::Overflowed));
+        assert!(!queue.push(snapshot(1, 0.0)));
+    }
+
+    /// Ждущий `pop` просыпается от `push` из другой задачи.
+    #[tokio::test]
+    async fn pop_waits_for_push() {
+        let queue = Arc::new(SendQueue::new(16));
+        let popper = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move { queue.pop().await })
+        };
+        tokio::task::yield_now().await;
+        queue.push(chat("late"));
+        assert!(matches!(
+            popper.await.unwrap(),
+            Pop::Packet(ServerPacket::SystemMessage { .. })
+        ));
+    }
+
+    /// Клиент, который не читает: запись упирается в буфер канала, надёжные
+    /// пакеты копятся до лимита — соединение закрывается, память не растёт.
+    #[tokio::test]
+    async fn stalled_reader_is_disconnected() {
+        let (mut server_side, _stalled_client) = tokio::io::duplex(16 * 1024);
+
+        let queue = Arc::new(SendQueue::new(64));
+        let writer = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move {
+                write_loop(
+                    &mut server_side,
+                    &queue,
+                    Duration::from_millis(500),
+                    |_, _| {},
+                )
+                .await
+            })
+        };
+
+        let text = "x".repeat(1024);
+        let started = Instant::now();
+        while queue.push(chat(&text)) {
+            assert!(queue.len() <= 64);
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "stalled client was never cut off"
+            );
+            tokio::task::yield_now().await;
+        }
+
+        let end = writer.await.unwrap();
+        assert!(
+            matches!(end, WriteEnd::Overflowed | WriteEnd::WriteFailed(_)),
+            "{end:?}"
+        );
+    }
+
+    /// Медленный клиент получает все надёжные пакеты и свежие snapshot'ы,
+    /// но не каждый промежуточный.
+    #[tokio::test]
+    async fn slow_reader_gets_reliable_packets_and_latest_snapshots() {
+        let (mut server_side, client) = tokio::io::duplex(4096);
+
+        let queue = Arc::new(SendQueue::new(MAX_RELIABLE));
+        let writer = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move {
+                write_loop(&mut server_side, &queue, WRITE_TIMEOUT, |_, _| {}).await
+            })
+        };
+
+        let reader = tokio::spawn(async move {
+            let mut received = Vec::new();
+            let mut lines = BufReader::new(client).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                received.push(serde_json::from_str::<ServerPacket>(&line).unwrap());
+                // Медленный клиент.
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            received
+        });
+
+        const SNAPSHOTS: usize = 2000;
+        for i in 0..SNAPSHOTS {
+            queue.push(snapshot(1, i as f32));
+            if i % 100 == 0 {
+                queue.push(chat(&i.to_string()));
+            }
+            tokio::task::yield_now().await;
+        }
+        queue.close();
+        assert!(matches!(writer.await.unwrap(), WriteEnd::Closed));
+
+        let received = reader.await.unwrap();
+        let chats = received
+            .iter()
+            .filter(|p| matches!(p, ServerPacket::SystemMessage { .. }))
+            .count();
+        let snapshots: Vec<f32> = received
+            .iter()
+            .filter_map(|p| match p {
+                ServerPacket::Snapshot(s) => Some(s.position.x),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(chats, SNAPSHOTS / 100);
+        assert!(snapshots.len() < SNAPSHOTS, "snapshots were not coalesced");
+        assert_eq!(snapshots.last().copied(), Some((SNAPSHOTS - 1) as f32));
+        assert!(snapshots.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,139 @@
+//! Точки спавна и выбор точки для респауна.
+//!
+//! Точка подходит игроку, если её `team`/`mode` либо не заданы, либо
+//! совпадают с командой игрока и активным режимом. Из подходящих берём ту,
+//! что дальше всего от живых игроков (меньше спавн-кемпинга). При равенстве
+//! точки перебираются по кругу, чтобы пустой сервер не спавнил всех в одну.
+
+use protocol::NetVec3;
+
+use crate::config::SpawnPointConfig;
+
+#[derive(Debug, Default)]
+pub struct SpawnSelector {
+    points: Vec<SpawnPointConfig>,
+    /// Смещение round-robin перебора.
+    next: usize,
+}
+
+impl SpawnSelector {
+    pub fn new(points: Vec<SpawnPointConfig>) -> Self {
+        Self { points, next: 0 }
+    }
+
+    /// Выбрать точку для игрока команды `team` в режиме `mode`.
+    ///
+    /// `others` — позиции живых игроков, от которых стоит держаться подальше.
+    /// `None`, если подходящих точек нет.
+    pub fn pick(
+        &mut self,
+        team: Option<&str>,
+        mode: &str,
+        others: &[NetVec3],
+    ) -> Option<SpawnPointConfig> {
+        let n = self.points.len();
+        if n == 0 {
+            return None;
+        }
+
+        let mut best: Option<(usize, f32)> = None;
+
+        for offset in 0..n {
+            let idx = (self.next + offset) % n;
+            let point = &self.points[idx];
+
+            if !matches_filter(point.mode.as_deref(), Some(mode))
+                || !matches_filter(point.team.as_deref(), team)
+            {
+                continue;
+            }
+
+            let clearance = others
+                .iter()
+                .map(|p| distance_sq(&point.position, p))
+                .fold(f32::INFINITY, f32::min);
+
+            if best.is_none_or(|(_, c)| clearance > c) {
+                best = Some((idx, clearance));
+            }
+        }
+
+        let (idx, _) = best?;
+        self.next = (idx + 1) % n;
+        Some(self.points[idx].clone())
+    }
+}
+
+/// Ограничение точки (`Some`) должно совпасть со значением игрока.
+fn matches_filter(constraint: Option<&str>, value: Option<&str>) -> bool {
+    match constraint {
+        None => true,
+        Some(c) => value.is_some_and(|v| v.eq_ignore_ascii_case(c)),
+    }
+}
+
+fn distance_sq(a: &NetVec3, b: &NetVec3) -> f32 {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    let dz = a.z - b.z;
+    dx * dx + dy * dy + dz * dz
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32, team: Option<&str>, mode: Option<&str>) -> SpawnPointConfig {
+        SpawnPointConfig {
+            position: NetVec3 { x, y: 0.0, z: 0.0 },
+            heading: 0.0,
+            team: team.map(str::to_string),
+            mode: mode.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn filters_by_team_and_mode() {
+        let mut sel = SpawnSelector::new(vec![
+            point(0.0, Some("cops"), None),
+            point(10.0, Some("robbers"), None),
+            point(20.0, None, Some("race")),
+        ]);
+
+        let p = sel.pick(Some("robbers"), "deathmatch", &[]).unwrap();
+        assert_eq!(p.position.x, 10.0);
+
+        let p = sel.pick(None, "race", &[]).unwrap();
+        assert_eq!(p.position.x, 20.0);
+
+        assert!(sel.pick(Some("civilians"), "deathmatch", &[]).is_none());
+    }
+
+    #[test]
+    fn prefers_point_far_from_players() {
+        let mut sel = SpawnSelector::new(vec![
+            point(0.0, None, None),
+            point(100.0, None, None),
+            point(50.0, None, None),
+        ]);
+
+        let others = [NetVec3 {
+            x: 5.0,
+            y: 0.0,
+            z: 0.0,
+        }];
+        let p = sel.pick(None, "freeroam", &others).unwrap();
+        assert_eq!(p.position.x, 100.0);
+    }
+
+    #[test]
+    fn round_robin_without_players() {
+        let mut sel = SpawnSelector::new(vec![point(0.0, None, None), point(1.0, None, None)]);
+
+        let a = sel.pick(None, "freeroam", &[]).unwrap().position.x;
+        let b = sel.pick(None, "freeroam", &[]).unwrap().position.x;
+        let c = sel.pick(None, "freeroam", &[]).unwrap().position.x;
+        assert_ne!(a, b);
+        assert_eq!(a, c);
+    }
+}
@@ -0,0 +1,338 @@
+//! Команды: состав, автобаланс и правило friendly fire.
+//!
+//! Команды описываются в `server.json` (`teams.teams`). Набор спавна
+//! команды — точки из `spawn_points` с `team`, равным `id` команды.
+//! Пустой список — команд нет, все играют сами за себя.
+
+use std::collections::HashMap;
+
+use common::logger;
+use protocol::{NetTeam, PlayerId, ServerPacket};
+
+use crate::SharedServer;
+use crate::config::{TeamConfig, TeamsConfig};
+
+/// Почему игрок не может перейти в команду.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TeamError {
+    Disabled,
+    UnknownTeam(String),
+    AlreadyInTeam,
+    /// Переход сделал бы команды неравными (при автобалансе).
+    Unbalanced,
+}
+
+impl std::fmt::Display for TeamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "на сервере нет команд"),
+            Self::UnknownTeam(id) => write!(f, "команды '{id}' нет"),
+            Self::AlreadyInTeam => write!(f, "вы уже в этой команде"),
+            Self::Unbalanced => write!(f, "в этой команде и так больше игроков"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Teams {
+    rules: TeamsConfig,
+    members: HashMap<PlayerId, String>,
+}
+
+impl Teams {
+    pub fn new(rules: TeamsConfig) -> Self {
+        Self {
+            rules,
+            members: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.rules.teams.is_empty()
+    }
+
+    pub fn definitions(&self) -> &[TeamConfig] {
+        &self.rules.teams
+    }
+
+    pub fn net_teams(&self) -> Vec<NetTeam> {
+        self.rules
+            .teams
+            .iter()
+            .map(|t| NetTeam {
+                id: t.id.clone(),
+                name: t.name.clone(),
+                color: t.color,
+            })
+            .collect()
+    }
+
+    pub fn team_of(&self, player_id: PlayerId) -> Option<&str> {
+        self.members.get(&player_id).map(String::as_str)
+    }
+
+    /// Оба в одной команде.
+    pub fn same_team(&self, a: PlayerId, b: PlayerId) -> bool {
+        match (self.team_of(a), self.team_of(b)) {
+            (Some(ta), Some(tb)) => ta == tb,
+            _ => false,
+        }
+    }
+
+    /// Попадание по своему запрещено правилами.
+    pub fn blocks_hit(&self, attacker: PlayerId, victim: PlayerId) -> bool {
+        !self.rules.friendly_fire && self.same_team(attacker, victim)
+    }
+
+    /// Все члены команды игрока, кроме него самого.
+    pub fn teammates(&self, player_id: PlayerId) -> Vec<PlayerId> {
+        let Some(team) = self.team_of(player_id) else {
+            return Vec::new();
+        };
+        self.members
+            .iter()
+            .filter(|(id, t)| **id != player_id && t.as_str() == team)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Все назначения (для новичка).
+    pub fn assignments(&self) -> Vec<(PlayerId, String)> {
+        let mut all: Vec<_> = self
+            .members
+            .iter()
+            .map(|(id, t)| (*id, t.clone()))
+            .collect();
+        all.sort_unstable_by_key(|(id, _)| *id);
+        all
+    }
+
+    fn size(&self, team: &str) -> usize {
+        self.members.values().filter(|t| t.as_str() == team).count()
+    }
+
+    /// Самая малочисленная команда; при равенстве — первая по конфигу.
+    fn smallest(&self) -> Option<&TeamConfig> {
+        self.rules.teams.iter().min_by_key(|t| self.size(&t.id))
+    }
+
+    /// Назначить новичка, если включён автобаланс.
+    pub fn auto_assign(&mut self, player_id: PlayerId) -> Option<String> {
+        if !self.rules.auto_balance {
+            return None;
+        }
+        let team = self.smallest()?.id.clone();
+        self.members.insert(player_id, team.clone());
+        Some(team)
+    }
+
+    /// Ручной выбор команды. Возвращает каноничный `id`.
+    pub fn join(&mut self, player_id: PlayerId, team_id: &str) -> Result<String, TeamError> {
+        if !self.is_enabled() {
+            return Err(TeamError::Disabled);
+        }
+        let team = self
+            .rules
+            .teams
+            .iter()
+            .find(|t| t.id.eq_ignore_ascii_case(team_id))
+            .map(|t| t.id.clone())
+            .ok_or_else(|| TeamError::UnknownTeam(team_id.to_string()))?;
+
+        let current = self.team_of(player_id);
+        if current == Some(team.as_str()) {
+            return Err(TeamError::AlreadyInTeam);
+        }
+
+        if self.rules.auto_balance {
+            // Размеры после перехода: в новой +1, в старой −1.
+            let target = self.size(&team) + 1;
+            let smallest_other = self
+                .rules
+                .teams
+                .iter()
+                .filter(|t| t.id != team)
+                .map(|t| self.size(&t.id) - usize::from(current == Some(t.id.as_str())))
+                .min()
+                .unwrap_or(0);
+            if target > smallest_other + 1 {
+                return Err(TeamError::Unbalanced);
+            }
+        }
+
+        self.members.insert(player_id, team.clone());
+        Ok(team)
+    }
+
+    pub fn remove_player(&mut self, player_id: PlayerId) {
+        self.members.remove(&player_id);
+    }
+}
+
+/// Новичку — список команд и составы; затем автоназначение.
+pub fn send_state(shared: &SharedServer, player_id: PlayerId) {
+    let (teams, assignments, assigned) = match shared.teams.lock() {
+        Ok(mut t) => {
+            if !t.is_enabled() {
+                return;
+            }
+            let assigned = t.auto_assign(player_id);
+            (t.net_teams(), t.assignments(), assigned)
+        }
+        Err(_) => return,
+    };
+
+    shared.send_to(player_id, ServerPacket::TeamList { teams });
+    for (id, team) in assignments {
+        if id != player_id {
+            shared.send_to(
+                player_id,
+                ServerPacket::TeamAssigned {
+                    player_id: id,
+                    team: Some(team),
+                },
+            );
+        }
+    }
+
+    match assigned {
+        Some(team) => announce(shared, player_id, &team),
+        None => shared.send_to(
+            player_id,
+            ServerPacket::SystemMessage {
+                text: "Выберите команду: /team <id>".to_string(),
+            },
+        ),
+    }
+}
+
+/// Ручная смена команды (`/team`).
+pub fn join(shared: &SharedServer, player_id: PlayerId, team_id: &str) -> Result<(), TeamError> {
+    let team = shared
+        .teams
+        .lock()
+        .map_err(|_| TeamError::Disabled)?
+        .join(player_id, team_id)?;
+    announce(shared, player_id, &team);
+    Ok(())
+}
+
+/// Разослать назначение всем, включая самого игрока.
+fn announce(shared: &SharedServer, player_id: PlayerId, team: &str) {
+    logger::info(&format!("[teams] player {player_id} -> {team}"));
+    shared.broadcast_except(
+        None,
+        ServerPacket::TeamAssigned {
+            player_id,
+            team: Some(team.to_string()),
+        },
+    );
+}
+
+/// `ClientPacket::TeamChat`: только союзникам в том же измерении.
+pub fn handle_chat(shared: &SharedServer, player_id: PlayerId, text: String) {
+    let teammates = match shared.teams.lock() {
+        Ok(t) if t.team_of(player_id).is_some() => t.teammates(player_id),
+        Ok(_) => {
+            shared.send_to(
+                player_id,
+                ServerPacket::SystemMessage {
+                    text: "Вы не в команде — командный чат недоступен".to_string(),
+                },
+            );
+            return;
+        }
+        Err(_) => return,
+    };
+
+    for id in teammates
+        .into_iter()
+        .filter(|&id| shared.same_dimension(player_id, id))
+    {
+        shared.send_to(
+            id,
+            ServerPacket::TeamChat {
+                player_id,
+                text: text.clone(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn teams(auto_balance: bool, friendly_fire: bool) -> Teams {
+        Teams::new(TeamsConfig {
+            teams: vec![
+                TeamConfig {
+                    id: "cops".into(),
+                    name: "Полиция".into(),
+                    color: [70, 120, 220],
+                },
+                TeamConfig {
+                    id: "robbers".into(),
+                    name: "Грабители".into(),
+                    color: [210, 80, 60],
+                },
+            ],
+            auto_balance,
+            friendly_fire,
+        })
+    }
+
+    #[test]
+    fn auto_assign_fills_smallest_team() {
+        let mut t = teams(true, false);
+
+        assert_eq!(t.auto_assign(1).as_deref(), Some("cops"));
+        assert_eq!(t.auto_assign(2).as_deref(), Some("robbers"));
+        assert_eq!(t.auto_assign(3).as_deref(), Some("cops"));
+
+        t.remove_player(1);
+        t.remove_player(3);
+        assert_eq!(t.auto_assign(4).as_deref(), Some("cops"));
+    }
+
+    #[test]
+    fn manual_join_respects_balance() {
+        let mut t = teams(true, false);
+        t.auto_assign(1); // cops
+        t.auto_assign(2); // robbers
+
+        // 2 против 0 — нельзя.
+        assert_eq!(t.join(1, "robbers"), Err(TeamError::Unbalanced));
+        assert_eq!(t.join(1, "cops"), Err(TeamError::AlreadyInTeam));
+        assert_eq!(
+            t.join(1, "mafia"),
+            Err(TeamError::UnknownTeam("mafia".into()))
+        );
+
+        t.auto_assign(3); // cops
+        assert_eq!(t.join(3, "ROBBERS"), Ok("robbers".to_string()));
+
+        let mut free = teams(false, false);
+        assert_eq!(free.auto_assign(1), None);
+        free.join(1, "cops").unwrap();
+        free.join(2, "cops").unwrap();
+        assert_eq!(free.teammates(1), vec![2]);
+    }
+
+    #[test]
+    fn friendly_fire_rule() {
+        let mut t = teams(false, false);
+        t.join(1, "cops").unwrap();
+        t.join(2, "cops").unwrap();
+        t.join(3, "robbers").unwrap();
+
+        assert!(t.blocks_hit(1, 2));
+        assert!(!t.blocks_hit(1, 3));
+        assert!(!t.blocks_hit(1, 4));
+
+        let mut ff = teams(false, true);
+        ff.join(1, "cops").unwrap();
+        ff.join(2, "cops").unwrap();
+        assert!(!ff.blocks_hit(1, 2));
+    }
+}
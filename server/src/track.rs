@@ -0,0 +1,232 @@
+//! Трассы для режима `race`.
+//!
+//! Трасса — JSON-файл в `data/tracks/` (имя файла = имя трассы):
+//!
+//! ```json
+//! {
+//!   "name": "Хилвуд — порт",
+//!   "laps": 2,
+//!   "checkpoint_radius": 12.0,
+//!   "checkpoints": [
+//!     { "position": { "x": -120.0, "y": 540.0, "z": 2.0 } },
+//!     { "position": { "x": 310.0, "y": 610.0, "z": 1.5 }, "radius": 20.0 }
+//!   ]
+//! }
+//! ```
+//!
+//! Последний чекпоинт — финишная линия круга. Круг начинается с первого.
+
+use std::path::{Path, PathBuf};
+
+use protocol::NetVec3;
+use serde::Deserialize;
+
+/// Каталог трасс по умолчанию.
+pub const DEFAULT_TRACKS_DIR: &str = "data/tracks";
+
+/// Радиус чекпоинта, если не задан ни в трассе, ни в точке.
+pub const DEFAULT_CHECKPOINT_RADIUS_M: f32 = 12.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub name: String,
+    pub laps: u32,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checkpoint {
+    pub position: NetVec3,
+    pub radius: f32,
+}
+
+impl Checkpoint {
+    /// Внутри ли точка радиуса чекпоинта.
+    pub fn contains(&self, p: &NetVec3) -> bool {
+        distance_sq(&self.position, p) <= self.radius * self.radius
+    }
+}
+
+#[derive(Debug)]
+pub enum TrackError {
+    Io(PathBuf, std::io::Error),
+    Parse(serde_json::Error),
+    TooFewCheckpoints(usize),
+    NoLaps,
+    BadCheckpoint(usize),
+}
+
+impl std::fmt::Display for TrackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "не удалось прочитать {}: {e}", path.display()),
+            Self::Parse(e) => write!(f, "ошибка разбора: {e}"),
+            Self::TooFewCheckpoints(n) => write!(f, "нужно минимум 2 чекпоинта, задано {n}"),
+            Self::NoLaps => write!(f, "laps должно быть больше нуля"),
+            Self::BadCheckpoint(i) => write!(f, "чекпоинт #{i}: некорректная позиция или радиус"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TrackFile {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default = "default_laps")]
+    laps: u32,
+    #[serde(default)]
+    checkpoint_radius: Option<f32>,
+    checkpoints: Vec<CheckpointFile>,
+}
+
+#[derive(Deserialize)]
+struct CheckpointFile {
+    position: NetVec3,
+    #[serde(default)]
+    radius: Option<f32>,
+}
+
+fn default_laps() -> u32 {
+    1
+}
+
+impl Track {
+    /// Разобрать и проверить трассу. `fallback_name` — если в файле нет `name`.
+    pub fn from_json(text: &str, fallback_name: &str) -> Result<Self, TrackError> {
+        let file: TrackFile = serde_json::from_str(text).map_err(TrackError::Parse)?;
+
+        if file.laps == 0 {
+            return Err(TrackError::NoLaps);
+        }
+        if file.checkpoints.len() < 2 {
+            return Err(TrackError::TooFewCheckpoints(file.checkpoints.len()));
+        }
+
+        let default_radius = file
+            .checkpoint_radius
+            .unwrap_or(DEFAULT_CHECKPOINT_RADIUS_M);
+
+        let checkpoints = file
+            .checkpoints
+            .into_iter()
+            .enumerate()
+            .map(|(i, cp)| {
+                let radius = cp.radius.unwrap_or(default_radius);
+                let p = cp.position;
+                let valid = p.x.is_finite()
+                    && p.y.is_finite()
+                    && p.z.is_finite()
+                    && radius.is_finite()
+                    && radius > 0.0;
+                if valid {
+                    Ok(Checkpoint {
+                        position: p,
+                        radius,
+                    })
+                } else {
+                    Err(TrackError::BadCheckpoint(i))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: file.name.unwrap_or_else(|| fallback_name.to_string()),
+            laps: file.laps,
+            checkpoints,
+        })
+    }
+
+    /// Загрузить трассу из файла.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TrackError> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|e| TrackError::Io(path.to_path_buf(), e))?;
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("track");
+        Self::from_json(&text, stem)
+    }
+
+    /// Загрузить трассу по имени из каталога `dir` (`<dir>/<name>.json`).
+    pub fn load_named(dir: impl AsRef<Path>, name: &str) -> Result<Self, TrackError> {
+        Self::load(dir.as_ref().join(format!("{name}.json")))
+    }
+}
+
+pub fn distance_sq(a: &NetVec3, b: &NetVec3) -> f32 {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    let dz = a.z - b.z;
+    dx * dx + dy * dy + dz * dz
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "laps": 3,
+        "checkpoint_radius": 10.0,
+        "checkpoints": [
+            { "position": { "x": 0.0, "y": 0.0, "z": 0.0 } },
+            { "position": { "x": 100.0, "y": 0.0, "z": 0.0 }, "radius": 25.0 }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_track_with_defaults() {
+        let track = Track::from_json(SAMPLE, "docks").unwrap();
+
+        assert_eq!(track.name, "docks");
+        assert_eq!(track.laps, 3);
+        assert_eq!(track.checkpoints.len(), 2);
+        assert_eq!(track.checkpoints[0].radius, 10.0);
+        assert_eq!(track.checkpoints[1].radius, 25.0);
+    }
+
+    #[test]
+    fn rejects_invalid_tracks() {
+        let one = r#"{ "checkpoints": [ { "position": { "x": 0.0, "y": 0.0, "z": 0.0 } } ] }"#;
+        assert!(matches!(
+            Track::from_json(one, "t"),
+            Err(TrackError::TooFewCheckpoints(1))
+        ));
+
+        let no_laps = SAMPLE.replace("\"laps\": 3", "\"laps\": 0");
+        assert!(matches!(
+            Track::from_json(&no_laps, "t"),
+            Err(TrackError::NoLaps)
+        ));
+
+        let bad_radius = SAMPLE.replace("25.0", "-1.0");
+        assert!(matches!(
+            Track::from_json(&bad_radius, "t"),
+            Err(TrackError::BadCheckpoint(1))
+        ));
+
+        assert!(matches!(
+            Track::from_json("{", "t"),
+            Err(TrackError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn checkpoint_contains_uses_radius() {
+        let cp = Checkpoint {
+            position: NetVec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            radius: 5.0,
+        };
+        assert!(cp.contains(&NetVec3 {
+            x: 3.0,
+            y: 4.0,
+            z: 0.0
+        }));
+        assert!(!cp.contains(&NetVec3 {
+            x: 3.0,
+            y: 4.1,
+            z: 0.0
+        }));
+    }
+}
@@ -0,0 +1,397 @@
+//! Машины: сетевые ID, места и водитель.
+//!
+//! Машины у каждого клиента свои (трафик и парковки спавнит движок), поэтому
+//! сервер узнаёт машину по модели и месту. Севший сообщает `model_hash` и
+//! позицию; сервер ищет известную машину той же модели в том же измерении
+//! не дальше `MATCH_RADIUS_M` со свободным местом — так пассажир попадает в
+//! машину водителя, — иначе заводит новую.
+//!
+//! Место называет клиент (какое игрок занял в игре), сервер только
+//! проверяет, что оно свободно: пассажир, севший раньше водителя, остаётся
+//! пассажиром. Если у всех подходящих машин рядом это место занято —
+//! конфликт, посадка отклоняется. Snapshot'ы машины принимаются только от
+//! того, кто на месте 0. Позиция машины следует за водителем (snapshot'ы
+//! машины и его собственные), чтобы пассажир нашёл её и после поездки.
+//! Опустевшая машина забывается.
+
+use std::collections::HashMap;
+
+use common::logger;
+use protocol::{NetVec3, NetVehicleSnapshot, PlayerId, ServerPacket, VehicleId};
+
+use crate::SharedServer;
+use crate::dimension::DimensionId;
+
+/// Мест в машине (седан M2 — четыре).
+pub const MAX_SEATS: u8 = 4;
+
+/// Насколько далеко от известной машины может стоять «та же самая».
+pub const MATCH_RADIUS_M: f32 = 6.0;
+
+/// Почему посадка отклонена.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnterError {
+    BadPosition,
+    /// Места с таким номером в машине нет.
+    BadSeat,
+    /// Место у машины рядом уже занято.
+    SeatTaken,
+}
+
+/// Место игрока в машине.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Seat {
+    pub vehicle_id: VehicleId,
+    pub seat: u8,
+    pub model_hash: u64,
+    pub position: NetVec3,
+}
+
+#[derive(Debug, Clone)]
+struct Vehicle {
+    model_hash: u64,
+    dimension: DimensionId,
+    position: NetVec3,
+    seats: [Option<PlayerId>; MAX_SEATS as usize],
+}
+
+impl Vehicle {
+    fn is_free(&self, seat: u8) -> bool {
+        self.seats[seat as usize].is_none()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Vehicles {
+    next_id: VehicleId,
+    vehicles: HashMap<VehicleId, Vehicle>,
+    seat_of: HashMap<PlayerId, (VehicleId, u8)>,
+}
+
+impl Vehicles {
+    /// Посадить игрока на место `seat`.
+    ///
+    /// Если игрок уже где-то сидел, он оттуда выходит (и при отказе тоже:
+    /// в игре он уже пересел).
+    pub fn enter(
+        &mut self,
+        player_id: PlayerId,
+        model_hash: u64,
+        position: NetVec3,
+        seat: u8,
+        dimension: DimensionId,
+    ) -> Result<Seat, EnterError> {
+        if !(position.x.is_finite() && position.y.is_finite() && position.z.is_finite()) {
+            return Err(EnterError::BadPosition);
+        }
+        if seat >= MAX_SEATS {
+            return Err(EnterError::BadSeat);
+        }
+        self.leave(player_id);
+
+        let nearby: Vec<_> = self
+            .vehicles
+            .iter()
+            .filter(|(_, v)| v.model_hash == model_hash && v.dimension == dimension)
+            .map(|(id, v)| (*id, v.is_free(seat), distance(v.position, position)))
+            .filter(|(_, _, d)| *d <= MATCH_RADIUS_M)
+            .collect();
+        let nearest_free = nearby
+            .iter()
+            .filter(|(_, free, _)| *free)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+
+        let vehicle_id = match nearest_free {
+            Some((id, _, _)) => *id,
+            None if !nearby.is_empty() => return Err(EnterError::SeatTaken),
+            None => {
+                self.next_id += 1;
+                let id = self.next_id;
+                self.vehicles.insert(
+                    id,
+                    Vehicle {
+                        model_hash,
+                        dimension,
+                        position,
+                        seats: [None; MAX_SEATS as usize],
+                    },
+                );
+                id
+            }
+        };
+
+        let vehicle = self
+            .vehicles
+            .get_mut(&vehicle_id)
+            .ok_or(EnterError::BadPosition)?;
+        vehicle.seats[seat as usize] = Some(player_id);
+        self.seat_of.insert(player_id, (vehicle_id, seat));
+        Ok(Seat {
+            vehicle_id,
+            seat,
+            model_hash,
+            position: vehicle.position,
+        })
+    }
+
+    /// Высадить игрока. Возвращает машину, из которой он вышел.
+    pub fn leave(&mut self, player_id: PlayerId) -> Option<VehicleId> {
+        let (vehicle_id, seat) = self.seat_of.remove(&player_id)?;
+        if let Some(vehicle) = self.vehicles.get_mut(&vehicle_id) {
+            vehicle.seats[seat as usize] = None;
+            if vehicle.seats.iter().all(Option::is_none) {
+                self.vehicles.remove(&vehicle_id);
+            }
+        }
+        Some(vehicle_id)
+    }
+
+    pub fn seat(&self, player_id: PlayerId) -> Option<Seat> {
+        let (vehicle_id, seat) = *self.seat_of.get(&player_id)?;
+        let vehicle = self.vehicles.get(&vehicle_id)?;
+        Some(Seat {
+            vehicle_id,
+            seat,
+            model_hash: vehicle.model_hash,
+            position: vehicle.position,
+        })
+    }
+
+    /// Принять snapshot машины. `false` — отправитель не её водитель.
+    pub fn update(&mut self, player_id: PlayerId, snapshot: &NetVehicleSnapshot) -> bool {
+        if self.seat_of.get(&player_id) != Some(&(snapshot.vehicle_id, 0)) {
+            return false;
+        }
+        self.driver_moved(player_id, snapshot.position)
+    }
+
+    /// Водитель сообщил свою позицию — машина там же. `false` — игрок не
+    /// водитель или позиция не число.
+    pub fn driver_moved(&mut self, player_id: PlayerId, position: NetVec3) -> bool {
+        let Some(&(vehicle_id, 0)) = self.seat_of.get(&player_id) else {
+            return false;
+        };
+        let p = position;
+        if !(p.x.is_finite() && p.y.is_finite() && p.z.is_finite()) {
+            return false;
+        }
+        match self.vehicles.get_mut(&vehicle_id) {
+            Some(vehicle) => {
+                vehicle.position = p;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn distance(a: NetVec3, b: NetVec3) -> f32 {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    let dz = a.z - b.z;
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// `VehicleEnter` для игрока `player_id`, если он сидит в машине.
+pub fn enter_packet(shared: &SharedServer, player_id: PlayerId) -> Option<ServerPacket> {
+    let seat = shared.vehicles.lock().ok()?.seat(player_id)?;
+    Some(ServerPacket::VehicleEnter {
+        player_id,
+        vehicle_id: seat.vehicle_id,
+        seat: seat.seat,
+        model_hash: seat.model_hash,
+        position: seat.position,
+    })
+}
+
+/// Обработать `ClientPacket::VehicleEnter` от `player_id`.
+pub fn handle_enter(
+    shared: &SharedServer,
+    player_id: PlayerId,
+    model_hash: u64,
+    position: NetVec3,
+    seat: u8,
+) {
+    // Пересел, не выйдя, — соседям сначала выход.
+    handle_leave(shared, player_id);
+
+    let Some(dimension) = shared.dimensions.lock().ok().and_then(|d| d.get(player_id)) else {
+        return;
+    };
+    let entered = match shared.vehicles.lock() {
+        Ok(mut vehicles) => vehicles.enter(player_id, model_hash, position, seat, dimension),
+        Err(_) => return,
+    };
+    let seat = match entered {
+        Ok(seat) => seat,
+        Err(e) => {
+            logger::warn(&format!(
+                "[vehicles] player {player_id} cannot take seat {seat} (model {model_hash:#x} at {position:?}): {e:?}"
+            ));
+            return;
+        }
+    };
+
+    logger::info(&format!(
+        "[vehicles] player {player_id} -> vehicle {} seat {} (model {:#x})",
+        seat.vehicle_id, seat.seat, model_hash
+    ));
+
+    if let Some(packet) = enter_packet(shared, player_id) {
+        shared.send_to(player_id, packet.clone());
+        shared.broadcast_dimension(player_id, packet);
+    }
+}
+
+/// Высадить `player_id` и сообщить соседям. Вызывается и при отключении,
+/// и перед сменой измерения.
+pub fn handle_leave(shared: &SharedServer, player_id: PlayerId) {
+    let left = shared
+        .vehicles
+        .lock()
+        .ok()
+        .and_then(|mut v| v.leave(player_id));
+    let Some(vehicle_id) = left else {
+        return;
+    };
+
+    logger::info(&format!(
+        "[vehicles] player {player_id} left vehicle {vehicle_id}"
+    ));
+
+    let packet = ServerPacket::VehicleLeave {
+        player_id,
+        vehicle_id,
+    };
+    shared.send_to(player_id, packet.clone());
+    shared.broadcast_dimension(player_id, packet);
+}
+
+/// Обработать `ClientPacket::VehicleSnapshot` от `player_id`.
+pub fn handle_snapshot(shared: &SharedServer, player_id: PlayerId, snapshot: NetVehicleSnapshot) {
+    let accepted = shared
+        .vehicles
+        .lock()
+        .is_ok_and(|mut v| v.update(player_id, &snapshot));
+    if !accepted {
+        return;
+    }
+    shared.broadcast_dimension(player_id, ServerPacket::VehicleSnapshot(snapshot));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEDAN: u64 = 0xA1;
+
+    fn at(x: f32) -> NetVec3 {
+        NetVec3 { x, y: 0.0, z: 0.0 }
+    }
+
+    fn snapshot(vehicle_id: VehicleId, x: f32) -> NetVehicleSnapshot {
+        NetVehicleSnapshot {
+            tick: 0,
+            vehicle_id,
+            position: at(x),
+            rotation: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn passenger_joins_nearby_car_of_same_model() {
+        let mut v = Vehicles::default();
+        let driver = v.enter(1, SEDAN, at(0.0), 0, 0).unwrap();
+        assert_eq!(driver.seat, 0);
+
+        let passenger = v.enter(2, SEDAN, at(2.0), 1, 0).unwrap();
+        assert_eq!(passenger.vehicle_id, driver.vehicle_id);
+        assert_eq!(passenger.seat, 1);
+
+        // Другая модель, другое измерение или далеко — другая машина.
+        assert_ne!(
+            v.enter(3, 0xB2, at(1.0), 0, 0).unwrap().vehicle_id,
+            driver.vehicle_id
+        );
+        assert_ne!(
+            v.enter(4, SEDAN, at(1.0), 0, 5).unwrap().vehicle_id,
+            driver.vehicle_id
+        );
+        let far = at(MATCH_RADIUS_M * 2.0);
+        assert_ne!(
+            v.enter(5, SEDAN, far, 0, 0).unwrap().vehicle_id,
+            driver.vehicle_id
+        );
+    }
+
+    #[test]
+    fn passenger_first_does_not_become_driver() {
+        let mut v = Vehicles::default();
+        let passenger = v.enter(1, SEDAN, at(0.0), 2, 0).unwrap();
+        assert_eq!(passenger.seat, 2);
+        // Пассажир не водит.
+        assert!(!v.update(1, &snapshot(passenger.vehicle_id, 5.0)));
+
+        let driver = v.enter(2, SEDAN, at(1.0), 0, 0).unwrap();
+        assert_eq!(driver.vehicle_id, passenger.vehicle_id);
+        assert_eq!(driver.seat, 0);
+        assert!(v.update(2, &snapshot(driver.vehicle_id, 5.0)));
+    }
+
+    #[test]
+    fn taken_or_missing_seat_is_rejected() {
+        let mut v = Vehicles::default();
+        let car = v.enter(1, SEDAN, at(0.0), 0, 0).unwrap().vehicle_id;
+
+        assert_eq!(v.enter(2, SEDAN, at(1.0), 0, 0), Err(EnterError::SeatTaken));
+        assert_eq!(
+            v.enter(2, SEDAN, at(1.0), MAX_SEATS, 0),
+            Err(EnterError::BadSeat)
+        );
+        let nan = NetVec3 {
+            x: f32::NAN,
+            y: 0.0,
+            z: 0.0,
+        };
+        assert_eq!(v.enter(2, SEDAN, nan, 1, 0), Err(EnterError::BadPosition));
+
+        // Водитель остался водителем.
+        assert_eq!(v.seat(1).unwrap().seat, 0);
+        assert_eq!(v.enter(2, SEDAN, at(1.0), 1, 0).unwrap().vehicle_id, car);
+    }
+
+    #[test]
+    fn car_follows_its_driver() {
+        let mut v = Vehicles::default();
+        let car = v.enter(1, SEDAN, at(0.0), 0, 0).unwrap().vehicle_id;
+        v.enter(2, SEDAN, at(0.0), 1, 0).unwrap();
+
+        assert!(!v.update(2, &snapshot(car, 50.0)));
+        assert!(v.update(1, &snapshot(car, 50.0)));
+        assert_eq!(v.seat(2).unwrap().position, at(50.0));
+
+        // Собственный snapshot водителя тоже двигает машину, пассажира — нет.
+        assert!(!v.driver_moved(2, at(80.0)));
+        assert!(v.driver_moved(1, at(100.0)));
+
+        // Пассажир ищет машину уже на новом месте.
+        v.leave(2);
+        assert_eq!(v.enter(2, SEDAN, at(99.0), 1, 0).unwrap().vehicle_id, car);
+    }
+
+    #[test]
+    fn empty_car_is_forgotten_and_seats_free_up() {
+        let mut v = Vehicles::default();
+        let car = v.enter(1, SEDAN, at(0.0), 0, 0).unwrap().vehicle_id;
+        v.enter(2, SEDAN, at(0.0), 1, 0).unwrap();
+
+        assert_eq!(v.leave(1), Some(car));
+        assert_eq!(v.leave(1), None);
+        // Место водителя снова свободно.
+        assert_eq!(v.enter(3, SEDAN, at(0.0), 0, 0).unwrap().vehicle_id, car);
+
+        v.leave(2);
+        v.leave(3);
+        assert_ne!(v.enter(4, SEDAN, at(0.0), 0, 0).unwrap().vehicle_id, car);
+    }
+}
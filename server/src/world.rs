@@ -0,0 +1,170 @@
+//! Время суток и погода мира.
+//!
+//! Сервер — владелец часов: время считается от старта сервера по
+//! `world.time_scale`, погода перебирает `world.weather` раз в
+//! `weather_cycle_secs`. Клиенты получают `ServerPacket::WorldState` при
+//! входе, при смене погоды и раз в `BROADCAST_EVERY` — между пакетами они
+//! двигают время сами, пакет только снимает накопленный дрейф.
+
+use std::time::{Duration, Instant};
+
+use protocol::{PlayerId, ServerPacket};
+
+use crate::SharedServer;
+use crate::config::WorldConfig;
+
+/// Период повторной рассылки состояния мира.
+pub const BROADCAST_EVERY: Duration = Duration::from_secs(30);
+
+const SECS_PER_HOUR: f32 = 3600.0;
+
+#[derive(Debug)]
+pub struct WorldClock {
+    rules: WorldConfig,
+    started: Instant,
+    last_broadcast: Option<Instant>,
+    /// Индекс погоды на последней рассылке.
+    last_weather: Option<usize>,
+}
+
+impl WorldClock {
+    pub fn new(rules: WorldConfig, now: Instant) -> Self {
+        Self {
+            rules,
+            started: now,
+            last_broadcast: None,
+            last_weather: None,
+        }
+    }
+
+    /// Час в `[0, 24)`.
+    pub fn hour(&self, now: Instant) -> f32 {
+        let elapsed = now.saturating_duration_since(self.started).as_secs_f32();
+        let hour = self.rules.start_hour + elapsed * self.rules.time_scale / SECS_PER_HOUR;
+        hour.rem_euclid(24.0)
+    }
+
+    fn weather_index(&self, now: Instant) -> Option<usize> {
+        let count = self.rules.weather.len();
+        if count == 0 {
+            return None;
+        }
+        if self.rules.weather_cycle_secs == 0 {
+            return Some(0);
+        }
+        let elapsed = now.saturating_duration_since(self.started).as_secs();
+        Some((elapsed / self.rules.weather_cycle_secs as u64) as usize % count)
+    }
+
+    /// Текущий шаблон погоды (`None` — погода не управляется).
+    pub fn weather(&self, now: Instant) -> Option<&str> {
+        self.weather_index(now)
+            .map(|i| self.rules.weather[i].as_str())
+    }
+
+    pub fn packet(&self, now: Instant) -> ServerPacket {
+        ServerPacket::WorldState {
+            hour: self.hour(now),
+            time_scale: self.rules.time_scale,
+            weather: self.weather(now).map(str::to_string),
+        }
+    }
+
+    /// Пакет для рассылки всем, если погода сменилась или подошёл период.
+    pub fn tick(&mut self, now: Instant) -> Option<ServerPacket> {
+        let weather = self.weather_index(now);
+        let due = self
+            .last_broadcast
+            .is_none_or(|at| now.saturating_duration_since(at) >= BROADCAST_EVERY);
+        if !due && weather == self.last_weather {
+            return None;
+        }
+        self.last_broadcast = Some(now);
+        self.last_weather = weather;
+        Some(self.packet(now))
+    }
+}
+
+/// Состояние мира новичку.
+pub fn send_state(shared: &SharedServer, player_id: PlayerId) {
+    let packet = shared.world.lock().ok().map(|w| w.packet(Instant::now()));
+    if let Some(packet) = packet {
+        shared.send_to(player_id, packet);
+    }
+}
+
+/// Периодическая рассылка и смена погоды.
+pub fn tick(shared: &SharedServer, now: Instant) {
+    let packet = shared.world.lock().ok().and_then(|mut w| w.tick(now));
+    if let Some(packet) = packet {
+        shared.broadcast_except(None, packet);
+    }
+}
+
+/// `12:30` для чат-команды `/time`.
+pub fn format_hour(hour: f32) -> String {
+    let minutes = (hour * 60.0) as u32 % (24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(time_scale: f32, weather: &[&str], cycle: u32) -> WorldConfig {
+        WorldConfig {
+            start_hour: 23.0,
+            time_scale,
+            weather: weather.iter().map(|w| w.to_string()).collect(),
+            weather_cycle_secs: cycle,
+        }
+    }
+
+    #[test]
+    fn time_runs_at_scale_and_wraps_midnight() {
+        let t0 = Instant::now();
+        let clock = WorldClock::new(rules(60.0, &[], 0), t0);
+        assert_eq!(clock.hour(t0), 23.0);
+        // 60 реальных секунд при x60 — игровой час.
+        assert!((clock.hour(t0 + Duration::from_secs(90)) - 0.5).abs() < 1e-3);
+        assert_eq!(
+            format_hour(clock.hour(t0 + Duration::from_secs(90))),
+            "00:30"
+        );
+
+        let locked = WorldClock::new(rules(0.0, &[], 0), t0);
+        assert_eq!(locked.hour(t0 + Duration::from_secs(3600)), 23.0);
+    }
+
+    #[test]
+    fn weather_is_locked_or_cycles() {
+        let t0 = Instant::now();
+        let none = WorldClock::new(rules(0.0, &[], 10), t0);
+        assert_eq!(none.weather(t0), None);
+
+        let locked = WorldClock::new(rules(0.0, &["a", "b"], 0), t0);
+        assert_eq!(locked.weather(t0 + Duration::from_secs(500)), Some("a"));
+
+        let cycling = WorldClock::new(rules(0.0, &["a", "b"], 10), t0);
+        assert_eq!(cycling.weather(t0 + Duration::from_secs(9)), Some("a"));
+        assert_eq!(cycling.weather(t0 + Duration::from_secs(10)), Some("b"));
+        assert_eq!(cycling.weather(t0 + Duration::from_secs(20)), Some("a"));
+    }
+
+    #[test]
+    fn broadcasts_on_period_and_weather_change() {
+        let t0 = Instant::now();
+        let mut clock = WorldClock::new(rules(30.0, &["a", "b"], 45), t0);
+        assert!(clock.tick(t0).is_some());
+        assert!(clock.tick(t0 + Duration::from_secs(1)).is_none());
+        assert!(clock.tick(t0 + BROADCAST_EVERY).is_some());
+        // Смена погоды раньше периода.
+        match clock.tick(t0 + Duration::from_secs(45)) {
+            Some(ServerPacket::WorldState { weather, .. }) => {
+                assert_eq!(weather.as_deref(), Some("b"))
+            }
+            other => panic!("expected WorldState, got {other:?}"),
+        }
+        assert!(clock.tick(t0 + Duration::from_secs(46)).is_none());
+    }
+}
@@ -0,0 +1,54 @@
+//! Внешний вид: новичок получает вид уже известных игроков, смена
+//! рассылается соседям, невалидные модели отбрасываются.
+
+mod common;
+
+use protocol::{ClientPacket, NetAppearance, ServerPacket};
+
+use common::TestClient;
+
+fn appearance(model: &str, appearance_id: Option<u32>) -> ClientPacket {
+    ClientPacket::Appearance(NetAppearance {
+        model: model.to_string(),
+        appearance_id,
+    })
+}
+
+#[test]
+fn appearance_reaches_newcomers_and_neighbours() {
+    let server = common::start_listening("appearance", serde_json::json!({}));
+    let mut a = TestClient::connect(server.port, "a");
+    let a_id = a.player_id;
+
+    a.send(&appearance("vito_suit_brown", Some(2)));
+    // Ответ на команду приходит после обработки Appearance.
+    a.send(&ClientPacket::ChatMessage {
+        text: "/money".to_string(),
+    });
+    a.recv_until("/money reply", |p| {
+        matches!(p, ServerPacket::SystemMessage { .. }).then_some(())
+    });
+
+    let mut b = TestClient::connect(server.port, "b");
+    let b_id = b.player_id;
+    let seen = b.recv_until("appearance of a", |p| match p {
+        ServerPacket::PlayerAppearance {
+            player_id,
+            appearance,
+        } if *player_id == a_id => Some(appearance.clone()),
+        _ => None,
+    });
+    assert_eq!(seen.model, "vito_suit_brown");
+    assert_eq!(seen.appearance_id, Some(2));
+
+    b.send(&appearance("../../vito", None));
+    b.send(&appearance("joe_coat", None));
+    let seen = a.recv_until("appearance of b", |p| match p {
+        ServerPacket::PlayerAppearance {
+            player_id,
+            appearance,
+        } if *player_id == b_id => Some(appearance.model.clone()),
+        _ => None,
+    });
+    assert_eq!(seen, "joe_coat");
+}
@@ -0,0 +1,38 @@
+//! Короткий прогон `bot` против настоящего сервера: все боты в игре до
+//! конца, snapshot'ы ходят между ними, команды получают ответ.
+
+mod common;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use bot::BotConfig;
+
+const BOTS: usize = 20;
+
+#[tokio::test]
+async fn bots_stay_connected_and_see_each_other() {
+    let server = common::start_listening("bot-soak", serde_json::json!({ "name": "Soak" }));
+
+    let summary = bot::run(
+        BotConfig {
+            server: SocketAddr::from((Ipv4Addr::LOCALHOST, server.port)),
+            bots: BOTS,
+            duration: Some(Duration::from_secs(3)),
+            chat_every: Some(Duration::from_millis(500)),
+            event_every: Some(Duration::from_millis(300)),
+            probe_every: Some(Duration::from_millis(200)),
+            ramp: Duration::from_millis(10),
+            report_every: None,
+            ..BotConfig::default()
+        },
+        |_| {},
+    )
+    .await;
+
+    assert!(summary.ok(), "failures: {:?}", summary.failures);
+    assert_eq!(summary.peak_connected, BOTS);
+    assert!(!summary.total.relay.is_empty(), "no snapshots relayed");
+    assert!(!summary.total.rtt.is_empty(), "no command replies");
+    assert!(summary.total.packets_in > summary.total.packets_out);
+}
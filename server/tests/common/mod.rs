@@ -0,0 +1,198 @@
+//! Общее для интеграционных тестов: сервер бинарником во временном каталоге.
+
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use protocol::{
+    ClientPacket, NetPlayerSnapshot, NetVec3, PROTOCOL_VERSION, PlayerId, ServerPacket,
+};
+
+pub const WAIT_LIMIT: Duration = Duration::from_secs(15);
+
+/// Убивает сервер и чистит каталог даже при панике в тесте.
+pub struct ServerProcess {
+    pub child: Child,
+    pub port: u16,
+    /// Рабочий каталог сервера.
+    pub dir: PathBuf,
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Порт, свободный и для TCP, и для UDP.
+pub fn free_port() -> u16 {
+    loop {
+        let tcp = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = tcp.local_addr().unwrap().port();
+        if UdpSocket::bind(("0.0.0.0", port)).is_ok() {
+            return port;
+        }
+    }
+}
+
+/// Запустить сервер с `server.json` = `config` + `port`.
+///
+/// `tag` различает каталоги тестов одного процесса.
+pub fn start_server(tag: &str, port: u16, mut config: serde_json::Value) -> ServerProcess {
+    let dir = std::env::temp_dir().join(format!("m2mp-{tag}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    config["port"] = port.into();
+    std::fs::write(dir.join("server.json"), config.to_string()).unwrap();
+
+    let child = Command::new(env!("CARGO_BIN_EXE_server"))
+        .current_dir(&dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    ServerProcess { child, port, dir }
+}
+
+/// Сервер без метрик, готовый принимать соединения.
+///
+/// Пробное подключение занимает один `PlayerId`.
+pub fn start_listening(tag: &str, mut config: serde_json::Value) -> ServerProcess {
+    let port = free_port();
+    config["metrics"] = serde_json::json!({ "addr": null });
+    let server = start_server(tag, port, config);
+    wait_until("server listening", || {
+        TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_ok()
+    });
+    server
+}
+
+pub fn wait_until(what: &str, mut check: impl FnMut() -> bool) {
+    let deadline = Instant::now() + WAIT_LIMIT;
+    while !check() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Клиент протокола без игры.
+pub struct TestClient {
+    pub player_id: PlayerId,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TestClient {
+    pub fn connect(port: u16, name: &str) -> Self {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.set_read_timeout(Some(WAIT_LIMIT)).unwrap();
+        let mut client = Self {
+            player_id: 0,
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+        };
+        client.send(&ClientPacket::Connect {
+            name: name.to_string(),
+            version: PROTOCOL_VERSION,
+            password: None,
+            account_token: None,
+        });
+        client.player_id = client.recv_until("ConnectAccepted", |p| match p {
+            ServerPacket::ConnectAccepted { player_id } => Some(*player_id),
+            _ => None,
+        });
+        client
+    }
+
+    pub fn send(&mut self, packet: &ClientPacket) {
+        self.send_raw(&serde_json::to_string(packet).unwrap());
+    }
+
+    pub fn send_raw(&mut self, line: &str) {
+        self.writer.write_all(line.as_bytes()).unwrap();
+        self.writer.write_all(b"\n").unwrap();
+    }
+
+    pub fn recv(&mut self) -> Option<ServerPacket> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(serde_json::from_str(line.trim_end()).unwrap()),
+        }
+    }
+
+    /// Читать, пока `pick` не вернёт значение.
+    pub fn recv_until<T>(
+        &mut self,
+        what: &str,
+        mut pick: impl FnMut(&ServerPacket) -> Option<T>,
+    ) -> T {
+        let deadline = Instant::now() + WAIT_LIMIT;
+        loop {
+            assert!(
+                Instant::now() < deadline,
+                "player {} timed out waiting for {what}",
+                self.player_id
+            );
+            let packet = self
+                .recv()
+                .unwrap_or_else(|| panic!("player {} lost connection", self.player_id));
+            if let Some(value) = pick(&packet) {
+                return value;
+            }
+        }
+    }
+
+    /// Читать, пока `seen` не наберёт все `ids`.
+    pub fn collect_ids(
+        &mut self,
+        what: &str,
+        ids: &HashSet<PlayerId>,
+        mut id_of: impl FnMut(&ServerPacket) -> Option<PlayerId>,
+    ) {
+        let mut seen = HashSet::new();
+        self.recv_until(what, |packet| {
+            if let Some(id) = id_of(packet) {
+                seen.insert(id);
+            }
+            ids.is_subset(&seen).then_some(())
+        });
+    }
+
+    /// Сервер закрыл соединение.
+    pub fn expect_closed(&mut self) {
+        let deadline = Instant::now() + WAIT_LIMIT;
+        while self.recv().is_some() {
+            assert!(Instant::now() < deadline, "connection was not closed");
+        }
+    }
+}
+
+pub fn snapshot(x: f32) -> ClientPacket {
+    ClientPacket::Snapshot(NetPlayerSnapshot {
+        tick: 1,
+        // Сервер подставляет свой.
+        player_id: 0,
+        position: NetVec3 { x, y: 0.0, z: 0.0 },
+        forward: NetVec3::default(),
+        health: 100.0,
+        is_dead: false,
+        state_code: 0,
+        car_wrapper_state: 0,
+        ctrl_style_mask: 0,
+        sub45c_state: 0,
+        in_vehicle: false,
+        is_aiming: false,
+        aim_dir: None,
+        is_moving: false,
+        movement_mode: 0,
+        weapon_id: None,
+    })
+}
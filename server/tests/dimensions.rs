@@ -0,0 +1,182 @@
+//! Измерения: игроки разных измерений не видят друг друга, переход
+//! рассылает spawn/despawn.
+
+mod common;
+
+use std::collections::HashSet;
+
+use protocol::{ClientPacket, ServerPacket};
+
+use common::{TestClient, snapshot};
+
+#[test]
+fn players_only_see_their_dimension() {
+    let server = common::start_listening("dimensions", serde_json::json!({}));
+    let mut a = TestClient::connect(server.port, "a");
+    let mut b = TestClient::connect(server.port, "b");
+    let mut c = TestClient::connect(server.port, "c");
+    let (a_id, b_id, c_id) = (a.player_id, b.player_id, c.player_id);
+
+    // c уходит: соседи и он сам получают despawn друг о друге.
+    c.send(&ClientPacket::ChatMessage {
+        text: "/dimension 5".to_string(),
+    });
+    let mut gone = HashSet::new();
+    // Ответ на команду приходит после рассылки.
+    c.recv_until("despawns of a and b", |p| {
+        if let ServerPacket::PlayerDespawn { player_id } = p {
+            gone.insert(*player_id);
+        }
+        matches!(p, ServerPacket::SystemMessage { .. }).then_some(())
+    });
+    assert_eq!(gone, HashSet::from([a_id, b_id]));
+    for client in [&mut a, &mut b] {
+        client.recv_until("despawn of c", |p| {
+            matches!(p, ServerPacket::PlayerDespawn { player_id } if *player_id == c_id)
+                .then_some(())
+        });
+    }
+
+    // Чат и snapshot a доходят до b, но не до c.
+    a.send(&snapshot(3.0));
+    a.send(&ClientPacket::ChatMessage {
+        text: "hello".to_string(),
+    });
+    b.recv_until("chat from a", |p| {
+        matches!(p, ServerPacket::ChatMessage { player_id, .. } if *player_id == a_id).then_some(())
+    });
+    c.send(&ClientPacket::ChatMessage {
+        text: "/dimension".to_string(),
+    });
+    let reply = c.recv_until("dimension reply", |p| match p {
+        ServerPacket::ChatMessage { .. } | ServerPacket::Snapshot(_) => {
+            panic!("c saw {p:?} from another dimension")
+        }
+        ServerPacket::SystemMessage { text } => Some(text.clone()),
+        _ => None,
+    });
+    assert!(reply.contains('5'), "{reply}");
+
+    // Возврат: c снова видит a (с последней позицией) до ответа на команду.
+    c.send(&ClientPacket::ChatMessage {
+        text: "/dimension 0".to_string(),
+    });
+    let mut a_position = None;
+    c.recv_until("dimension 0 reply", |p| match p {
+        ServerPacket::Snapshot(s) if s.player_id == a_id => {
+            a_position = Some(s.position.x);
+            None
+        }
+        ServerPacket::SystemMessage { .. } => Some(()),
+        _ => None,
+    });
+    assert_eq!(a_position, Some(3.0));
+    a.recv_until("spawn of c", |p| {
+        matches!(p, ServerPacket::PlayerSpawn { player_id, .. } if *player_id == c_id).then_some(())
+    });
+}
+
+fn command(client: &mut TestClient, text: &str) -> String {
+    client.send(&ClientPacket::ChatMessage {
+        text: text.to_string(),
+    });
+    client.recv_until(text, |p| match p {
+        ServerPacket::SystemMessage { text } => Some(text.clone()),
+        _ => None,
+    })
+}
+
+#[test]
+fn activity_state_stays_in_its_dimension() {
+    let server = common::start_listening(
+        "dimensions_activity",
+        serde_json::json!({
+            "admins": ["boss"],
+            "dimensions": { "modes": { "5": "deathmatch" } },
+        }),
+    );
+    let mut a = TestClient::connect(server.port, "a");
+    let mut c = TestClient::connect(server.port, "c");
+    let mut boss = TestClient::connect(server.port, "boss");
+    let (a_id, c_id) = (a.player_id, c.player_id);
+
+    // В измерении 5 идёт раунд — пришедший получает его до ответа.
+    c.send(&ClientPacket::ChatMessage {
+        text: "/dimension 5".to_string(),
+    });
+    let mut round = false;
+    c.recv_until("dimension 5 reply", |p| {
+        round |= matches!(p, ServerPacket::RoundStart { .. });
+        matches!(p, ServerPacket::SystemMessage { .. }).then_some(())
+    });
+    assert!(round, "c did not get RoundStart of dimension 5");
+
+    // Смерть в измерении 0 — kill feed только там.
+    a.send(&ClientPacket::Event(protocol::NetPlayerEvent::Death));
+    a.recv_until("kill of a", |p| {
+        matches!(p, ServerPacket::Kill { victim_id, .. } if *victim_id == a_id).then_some(())
+    });
+    c.send(&ClientPacket::ChatMessage {
+        text: "/dimension 0".to_string(),
+    });
+    let refused = c.recv_until("refused leave", |p| match p {
+        ServerPacket::Kill { .. } | ServerPacket::ScoreUpdate { .. } => {
+            panic!("c saw {p:?} from another dimension")
+        }
+        ServerPacket::SystemMessage { text } => Some(text.clone()),
+        _ => None,
+    });
+    assert!(refused.contains("раунда"), "{refused}");
+
+    // Обычный игрок не переводит других; администратор — может.
+    let denied = command(&mut a, &format!("/dimension 0 {c_id}"));
+    assert!(denied.contains("администратор"), "{denied}");
+    let moved = command(&mut boss, &format!("/dimension 0 {c_id}"));
+    assert!(moved.contains(&c_id.to_string()), "{moved}");
+    let notice = c.recv_until("admin notice", |p| match p {
+        ServerPacket::SystemMessage { text } => Some(text.clone()),
+        _ => None,
+    });
+    assert!(notice.contains("Администратор"), "{notice}");
+}
+
+#[test]
+fn self_service_can_be_disabled() {
+    let server = common::start_listening(
+        "dimensions_locked",
+        serde_json::json!({ "dimensions": { "self_service": false } }),
+    );
+    let mut a = TestClient::connect(server.port, "a");
+
+    let reply = command(&mut a, "/dimension 5");
+    assert!(reply.contains("отключена"), "{reply}");
+    assert!(command(&mut a, "/dimension").contains('0'));
+}
+
+#[test]
+fn team_chat_stays_in_its_dimension() {
+    let server = common::start_listening(
+        "dimensions_team_chat",
+        serde_json::json!({ "teams": { "teams": [{ "id": "red", "name": "Red" }] } }),
+    );
+    let mut a = TestClient::connect(server.port, "a");
+    let mut b = TestClient::connect(server.port, "b");
+    let mut c = TestClient::connect(server.port, "c");
+    let a_id = a.player_id;
+
+    command(&mut c, "/dimension 5");
+    a.send(&ClientPacket::TeamChat {
+        text: "push".to_string(),
+    });
+    b.recv_until("team chat from a", |p| {
+        matches!(p, ServerPacket::TeamChat { player_id, .. } if *player_id == a_id).then_some(())
+    });
+    c.send(&ClientPacket::ChatMessage {
+        text: "/dimension".to_string(),
+    });
+    c.recv_until("dimension reply", |p| match p {
+        ServerPacket::TeamChat { .. } => panic!("c saw {p:?} from another dimension"),
+        ServerPacket::SystemMessage { .. } => Some(()),
+        _ => None,
+    });
+}
@@ -0,0 +1,163 @@
+//! Десятки симулированных клиентов против настоящего сервера.
+//!
+//! Клиенты подключаются параллельно, дальше тест ведёт их по очереди:
+//! все пакеты к этому моменту уже лежат в буферах сокетов.
+
+mod common;
+
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpStream};
+use std::thread;
+
+use protocol::{ClientPacket, PROTOCOL_VERSION, PlayerId, ServerPacket};
+
+use common::{ServerProcess, TestClient, WAIT_LIMIT, snapshot};
+
+const CLIENTS: usize = 40;
+
+fn start(tag: &str) -> ServerProcess {
+    common::start_listening(tag, serde_json::json!({}))
+}
+
+/// Все клиенты подключаются одновременно.
+fn connect_all(port: u16, count: usize) -> Vec<TestClient> {
+    let handles: Vec<_> = (0..count)
+        .map(|i| thread::spawn(move || TestClient::connect(port, &format!("bot{i}"))))
+        .collect();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+fn ids_except(clients: &[TestClient], me: PlayerId) -> HashSet<PlayerId> {
+    clients
+        .iter()
+        .map(|c| c.player_id)
+        .filter(|id| *id != me)
+        .collect()
+}
+
+#[test]
+fn dozens_of_clients_see_each_other() {
+    let server = start("many-clients");
+    let mut clients = connect_all(server.port, CLIENTS);
+
+    let ids: HashSet<PlayerId> = clients.iter().map(|c| c.player_id).collect();
+    assert_eq!(ids.len(), CLIENTS, "player ids must be unique");
+
+    for (i, client) in clients.iter_mut().enumerate() {
+        client.send(&snapshot(i as f32));
+    }
+    let speaker = clients[0].player_id;
+    clients[0].send(&ClientPacket::ChatMessage {
+        text: "hello".to_string(),
+    });
+
+    for i in 0..clients.len() {
+        let others = ids_except(&clients, clients[i].player_id);
+        let client = &mut clients[i];
+        client.collect_ids("spawns", &others, |p| match p {
+            ServerPacket::PlayerSpawn { player_id, .. } => Some(*player_id),
+            _ => None,
+        });
+    }
+    // Чат и snapshot'ы разных клиентов приходят в любом порядке.
+    for i in 0..clients.len() {
+        let others = ids_except(&clients, clients[i].player_id);
+        let client = &mut clients[i];
+        let mut snapshots = HashSet::new();
+        let mut chat = client.player_id == speaker;
+        client.recv_until("snapshots and chat", |p| {
+            match p {
+                ServerPacket::Snapshot(s) => {
+                    snapshots.insert(s.player_id);
+                }
+                ServerPacket::ChatMessage { player_id, text } => {
+                    assert_eq!((*player_id, text.as_str()), (speaker, "hello"));
+                    chat = true;
+                }
+                _ => {}
+            }
+            (chat && others.is_subset(&snapshots)).then_some(())
+        });
+    }
+
+    // Половина уходит — оставшиеся видят каждый despawn.
+    let leaving: Vec<TestClient> = clients.drain(CLIENTS / 2..).collect();
+    let left: HashSet<PlayerId> = leaving.iter().map(|c| c.player_id).collect();
+    for (i, mut client) in leaving.into_iter().enumerate() {
+        if i % 2 == 0 {
+            client.send(&ClientPacket::Disconnect);
+        }
+        // Остальные просто рвут соединение.
+    }
+    for client in &mut clients {
+        client.collect_ids("despawns", &left, |p| match p {
+            ServerPacket::PlayerDespawn { player_id } => Some(*player_id),
+            _ => None,
+        });
+    }
+}
+
+#[test]
+fn bad_clients_do_not_affect_others() {
+    let server = start("bad-clients");
+    let mut clients = connect_all(server.port, 10);
+
+    // Битый JSON — отключают только автора.
+    let mut broken = TestClient::connect(server.port, "broken");
+    broken.send_raw("{not json");
+    broken.expect_closed();
+
+    // Чужая версия протокола.
+    let mut outdated = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).unwrap();
+    outdated.set_read_timeout(Some(WAIT_LIMIT)).unwrap();
+    let connect = ClientPacket::Connect {
+        name: "outdated".to_string(),
+        version: PROTOCOL_VERSION + 1,
+        password: None,
+        account_token: None,
+    };
+    writeln!(outdated, "{}", serde_json::to_string(&connect).unwrap()).unwrap();
+    let mut reply = String::new();
+    BufReader::new(&outdated).read_line(&mut reply).unwrap();
+    assert!(matches!(
+        serde_json::from_str(reply.trim_end()).unwrap(),
+        ServerPacket::ConnectRejected { .. }
+    ));
+
+    // Ник, под которым уже играют: чужой аккаунт не достаётся.
+    let mut impostor = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).unwrap();
+    impostor.set_read_timeout(Some(WAIT_LIMIT)).unwrap();
+    let connect = ClientPacket::Connect {
+        name: "bot0".to_string(),
+        version: PROTOCOL_VERSION,
+        password: None,
+        account_token: None,
+    };
+    writeln!(impostor, "{}", serde_json::to_string(&connect).unwrap()).unwrap();
+    let mut reply = String::new();
+    BufReader::new(&impostor).read_line(&mut reply).unwrap();
+    assert!(matches!(
+        serde_json::from_str(reply.trim_end()).unwrap(),
+        ServerPacket::ConnectRejected { .. }
+    ));
+
+    // Клиент, который не читает вообще, не мешает остальным.
+    let _silent = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).unwrap();
+
+    let speaker = clients[0].player_id;
+    clients[0].send(&ClientPacket::ChatMessage {
+        text: "still here".to_string(),
+    });
+    for client in &mut clients[1..] {
+        let from = client.recv_until("chat", |p| match p {
+            ServerPacket::ChatMessage { player_id, .. } => Some(*player_id),
+            _ => None,
+        });
+        assert_eq!(from, speaker);
+    }
+
+    // Новый клиент после всего этого подключается как обычно.
+    let late = TestClient::connect(server.port, "late");
+    assert!(!clients.iter().any(|c| c.player_id == late.player_id));
+}
@@ -0,0 +1,119 @@
+//! Мастер + настоящий сервер + фейковый клиент на loopback.
+//!
+//! Сервер запускается бинарником во временном каталоге со своим
+//! `server.json` (свободный порт, heartbeat раз в секунду), мастер — в
+//! процессе теста.
+
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use master::MasterConfig;
+use protocol::master::{ListFilter, MasterRequest, MasterResponse, MasterServer};
+
+use common::{ServerProcess, free_port, wait_until};
+
+const STALE_AFTER: Duration = Duration::from_secs(3);
+
+fn start_master() -> SocketAddr {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = MasterConfig {
+        stale_after: STALE_AFTER,
+        verify_timeout: Duration::from_secs(1),
+    };
+    thread::spawn(move || master::run(listener, config));
+    addr
+}
+
+fn start_server(master: SocketAddr, port: u16) -> ServerProcess {
+    let config = serde_json::json!({
+        "name": "Loopback Test",
+        "master": { "addr": master.to_string(), "heartbeat_secs": 1 },
+        "metrics": { "addr": null },
+    });
+    common::start_server("master-loopback", port, config)
+}
+
+fn request(master: SocketAddr, request: &MasterRequest) -> MasterResponse {
+    let stream = TcpStream::connect(master).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut line = serde_json::to_string(request).unwrap();
+    line.push('\n');
+    (&stream).write_all(line.as_bytes()).unwrap();
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).unwrap();
+    serde_json::from_str(reply.trim_end()).unwrap()
+}
+
+fn list(master: SocketAddr, filter: ListFilter) -> Vec<MasterServer> {
+    match request(master, &MasterRequest::List { filter }) {
+        MasterResponse::Servers { servers } => servers,
+        other => panic!("unexpected response: {other:?}"),
+    }
+}
+
+#[test]
+fn server_registers_is_listed_and_evicted() {
+    let master = start_master();
+    let port = free_port();
+    let server_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut server = start_server(master, port);
+
+    wait_until("server in master list", || {
+        list(master, ListFilter::default())
+            .iter()
+            .any(|s| s.addr == server_addr)
+    });
+
+    let servers = list(master, ListFilter::default());
+    let entry = servers.iter().find(|s| s.addr == server_addr).unwrap();
+    assert_eq!(entry.info.name, "Loopback Test");
+    assert_eq!(entry.info.port, port);
+    assert!(!entry.info.password);
+
+    // Фильтры клиента.
+    let by_name = ListFilter {
+        name: Some("loopback".into()),
+        hide_password: true,
+        ..ListFilter::default()
+    };
+    assert_eq!(list(master, by_name).len(), 1);
+    let other_name = ListFilter {
+        name: Some("nope".into()),
+        ..ListFilter::default()
+    };
+    assert!(list(master, other_name).is_empty());
+    let hide_empty = ListFilter {
+        hide_empty: true,
+        ..ListFilter::default()
+    };
+    assert!(list(master, hide_empty).is_empty());
+
+    // Остановленный сервер перестаёт слать heartbeat и выпадает из списка.
+    server.child.kill().unwrap();
+    server.child.wait().unwrap();
+    wait_until("stale server eviction", || {
+        list(master, ListFilter::default()).is_empty()
+    });
+}
+
+#[test]
+fn register_without_query_answer_is_rejected() {
+    let master = start_master();
+    // Порт, на котором никто не отвечает на query.
+    let port = free_port();
+
+    let response = request(master, &MasterRequest::Register { port });
+    assert!(
+        matches!(response, MasterResponse::Error { .. }),
+        "{response:?}"
+    );
+    assert!(list(master, ListFilter::default()).is_empty());
+}
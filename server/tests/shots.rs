@@ -0,0 +1,96 @@
+//! Выстрелы и урон: `ShotFired` доходит до соседей, только если сходится
+//! со snapshot'ом стрелка; принятое попадание — `Damage` жертве,
+//! отклонённое не доходит никуда.
+
+mod common;
+
+use protocol::{ClientPacket, NetPlayerEvent, NetVec3, PlayerId, ServerPacket};
+
+use common::TestClient;
+
+fn shot(weapon_id: u32, origin_x: f32, hit_player: Option<PlayerId>) -> ClientPacket {
+    ClientPacket::Event(NetPlayerEvent::ShotFired {
+        weapon_id,
+        origin: NetVec3 {
+            x: origin_x,
+            y: 0.0,
+            z: 1.4,
+        },
+        direction: NetVec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        hit_player,
+        hit_pos: None,
+    })
+}
+
+fn armed_snapshot(weapon_id: u32) -> ClientPacket {
+    let mut packet = common::snapshot(0.0);
+    if let ClientPacket::Snapshot(snapshot) = &mut packet {
+        snapshot.weapon_id = Some(weapon_id);
+    }
+    packet
+}
+
+fn hit(victim_id: PlayerId, damage: f32) -> ClientPacket {
+    ClientPacket::Hit {
+        victim_id,
+        weapon_id: 4,
+        damage,
+        headshot: true,
+    }
+}
+
+#[test]
+fn confirmed_hit_reaches_only_victim_as_damage() {
+    let server = common::start_listening("shots", serde_json::json!({}));
+    let mut a = TestClient::connect(server.port, "a");
+    let mut b = TestClient::connect(server.port, "b");
+    let a_id = a.player_id;
+    let b_id = b.player_id;
+
+    // До snapshot'а сверять не с чем; затем — чужое оружие и выстрел
+    // издалека. Ни один из них до b не доходит, дошедший — последний.
+    a.send(&shot(4, 0.0, None));
+    a.send(&armed_snapshot(4));
+    a.send(&shot(12, 0.0, None));
+    a.send(&shot(4, 300.0, None));
+    a.send(&shot(4, 0.0, Some(b_id)));
+    let target = b.recv_until("shot of a", |p| match p {
+        ServerPacket::Event {
+            player_id,
+            event: NetPlayerEvent::ShotFired { hit_player, .. },
+        } if *player_id == a_id => Some(*hit_player),
+        _ => None,
+    });
+    assert_eq!(target, Some(b_id));
+
+    // Отрицательный урон и попадание по себе отклоняются.
+    a.send(&hit(b_id, -5.0));
+    a.send(&hit(a_id, 30.0));
+    a.send(&hit(b_id, 42.0));
+    let (attacker, damage, headshot) = b.recv_until("damage", |p| match p {
+        ServerPacket::Damage {
+            attacker_id,
+            damage,
+            headshot,
+            ..
+        } => Some((*attacker_id, *damage, *headshot)),
+        _ => None,
+    });
+    assert_eq!(attacker, a_id);
+    assert_eq!(damage, 42.0);
+    assert!(headshot);
+
+    // Стрелку урон не приходит — ответ на команду раньше любого Damage.
+    a.send(&ClientPacket::ChatMessage {
+        text: "/money".to_string(),
+    });
+    a.recv_until("/money reply", |p| match p {
+        ServerPacket::Damage { .. } => panic!("attacker got Damage: {p:?}"),
+        ServerPacket::SystemMessage { .. } => Some(()),
+        _ => None,
+    });
+}
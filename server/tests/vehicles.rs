@@ -0,0 +1,105 @@
+//! Машины: пассажир попадает в машину водителя на своё место, snapshot'ы
+//! машины идут только от водителя, новичок узнаёт, кто где сидит.
+
+mod common;
+
+use protocol::{ClientPacket, NetVec3, NetVehicleSnapshot, PlayerId, ServerPacket, VehicleId};
+
+use common::TestClient;
+
+const SEDAN: u64 = 0xA1;
+
+fn enter(x: f32, seat: u8) -> ClientPacket {
+    ClientPacket::VehicleEnter {
+        model_hash: SEDAN,
+        position: NetVec3 { x, y: 0.0, z: 0.0 },
+        seat,
+    }
+}
+
+fn vehicle_snapshot(vehicle_id: VehicleId, x: f32) -> ClientPacket {
+    ClientPacket::VehicleSnapshot(NetVehicleSnapshot {
+        tick: 1,
+        vehicle_id,
+        position: NetVec3 { x, y: 0.0, z: 0.0 },
+        rotation: [0.0, 0.0, 0.0, 1.0],
+    })
+}
+
+/// Дождаться `VehicleEnter` игрока `who`: (машина, место).
+fn seat_of(client: &mut TestClient, who: PlayerId) -> (VehicleId, u8) {
+    client.recv_until("vehicle enter", |p| match p {
+        ServerPacket::VehicleEnter {
+            player_id,
+            vehicle_id,
+            seat,
+            ..
+        } if *player_id == who => Some((*vehicle_id, *seat)),
+        _ => None,
+    })
+}
+
+#[test]
+fn driver_and_passenger_share_one_car() {
+    let server = common::start_listening("vehicles", serde_json::json!({}));
+    let mut a = TestClient::connect(server.port, "a");
+    let mut b = TestClient::connect(server.port, "b");
+    let a_id = a.player_id;
+    let b_id = b.player_id;
+
+    // Пассажир сел первым — водителем он от этого не становится.
+    b.send(&enter(0.0, 1));
+    let (car, seat) = seat_of(&mut b, b_id);
+    assert_eq!(seat, 1);
+    assert_eq!(seat_of(&mut a, b_id), (car, 1));
+
+    a.send(&enter(2.0, 0));
+    assert_eq!(seat_of(&mut a, a_id), (car, 0));
+    assert_eq!(seat_of(&mut b, a_id), (car, 0));
+
+    // Snapshot пассажира отбрасывается, водителя — доходит.
+    b.send(&vehicle_snapshot(car, 99.0));
+    a.send(&vehicle_snapshot(car, 10.0));
+    let x = b.recv_until("vehicle snapshot", |p| match p {
+        ServerPacket::VehicleSnapshot(s) if s.vehicle_id == car => Some(s.position.x),
+        _ => None,
+    });
+    assert_eq!(x, 10.0);
+    a.send(&ClientPacket::ChatMessage {
+        text: "/money".to_string(),
+    });
+    a.recv_until("/money reply", |p| match p {
+        ServerPacket::VehicleSnapshot(_) => panic!("passenger snapshot relayed: {p:?}"),
+        ServerPacket::SystemMessage { .. } => Some(()),
+        _ => None,
+    });
+
+    // Новичок узнаёт обоих сидящих.
+    let mut c = TestClient::connect(server.port, "c");
+    let mut seats = Vec::new();
+    c.recv_until("seats of a and b", |p| {
+        if let ServerPacket::VehicleEnter {
+            player_id,
+            vehicle_id,
+            seat,
+            ..
+        } = p
+        {
+            seats.push((*player_id, *vehicle_id, *seat));
+        }
+        (seats.len() == 2).then_some(())
+    });
+    seats.sort_unstable();
+    assert_eq!(seats, [(a_id, car, 0), (b_id, car, 1)]);
+
+    // Отключение водителя высаживает его.
+    a.send(&ClientPacket::Disconnect);
+    let left = b.recv_until("vehicle leave", |p| match p {
+        ServerPacket::VehicleLeave {
+            player_id,
+            vehicle_id,
+        } if *player_id == a_id => Some(*vehicle_id),
+        _ => None,
+    });
+    assert_eq!(left, car);
+}
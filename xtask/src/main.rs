@@ -20,6 +20,9 @@
 //!   m2mp_client.dll         — основной mod-DLL (release)
 //!   m2mp_devtools.dll       — devtools mod-DLL (release)
 //!   server.exe              — сервер (release)
+//!   master.exe              — мастер-сервер списка серверов (release)
+//!   replay.exe              — просмотр и повтор записей сессий (release)
+//!   bot.exe                 — нагрузочные боты (release)
 //!   steam_api64.dll         — копия из assets/ (нужно положить вручную)
 //!   steam_appid.txt         — `1030830` (Mafia II Definitive Edition)
 //!   client.bat              — обычный запуск клиента
@@ -106,6 +109,9 @@ fn dist() -> Result<(), String> {
             "-p", "client",
             "-p", "devtools",
             "-p", "server",
+            "-p", "master",
+            "-p", "replay",
+            "-p", "bot",
         ])
         .current_dir(workspace_root())
         .status()
@@ -127,6 +133,9 @@ fn dist() -> Result<(), String> {
         ("m2mp_client.dll",   "m2mp_client.dll",   true),
         ("m2mp_devtools.dll", "m2mp_devtools.dll", true),
         ("server.exe",        "server.exe",        true),
+        ("master.exe",        "master.exe",        false),
+        ("replay.exe",        "replay.exe",        false),
+        ("bot.exe",           "bot.exe",           false),
     ];
 
     for (src_name, dst_name, required) in copies {